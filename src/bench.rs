@@ -0,0 +1,143 @@
+//! Throughput and memory measurements for the `bench`
+//! subcommand.
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    io::sink,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+
+use ::json::json::{self, Document, Event, EventReader, EventWriter, Value};
+
+/// An allocator that counts the bytes in use, so that the
+/// peak memory of each engine can be reported.
+pub struct CountingAllocator;
+
+static CURRENT: AtomicUsize = AtomicUsize::new(0);
+static PEAK: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = System.alloc(layout);
+        if !ptr.is_null() {
+            let current = CURRENT.fetch_add(layout.size(), Ordering::Relaxed) + layout.size();
+            PEAK.fetch_max(current, Ordering::Relaxed);
+        }
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout);
+        CURRENT.fetch_sub(layout.size(), Ordering::Relaxed);
+    }
+}
+
+/// The measurements of one engine.
+pub struct Measurement {
+    /// The name of the engine.
+    pub engine: &'static str,
+    /// Bytes of input parsed per second.
+    pub parse: f64,
+    /// Bytes of output serialized per second, if the engine
+    /// can serialize.
+    pub serialize: Option<f64>,
+    /// The most memory in use beyond the input text while
+    /// parsing once, in bytes.
+    pub peak_memory: usize,
+}
+
+impl Measurement {
+    /// Returns the measurement as a JSON object.
+    pub fn to_value(&self) -> Value {
+        let mut object = Value::object()
+            .set("engine", self.engine)
+            .set("parse_bytes_per_second", self.parse.round() as u64)
+            .set("peak_memory_bytes", self.peak_memory as u64);
+        if let Some(serialize) = self.serialize {
+            object = object.set("serialize_bytes_per_second", serialize.round() as u64);
+        }
+        object.build()
+    }
+}
+
+/// Measures the DOM, streaming, and tape engines on a JSON
+/// text. Each operation is repeated until it has run for at
+/// least the given duration. It will return an error if the
+/// text is not valid JSON.
+pub fn run(text: &str, duration: Duration) -> json::Result<Vec<Measurement>> {
+    let value = json::parse(text)?;
+    let serialized = value.to_string().len();
+
+    let dom = Measurement {
+        engine: "dom",
+        parse: throughput(text.len(), duration, || json::parse(text).map(drop))?,
+        serialize: Some(throughput(serialized, duration, || {
+            drop(value.to_string());
+            Ok(())
+        })?),
+        peak_memory: peak_memory(|| json::parse(text).map(drop))?,
+    };
+
+    let stream = Measurement {
+        engine: "streaming",
+        parse: throughput(text.len(), duration, || read_events(text, |_| Ok(())))?,
+        serialize: Some(throughput(serialized, duration, || {
+            let mut writer = EventWriter::new(sink());
+            read_events(text, |event| writer.write(&event))
+        })?),
+        peak_memory: peak_memory(|| read_events(text, |_| Ok(())))?,
+    };
+
+    let tape = Measurement {
+        engine: "tape",
+        parse: throughput(text.len(), duration, || Document::parse(text).map(drop))?,
+        serialize: None,
+        peak_memory: peak_memory(|| Document::parse(text).map(drop))?,
+    };
+
+    Ok(vec![dom, stream, tape])
+}
+
+/// Reads every event of a JSON text, passing each to a
+/// callback.
+fn read_events(text: &str, mut f: impl FnMut(Event) -> json::Result<()>) -> json::Result<()> {
+    EventReader::new(text.as_bytes()).try_for_each(|event| f(event?))
+}
+
+/// Returns the bytes processed per second by repeating an
+/// operation for at least the given duration.
+fn throughput(
+    bytes: usize,
+    duration: Duration,
+    mut f: impl FnMut() -> json::Result<()>,
+) -> json::Result<f64> {
+    let start = Instant::now();
+    let mut runs = 0;
+    while runs == 0 || start.elapsed() < duration {
+        f()?;
+        runs += 1;
+    }
+    Ok((bytes * runs) as f64 / start.elapsed().as_secs_f64())
+}
+
+/// Returns the most memory allocated at once while an
+/// operation runs, beyond what was in use before it.
+fn peak_memory(f: impl FnOnce() -> json::Result<()>) -> json::Result<usize> {
+    let before = CURRENT.load(Ordering::Relaxed);
+    PEAK.store(before, Ordering::Relaxed);
+    f()?;
+    Ok(PEAK.load(Ordering::Relaxed) - before)
+}
+
+/// Formats a number of bytes with a binary unit.
+pub fn format_bytes(bytes: f64) -> String {
+    let units = ["B", "KiB", "MiB", "GiB"];
+    let mut bytes = bytes;
+    let mut unit = 0;
+    while bytes >= 1024.0 && unit < units.len() - 1 {
+        bytes /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", bytes, units[unit])
+}
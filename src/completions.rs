@@ -0,0 +1,102 @@
+//! Shell completion scripts for the command-line interface.
+
+/// The subcommands of the command-line interface.
+const COMMANDS: &[&str] = &[
+    "concat",
+    "flatten",
+    "unflatten",
+    "to-env",
+    "from-env",
+    "infer-schema",
+    "rust-types",
+    "generate",
+    "completions",
+    #[cfg(feature = "bench")]
+    "bench",
+    "stats",
+];
+
+/// The flags accepted by any subcommand.
+const FLAGS: &[&str] = &[
+    "--output",
+    "--quiet",
+    "--strict",
+    "--separator",
+    "--schema",
+    "--random",
+    "--count",
+    "--depth",
+    "--seed",
+    #[cfg(feature = "bench")]
+    "--seconds",
+    "--name",
+    "--serde",
+    "--from-value",
+    "--path",
+];
+
+/// Returns the completion script for a shell, or `None` if
+/// the shell is not supported.
+pub fn script(shell: &str) -> Option<String> {
+    let commands = COMMANDS.join(" ");
+    let flags = FLAGS.join(" ");
+    match shell {
+        "bash" => Some(format!(
+            r#"_json() {{
+    local current="${{COMP_WORDS[COMP_CWORD]}}"
+    local previous="${{COMP_WORDS[COMP_CWORD-1]}}"
+    case "$previous" in
+        --output) COMPREPLY=($(compgen -W "text json" -- "$current")); return ;;
+        completions) COMPREPLY=($(compgen -W "bash zsh fish" -- "$current")); return ;;
+    esac
+    if [[ "$current" == -* ]]; then
+        COMPREPLY=($(compgen -W "{flags}" -- "$current"))
+    elif [[ $COMP_CWORD -eq 1 ]]; then
+        COMPREPLY=($(compgen -W "{commands}" -- "$current"))
+    else
+        COMPREPLY=($(compgen -f -- "$current"))
+    fi
+}}
+complete -F _json json
+"#
+        )),
+        "zsh" => Some(format!(
+            r#"#compdef json
+
+_json() {{
+    case "$words[CURRENT-1]" in
+        --output) compadd text json; return ;;
+        completions) compadd bash zsh fish; return ;;
+    esac
+    if [[ "$PREFIX" == -* ]]; then
+        compadd -- {flags}
+    elif (( CURRENT == 2 )); then
+        compadd -- {commands}
+    else
+        _files
+    fi
+}}
+
+_json "$@"
+"#
+        )),
+        "fish" => {
+            let mut script = String::new();
+            for command in COMMANDS {
+                script.push_str(&format!(
+                    "complete -c json -n __fish_use_subcommand -a {}\n",
+                    command
+                ));
+            }
+            for flag in FLAGS {
+                script.push_str(&format!("complete -c json -l {}\n", &flag[2..]));
+            }
+            script.push_str("complete -c json -l output -x -a 'text json'\n");
+            script.push_str(
+                "complete -c json -n '__fish_seen_subcommand_from completions' -x -a 'bash zsh fish'\n",
+            );
+            Some(script)
+        }
+        _ => None,
+    }
+}
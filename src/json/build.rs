@@ -0,0 +1,277 @@
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+};
+
+use super::{
+    map::Map,
+    number::Number,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+impl Value {
+    /// Returns a builder for an object value, whose members
+    /// are added with chained calls to `set`.
+    pub fn object() -> ObjectBuilder {
+        ObjectBuilder(Map::new())
+    }
+
+    /// Returns a builder for an array value, whose elements
+    /// are added with chained calls to `push`.
+    pub fn array() -> ArrayBuilder {
+        ArrayBuilder(vec![])
+    }
+
+    /// Converts the entries of a Rust map into an object,
+    /// keeping the order in which they are iterated. Keys
+    /// that are not strings are handled according to the
+    /// policy. It will return an error if the policy
+    /// rejects a key.
+    pub fn from_map<K, V>(
+        entries: impl IntoIterator<Item = (K, V)>,
+        policy: KeyPolicy,
+    ) -> Result<Value>
+    where
+        K: MapKey,
+        V: Into<Value>,
+    {
+        let mut object = Map::new();
+        for (key, value) in entries {
+            if !K::IS_STRING && policy == KeyPolicy::Reject {
+                return Err(Error::new(ErrorKind::NonStringKey(key.to_key())));
+            }
+            object.insert(key.to_key(), value.into());
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+/// An enumeration of the ways in which map keys that are
+/// not strings, such as integers, are converted into object
+/// keys.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyPolicy {
+    /// Keys are written as their text, so that the key `1`
+    /// becomes `"1"`.
+    #[default]
+    Stringify,
+    /// Keys that are not strings are rejected with an
+    /// error.
+    Reject,
+}
+
+/// A type that can be used as the key of a map that is
+/// converted into an object.
+pub trait MapKey {
+    /// Whether the type is a string type, whose keys are
+    /// used as is under every [`KeyPolicy`].
+    const IS_STRING: bool;
+
+    /// Returns the text of the key.
+    fn to_key(&self) -> String;
+}
+
+macro_rules! impl_map_key {
+    ($is_string:literal: $($key:ty),*) => {
+        $(
+            impl MapKey for $key {
+                const IS_STRING: bool = $is_string;
+
+                fn to_key(&self) -> String {
+                    self.to_string()
+                }
+            }
+        )*
+    };
+}
+
+impl_map_key!(true: String, &str, Cow<'_, str>);
+impl_map_key!(false: char, bool, i8, i16, i32, i64, i128, isize);
+impl_map_key!(false: u8, u16, u32, u64, u128, usize);
+
+/// A builder for an object value, created by
+/// [`Value::object`].
+#[derive(Debug, Clone, Default)]
+pub struct ObjectBuilder(Map);
+
+impl ObjectBuilder {
+    /// Adds a member, replacing the value of any member
+    /// with the same key.
+    pub fn set(mut self, key: impl Into<String>, value: impl Into<Value>) -> Self {
+        self.0.insert(key, value.into());
+        self
+    }
+
+    /// Returns the built object.
+    pub fn build(self) -> Value {
+        Value::Object(self.0)
+    }
+}
+
+/// A builder for an array value, created by
+/// [`Value::array`].
+#[derive(Debug, Clone, Default)]
+pub struct ArrayBuilder(Vec<Value>);
+
+impl ArrayBuilder {
+    /// Adds an element at the end.
+    pub fn push(mut self, value: impl Into<Value>) -> Self {
+        self.0.push(value.into());
+        self
+    }
+
+    /// Returns the built array.
+    pub fn build(self) -> Value {
+        Value::Array(self.0)
+    }
+}
+
+impl From<ObjectBuilder> for Value {
+    fn from(builder: ObjectBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<ArrayBuilder> for Value {
+    fn from(builder: ArrayBuilder) -> Self {
+        builder.build()
+    }
+}
+
+impl From<bool> for Value {
+    fn from(bool: bool) -> Self {
+        Value::Boolean(bool)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(string: &str) -> Self {
+        Value::String(string.to_owned())
+    }
+}
+
+impl From<String> for Value {
+    fn from(string: String) -> Self {
+        Value::String(string)
+    }
+}
+
+impl From<Number> for Value {
+    fn from(number: Number) -> Self {
+        Value::Number(number)
+    }
+}
+
+macro_rules! impl_from_number {
+    ($($number:ty),*) => {
+        $(
+            impl From<$number> for Value {
+                fn from(number: $number) -> Self {
+                    Value::Number(number.into())
+                }
+            }
+        )*
+    };
+}
+
+impl_from_number!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, f32, f64);
+
+impl From<Map> for Value {
+    fn from(object: Map) -> Self {
+        Value::Object(object)
+    }
+}
+
+impl From<Vec<Value>> for Value {
+    fn from(array: Vec<Value>) -> Self {
+        Value::Array(array)
+    }
+}
+
+/// Converts a hash map into an object whose members are
+/// sorted by key, so that the result does not depend on
+/// the iteration order of the map. Keys that are not
+/// strings are written as their text.
+impl<K: MapKey, V: Into<Value>, S> From<HashMap<K, V, S>> for Value {
+    fn from(map: HashMap<K, V, S>) -> Self {
+        let mut entries: Vec<_> = map.into_iter().map(|(k, v)| (k.to_key(), v)).collect();
+        entries.sort_by(|a, b| a.0.cmp(&b.0));
+        Value::from_map(entries, KeyPolicy::Stringify).unwrap()
+    }
+}
+
+/// Converts a B-tree map into an object whose members are
+/// in the order of the map. Keys that are not strings are
+/// written as their text.
+impl<K: MapKey, V: Into<Value>> From<BTreeMap<K, V>> for Value {
+    fn from(map: BTreeMap<K, V>) -> Self {
+        Value::from_map(map, KeyPolicy::Stringify).unwrap()
+    }
+}
+
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Self {
+        option.map_or(Value::Null, Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashMap};
+
+    use crate::json::{parse, ErrorKind, Value};
+
+    use super::KeyPolicy;
+
+    #[test]
+    fn builds_nested_documents() {
+        let value = Value::object()
+            .set("name", "server")
+            .set("port", 8080)
+            .set("secure", true)
+            .set("ratio", 0.5)
+            .set("owner", None::<&str>)
+            .set("tags", Value::array().push("a").push(Value::array()))
+            .set("limits", Value::object().set("cpu", 2))
+            .build();
+        let expected = parse(
+            r#"{"name": "server", "port": 8080, "secure": true, "ratio": 0.5,
+                "owner": null, "tags": ["a", []], "limits": {"cpu": 2}}"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn replaces_repeated_keys() {
+        let value = Value::object().set("a", 1).set("b", 2).set("a", 3).build();
+        assert_eq!(value.to_string(), r#"{"a":3,"b":2}"#);
+    }
+
+    #[test]
+    fn builds_empty_containers() {
+        assert_eq!(Value::object().build(), parse("{}").unwrap());
+        assert_eq!(Value::array().build(), parse("[]").unwrap());
+    }
+
+    #[test]
+    fn converts_maps_with_non_string_keys() {
+        let map = HashMap::from([(10, "b"), (2, "a")]);
+        assert_eq!(Value::from(map).to_string(), r#"{"10":"b","2":"a"}"#);
+
+        let map = BTreeMap::from([(10, true), (2, false)]);
+        assert_eq!(Value::from(map).to_string(), r#"{"2":false,"10":true}"#);
+
+        let map = HashMap::from([("a".to_owned(), vec![Value::from(1)])]);
+        assert_eq!(Value::from(map).to_string(), r#"{"a":[1]}"#);
+    }
+
+    #[test]
+    fn rejects_non_string_keys_when_requested() {
+        let value = Value::from_map([("a", 1)], KeyPolicy::Reject).unwrap();
+        assert_eq!(value, parse(r#"{"a": 1}"#).unwrap());
+
+        let error = Value::from_map([('x', 1)], KeyPolicy::Reject).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::NonStringKey("x".into()));
+    }
+}
@@ -0,0 +1,297 @@
+use super::{
+    map::Map,
+    number::Number,
+    options::DEFAULT_MAX_DEPTH,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// Marks the start of every cache.
+const MAGIC: &[u8; 4] = b"JSNC";
+
+/// The version of the cache format. It must be bumped
+/// whenever the encoding changes, so that caches written
+/// by other versions are rejected rather than misread.
+const VERSION: u8 = 1;
+
+const TAG_NULL: u8 = 0;
+const TAG_FALSE: u8 = 1;
+const TAG_TRUE: u8 = 2;
+const TAG_NUMBER: u8 = 3;
+const TAG_STRING: u8 = 4;
+const TAG_ARRAY: u8 = 5;
+const TAG_OBJECT: u8 = 6;
+const TAG_RAW: u8 = 7;
+const TAG_EXT: u8 = 8;
+
+impl Value {
+    /// Encodes the value in a compact binary format that
+    /// can be loaded much faster than JSON text, because
+    /// strings and containers are length-prefixed and
+    /// numbers are stored in their parsed representation.
+    /// The format is private to this crate and versioned;
+    /// it is meant for caching parsed documents, not for
+    /// exchanging them.
+    pub fn to_cache_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::from(&MAGIC[..]);
+        bytes.push(VERSION);
+        write(self, &mut bytes);
+        bytes
+    }
+
+    /// Decodes a value written by `to_cache_bytes`. It will
+    /// return an error under the following conditions:
+    ///
+    /// * The bytes do not begin with the cache header.
+    /// * The cache was written by another format version.
+    /// * The bytes are truncated or otherwise malformed.
+    /// * Arrays and objects are nested more than 128 levels
+    ///   deep, as the default parse options allow.
+    pub fn from_cache_bytes(bytes: &[u8]) -> Result<Value> {
+        if !bytes.starts_with(MAGIC) {
+            return Err(invalid("missing cache header"));
+        }
+
+        let mut reader = Reader {
+            bytes,
+            pos: MAGIC.len(),
+        };
+        if reader.byte()? != VERSION {
+            return Err(invalid("unsupported cache version"));
+        }
+
+        let value = read(&mut reader, 0)?;
+        match reader.pos == bytes.len() {
+            true => Ok(value),
+            false => Err(invalid("trailing bytes")),
+        }
+    }
+}
+
+fn write(value: &Value, bytes: &mut Vec<u8>) {
+    match value {
+        Value::Null => bytes.push(TAG_NULL),
+        Value::Boolean(false) => bytes.push(TAG_FALSE),
+        Value::Boolean(true) => bytes.push(TAG_TRUE),
+        Value::Number(number) => {
+            bytes.push(TAG_NUMBER);
+            number.write_cache(bytes);
+        }
+        Value::String(string) => {
+            bytes.push(TAG_STRING);
+            write_str(string, bytes);
+        }
+        Value::Array(array) => {
+            bytes.push(TAG_ARRAY);
+            write_len(array.len(), bytes);
+            array.iter().for_each(|element| write(element, bytes));
+        }
+        Value::Object(object) => {
+            bytes.push(TAG_OBJECT);
+            write_len(object.len(), bytes);
+            for (key, value) in object {
+                write_str(key, bytes);
+                write(value, bytes);
+            }
+        }
+        Value::Raw(raw) => {
+            bytes.push(TAG_RAW);
+            write_str(raw, bytes);
+        }
+        Value::Ext(tag, value) => {
+            bytes.push(TAG_EXT);
+            write_str(tag, bytes);
+            write(value, bytes);
+        }
+    }
+}
+
+fn write_len(len: usize, bytes: &mut Vec<u8>) {
+    let len = u32::try_from(len).expect("cached values are limited to 4 GiB lengths");
+    bytes.extend_from_slice(&len.to_le_bytes());
+}
+
+fn write_str(string: &str, bytes: &mut Vec<u8>) {
+    write_len(string.len(), bytes);
+    bytes.extend_from_slice(string.as_bytes());
+}
+
+/// Reads a value nested in `depth` containers.
+fn read(reader: &mut Reader, depth: usize) -> Result<Value> {
+    match reader.byte()? {
+        TAG_NULL => Ok(Value::Null),
+        TAG_FALSE => Ok(Value::Boolean(false)),
+        TAG_TRUE => Ok(Value::Boolean(true)),
+        TAG_NUMBER => Ok(Value::Number(Number::read_cache(reader)?)),
+        TAG_STRING => Ok(Value::String(reader.string()?)),
+        TAG_ARRAY => {
+            let depth = nest(depth)?;
+            let len = reader.len()?;
+            let mut array = Vec::with_capacity(len.min(reader.remaining()));
+            for _ in 0..len {
+                array.push(read(reader, depth)?);
+            }
+            Ok(Value::Array(array))
+        }
+        TAG_OBJECT => {
+            let depth = nest(depth)?;
+            let len = reader.len()?;
+            let mut object = Map::with_capacity(len.min(reader.remaining()));
+            for _ in 0..len {
+                let key = reader.string()?;
+                object.insert(key, read(reader, depth)?);
+            }
+            Ok(Value::Object(object))
+        }
+        TAG_RAW => Ok(Value::Raw(reader.string()?)),
+        TAG_EXT => {
+            let depth = nest(depth)?;
+            let tag = reader.string()?;
+            Ok(Value::Ext(tag, Box::new(read(reader, depth)?)))
+        }
+        _ => Err(invalid("unknown value tag")),
+    }
+}
+
+/// Returns the depth of the values inside a container at
+/// the given depth, or an error if they are nested too
+/// deeply to be read without overflowing the stack.
+fn nest(depth: usize) -> Result<usize> {
+    match depth < DEFAULT_MAX_DEPTH {
+        true => Ok(depth + 1),
+        false => Err(Error::new(ErrorKind::TooDeep {
+            max: DEFAULT_MAX_DEPTH,
+        })),
+    }
+}
+
+/// A cursor over the bytes of a cache.
+pub(super) struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    /// Reads the next `len` bytes.
+    pub(super) fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid("unexpected end of cache"))?;
+        let bytes = &self.bytes[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    /// Reads the next `N` bytes as an array.
+    pub(super) fn array<const N: usize>(&mut self) -> Result<[u8; N]> {
+        Ok(self.take(N)?.try_into().unwrap())
+    }
+
+    pub(super) fn byte(&mut self) -> Result<u8> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn len(&mut self) -> Result<usize> {
+        Ok(u32::from_le_bytes(self.array()?) as usize)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        let len = self.len()?;
+        let bytes = self.take(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("invalid UTF-8 in string"))
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.pos
+    }
+}
+
+pub(super) fn invalid(reason: &'static str) -> Error {
+    Error::new(ErrorKind::InvalidCache(reason))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, parse_with, ErrorKind, ParseOptions, Value};
+
+    #[test]
+    fn round_trips_values() {
+        let value =
+            parse(r#"{"a": [1, 2.5, -3e10, "x\ny"], "b": {"c": null, "d": true}, "e": false}"#)
+                .unwrap();
+        let bytes = value.to_cache_bytes();
+        assert_eq!(Value::from_cache_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn round_trips_tagged_values() {
+        let value = Value::Ext("$oid".into(), Box::new(Value::from("5f1d")));
+        let bytes = value.to_cache_bytes();
+        assert_eq!(Value::from_cache_bytes(&bytes).unwrap(), value);
+    }
+
+    #[test]
+    fn round_trips_exact_integers() {
+        let options = ParseOptions {
+            exact_integers: true,
+            ..Default::default()
+        };
+        let value = parse_with("[170141183460469231731687303715884105727, -1]", &options).unwrap();
+        let bytes = value.to_cache_bytes();
+        let decoded = Value::from_cache_bytes(&bytes).unwrap();
+        assert_eq!(decoded, value);
+        assert_eq!(decoded.to_string(), value.to_string());
+    }
+
+    #[test]
+    fn rejects_missing_header() {
+        let error = Value::from_cache_bytes(b"[1, 2]").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::InvalidCache("missing cache header")
+        );
+    }
+
+    #[test]
+    fn rejects_other_versions() {
+        let mut bytes = Value::Null.to_cache_bytes();
+        bytes[4] = 0;
+        let error = Value::from_cache_bytes(&bytes).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::InvalidCache("unsupported cache version")
+        );
+    }
+
+    #[test]
+    fn rejects_truncated_cache() {
+        let bytes = parse(r#"["abc"]"#).unwrap().to_cache_bytes();
+        for len in 0..bytes.len() {
+            assert!(Value::from_cache_bytes(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn rejects_trailing_bytes() {
+        let mut bytes = Value::Null.to_cache_bytes();
+        bytes.push(0);
+        assert!(Value::from_cache_bytes(&bytes).is_err());
+    }
+
+    #[test]
+    fn rejects_deeply_nested_values() {
+        let deep = "[".repeat(128) + &"]".repeat(128);
+        let bytes = parse(&deep).unwrap().to_cache_bytes();
+        assert!(Value::from_cache_bytes(&bytes).is_ok());
+
+        let mut bytes = Value::Null.to_cache_bytes();
+        bytes.pop();
+        for _ in 0..100_000 {
+            bytes.extend_from_slice(&[super::TAG_ARRAY, 1, 0, 0, 0]);
+        }
+        bytes.push(super::TAG_NULL);
+        let error = Value::from_cache_bytes(&bytes).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TooDeep { max: 128 });
+    }
+}
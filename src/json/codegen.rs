@@ -0,0 +1,461 @@
+use super::{infer::infer_schema, map::Map, number::Number, pointer::escape_token, types::Value};
+
+/// The indentation of each level of generated code.
+const INDENT: &str = "    ";
+
+/// The words that cannot be used as Rust identifiers
+/// without the `r#` prefix.
+const KEYWORDS: [&str; 38] = [
+    "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum", "extern",
+    "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move", "mut", "pub",
+    "ref", "return", "static", "struct", "trait", "true", "type", "unsafe", "use", "where",
+    "while", "abstract", "final", "try",
+];
+
+/// Options that control the code written by
+/// [`generate_rust_types`].
+#[derive(Debug, Clone, Default)]
+pub struct RustTypesOptions {
+    /// The name of the type of the samples themselves. When
+    /// `None`, the type is named `Root`.
+    pub root_name: Option<String>,
+    /// Whether the structs derive serde's `Serialize` and
+    /// `Deserialize`, with `rename` attributes for members
+    /// whose keys are not valid field names.
+    pub serde: bool,
+    /// Whether an implementation of [`FromValue`] is written
+    /// for each struct, so that values of this crate can be
+    /// converted into the structs.
+    ///
+    /// [`FromValue`]: super::FromValue
+    pub from_value: bool,
+}
+
+/// Writes Rust code that constructs a value with the
+/// builders of this crate, so that a captured fixture can
+/// be pasted into a test as code rather than as embedded
+/// text. Integers that do not fit in an `i32` are given a
+/// type suffix, and other numbers are written as floats.
+/// Arrays and objects are written with one element or
+/// member per line, and nested builders are passed as
+/// values without calling `build`.
+pub fn to_rust_literal(value: &Value) -> String {
+    let mut code = String::new();
+    match value {
+        Value::Array(_) | Value::Object(_) => {
+            write_argument(value, 0, &mut code);
+            match code.contains('\n') {
+                true => code.push_str(&format!("\n{}.build()", INDENT)),
+                false => code.push_str(".build()"),
+            }
+        }
+        Value::Boolean(true) => code.push_str("Value::TRUE"),
+        Value::Boolean(false) => code.push_str("Value::FALSE"),
+        Value::Null | Value::Raw(_) | Value::Ext(..) => write_argument(value, 0, &mut code),
+        Value::String(_) | Value::Number(_) => {
+            code.push_str("Value::from(");
+            write_argument(value, 0, &mut code);
+            code.push(')');
+        }
+    }
+    code
+}
+
+/// Writes code for a value passed to `set` or `push`, which
+/// accept anything that converts into a value.
+fn write_argument(value: &Value, depth: usize, code: &mut String) {
+    match value {
+        Value::String(string) => code.push_str(&format!("{:?}", string)),
+        Value::Number(number) => code.push_str(&number_literal(number)),
+        Value::Boolean(bool) => code.push_str(&bool.to_string()),
+        Value::Null => code.push_str("Value::Null"),
+        Value::Raw(raw) => code.push_str(&format!("Value::Raw({:?}.to_owned())", raw)),
+        Value::Ext(tag, value) => {
+            code.push_str(&format!(
+                "Value::Ext({:?}.to_owned(), Box::new(Value::from(",
+                tag
+            ));
+            write_argument(value, depth, code);
+            code.push_str(")))");
+        }
+        Value::Array(array) if array.is_empty() => code.push_str("Value::array()"),
+        Value::Object(object) if object.is_empty() => code.push_str("Value::object()"),
+        Value::Array(_) | Value::Object(_) => write_container(value, depth, code),
+    }
+}
+
+/// Writes a builder for a non-empty array or object, with
+/// each call on its own line one level deeper than `depth`.
+fn write_container(value: &Value, depth: usize, code: &mut String) {
+    let indent = INDENT.repeat(depth + 1);
+    match value {
+        Value::Array(array) => {
+            code.push_str("Value::array()");
+            for element in array {
+                code.push_str(&format!("\n{}.push(", indent));
+                write_argument(element, depth + 1, code);
+                code.push(')');
+            }
+        }
+        Value::Object(object) => {
+            code.push_str("Value::object()");
+            for (key, member) in object {
+                code.push_str(&format!("\n{}.set({:?}, ", indent, key));
+                write_argument(member, depth + 1, code);
+                code.push(')');
+            }
+        }
+        _ => unreachable!(),
+    }
+}
+
+/// Writes a number as a Rust literal of a type that holds
+/// it, so that it converts into the same number.
+fn number_literal(number: &Number) -> String {
+    if number.is_exact_integer() {
+        return match (number.as_i128(), number.as_u128()) {
+            (Some(int), _) if i32::try_from(int).is_ok() => int.to_string(),
+            (Some(int), _) if i64::try_from(int).is_ok() => format!("{}_i64", int),
+            (Some(int), _) => format!("{}_i128", int),
+            (None, Some(uint)) => format!("{}_u128", uint),
+            (None, None) => unreachable!(),
+        };
+    }
+    format!("{:?}", number.as_f64())
+}
+
+/// Writes Rust struct definitions that match the sample
+/// values, in the manner of quicktype. A JSON Schema is
+/// inferred from the samples, as by [`infer_schema`], and
+/// each object in it becomes a struct named after the key
+/// under which it was found. Members that are missing from
+/// some samples or are sometimes null become `Option`s,
+/// integers become `i64`, other numbers `f64`, and
+/// locations where several types were seen become
+/// [`Value`]s. If the samples are not objects, the root is
+/// a type alias.
+pub fn generate_rust_types<'a>(
+    samples: impl IntoIterator<Item = &'a Value>,
+    options: &RustTypesOptions,
+) -> String {
+    let schema = infer_schema(samples);
+    let root_name = options.root_name.as_deref().unwrap_or("Root");
+    let mut generator = TypeGenerator {
+        options,
+        structs: vec![],
+        names: vec![],
+    };
+    let root = generator.type_of(&schema, root_name);
+
+    let mut code = String::new();
+    if root != root_name {
+        code.push_str(&format!("pub type {} = {};\n", root_name, root));
+    }
+    for definition in generator.structs {
+        if !code.is_empty() {
+            code.push('\n');
+        }
+        code.push_str(&definition);
+    }
+    code
+}
+
+struct TypeGenerator<'a> {
+    options: &'a RustTypesOptions,
+    /// The code of each struct written so far.
+    structs: Vec<String>,
+    /// The names of the structs, so that they are unique.
+    names: Vec<String>,
+}
+
+impl TypeGenerator<'_> {
+    /// Returns the Rust type for a schema, writing structs
+    /// for any objects it describes. The name is used for
+    /// the struct of an object.
+    fn type_of(&mut self, schema: &Value, name: &str) -> String {
+        let mut types: Vec<&str> = match schema.pointer("/type") {
+            Some(Value::String(type_)) => vec![type_],
+            Some(Value::Array(types)) => types
+                .iter()
+                .filter_map(|type_| match type_ {
+                    Value::String(type_) => Some(type_.as_str()),
+                    _ => None,
+                })
+                .collect(),
+            _ => vec![],
+        };
+        let nullable = types.contains(&"null");
+        types.retain(|type_| *type_ != "null");
+
+        let type_ = match types.as_slice() {
+            ["boolean"] => "bool".to_owned(),
+            ["integer"] => "i64".to_owned(),
+            ["number"] => "f64".to_owned(),
+            ["string"] => "String".to_owned(),
+            ["array"] => match schema.pointer("/items") {
+                Some(items) => format!("Vec<{}>", self.type_of(items, &singular(name))),
+                None => "Vec<json::Value>".to_owned(),
+            },
+            ["object"] => match schema.pointer("/properties") {
+                Some(Value::Object(properties)) => self.write_struct(schema, properties, name),
+                _ => "json::Map".to_owned(),
+            },
+            [] if nullable => return "Option<json::Value>".to_owned(),
+            _ => return "json::Value".to_owned(),
+        };
+        match nullable {
+            true => format!("Option<{}>", type_),
+            false => type_,
+        }
+    }
+
+    /// Writes a struct for an object schema, and returns
+    /// its name.
+    fn write_struct(&mut self, schema: &Value, properties: &Map, name: &str) -> String {
+        let mut name = pascal_case(name);
+        if self.names.contains(&name) {
+            let base = name.clone();
+            let mut suffix = 2;
+            while self.names.contains(&name) {
+                name = format!("{}{}", base, suffix);
+                suffix += 1;
+            }
+        }
+        self.names.push(name.clone());
+
+        let required: Vec<&Value> = match schema.pointer("/required") {
+            Some(Value::Array(required)) => required.iter().collect(),
+            _ => vec![],
+        };
+        let mut fields = vec![];
+        for (key, property) in properties {
+            let mut type_ = self.type_of(property, key);
+            let is_required = required.contains(&&Value::String(key.clone()));
+            if !is_required && !type_.starts_with("Option<") {
+                type_ = format!("Option<{}>", type_);
+            }
+            fields.push((key.clone(), field_name(key), type_));
+        }
+
+        let mut code = String::new();
+        match self.options.serde {
+            true => code.push_str(
+                "#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]\n",
+            ),
+            false => code.push_str("#[derive(Debug, Clone, PartialEq)]\n"),
+        }
+        code.push_str(&format!("pub struct {} {{\n", name));
+        for (key, field, type_) in &fields {
+            if self.options.serde && field.trim_start_matches("r#") != key {
+                code.push_str(&format!("{}#[serde(rename = {:?})]\n", INDENT, key));
+            }
+            code.push_str(&format!("{}pub {}: {},\n", INDENT, field, type_));
+        }
+        code.push_str("}\n");
+
+        if self.options.from_value {
+            code.push_str(&format!("\nimpl json::FromValue for {} {{\n", name));
+            code.push_str(&format!(
+                "{0}fn from_value(value: &json::Value) -> json::Result<Self> {{\n{0}{0}Ok(Self {{\n",
+                INDENT
+            ));
+            for (key, field, _) in &fields {
+                code.push_str(&format!(
+                    "{0}{0}{0}{1}: json::FromValue::from_value(\n{0}{0}{0}{0}value.pointer({2:?}).unwrap_or(&json::Value::Null),\n{0}{0}{0})?,\n",
+                    INDENT,
+                    field,
+                    format!("/{}", escape_token(key)),
+                ));
+            }
+            code.push_str(&format!("{0}{0}}})\n{0}}}\n}}\n", INDENT));
+        }
+
+        self.structs.push(code);
+        name
+    }
+}
+
+/// Converts a key into a field name in snake case, such as
+/// `user_id` for `userId`. Characters that cannot appear in
+/// identifiers are replaced with underscores.
+fn field_name(key: &str) -> String {
+    let mut name = String::new();
+    let mut previous_lower = false;
+    for char in key.chars() {
+        if char.is_ascii_uppercase() {
+            if previous_lower {
+                name.push('_');
+            }
+            name.push(char.to_ascii_lowercase());
+        } else if char.is_ascii_alphanumeric() {
+            name.push(char);
+        } else if !name.ends_with('_') {
+            name.push('_');
+        }
+        previous_lower = char.is_ascii_lowercase() || char.is_ascii_digit();
+    }
+
+    let name = name.trim_matches('_');
+    match name {
+        "" => "field".to_owned(),
+        name if name.starts_with(|char: char| char.is_ascii_digit()) => format!("field_{}", name),
+        "self" | "Self" | "super" | "crate" => format!("{}_", name),
+        name if KEYWORDS.contains(&name) => format!("r#{}", name),
+        name => name.to_owned(),
+    }
+}
+
+/// Converts a key into a type name in Pascal case, such as
+/// `UserId` for `user_id`.
+fn pascal_case(key: &str) -> String {
+    let name: String = key
+        .split(|char: char| !char.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let mut chars = word.chars();
+            let first = chars.next().unwrap().to_ascii_uppercase();
+            std::iter::once(first).chain(chars).collect::<String>()
+        })
+        .collect();
+    match name.starts_with(|char: char| char.is_ascii_alphabetic()) {
+        true => name,
+        false => format!("Type{}", name),
+    }
+}
+
+/// Returns the name for the elements of an array found
+/// under a key, such as `tag` for `tags`.
+fn singular(name: &str) -> String {
+    match name.strip_suffix('s') {
+        Some(stem) if !stem.is_empty() && !stem.ends_with('s') => stem.to_owned(),
+        _ => format!("{}Item", pascal_case(name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, parse_with, ParseOptions, Value};
+
+    use super::{generate_rust_types, to_rust_literal, RustTypesOptions};
+
+    #[test]
+    fn writes_builder_code() {
+        let value = parse(r#"{"a": [1.5, "x\"y", null, {}], "b": {"c": true}, "d": []}"#).unwrap();
+        assert_eq!(
+            to_rust_literal(&value),
+            r#"Value::object()
+    .set("a", Value::array()
+        .push(1.5)
+        .push("x\"y")
+        .push(Value::Null)
+        .push(Value::object()))
+    .set("b", Value::object()
+        .set("c", true))
+    .set("d", Value::array())
+    .build()"#
+        );
+
+        let built = Value::object()
+            .set(
+                "a",
+                Value::array()
+                    .push(1.5)
+                    .push("x\"y")
+                    .push(Value::Null)
+                    .push(Value::object()),
+            )
+            .set("b", Value::object().set("c", true))
+            .set("d", Value::array())
+            .build();
+        assert_eq!(built, value);
+    }
+
+    #[test]
+    fn writes_scalars_and_wide_integers() {
+        assert_eq!(to_rust_literal(&parse("1").unwrap()), "Value::from(1.0)");
+        assert_eq!(to_rust_literal(&parse("null").unwrap()), "Value::Null");
+        assert_eq!(to_rust_literal(&parse("false").unwrap()), "Value::FALSE");
+        assert_eq!(
+            to_rust_literal(&parse("[]").unwrap()),
+            "Value::array().build()"
+        );
+
+        let options = ParseOptions {
+            exact_integers: true,
+            ..Default::default()
+        };
+        let value = parse_with(
+            "[1, -3000000000, 1e20, 340282366920938463463374607431768211455]",
+            &options,
+        )
+        .unwrap();
+        assert_eq!(
+            to_rust_literal(&value),
+            "Value::array()\n    .push(1)\n    .push(-3000000000_i64)\n    .push(1e20)\n    .push(340282366920938463463374607431768211455_u128)\n    .build()"
+        );
+    }
+
+    #[test]
+    fn generates_structs_from_samples() {
+        let samples = [
+            parse(r#"{"id": 1, "userName": "a", "tags": [{"name": "x"}], "type": null}"#).unwrap(),
+            parse(r#"{"id": 2, "userName": "b", "tags": [], "score": 1.5, "type": "t"}"#).unwrap(),
+        ];
+        let options = RustTypesOptions {
+            serde: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_rust_types(&samples, &options),
+            r#"#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Tag {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Root {
+    pub id: i64,
+    #[serde(rename = "userName")]
+    pub user_name: String,
+    pub tags: Vec<Tag>,
+    pub r#type: Option<String>,
+    pub score: Option<f64>,
+}
+"#
+        );
+    }
+
+    #[test]
+    fn generates_from_value_impls_and_aliases() {
+        let samples = [parse(r#"[{"a/b": true}]"#).unwrap()];
+        let options = RustTypesOptions {
+            root_name: Some("Flags".to_owned()),
+            from_value: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            generate_rust_types(&samples, &options),
+            r#"pub type Flags = Vec<Flag>;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Flag {
+    pub a_b: bool,
+}
+
+impl json::FromValue for Flag {
+    fn from_value(value: &json::Value) -> json::Result<Self> {
+        Ok(Self {
+            a_b: json::FromValue::from_value(
+                value.pointer("/a~1b").unwrap_or(&json::Value::Null),
+            )?,
+        })
+    }
+}
+"#
+        );
+        assert_eq!(
+            generate_rust_types(&[parse("1").unwrap()], &RustTypesOptions::default()),
+            "pub type Root = i64;\n"
+        );
+    }
+}
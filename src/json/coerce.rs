@@ -0,0 +1,83 @@
+use super::types::{Error, ErrorKind, Result, Value};
+
+impl Value {
+    /// Converts the value to a string, for dealing with
+    /// sources that are inconsistent about types. Strings
+    /// are returned as they are, while numbers and booleans
+    /// are converted to their JSON text, so `1` becomes
+    /// `"1"` and `true` becomes `"true"`. It will return an
+    /// error if the value is null, an array, or an object.
+    pub fn coerce_str(&self) -> Result<String> {
+        match self {
+            Value::String(string) => Ok(string.clone()),
+            Value::Number(number) => Ok(number.to_string()),
+            Value::Boolean(bool) => Ok(bool.to_string()),
+            _ => Err(Error::new(ErrorKind::UnexpectedType(
+                "string, number, or boolean",
+            ))),
+        }
+    }
+
+    /// Converts the value to a float, for dealing with
+    /// sources that are inconsistent about types. Numbers
+    /// are returned as they are, and strings are parsed as
+    /// decimal numbers after trimming whitespace, so `"42"`
+    /// becomes `42`. It will return an error under the
+    /// following conditions:
+    ///
+    /// * The value is not a number or a string.
+    /// * The string is not a finite decimal number.
+    pub fn coerce_f64(&self) -> Result<f64> {
+        match self {
+            Value::Number(number) => Ok(number.as_f64()),
+            Value::String(string) => string
+                .trim()
+                .parse::<f64>()
+                .ok()
+                .filter(|float| float.is_finite())
+                .ok_or_else(|| Error::new(ErrorKind::UnexpectedType("numeric string"))),
+            _ => Err(Error::new(ErrorKind::UnexpectedType("number or string"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    #[test]
+    fn coerces_to_strings() {
+        let value = parse(r#"["a", 1, 2.5, true]"#).unwrap();
+        let Value::Array(array) = value else {
+            unreachable!()
+        };
+        let strings: Vec<_> = array
+            .iter()
+            .map(|value| value.coerce_str().unwrap())
+            .collect();
+        assert_eq!(strings, ["a", "1", "2.5", "true"]);
+    }
+
+    #[test]
+    fn rejects_structured_values_as_strings() {
+        assert!(Value::Null.coerce_str().is_err());
+        assert!(parse("[]").unwrap().coerce_str().is_err());
+    }
+
+    #[test]
+    fn coerces_to_floats() {
+        assert_eq!(parse(r#""42""#).unwrap().coerce_f64().unwrap(), 42.0);
+        assert_eq!(
+            parse(r#"" -1.5e2 ""#).unwrap().coerce_f64().unwrap(),
+            -150.0
+        );
+        assert_eq!(parse("7").unwrap().coerce_f64().unwrap(), 7.0);
+    }
+
+    #[test]
+    fn rejects_non_numeric_strings() {
+        assert!(parse(r#""abc""#).unwrap().coerce_f64().is_err());
+        assert!(parse(r#""NaN""#).unwrap().coerce_f64().is_err());
+        assert!(Value::Boolean(true).coerce_f64().is_err());
+    }
+}
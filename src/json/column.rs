@@ -0,0 +1,64 @@
+use super::{extract::FromValue, pointer::JsonPointer, types::Value};
+
+impl Value {
+    /// Returns the value at a JSON pointer in each element
+    /// of an array, such as the price of every item in a
+    /// list of orders. An element is `None` if the value is
+    /// missing there or the pointer is invalid. Returns an
+    /// empty list if the value is not an array.
+    pub fn column(&self, pointer: &str) -> Vec<Option<&Value>> {
+        let Value::Array(elements) = self else {
+            return vec![];
+        };
+        let pointer = JsonPointer::parse(pointer).ok();
+        elements
+            .iter()
+            .map(|element| pointer.as_ref()?.resolve(element))
+            .collect()
+    }
+
+    /// Returns the values in a column, as by
+    /// [`column`](Self::column), converted to a type. An
+    /// element is `None` if the value is missing, null, or
+    /// cannot be converted.
+    pub fn column_as<T: FromValue>(&self, pointer: &str) -> Vec<Option<T>> {
+        self.column(pointer)
+            .into_iter()
+            .map(|value| T::from_value(value?).ok())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    const TEXT: &str = r#"[
+        {"item": {"price": 1.5}},
+        {"item": {"price": null}},
+        {"item": {}},
+        {"item": {"price": "free"}},
+        {"item": {"price": 3}}
+    ]"#;
+
+    #[test]
+    fn extracts_column() {
+        let value = parse(TEXT).unwrap();
+        let column = value.column("/item/price");
+        assert_eq!(column.len(), 5);
+        assert_eq!(column[1], Some(&Value::Null));
+        assert_eq!(column[2], None);
+        assert_eq!(value.column("item").len(), 5);
+        assert!(value.column("item").iter().all(Option::is_none));
+        assert!(parse("{}").unwrap().column("/a").is_empty());
+    }
+
+    #[test]
+    fn extracts_typed_column() {
+        let value = parse(TEXT).unwrap();
+        let prices = value.column_as::<f64>("/item/price");
+        assert_eq!(prices, [Some(1.5), None, None, None, Some(3.0)]);
+        let total: f64 = prices.iter().flatten().sum();
+        assert_eq!(total, 4.5);
+    }
+}
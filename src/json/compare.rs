@@ -0,0 +1,290 @@
+use std::collections::VecDeque;
+
+use super::{parse, types::Value};
+
+/// Options that control how two documents are compared by
+/// [`semantic_eq`]. The default options compare documents
+/// strictly, so that only structurally identical values
+/// are considered equal.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CompareOptions {
+    /// Whether array elements must appear in the same
+    /// order to be considered equal.
+    pub ordered_arrays: bool,
+    /// The largest absolute difference at which two
+    /// numbers are still considered equal.
+    pub numeric_tolerance: f64,
+    /// Whether an object member with a null value is
+    /// considered equal to a missing member.
+    pub null_equals_absent: bool,
+}
+
+impl Default for CompareOptions {
+    fn default() -> Self {
+        Self {
+            ordered_arrays: true,
+            numeric_tolerance: 0.0,
+            null_equals_absent: false,
+        }
+    }
+}
+
+/// Compares two JSON values for equality under the given
+/// options. Object member order is never significant.
+/// When array order is ignored, each element of one array
+/// must be matched by a distinct element of the other.
+/// Raw text is parsed and compared as the value it holds,
+/// and is otherwise only equal to the same text. Tagged
+/// values are equal to the objects they are written as.
+pub fn semantic_eq(a: &Value, b: &Value, options: CompareOptions) -> bool {
+    match (a, b) {
+        (Value::Raw(a), Value::Raw(b)) if a == b => true,
+        (Value::Raw(raw), other) | (other, Value::Raw(raw)) => match parse(raw) {
+            Ok(raw) => semantic_eq(&raw, other, options),
+            Err(_) => false,
+        },
+        (Value::String(a), Value::String(b)) => a == b,
+        (Value::Number(a), Value::Number(b)) => {
+            a == b || (a.as_f64() - b.as_f64()).abs() <= options.numeric_tolerance
+        }
+        (Value::Boolean(a), Value::Boolean(b)) => a == b,
+        (Value::Null, Value::Null) => true,
+        (Value::Array(a), Value::Array(b)) => arrays_eq(a, b, options),
+        (Value::Object(_) | Value::Ext(..), Value::Object(_) | Value::Ext(..)) => {
+            objects_eq(a, b, options)
+        }
+        _ => false,
+    }
+}
+
+/// Compares two objects, either of which may be a tagged
+/// value, which is compared as the object with a single
+/// member that it is written as.
+fn objects_eq(a: &Value, b: &Value, options: CompareOptions) -> bool {
+    let members_match = |from: &Value, to: &Value| {
+        from.entries().all(|(key, value)| match member(to, key) {
+            Some(other) => semantic_eq(value, other, options),
+            None => options.null_equals_absent && matches!(value, Value::Null),
+        })
+    };
+
+    members_match(a, b) && members_match(b, a)
+}
+
+fn member<'a>(object: &'a Value, key: &str) -> Option<&'a Value> {
+    match object {
+        Value::Object(object) => object.get(key),
+        Value::Ext(tag, value) if tag == key => Some(value),
+        _ => None,
+    }
+}
+
+fn arrays_eq(a: &[Value], b: &[Value], options: CompareOptions) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+
+    if options.ordered_arrays {
+        return a.iter().zip(b).all(|(a, b)| semantic_eq(a, b, options));
+    }
+
+    // Matching each element to the first equal one is
+    // enough when equality is transitive, as it is for
+    // strict comparisons. Tolerances make it intransitive,
+    // so when that fails, the elements are matched as a
+    // bipartite graph instead.
+    let mut matched = vec![false; b.len()];
+    let greedy = a.iter().all(|a| {
+        let position = b
+            .iter()
+            .enumerate()
+            .position(|(i, b)| !matched[i] && semantic_eq(a, b, options));
+        position.map(|i| matched[i] = true).is_some()
+    });
+    greedy || has_perfect_matching(a, b, options)
+}
+
+/// Returns whether every element of `a` can be paired with a
+/// distinct equal element of `b`, by finding an augmenting
+/// path from each element of `a` in turn.
+fn has_perfect_matching(a: &[Value], b: &[Value], options: CompareOptions) -> bool {
+    let equal: Vec<Vec<usize>> = a
+        .iter()
+        .map(|a| {
+            (0..b.len())
+                .filter(|&j| semantic_eq(a, &b[j], options))
+                .collect()
+        })
+        .collect();
+    let mut partner_of_a: Vec<Option<usize>> = vec![None; a.len()];
+    let mut partner_of_b: Vec<Option<usize>> = vec![None; b.len()];
+
+    for start in 0..a.len() {
+        let mut reached_from: Vec<Option<usize>> = vec![None; b.len()];
+        let mut queue = VecDeque::from([start]);
+        let mut free = None;
+        'search: while let Some(i) = queue.pop_front() {
+            for &j in &equal[i] {
+                if reached_from[j].is_some() {
+                    continue;
+                }
+                reached_from[j] = Some(i);
+                match partner_of_b[j] {
+                    Some(partner) => queue.push_back(partner),
+                    None => {
+                        free = Some(j);
+                        break 'search;
+                    }
+                }
+            }
+        }
+
+        let Some(mut j) = free else {
+            return false;
+        };
+        loop {
+            let i = reached_from[j].unwrap();
+            let previous = partner_of_a[i].replace(j);
+            partner_of_b[j] = Some(i);
+            match previous {
+                Some(previous) => j = previous,
+                None => break,
+            }
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    use super::{semantic_eq, CompareOptions};
+
+    fn compare(a: &str, b: &str, options: CompareOptions) -> bool {
+        semantic_eq(&parse(a).unwrap(), &parse(b).unwrap(), options)
+    }
+
+    #[test]
+    fn ignores_member_order() {
+        let options = CompareOptions::default();
+        assert!(compare(
+            r#"{"a": 1, "b": 2}"#,
+            r#"{"b": 2, "a": 1}"#,
+            options
+        ));
+    }
+
+    #[test]
+    fn respects_array_order_by_default() {
+        let options = CompareOptions::default();
+        assert!(!compare("[1, 2]", "[2, 1]", options));
+    }
+
+    #[test]
+    fn ignores_array_order_when_requested() {
+        let options = CompareOptions {
+            ordered_arrays: false,
+            ..Default::default()
+        };
+        assert!(compare("[1, [2, 3], 1]", "[[3, 2], 1, 1]", options));
+    }
+
+    #[test]
+    fn matches_unordered_elements_once() {
+        let options = CompareOptions {
+            ordered_arrays: false,
+            ..Default::default()
+        };
+        assert!(!compare("[1, 1, 2]", "[1, 2, 2]", options));
+    }
+
+    #[test]
+    fn compares_numbers_within_tolerance() {
+        let options = CompareOptions {
+            numeric_tolerance: 0.01,
+            ..Default::default()
+        };
+        assert!(compare("1.005", "1", options));
+        assert!(!compare("1.02", "1", options));
+    }
+
+    #[test]
+    fn distinguishes_null_from_absent_by_default() {
+        let options = CompareOptions::default();
+        assert!(!compare(r#"{"a": null}"#, "{}", options));
+    }
+
+    #[test]
+    fn treats_null_as_absent_when_requested() {
+        let options = CompareOptions {
+            null_equals_absent: true,
+            ..Default::default()
+        };
+        assert!(compare(r#"{"a": null, "b": 1}"#, r#"{"b": 1}"#, options));
+        assert!(compare(r#"{"b": 1}"#, r#"{"a": null, "b": 1}"#, options));
+    }
+
+    #[test]
+    fn rejects_different_types() {
+        let options = CompareOptions::default();
+        assert!(!compare("1", r#""1""#, options));
+    }
+
+    #[test]
+    fn compares_raw_text_as_its_value() {
+        let options = CompareOptions::default();
+        let raw = |text: &str| Value::Raw(text.to_owned());
+        assert!(semantic_eq(&raw("[1, 2]"), &raw("[1, 2]"), options));
+        assert!(semantic_eq(&raw("[1, 2]"), &raw("[1,2]"), options));
+        assert!(semantic_eq(
+            &raw(r#"{"a": 1}"#),
+            &parse(r#"{"a":1}"#).unwrap(),
+            options
+        ));
+        assert!(semantic_eq(
+            &parse("[1, 2]").unwrap(),
+            &raw("[1,2]"),
+            options
+        ));
+        assert!(!semantic_eq(&raw("[1, 2]"), &raw("[2, 1]"), options));
+        assert!(semantic_eq(&raw("[1,"), &raw("[1,"), options));
+        assert!(!semantic_eq(&raw("[1,"), &raw("[1, "), options));
+    }
+
+    #[test]
+    fn compares_tagged_values() {
+        let options = CompareOptions {
+            numeric_tolerance: 0.01,
+            ..Default::default()
+        };
+        let ext = |tag: &str, value| Value::Ext(tag.into(), Box::new(Value::from(value)));
+        assert!(semantic_eq(&ext("$n", 1.005), &ext("$n", 1.0), options));
+        assert!(!semantic_eq(&ext("$n", 1.0), &ext("$m", 1.0), options));
+        assert!(!semantic_eq(&ext("$n", 1.0), &ext("$n", 2.0), options));
+
+        let object = parse(r#"{"$n": 1}"#).unwrap();
+        assert!(semantic_eq(&ext("$n", 1.0), &object, options));
+        assert!(semantic_eq(&object, &ext("$n", 1.005), options));
+        assert!(!semantic_eq(&ext("$m", 1.0), &object, options));
+        let object = parse(r#"{"$n": 1, "b": null}"#).unwrap();
+        assert!(!semantic_eq(&ext("$n", 1.0), &object, options));
+        let options = CompareOptions {
+            null_equals_absent: true,
+            ..options
+        };
+        assert!(semantic_eq(&ext("$n", 1.0), &object, options));
+    }
+
+    #[test]
+    fn matches_unordered_elements_within_tolerance() {
+        let options = CompareOptions {
+            ordered_arrays: false,
+            numeric_tolerance: 0.5,
+            ..Default::default()
+        };
+        assert!(compare("[1.0, 1.5]", "[1.4, 0.9]", options));
+        assert!(compare("[1, 2, 3]", "[2.4, 3.4, 1.4]", options));
+        assert!(!compare("[1.0, 1.1]", "[1.4, 2.0]", options));
+    }
+}
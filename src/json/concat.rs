@@ -0,0 +1,59 @@
+use std::io::{Read, Write};
+
+use super::{
+    events::{Event, EventReader, EventWriter},
+    types::Result,
+};
+
+/// Streams several JSON texts into a single JSON array
+/// whose elements are the top-level values of the texts, in
+/// order. The texts are copied event by event, so none of
+/// them is held in memory. It will return an error if any
+/// text is not valid JSON or if reading or writing fails,
+/// in which case part of the array may already have been
+/// written.
+pub fn concat_to_array<R: Read>(
+    readers: impl IntoIterator<Item = R>,
+    writer: impl Write,
+) -> Result<()> {
+    let mut writer = EventWriter::new(writer);
+    writer.write(&Event::StartArray)?;
+    for reader in readers {
+        for event in EventReader::new(reader) {
+            writer.write(&event?)?;
+        }
+    }
+    writer.write(&Event::EndArray)?;
+    writer.into_inner()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::ErrorKind;
+
+    use super::concat_to_array;
+
+    fn concat(texts: &[&str]) -> crate::json::Result<String> {
+        let mut output = vec![];
+        concat_to_array(texts.iter().map(|text| text.as_bytes()), &mut output)?;
+        Ok(String::from_utf8(output).unwrap())
+    }
+
+    #[test]
+    fn concatenates_documents() {
+        assert_eq!(
+            concat(&[r#"{"a": 1}"#, " [2, 3] ", "\"x\"", "null"]).unwrap(),
+            r#"[{"a":1},[2,3],"x",null]"#
+        );
+        assert_eq!(concat(&[]).unwrap(), "[]");
+    }
+
+    #[test]
+    fn rejects_invalid_documents() {
+        for text in ["", "[1", "1 2"] {
+            let error = concat(&["1", text]).unwrap_err();
+            assert_eq!(error.kind(), &ErrorKind::Syntax, "{}", text);
+        }
+    }
+}
@@ -0,0 +1,256 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    cst::parse_cst,
+    env::expand_env,
+    map::Map,
+    parse,
+    types::{Error, ErrorKind, Result, Value},
+    update::UpdateRules,
+    utf8::decode,
+};
+
+/// The member that includes other files into an object.
+const INCLUDE: &str = "$include";
+
+/// Options that control how configuration files are
+/// loaded by [`load_config`].
+#[derive(Debug, Clone)]
+pub struct ConfigOptions {
+    /// Whether files may contain comments, as in JSONC.
+    pub allow_comments: bool,
+    /// Whether `${VAR}` references to environment variables
+    /// are expanded after all files have been merged.
+    pub expand_env: bool,
+    /// Files that are loaded after the main file and
+    /// merged over it in order, such as per-environment
+    /// overrides.
+    pub overlays: Vec<PathBuf>,
+}
+
+impl Default for ConfigOptions {
+    fn default() -> Self {
+        Self {
+            allow_comments: true,
+            expand_env: true,
+            overlays: vec![],
+        }
+    }
+}
+
+/// Loads a configuration file. An object may include other
+/// files with an `"$include"` member, whose value is a path
+/// or an array of paths relative to the including file.
+/// Included files are merged in order, and the other
+/// members of the object are merged over them. Overlays are
+/// merged over the main file in the same way. Merging
+/// follows JSON Merge Patch: objects are merged member by
+/// member, null deletes a member, and other values replace
+/// it. It will return an error under the following
+/// conditions:
+///
+/// * A file cannot be read or is not valid JSON.
+/// * A file includes itself, directly or indirectly.
+/// * An `"$include"` member is not a path or paths.
+/// * An environment variable is not set and has no default.
+pub fn load_config(path: impl AsRef<Path>, options: &ConfigOptions) -> Result<Value> {
+    let mut config = load_file(path.as_ref(), options, &mut vec![])?;
+    for overlay in &options.overlays {
+        let overlay = load_file(overlay, options, &mut vec![])?;
+        merge(&mut config, overlay);
+    }
+
+    if options.expand_env {
+        expand_env(&mut config)?;
+    }
+    Ok(config)
+}
+
+/// Loads a file and its includes. The stack holds the files
+/// that are being loaded, to detect include cycles.
+fn load_file(path: &Path, options: &ConfigOptions, stack: &mut Vec<PathBuf>) -> Result<Value> {
+    let io_error =
+        |error: std::io::Error| Error::new(ErrorKind::Io(format!("{}: {}", path.display(), error)));
+    let canonical = fs::canonicalize(path).map_err(io_error)?;
+    if stack.contains(&canonical) {
+        return Err(Error::new(ErrorKind::IncludeCycle(
+            path.display().to_string(),
+        )));
+    }
+
+    let bytes = fs::read(&canonical).map_err(io_error)?;
+    let text = decode(&bytes)?;
+    let mut value = match options.allow_comments {
+        true => parse_cst(text)?.to_value(),
+        false => parse(text)?,
+    };
+
+    let dir = canonical.parent().unwrap_or(Path::new("")).to_owned();
+    stack.push(canonical);
+    resolve_includes(&mut value, &dir, options, stack)?;
+    stack.pop();
+    Ok(value)
+}
+
+fn resolve_includes(
+    value: &mut Value,
+    dir: &Path,
+    options: &ConfigOptions,
+    stack: &mut Vec<PathBuf>,
+) -> Result<()> {
+    match value {
+        Value::Array(array) => array
+            .iter_mut()
+            .try_for_each(|element| resolve_includes(element, dir, options, stack)),
+        Value::Object(object) => {
+            for member in object.values_mut() {
+                resolve_includes(member, dir, options, stack)?;
+            }
+
+            let paths = match object.remove(INCLUDE) {
+                None => return Ok(()),
+                Some(Value::String(path)) => vec![path],
+                Some(Value::Array(paths)) => paths
+                    .into_iter()
+                    .map(|path| match path {
+                        Value::String(path) => Ok(path),
+                        _ => Err(invalid_include()),
+                    })
+                    .collect::<Result<_>>()?,
+                Some(_) => return Err(invalid_include()),
+            };
+
+            let mut included = Value::Object(Map::new());
+            for path in paths {
+                merge(&mut included, load_file(&dir.join(path), options, stack)?);
+            }
+            merge(&mut included, std::mem::take(object).into());
+            *value = included;
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Merges a value over another as a JSON Merge Patch.
+fn merge(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            target.apply_sparse_update(&patch, UpdateRules::default())
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+fn invalid_include() -> Error {
+    Error::new(ErrorKind::UnexpectedType("path or array of paths"))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf};
+
+    use crate::json::{parse, ErrorKind};
+
+    use super::{load_config, ConfigOptions};
+
+    /// Creates an empty directory for a test's files.
+    fn dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("json-config-{}-{}", std::process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn loads_jsonc_with_includes() {
+        let dir = dir("includes");
+        fs::write(
+            dir.join("main.jsonc"),
+            r#"{
+                // Shared settings come first.
+                "$include": ["base.json", "sub/log.json"],
+                "port": 8080,
+                "db": {"$include": "sub/db.json", "user": "app"}
+            }"#,
+        )
+        .unwrap();
+        fs::write(
+            dir.join("base.json"),
+            r#"{"host": "localhost", "port": 80}"#,
+        )
+        .unwrap();
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/log.json"), r#"{"log": {"level": "info"}}"#).unwrap();
+        fs::write(dir.join("sub/db.json"), r#"{"user": "root", "pool": 4}"#).unwrap();
+
+        let config = load_config(dir.join("main.jsonc"), &ConfigOptions::default()).unwrap();
+        let expected = parse(
+            r#"{"host": "localhost", "port": 8080, "log": {"level": "info"},
+                "db": {"user": "app", "pool": 4}}"#,
+        )
+        .unwrap();
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn merges_overlays() {
+        let dir = dir("overlays");
+        fs::write(dir.join("main.json"), r#"{"a": {"b": 1, "c": 2}, "d": 3}"#).unwrap();
+        fs::write(dir.join("prod.json"), r#"{"a": {"c": null}, "d": 4}"#).unwrap();
+
+        let options = ConfigOptions {
+            overlays: vec![dir.join("prod.json")],
+            ..Default::default()
+        };
+        let config = load_config(dir.join("main.json"), &options).unwrap();
+        assert_eq!(config, parse(r#"{"a": {"b": 1}, "d": 4}"#).unwrap());
+    }
+
+    #[test]
+    fn expands_environment_variables() {
+        let dir = dir("env");
+        fs::write(
+            dir.join("main.json"),
+            r#"{"home": "${JSON_CONFIG_TEST_UNSET:-/tmp}"}"#,
+        )
+        .unwrap();
+
+        let config = load_config(dir.join("main.json"), &ConfigOptions::default()).unwrap();
+        assert_eq!(config, parse(r#"{"home": "/tmp"}"#).unwrap());
+    }
+
+    #[test]
+    fn rejects_include_cycles() {
+        let dir = dir("cycles");
+        fs::write(dir.join("a.json"), r#"{"$include": "b.json"}"#).unwrap();
+        fs::write(dir.join("b.json"), r#"{"x": {"$include": "a.json"}}"#).unwrap();
+
+        let error = load_config(dir.join("a.json"), &ConfigOptions::default()).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::IncludeCycle(_)));
+    }
+
+    #[test]
+    fn rejects_missing_files() {
+        let dir = dir("missing");
+        fs::write(dir.join("main.json"), r#"{"$include": "nope.json"}"#).unwrap();
+
+        let error = load_config(dir.join("main.json"), &ConfigOptions::default()).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Io(_)));
+    }
+
+    #[test]
+    fn rejects_comments_when_disallowed() {
+        let dir = dir("comments");
+        fs::write(dir.join("main.json"), "// comment\n{}").unwrap();
+
+        let options = ConfigOptions {
+            allow_comments: false,
+            ..Default::default()
+        };
+        assert!(load_config(dir.join("main.json"), &options).is_err());
+    }
+}
@@ -0,0 +1,210 @@
+use std::ops::{Deref, DerefMut};
+
+use super::{map::Map, types::Value};
+
+/// The elements of a JSON array, for building arrays whose
+/// size is known in advance. It dereferences to the `Vec`
+/// of elements and converts into [`Value::Array`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Array(Vec<Value>);
+
+impl Array {
+    /// Creates an empty array.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty array with space for at least
+    /// `capacity` elements.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Vec::with_capacity(capacity))
+    }
+
+    /// Reserves space for at least `additional` more
+    /// elements.
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    /// Returns the elements.
+    pub fn as_vec(&self) -> &Vec<Value> {
+        &self.0
+    }
+
+    /// Returns the elements for modification.
+    pub fn as_vec_mut(&mut self) -> &mut Vec<Value> {
+        &mut self.0
+    }
+
+    /// Returns the elements, consuming the array.
+    pub fn into_vec(self) -> Vec<Value> {
+        self.0
+    }
+}
+
+impl Deref for Array {
+    type Target = Vec<Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Array {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Vec<Value>> for Array {
+    fn from(elements: Vec<Value>) -> Self {
+        Self(elements)
+    }
+}
+
+impl From<Array> for Value {
+    fn from(array: Array) -> Self {
+        Value::Array(array.0)
+    }
+}
+
+impl FromIterator<Value> for Array {
+    fn from_iter<T: IntoIterator<Item = Value>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl Extend<Value> for Array {
+    fn extend<T: IntoIterator<Item = Value>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Array {
+    type Item = Value;
+    type IntoIter = std::vec::IntoIter<Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// The members of a JSON object, for building objects whose
+/// size is known in advance. It dereferences to the
+/// [`Map`] of members and converts into [`Value::Object`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Object(Map);
+
+impl Object {
+    /// Creates an empty object.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty object with space for at least
+    /// `capacity` members.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self(Map::with_capacity(capacity))
+    }
+
+    /// Reserves space for at least `additional` more
+    /// members.
+    pub fn reserve(&mut self, additional: usize) {
+        self.0.reserve(additional);
+    }
+
+    /// Returns the members.
+    pub fn as_map(&self) -> &Map {
+        &self.0
+    }
+
+    /// Returns the members for modification.
+    pub fn as_map_mut(&mut self) -> &mut Map {
+        &mut self.0
+    }
+
+    /// Returns the members, consuming the object.
+    pub fn into_map(self) -> Map {
+        self.0
+    }
+}
+
+impl Deref for Object {
+    type Target = Map;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for Object {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Map> for Object {
+    fn from(members: Map) -> Self {
+        Self(members)
+    }
+}
+
+impl From<Object> for Value {
+    fn from(object: Object) -> Self {
+        Value::Object(object.0)
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Value)> for Object {
+    fn from_iter<T: IntoIterator<Item = (K, Value)>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+impl<K: Into<String>> Extend<(K, Value)> for Object {
+    fn extend<T: IntoIterator<Item = (K, Value)>>(&mut self, iter: T) {
+        self.0.extend(iter);
+    }
+}
+
+impl IntoIterator for Object {
+    type Item = (String, Value);
+    type IntoIter = <Map as IntoIterator>::IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    use super::{Array, Object};
+
+    #[test]
+    fn preallocates_arrays() {
+        let mut array = Array::with_capacity(100);
+        assert!(array.capacity() >= 100);
+        array.extend((0..100).map(Value::from));
+        array.reserve(50);
+        assert!(array.capacity() >= 150);
+        array.as_vec_mut().truncate(2);
+        assert_eq!(Value::from(array), parse("[0, 1]").unwrap());
+    }
+
+    #[test]
+    fn preallocates_objects() {
+        let mut object = Object::with_capacity(10);
+        assert!(object.capacity() >= 10);
+        object.insert("a", Value::from(1));
+        object.reserve(20);
+        assert!(object.capacity() >= 21);
+        object.extend([("b", Value::Null)]);
+        assert_eq!(object.as_map().len(), 2);
+        assert_eq!(
+            Value::from(object),
+            parse(r#"{"a": 1, "b": null}"#).unwrap()
+        );
+    }
+}
@@ -0,0 +1,583 @@
+use std::fmt::Display;
+
+use super::{
+    escape::unescape_valid,
+    limits::enforce_limits,
+    map::Map,
+    number::Number,
+    options::ParseOptions,
+    pointer::{parse_index, JsonPointer},
+    tokenizer::{tokenize_with, TokenizeOptions},
+    types::{Error, ErrorKind, Result, Span, Token, Value},
+};
+
+/// A lossless concrete syntax tree of a JSONC document.
+/// Unlike a `Value`, the tree keeps all whitespace and
+/// comments, so that it displays as exactly the text it was
+/// parsed from. This allows documents to be edited without
+/// disturbing their formatting or comments.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cst {
+    root: CstNode,
+}
+
+/// A value in a concrete syntax tree, together with the
+/// whitespace and comments around it. For an object member,
+/// the node also holds the member's key.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CstNode {
+    /// Trivia before the node, or before its key.
+    leading: Vec<Token>,
+    key: Option<Key>,
+    value: CstValue,
+    /// Trivia after the node, with the comma that separates
+    /// it from the next node inserted at `comma`.
+    trailing: Vec<Token>,
+    comma: Option<usize>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Key {
+    lexeme: String,
+    before_colon: Vec<Token>,
+    after_colon: Vec<Token>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum CstValue {
+    Scalar(String),
+    /// Containers hold their trivia separately only when
+    /// they are empty.
+    Array(Vec<CstNode>, Vec<Token>),
+    Object(Vec<CstNode>, Vec<Token>),
+}
+
+/// Where a comment appears relative to the node it is
+/// attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CommentPosition {
+    /// Before the node, usually on the lines above it.
+    Leading,
+    /// After the node, on the same line.
+    Trailing,
+}
+
+/// A comment attached to a node, including its `//` or
+/// `/* */` delimiters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Comment<'a> {
+    pub text: &'a str,
+    pub position: CommentPosition,
+}
+
+/// Parses a JSONC string into a lossless concrete syntax
+/// tree. Comments are attached to the nearest node: a
+/// comment on the lines before a node leads it, and a
+/// comment after a node on the same line trails it. It will
+/// return an error under the same conditions as `parse`,
+/// including its default depth limit, except that comments
+/// are accepted.
+pub fn parse_cst(text: &str) -> Result<Cst> {
+    let options = TokenizeOptions {
+        keep_trivia: true,
+        allow_comments: true,
+        ..Default::default()
+    };
+    let tokens = tokenize_with(text, &options)?;
+    enforce_limits(&tokens, &ParseOptions::default())?;
+    let mut builder = Builder {
+        text,
+        tokens,
+        pos: 0,
+    };
+
+    let root = builder.node(None)?;
+    match builder.tokens.get(builder.pos) {
+        None => Ok(Cst { root }),
+        Some((_, span)) => Err(Error::at(ErrorKind::Syntax, span.start)),
+    }
+}
+
+impl Cst {
+    /// Returns the root node of the tree.
+    pub fn root(&self) -> &CstNode {
+        &self.root
+    }
+
+    /// Returns the root node of the tree for modification.
+    pub fn root_mut(&mut self) -> &mut CstNode {
+        &mut self.root
+    }
+
+    /// Converts the tree into a value, dropping all trivia.
+    pub fn to_value(&self) -> Value {
+        self.root.to_value()
+    }
+}
+
+impl CstNode {
+    /// Returns the comments attached to the node, in the
+    /// order they appear in the text.
+    pub fn comments(&self) -> Vec<Comment<'_>> {
+        let mut all = comments(&self.leading, CommentPosition::Leading);
+        all.extend(comments(&self.trailing, CommentPosition::Trailing));
+        all
+    }
+
+    /// Adds a comment before the node. If the node is on a
+    /// line of its own, the comment is written as `// text`
+    /// on the line above it, with the same indentation.
+    /// Otherwise, it is written inline as `/* text */`.
+    pub fn add_leading_comment(&mut self, text: &str) {
+        let indent = match self.leading.last() {
+            Some(Token::Whitespace(space)) => space.rfind('\n').map(|end| &space[end + 1..]),
+            _ => None,
+        };
+
+        match indent {
+            Some(indent) => {
+                let indent = indent.to_owned();
+                for line in text.lines() {
+                    self.leading.push(Token::Comment(format!("// {}", line)));
+                    self.leading
+                        .push(Token::Whitespace(format!("\n{}", indent)));
+                }
+            }
+            None => {
+                self.leading.push(block_comment(text));
+                self.leading.push(Token::Whitespace(" ".to_owned()));
+            }
+        }
+    }
+
+    /// Adds a comment after the node, and after its comma if
+    /// it has one, written inline as `/* text */`.
+    pub fn add_trailing_comment(&mut self, text: &str) {
+        let position = self.comma.unwrap_or(0);
+        let position = self.trailing[position..]
+            .iter()
+            .position(|token| !matches!(token, Token::Comment(_)))
+            .map_or(self.trailing.len(), |offset| position + offset);
+        self.trailing.insert(position, block_comment(text));
+        self.trailing
+            .insert(position, Token::Whitespace(" ".to_owned()));
+    }
+
+    /// Returns the member of an object with the given key.
+    /// If the key appears more than once, the last member
+    /// is returned.
+    pub fn get(&self, key: &str) -> Option<&CstNode> {
+        match &self.value {
+            CstValue::Object(members, _) => members.iter().rev().find(|member| member.is_key(key)),
+            _ => None,
+        }
+    }
+
+    /// Returns the member of an object with the given key
+    /// for modification.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut CstNode> {
+        match &mut self.value {
+            CstValue::Object(members, _) => {
+                members.iter_mut().rev().find(|member| member.is_key(key))
+            }
+            _ => None,
+        }
+    }
+
+    /// Returns the element of an array at the given index.
+    pub fn get_index(&self, index: usize) -> Option<&CstNode> {
+        match &self.value {
+            CstValue::Array(elements, _) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    /// Returns the element of an array at the given index
+    /// for modification.
+    pub fn get_index_mut(&mut self, index: usize) -> Option<&mut CstNode> {
+        match &mut self.value {
+            CstValue::Array(elements, _) => elements.get_mut(index),
+            _ => None,
+        }
+    }
+
+    /// Looks up a node by the text form of a JSON pointer.
+    /// Returns `None` if the pointer is invalid or refers to
+    /// a missing node.
+    pub fn pointer(&self, pointer: &str) -> Option<&CstNode> {
+        JsonPointer::parse(pointer)
+            .ok()?
+            .tokens()
+            .iter()
+            .try_fold(self, |node, token| match &node.value {
+                CstValue::Array(..) => node.get_index(parse_index(token)?),
+                _ => node.get(token),
+            })
+    }
+
+    /// Looks up a node for modification by the text form of
+    /// a JSON pointer. Returns `None` if the pointer is
+    /// invalid or refers to a missing node.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut CstNode> {
+        JsonPointer::parse(pointer)
+            .ok()?
+            .tokens()
+            .iter()
+            .try_fold(self, |node, token| match &node.value {
+                CstValue::Array(..) => node.get_index_mut(parse_index(token)?),
+                _ => node.get_mut(token),
+            })
+    }
+
+    /// Converts the node into a value, dropping all trivia.
+    pub fn to_value(&self) -> Value {
+        match &self.value {
+            CstValue::Scalar(lexeme) => match lexeme.as_str() {
+                "true" => Value::Boolean(true),
+                "false" => Value::Boolean(false),
+                "null" => Value::Null,
+                string if string.starts_with('"') => {
+                    Value::String(unescape_valid(&string[1..string.len() - 1]).into_owned())
+                }
+                number => Value::Number(Number::from_lexeme(number, &ParseOptions::default())),
+            },
+            CstValue::Array(elements, _) => {
+                Value::Array(elements.iter().map(CstNode::to_value).collect())
+            }
+            CstValue::Object(members, _) => Value::Object(
+                members
+                    .iter()
+                    .map(|member| {
+                        let key = &member.key.as_ref().unwrap().lexeme;
+                        let key = unescape_valid(&key[1..key.len() - 1]).into_owned();
+                        (key, member.to_value())
+                    })
+                    .collect::<Map>(),
+            ),
+        }
+    }
+
+    fn is_key(&self, key: &str) -> bool {
+        self.key
+            .as_ref()
+            .is_some_and(|own| unescape_valid(&own.lexeme[1..own.lexeme.len() - 1]) == key)
+    }
+}
+
+fn comments(trivia: &[Token], position: CommentPosition) -> Vec<Comment<'_>> {
+    trivia
+        .iter()
+        .filter_map(|token| match token {
+            Token::Comment(text) => Some(Comment { text, position }),
+            _ => None,
+        })
+        .collect()
+}
+
+fn block_comment(text: &str) -> Token {
+    Token::Comment(format!("/* {} */", text.replace("*/", "* /")))
+}
+
+struct Builder<'a> {
+    text: &'a str,
+    tokens: Vec<(Token, Span)>,
+    pos: usize,
+}
+
+impl Builder<'_> {
+    /// Parses a node, beginning with the trivia before it.
+    /// For object members, the key is parsed as well.
+    fn node(&mut self, leading: Option<Vec<Token>>) -> Result<CstNode> {
+        let leading = leading.unwrap_or_else(|| self.trivia());
+        Ok(CstNode {
+            leading,
+            key: None,
+            value: self.value()?,
+            trailing: self.trivia(),
+            comma: None,
+        })
+    }
+
+    fn member(&mut self, leading: Vec<Token>) -> Result<CstNode> {
+        let lexeme = match self.next() {
+            Some((Token::String(_), span)) => self.text[span.start..span.end].to_owned(),
+            next => return Err(self.error(next)),
+        };
+        let before_colon = self.trivia();
+        match self.next() {
+            Some((Token::Punct(':'), _)) => {}
+            next => return Err(self.error(next)),
+        }
+        let after_colon = self.trivia();
+
+        let mut node = self.node(Some(vec![]))?;
+        node.leading = leading;
+        node.key = Some(Key {
+            lexeme,
+            before_colon,
+            after_colon,
+        });
+        Ok(node)
+    }
+
+    fn value(&mut self) -> Result<CstValue> {
+        match self.next() {
+            Some((Token::Punct('['), _)) => {
+                let (elements, inner) =
+                    self.sequence(']', |builder, leading| builder.node(Some(leading)))?;
+                Ok(CstValue::Array(elements, inner))
+            }
+            Some((Token::Punct('{'), _)) => {
+                let (members, inner) = self.sequence('}', Builder::member)?;
+                Ok(CstValue::Object(members, inner))
+            }
+            Some((Token::Punct(_), _)) | None => {
+                Err(self.error(self.tokens.get(self.pos - 1).cloned()))
+            }
+            Some((_, span)) => Ok(CstValue::Scalar(self.text[span.start..span.end].to_owned())),
+        }
+    }
+
+    /// Parses the comma-separated nodes of a container up to
+    /// its closing character, and attaches each comment that
+    /// follows a comma on the same line to the node before,
+    /// unless the next node is on that line as well.
+    fn sequence(
+        &mut self,
+        close: char,
+        mut item: impl FnMut(&mut Self, Vec<Token>) -> Result<CstNode>,
+    ) -> Result<(Vec<CstNode>, Vec<Token>)> {
+        let mut nodes: Vec<CstNode> = vec![];
+        let mut leading = self.trivia();
+
+        if matches!(self.peek(), Some(Token::Punct(char)) if *char == close) {
+            self.pos += 1;
+            return Ok((nodes, leading));
+        }
+
+        loop {
+            let mut node = item(self, leading)?;
+            match self.next() {
+                Some((Token::Punct(','), _)) => {
+                    node.comma = Some(node.trailing.len());
+                    nodes.push(node);
+                    leading = self.trivia();
+                }
+                Some((Token::Punct(char), _)) if char == close => {
+                    nodes.push(node);
+                    break;
+                }
+                next => return Err(self.error(next)),
+            }
+        }
+
+        for index in 1..nodes.len() {
+            let leading = &mut nodes[index].leading;
+            let newline = leading.iter().position(
+                |token| matches!(token, Token::Whitespace(space) if space.contains('\n')),
+            );
+            let Some(same_line) = newline else {
+                continue;
+            };
+            let has_comment = leading[..same_line]
+                .iter()
+                .any(|token| matches!(token, Token::Comment(_)));
+            if has_comment {
+                let moved: Vec<_> = leading.drain(..same_line).collect();
+                nodes[index - 1].trailing.extend(moved);
+            }
+        }
+
+        Ok((nodes, vec![]))
+    }
+
+    fn trivia(&mut self) -> Vec<Token> {
+        let mut trivia = vec![];
+        while let Some(token @ (Token::Whitespace(_) | Token::Comment(_))) = self.peek() {
+            trivia.push(token.clone());
+            self.pos += 1;
+        }
+        trivia
+    }
+
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos).map(|(token, _)| token)
+    }
+
+    fn next(&mut self) -> Option<(Token, Span)> {
+        let next = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        next
+    }
+
+    fn error(&self, token: Option<(Token, Span)>) -> Error {
+        match token {
+            Some((_, span)) => Error::at(ErrorKind::Syntax, span.start),
+            None => Error::at(ErrorKind::Syntax, self.text.len()),
+        }
+    }
+}
+
+impl Display for Cst {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.root)
+    }
+}
+
+impl Display for CstNode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write_trivia(f, &self.leading)?;
+        if let Some(key) = &self.key {
+            write!(f, "{}", key.lexeme)?;
+            write_trivia(f, &key.before_colon)?;
+            write!(f, ":")?;
+            write_trivia(f, &key.after_colon)?;
+        }
+
+        match &self.value {
+            CstValue::Scalar(lexeme) => write!(f, "{}", lexeme)?,
+            CstValue::Array(elements, inner) => write_container(f, '[', ']', elements, inner)?,
+            CstValue::Object(members, inner) => write_container(f, '{', '}', members, inner)?,
+        }
+
+        let comma = self.comma.unwrap_or(self.trailing.len());
+        write_trivia(f, &self.trailing[..comma])?;
+        if self.comma.is_some() {
+            write!(f, ",")?;
+        }
+        write_trivia(f, &self.trailing[comma..])
+    }
+}
+
+fn write_container(
+    f: &mut std::fmt::Formatter<'_>,
+    open: char,
+    close: char,
+    nodes: &[CstNode],
+    inner: &[Token],
+) -> std::fmt::Result {
+    write!(f, "{}", open)?;
+    write_trivia(f, inner)?;
+    for node in nodes {
+        write!(f, "{}", node)?;
+    }
+    write!(f, "{}", close)
+}
+
+fn write_trivia(f: &mut std::fmt::Formatter<'_>, trivia: &[Token]) -> std::fmt::Result {
+    for token in trivia {
+        match token {
+            Token::Whitespace(text) | Token::Comment(text) => write!(f, "{}", text)?,
+            _ => unreachable!("Trivia contained a non-trivia token"),
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, parse_cst};
+
+    use super::{Comment, CommentPosition};
+
+    const TEXT: &str = r#"// Server settings.
+{
+  // The host to bind to.
+  "host": "localhost", // or 0.0.0.0
+  "ports": [80, /* TLS */ 443],
+  "debug": false /* for now */
+}
+"#;
+
+    #[test]
+    fn round_trips_text_exactly() {
+        let cst = parse_cst(TEXT).unwrap();
+        assert_eq!(cst.to_string(), TEXT);
+
+        for text in [
+            "[]",
+            " [ ] ",
+            "{ /* empty */ }",
+            "1",
+            "\"a\"\n",
+            "[1,2 , {\"a\" :3}]",
+        ] {
+            assert_eq!(parse_cst(text).unwrap().to_string(), text);
+        }
+    }
+
+    #[test]
+    fn attaches_comments_to_nodes() {
+        let cst = parse_cst(TEXT).unwrap();
+        let leading = |text| Comment {
+            text,
+            position: CommentPosition::Leading,
+        };
+        let trailing = |text| Comment {
+            text,
+            position: CommentPosition::Trailing,
+        };
+
+        assert_eq!(cst.root().comments(), [leading("// Server settings.")]);
+        assert_eq!(
+            cst.root().get("host").unwrap().comments(),
+            [
+                leading("// The host to bind to."),
+                trailing("// or 0.0.0.0")
+            ]
+        );
+        assert_eq!(
+            cst.root().pointer("/ports/1").unwrap().comments(),
+            [leading("/* TLS */")]
+        );
+        assert_eq!(
+            cst.root().get("debug").unwrap().comments(),
+            [trailing("/* for now */")]
+        );
+    }
+
+    #[test]
+    fn adds_comments() {
+        let mut cst = parse_cst("{\n  \"a\": 1,\n  \"b\": [2, 3]\n}").unwrap();
+        cst.root_mut()
+            .get_mut("a")
+            .unwrap()
+            .add_leading_comment("The a value.");
+        cst.root_mut()
+            .get_mut("a")
+            .unwrap()
+            .add_trailing_comment("one");
+        cst.root_mut()
+            .pointer_mut("/b/1")
+            .unwrap()
+            .add_leading_comment("three");
+        assert_eq!(
+            cst.to_string(),
+            "{\n  // The a value.\n  \"a\": 1, /* one */\n  \"b\": [2, /* three */ 3]\n}"
+        );
+        assert!(parse_cst(&cst.to_string()).is_ok());
+    }
+
+    #[test]
+    fn converts_to_values() {
+        let cst = parse_cst(TEXT).unwrap();
+        let expected = parse(r#"{"host": "localhost", "ports": [80, 443], "debug": false}"#);
+        assert_eq!(cst.to_value(), expected.unwrap());
+    }
+
+    #[test]
+    fn rejects_invalid_text() {
+        for text in [
+            "",
+            "[1,]",
+            "{\"a\" 1}",
+            "[1 2]",
+            "1 2",
+            "{1: 2}",
+            "[",
+            "/* a",
+        ] {
+            assert!(parse_cst(text).is_err(), "{}", text);
+        }
+    }
+}
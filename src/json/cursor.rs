@@ -0,0 +1,256 @@
+use super::{
+    pointer::{parse_index, JsonPointer},
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// A position within a value that can be moved around the
+/// tree and used to modify it in place. The cursor always
+/// refers to an existing value, starting at the root, and
+/// keeps the pointer to that value up to date as it moves.
+pub struct Cursor<'a> {
+    root: &'a mut Value,
+    pointer: JsonPointer,
+}
+
+impl Value {
+    /// Returns a cursor positioned at this value.
+    pub fn cursor(&mut self) -> Cursor<'_> {
+        Cursor {
+            root: self,
+            pointer: JsonPointer::root(),
+        }
+    }
+}
+
+impl Cursor<'_> {
+    /// Returns the pointer to the current value.
+    pub fn pointer(&self) -> &JsonPointer {
+        &self.pointer
+    }
+
+    /// Returns the current value.
+    pub fn value(&self) -> &Value {
+        self.pointer
+            .resolve(self.root)
+            .expect("cursor refers to a value")
+    }
+
+    /// Returns the current value for modification.
+    pub fn value_mut(&mut self) -> &mut Value {
+        self.pointer
+            .resolve_mut(self.root)
+            .expect("cursor refers to a value")
+    }
+
+    /// Moves to the first element or member of the current
+    /// value. Returns whether the cursor moved, which it
+    /// does not if the value is not a non-empty container.
+    pub fn down(&mut self) -> bool {
+        let token = match self.value() {
+            Value::Array(array) if !array.is_empty() => "0".to_owned(),
+            Value::Object(object) => match object.get_index(0) {
+                Some((key, _)) => key.clone(),
+                None => return false,
+            },
+            Value::Ext(tag, _) => tag.clone(),
+            _ => return false,
+        };
+        self.pointer.push(token);
+        true
+    }
+
+    /// Moves to the element or member of the current value
+    /// named by a reference token. Returns whether the
+    /// cursor moved, which it does not if there is no such
+    /// child.
+    pub fn child(&mut self, token: &str) -> bool {
+        let exists = match self.value() {
+            Value::Array(array) => parse_index(token).is_some_and(|index| index < array.len()),
+            Value::Object(object) => object.contains_key(token),
+            Value::Ext(tag, _) => tag == token,
+            _ => false,
+        };
+        if exists {
+            self.pointer.push(token);
+        }
+        exists
+    }
+
+    /// Moves to the container holding the current value.
+    /// Returns whether the cursor moved, which it does not
+    /// at the root.
+    pub fn up(&mut self) -> bool {
+        self.pointer.pop().is_some()
+    }
+
+    /// Moves to the element or member after the current
+    /// value in its container. Returns whether the cursor
+    /// moved, which it does not for the last child or at the
+    /// root.
+    pub fn next_sibling(&mut self) -> bool {
+        let (Some(parent), Some(token)) = (self.pointer.parent(), self.pointer.last()) else {
+            return false;
+        };
+        let next = match parent.resolve(self.root) {
+            Some(Value::Array(array)) => parse_index(token)
+                .map(|index| index + 1)
+                .filter(|&index| index < array.len())
+                .map(|index| index.to_string()),
+            Some(Value::Object(object)) => object
+                .index_of(token)
+                .and_then(|position| object.get_index(position + 1))
+                .map(|(key, _)| key.clone()),
+            _ => None,
+        };
+        match next {
+            Some(next) => {
+                self.pointer.pop();
+                self.pointer.push(next);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Moves to the next value in depth-first order: the
+    /// first child of the current value if it has one, or
+    /// otherwise the next sibling of the current value or of
+    /// its nearest ancestor that has one. Returns `false`
+    /// and moves back to the root once every value has been
+    /// visited.
+    pub fn advance(&mut self) -> bool {
+        if self.down() {
+            return true;
+        }
+        loop {
+            if self.next_sibling() {
+                return true;
+            }
+            if !self.up() {
+                return false;
+            }
+        }
+    }
+
+    /// Replaces the current value, returning the old one.
+    pub fn set(&mut self, value: Value) -> Value {
+        std::mem::replace(self.value_mut(), value)
+    }
+
+    /// Inserts a child into the current value and moves to
+    /// it. Members replace any member with the same key.
+    /// Elements are inserted before the element at the given
+    /// index, or at the end for `-`. It will return an error
+    /// under the following conditions:
+    ///
+    /// * The current value is not an object or array.
+    /// * The token is not an array index or `-`, or is an
+    ///   index past the end of the array.
+    pub fn insert(&mut self, token: &str, value: Value) -> Result<()> {
+        let token = match self.value_mut() {
+            Value::Object(object) => {
+                object.insert(token, value);
+                token.to_owned()
+            }
+            Value::Array(array) => {
+                let index = match token {
+                    "-" => Some(array.len()),
+                    token => parse_index(token).filter(|&index| index <= array.len()),
+                };
+                let index = index.ok_or_else(|| {
+                    Error::new(ErrorKind::NotFound(format!("array index {}", token)))
+                })?;
+                array.insert(index, value);
+                index.to_string()
+            }
+            _ => return Err(Error::new(ErrorKind::UnexpectedType("object or array"))),
+        };
+        self.pointer.push(token);
+        Ok(())
+    }
+
+    /// Removes the current value from its container and
+    /// moves to the container. Returns the removed value,
+    /// or `None` at the root, which cannot be removed.
+    pub fn delete(&mut self) -> Option<Value> {
+        let token = self.pointer.pop()?;
+        match self.value_mut() {
+            Value::Array(array) => Some(array.remove(parse_index(&token)?)),
+            Value::Object(object) => object.remove(&token),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind, Value};
+
+    #[test]
+    fn navigates_with_pointer() {
+        let mut value = parse(r#"{"a": [1, {"b": 2}], "c": 3}"#).unwrap();
+        let mut cursor = value.cursor();
+        assert!(cursor.down());
+        assert_eq!(cursor.pointer().to_string(), "/a");
+        assert!(cursor.down());
+        assert!(cursor.next_sibling());
+        assert!(cursor.child("b"));
+        assert_eq!(cursor.pointer().to_string(), "/a/1/b");
+        assert_eq!(cursor.value(), &Value::Number(2.into()));
+        assert!(!cursor.next_sibling());
+        assert!(!cursor.down());
+        assert!(cursor.up());
+        assert!(cursor.up());
+        assert!(cursor.next_sibling());
+        assert_eq!(cursor.pointer().to_string(), "/c");
+        assert!(!cursor.next_sibling());
+        assert!(!cursor.child("d"));
+    }
+
+    #[test]
+    fn visits_values_depth_first() {
+        let mut value = parse(r#"{"a": [1, {"b": 2}], "c": []}"#).unwrap();
+        let mut cursor = value.cursor();
+        let mut pointers = vec![];
+        while cursor.advance() {
+            pointers.push(cursor.pointer().to_string());
+        }
+        assert_eq!(pointers, ["/a", "/a/0", "/a/1", "/a/1/b", "/c"]);
+        assert!(cursor.pointer().is_root());
+    }
+
+    #[test]
+    fn modifies_values_in_place() {
+        let mut value = parse(r#"{"a": [1, 2], "b": 3}"#).unwrap();
+        let mut cursor = value.cursor();
+        cursor.child("a");
+        cursor.insert("1", Value::Boolean(true)).unwrap();
+        assert_eq!(cursor.pointer().to_string(), "/a/1");
+        assert_eq!(cursor.set(Value::Null), Value::Boolean(true));
+        cursor.up();
+        cursor.child("0");
+        assert_eq!(cursor.delete(), Some(Value::Number(1.into())));
+        assert_eq!(cursor.pointer().to_string(), "/a");
+        cursor.up();
+        cursor.insert("c", Value::Array(vec![])).unwrap();
+        cursor.insert("-", Value::Null).unwrap();
+        assert_eq!(
+            value,
+            parse(r#"{"a": [null, 2], "b": 3, "c": [null]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_insertions() {
+        let mut value = parse(r#"{"a": [1], "b": 2}"#).unwrap();
+        let mut cursor = value.cursor();
+        assert_eq!(cursor.delete(), None);
+        cursor.child("a");
+        let error = cursor.insert("2", Value::Null).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::NotFound(_)));
+        cursor.up();
+        cursor.child("b");
+        let error = cursor.insert("c", Value::Null).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedType("object or array"));
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashSet;
+
+use super::{
+    pointer::JsonPointer,
+    serializer::to_canonical_string,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+impl Value {
+    /// Removes duplicate elements from an array, keeping
+    /// the first occurrence of each in its original order.
+    /// When `by` is `None`, elements are duplicates if they
+    /// are structurally equal. Otherwise, `by` is a pointer
+    /// and elements are duplicates if the values found at
+    /// the pointer are structurally equal. Elements that are
+    /// missing the pointed-to value are always kept. It will
+    /// return an error under the following conditions:
+    ///
+    /// * The pointer is invalid.
+    /// * The value is not an array.
+    pub fn dedup_array(&mut self, by: Option<&str>) -> Result<()> {
+        let by = by.map(JsonPointer::parse).transpose()?;
+        let array = match self {
+            Value::Array(array) => array,
+            _ => return Err(Error::new(ErrorKind::UnexpectedType("array"))),
+        };
+
+        let mut seen = HashSet::new();
+        array.retain(|element| {
+            let key = match &by {
+                None => Some(element),
+                Some(pointer) => pointer.resolve(element),
+            };
+            key.is_none_or(|key| seen.insert(to_canonical_string(key)))
+        });
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::parse;
+
+    #[test]
+    fn removes_structural_duplicates() {
+        let mut value = parse(r#"[1, {"a": 1, "b": 2}, 1, {"b": 2, "a": 1}, "1"]"#).unwrap();
+        value.dedup_array(None).unwrap();
+        assert_eq!(value, parse(r#"[1, {"a": 1, "b": 2}, "1"]"#).unwrap());
+    }
+
+    #[test]
+    fn preserves_first_seen_order() {
+        let mut value = parse("[3, 1, 3, 2, 1]").unwrap();
+        value.dedup_array(None).unwrap();
+        assert_eq!(value, parse("[3, 1, 2]").unwrap());
+    }
+
+    #[test]
+    fn removes_duplicates_by_key() {
+        let mut value = parse(r#"[{"id": 1, "v": "a"}, {"id": 2}, {"id": 1, "v": "b"}]"#).unwrap();
+        value.dedup_array(Some("/id")).unwrap();
+        assert_eq!(value, parse(r#"[{"id": 1, "v": "a"}, {"id": 2}]"#).unwrap());
+    }
+
+    #[test]
+    fn keeps_elements_missing_key() {
+        let mut value = parse(r#"[{"a": 1}, {"a": 2}, {"id": 1}, {"id": 1}]"#).unwrap();
+        value.dedup_array(Some("/id")).unwrap();
+        assert_eq!(value, parse(r#"[{"a": 1}, {"a": 2}, {"id": 1}]"#).unwrap());
+    }
+
+    #[test]
+    fn rejects_non_array() {
+        let mut value = parse("{}").unwrap();
+        assert!(value.dedup_array(None).is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_pointer() {
+        let mut value = parse("[]").unwrap();
+        assert!(value.dedup_array(Some("id")).is_err());
+    }
+}
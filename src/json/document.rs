@@ -0,0 +1,612 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{PoisonError, RwLock},
+};
+
+use super::{
+    escape::unescape_valid,
+    map::Map,
+    number::Number,
+    options::{ParseOptions, DEFAULT_MAX_DEPTH},
+    pointer::{parse_index, JsonPointer},
+    spanned::SpannedValue,
+    tokenizer::scan_spans,
+    types::{Error, ErrorKind, Result, Span, Value},
+};
+
+/// A lazily materialized JSON document. Parsing validates
+/// the text and records its structure on a tape, but does
+/// not convert any strings or numbers into values. Values
+/// are materialized only when they are accessed, so that
+/// looking up a single member of a large document does
+/// little work beyond the initial scan. Arrays and objects
+/// that are looked up are cached, so that looking them up
+/// again only clones them. A document can be shared between
+/// threads, which share its cache.
+#[derive(Debug)]
+pub struct Document<'a> {
+    text: &'a str,
+    tape: Vec<Entry>,
+    /// Materialized containers, by tape index.
+    cache: RwLock<HashMap<usize, Value>>,
+}
+
+/// A value on the tape. Containers are followed by their
+/// contents, with each object member stored as a key entry
+/// followed by its value.
+#[derive(Debug, Clone)]
+struct Entry {
+    kind: Kind,
+    span: Span,
+    /// The index of the first entry after this value and
+    /// its contents.
+    end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    String,
+    Number,
+    True,
+    False,
+    Null,
+    Array,
+    Object,
+}
+
+/// The token expected next while recording the tape.
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    ValueOrClose,
+    Key,
+    KeyOrClose,
+    Colon,
+    CommaOrClose,
+    End,
+}
+
+/// A container that is being converted into a value, with
+/// the key of the member being converted if it is an object.
+enum Partial {
+    Array(Vec<Value>),
+    Object(Map, String),
+}
+
+/// A step in the walk that collects duplicate keys.
+enum Step {
+    /// Visits the value at a tape index.
+    Visit(usize),
+    /// Enters a child, with its pointer token, the tape index
+    /// of its key, and whether the key is a duplicate.
+    Enter(String, usize, bool),
+    /// Leaves the child that was last entered.
+    Leave,
+}
+
+impl<'a> Document<'a> {
+    /// Parses a JSON string into a document. It will return
+    /// an error under the same conditions as `parse` with
+    /// the default options, so arrays and objects may be
+    /// nested at most 128 levels deep.
+    pub fn parse(text: &'a str) -> Result<Self> {
+        let spans = scan_spans(text)?;
+        if let Some(pair) = spans.windows(2).find(|pair| {
+            text.as_bytes()[pair[0].start] == b','
+                && matches!(text.as_bytes()[pair[1].start], b']' | b'}')
+        }) {
+            return Err(Error::at(ErrorKind::TrailingComma, pair[0].start));
+        }
+
+        let mut tape: Vec<Entry> = Vec::with_capacity(spans.len());
+        let mut open = vec![];
+        let mut expect = Expect::Value;
+
+        for span in spans {
+            let first = char::from(text.as_bytes()[span.start]);
+            let kind = match first {
+                '"' => Some(Kind::String),
+                '-' | '0'..='9' => Some(Kind::Number),
+                't' => Some(Kind::True),
+                'f' => Some(Kind::False),
+                'n' => Some(Kind::Null),
+                '[' => Some(Kind::Array),
+                '{' => Some(Kind::Object),
+                _ => None,
+            };
+
+            expect = match (expect, kind, first) {
+                (Expect::Key | Expect::KeyOrClose, Some(Kind::String), _) => {
+                    tape.push(Entry::scalar(Kind::String, span, tape.len()));
+                    Expect::Colon
+                }
+                (Expect::Colon, _, ':') => Expect::Value,
+                (Expect::Value | Expect::ValueOrClose, Some(kind), _) => {
+                    tape.push(Entry::scalar(kind, span, tape.len()));
+                    match kind {
+                        Kind::Array | Kind::Object if open.len() == DEFAULT_MAX_DEPTH => {
+                            let max = DEFAULT_MAX_DEPTH;
+                            return Err(Error::at(ErrorKind::TooDeep { max }, span.start));
+                        }
+                        Kind::Array => {
+                            open.push(tape.len() - 1);
+                            Expect::ValueOrClose
+                        }
+                        Kind::Object => {
+                            open.push(tape.len() - 1);
+                            Expect::KeyOrClose
+                        }
+                        _ => after_value(&open),
+                    }
+                }
+                (Expect::CommaOrClose, _, ',') => match tape[*open.last().unwrap()].kind {
+                    Kind::Array => Expect::Value,
+                    _ => Expect::Key,
+                },
+                (
+                    Expect::ValueOrClose | Expect::KeyOrClose | Expect::CommaOrClose,
+                    _,
+                    close @ (']' | '}'),
+                ) => {
+                    let index = *open.last().unwrap();
+                    let matches = match tape[index].kind {
+                        Kind::Array => close == ']' && expect != Expect::KeyOrClose,
+                        _ => close == '}' && expect != Expect::ValueOrClose,
+                    };
+                    if !matches {
+                        return Err(Error::at(ErrorKind::Syntax, span.start));
+                    }
+                    open.pop();
+                    tape[index].end = tape.len();
+                    tape[index].span.end = span.end;
+                    after_value(&open)
+                }
+                _ => return Err(Error::at(ErrorKind::Syntax, span.start)),
+            };
+        }
+
+        match expect {
+            Expect::End => Ok(Self {
+                text,
+                tape,
+                cache: RwLock::default(),
+            }),
+            _ => Err(Error::at(ErrorKind::Syntax, text.len())),
+        }
+    }
+
+    /// Materializes the whole document.
+    pub fn to_value(&self) -> Value {
+        self.cached(0)
+    }
+
+    /// Materializes the whole document, recording the span
+    /// of every value and object key.
+    pub fn to_spanned(&self) -> SpannedValue {
+        self.materialize_spanned(0)
+    }
+
+    /// Materializes the document down to a number of
+    /// levels. Arrays and objects nested `max_depth` levels
+    /// below the root are kept as [`Value::Raw`] text rather
+    /// than converted, with a depth of zero keeping the root
+    /// itself as text. Scalars are always converted.
+    pub fn to_shallow_value(&self, max_depth: usize) -> Value {
+        self.materialize_to(0, max_depth)
+    }
+
+    /// Looks up and materializes the value referred to by a
+    /// JSON pointer. Only the referenced value is converted;
+    /// the rest of the document is skipped over. Returns
+    /// `None` if the pointer is invalid or does not refer to
+    /// a value.
+    pub fn pointer(&self, pointer: &str) -> Option<Value> {
+        let pointer = JsonPointer::parse(pointer).ok()?;
+        Some(self.cached(self.index(&pointer)?))
+    }
+
+    /// Returns the span of the source text taken up by the
+    /// value referred to by a JSON pointer. Returns `None`
+    /// if the pointer is invalid or does not refer to a
+    /// value.
+    pub fn span(&self, pointer: &str) -> Option<Span> {
+        self.span_of(&JsonPointer::parse(pointer).ok()?)
+    }
+
+    /// Returns every member of an object whose key also
+    /// names another member of the same object, in the order
+    /// the members appear. Each member is reported with a
+    /// pointer to it and the span of its key, including the
+    /// first occurrence of the key.
+    pub fn duplicate_keys(&self) -> Vec<(JsonPointer, Span)> {
+        let mut duplicates = vec![];
+        let mut pointer = JsonPointer::root();
+        // The walk keeps its own stack so that deeply nested
+        // text does not overflow the call stack.
+        let mut stack = vec![Step::Visit(0)];
+        while let Some(step) = stack.pop() {
+            let index = match step {
+                Step::Visit(index) => index,
+                Step::Enter(token, index, is_duplicate) => {
+                    pointer.push(token);
+                    if is_duplicate {
+                        duplicates.push((pointer.clone(), self.tape[index].span));
+                    }
+                    continue;
+                }
+                Step::Leave => {
+                    pointer.pop();
+                    continue;
+                }
+            };
+
+            let entry = &self.tape[index];
+            let mut child = index + 1;
+            let mut steps = vec![];
+            match entry.kind {
+                Kind::Array => {
+                    let mut position = 0;
+                    while child < entry.end {
+                        steps.push(Step::Enter(position.to_string(), child, false));
+                        steps.push(Step::Visit(child));
+                        steps.push(Step::Leave);
+                        position += 1;
+                        child = self.tape[child].end;
+                    }
+                }
+                Kind::Object => {
+                    let mut counts: HashMap<Cow<str>, usize> = HashMap::new();
+                    let mut keys = vec![];
+                    while child < entry.end {
+                        *counts.entry(self.string(child)).or_default() += 1;
+                        keys.push(child);
+                        child = self.tape[child + 1].end;
+                    }
+                    for key in keys {
+                        let is_duplicate = counts[&self.string(key)] > 1;
+                        let token = self.string(key).into_owned();
+                        steps.push(Step::Enter(token, key, is_duplicate));
+                        steps.push(Step::Visit(key + 1));
+                        steps.push(Step::Leave);
+                    }
+                }
+                _ => {}
+            }
+            stack.extend(steps.into_iter().rev());
+        }
+        duplicates
+    }
+
+    pub(super) fn span_of(&self, pointer: &JsonPointer) -> Option<Span> {
+        Some(self.tape[self.index(pointer)?].span)
+    }
+
+    /// Returns the tape index of the value referred to by a
+    /// JSON pointer.
+    fn index(&self, pointer: &JsonPointer) -> Option<usize> {
+        pointer
+            .tokens()
+            .iter()
+            .try_fold(0, |index, token| self.child(index, token))
+    }
+
+    /// Returns the tape index of the child of a container
+    /// named by a pointer token.
+    fn child(&self, index: usize, token: &str) -> Option<usize> {
+        let entry = &self.tape[index];
+        let mut child = index + 1;
+        match entry.kind {
+            Kind::Array => {
+                let position = parse_index(token)?;
+                for _ in 0..position {
+                    child = self.tape[child].end;
+                }
+                (child < entry.end).then_some(child)
+            }
+            Kind::Object => {
+                let mut found = None;
+                while child < entry.end {
+                    if self.string(child) == token {
+                        found = Some(child + 1);
+                    }
+                    child = self.tape[child + 1].end;
+                }
+                found
+            }
+            _ => None,
+        }
+    }
+
+    /// Converts the value at a tape index, reusing the
+    /// cached value of a container that was converted
+    /// before. Scalars are cheap to convert, so they are not
+    /// cached.
+    fn cached(&self, index: usize) -> Value {
+        if !matches!(self.tape[index].kind, Kind::Array | Kind::Object) {
+            return self.materialize(index);
+        }
+
+        let cache = self.cache.read().unwrap_or_else(PoisonError::into_inner);
+        if let Some(value) = cache.get(&index) {
+            return value.clone();
+        }
+        drop(cache);
+
+        let value = self.materialize(index);
+        let mut cache = self.cache.write().unwrap_or_else(PoisonError::into_inner);
+        cache.entry(index).or_insert(value).clone()
+    }
+
+    /// Converts the value at a tape index.
+    fn materialize(&self, index: usize) -> Value {
+        self.materialize_to(index, usize::MAX)
+    }
+
+    /// Converts the value at a tape index, keeping
+    /// containers more than `depth` levels down as text.
+    fn materialize_to(&self, mut index: usize, depth: usize) -> Value {
+        // Containers that are being converted, with the tape
+        // index of their next child and of their end. They are
+        // kept on a stack so that deeply nested text does not
+        // overflow the call stack.
+        let mut stack: Vec<(Partial, usize, usize)> = vec![];
+        loop {
+            let entry = &self.tape[index];
+            let mut value = match entry.kind {
+                Kind::Array | Kind::Object if depth - stack.len() == 0 => Some(Value::Raw(
+                    self.text[entry.span.start..entry.span.end].to_owned(),
+                )),
+                Kind::String => Some(Value::String(self.string(index).into_owned())),
+                Kind::Number => Some(Value::Number(Number::from_lexeme(
+                    &self.text[entry.span.start..entry.span.end],
+                    &ParseOptions::default(),
+                ))),
+                Kind::True => Some(Value::Boolean(true)),
+                Kind::False => Some(Value::Boolean(false)),
+                Kind::Null => Some(Value::Null),
+                Kind::Array => {
+                    stack.push((Partial::Array(vec![]), index + 1, entry.end));
+                    None
+                }
+                Kind::Object => {
+                    let partial = Partial::Object(Map::new(), String::new());
+                    stack.push((partial, index + 1, entry.end));
+                    None
+                }
+            };
+
+            loop {
+                let Some((partial, child, end)) = stack.last_mut() else {
+                    return value.expect("root value is converted");
+                };
+                if let Some(value) = value.take() {
+                    match partial {
+                        Partial::Array(array) => array.push(value),
+                        Partial::Object(object, key) => {
+                            object.insert(std::mem::take(key), value);
+                        }
+                    }
+                }
+                if *child < *end {
+                    index = match partial {
+                        Partial::Array(_) => *child,
+                        Partial::Object(_, key) => {
+                            *key = self.string(*child).into_owned();
+                            *child + 1
+                        }
+                    };
+                    *child = self.tape[index].end;
+                    break;
+                }
+                value = Some(match stack.pop() {
+                    Some((Partial::Array(array), ..)) => Value::Array(array),
+                    Some((Partial::Object(object, _), ..)) => Value::Object(object),
+                    None => unreachable!(),
+                });
+            }
+        }
+    }
+
+    /// Converts the value at a tape index, recording spans.
+    fn materialize_spanned(&self, index: usize) -> SpannedValue {
+        let entry = &self.tape[index];
+        match entry.kind {
+            Kind::Array => {
+                let mut elements = vec![];
+                let mut child = index + 1;
+                while child < entry.end {
+                    elements.push(self.materialize_spanned(child));
+                    child = self.tape[child].end;
+                }
+                SpannedValue::array(elements, entry.span)
+            }
+            Kind::Object => {
+                let mut members = vec![];
+                let mut child = index + 1;
+                while child < entry.end {
+                    let key = self.string(child).into_owned();
+                    let key_span = self.tape[child].span;
+                    members.push((key, key_span, self.materialize_spanned(child + 1)));
+                    child = self.tape[child + 1].end;
+                }
+                SpannedValue::object(members, entry.span)
+            }
+            _ => SpannedValue::scalar(self.materialize(index), entry.span),
+        }
+    }
+
+    /// Returns the contents of the string at a tape index,
+    /// without its quotes and with its escapes decoded.
+    fn string(&self, index: usize) -> Cow<'a, str> {
+        let span = self.tape[index].span;
+        unescape_valid(&self.text[span.start + 1..span.end - 1])
+    }
+}
+
+impl Clone for Document<'_> {
+    fn clone(&self) -> Self {
+        let cache = self.cache.read().unwrap_or_else(PoisonError::into_inner);
+        Self {
+            text: self.text,
+            tape: self.tape.clone(),
+            cache: RwLock::new(cache.clone()),
+        }
+    }
+}
+
+impl Entry {
+    fn scalar(kind: Kind, span: Span, index: usize) -> Self {
+        Self {
+            kind,
+            span,
+            end: index + 1,
+        }
+    }
+}
+
+fn after_value(open: &[usize]) -> Expect {
+    match open.is_empty() {
+        true => Expect::End,
+        false => Expect::CommaOrClose,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind, Span, Value};
+
+    use super::Document;
+
+    const TEXT: &str = r#"{
+        "meta": {"id": 42, "tags": ["a", "b"]},
+        "items": [{"name": "x"}, [1, 2], {"name": "y"}]
+    }"#;
+
+    #[test]
+    fn decodes_escaped_keys_and_strings() {
+        let text = r#"{"a\"b": "\u0078\n", "a\"b": 1}"#;
+        let document = Document::parse(text).unwrap();
+        assert_eq!(document.to_value(), parse(text).unwrap());
+        assert_eq!(document.pointer("/a\"b"), Some(Value::Number(1.0.into())));
+        let duplicates = document.duplicate_keys();
+        assert_eq!(duplicates.len(), 2);
+        assert_eq!(duplicates[0].0.to_string(), "/a\"b");
+    }
+
+    #[test]
+    fn materializes_whole_document() {
+        let document = Document::parse(TEXT).unwrap();
+        assert_eq!(document.to_value(), parse(TEXT).unwrap());
+    }
+
+    #[test]
+    fn resolves_pointers() {
+        let document = Document::parse(TEXT).unwrap();
+        assert_eq!(document.pointer("/meta/id"), Some(Value::Number(42.into())));
+        assert_eq!(
+            document.pointer("/meta/tags"),
+            Some(parse(r#"["a", "b"]"#).unwrap())
+        );
+        assert_eq!(
+            document.pointer("/items/2/name"),
+            Some(Value::String("y".to_owned()))
+        );
+        assert_eq!(document.pointer(""), Some(parse(TEXT).unwrap()));
+    }
+
+    #[test]
+    fn returns_none_for_missing_values() {
+        let document = Document::parse(TEXT).unwrap();
+        assert_eq!(document.pointer("/meta/missing"), None);
+        assert_eq!(document.pointer("/items/3"), None);
+        assert_eq!(document.pointer("/meta/id/0"), None);
+        assert_eq!(document.pointer("meta"), None);
+    }
+
+    #[test]
+    fn returns_spans_of_values() {
+        let document = Document::parse(r#"{"a": [1, {"b": true}]}"#).unwrap();
+        assert_eq!(document.span("/a/1/b"), Some(Span { start: 16, end: 20 }));
+        assert_eq!(document.span("/a"), Some(Span { start: 6, end: 22 }));
+        assert_eq!(document.span(""), Some(Span { start: 0, end: 23 }));
+        assert_eq!(document.span("/c"), None);
+    }
+
+    #[test]
+    fn prefers_last_duplicate_key() {
+        let document = Document::parse(r#"{"a": 1, "a": 2}"#).unwrap();
+        assert_eq!(document.pointer("/a"), Some(Value::Number(2.into())));
+    }
+
+    #[test]
+    fn finds_duplicate_keys() {
+        let text = r#"{"a": 1, "b": [{"c": 2, "c": 3}], "a": {"d": 4}}"#;
+        let duplicates = Document::parse(text).unwrap().duplicate_keys();
+        let duplicates: Vec<_> = duplicates
+            .iter()
+            .map(|(pointer, span)| (pointer.to_string(), &text[span.start..span.end]))
+            .collect();
+        assert_eq!(
+            duplicates,
+            [
+                ("/a".to_owned(), r#""a""#),
+                ("/b/0/c".to_owned(), r#""c""#),
+                ("/b/0/c".to_owned(), r#""c""#),
+                ("/a".to_owned(), r#""a""#),
+            ]
+        );
+        assert_eq!(Document::parse(r#"{"a": 1}"#).unwrap().duplicate_keys(), []);
+    }
+
+    #[test]
+    fn rejects_invalid_structure() {
+        for text in [
+            "",
+            "[",
+            "]",
+            "[1 2]",
+            "{\"a\" 1}",
+            "{1: 2}",
+            "[1}",
+            "{\"a\": 1]",
+            "1 2",
+            "[,1]",
+            "{,}",
+            "[1,]",
+        ] {
+            assert!(Document::parse(text).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn reports_invalid_strings_at_their_offset() {
+        let error = Document::parse(r#"["a", "b\ud800"]"#).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidEscape(r"\ud800".into()));
+        assert_eq!(error.offset(), Some(8));
+        let error = Document::parse(r#"["a", "b\x"]"#).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidEscape(r"\x".into()));
+        let error = Document::parse("[1, 2,]").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TrailingComma);
+        assert_eq!(error.offset(), Some(5));
+    }
+
+    #[test]
+    fn shares_cached_values_between_threads() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Document>();
+
+        let document = Document::parse(TEXT).unwrap();
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let items = parse(r#"[{"name": "x"}, [1, 2], {"name": "y"}]"#).unwrap();
+                    assert_eq!(document.pointer("/items"), Some(items.clone()));
+                    assert_eq!(document.pointer("/items"), Some(items));
+                });
+            }
+        });
+        assert_eq!(document.cache.read().unwrap().len(), 1);
+        assert_eq!(document.clone().to_value(), parse(TEXT).unwrap());
+    }
+}
@@ -0,0 +1,259 @@
+use std::{slice, vec};
+
+use super::{
+    events::{Event, EventReader},
+    map::{self, Map},
+    number::Number,
+    options::ParseOptions,
+    tokenizer::tokenize,
+    types::{Error, ErrorKind, Result, Token, Value},
+};
+
+/// A container that is being built from events, along with
+/// the key of the member whose value comes next.
+enum Open {
+    Array(Vec<Value>),
+    Object(Map, Option<String>),
+}
+
+impl Value {
+    /// Builds a value from a stream of events, such as those
+    /// produced by an [`EventReader`](super::EventReader) or
+    /// by a converter from another format. Strings, keys,
+    /// and numbers hold their text as it would appear in
+    /// JSON, without quotes. It will return an error under
+    /// the following conditions:
+    ///
+    /// * The events do not describe exactly one value.
+    /// * A key appears outside of an object, or a value
+    ///   appears in an object without a key.
+    /// * A number event does not hold a valid number.
+    pub fn from_events(events: impl IntoIterator<Item = Event>) -> Result<Value> {
+        let mut open: Vec<Open> = vec![];
+        let mut root = None;
+
+        for event in events {
+            if root.is_some() {
+                return Err(Error::new(ErrorKind::Syntax));
+            }
+
+            let value = match event {
+                Event::StartArray => {
+                    open.push(Open::Array(vec![]));
+                    continue;
+                }
+                Event::StartObject => {
+                    open.push(Open::Object(Map::new(), None));
+                    continue;
+                }
+                Event::Key(key) => match open.last_mut() {
+                    Some(Open::Object(_, pending @ None)) => {
+                        *pending = Some(key);
+                        continue;
+                    }
+                    _ => return Err(Error::new(ErrorKind::Syntax)),
+                },
+                Event::EndArray => match open.pop() {
+                    Some(Open::Array(array)) => Value::Array(array),
+                    _ => return Err(Error::new(ErrorKind::Syntax)),
+                },
+                Event::EndObject => match open.pop() {
+                    Some(Open::Object(object, None)) => Value::Object(object),
+                    _ => return Err(Error::new(ErrorKind::Syntax)),
+                },
+                Event::String(string) => Value::String(string),
+                Event::Number(number) => Value::Number(number_from_text(&number)?),
+                Event::Boolean(bool) => Value::Boolean(bool),
+                Event::Null => Value::Null,
+            };
+
+            match open.last_mut() {
+                None => root = Some(value),
+                Some(Open::Array(array)) => array.push(value),
+                Some(Open::Object(object, pending)) => {
+                    let key = pending.take().ok_or(Error::new(ErrorKind::Syntax))?;
+                    object.insert(key, value);
+                }
+            }
+        }
+
+        root.ok_or(Error::new(ErrorKind::Syntax))
+    }
+}
+
+/// An iterator over the events that describe a value,
+/// created by [`Value::to_events`].
+pub struct ValueEvents<'a> {
+    next: Option<&'a Value>,
+    open: Vec<Frame<'a>>,
+}
+
+/// A container whose events are being produced.
+enum Frame<'a> {
+    Array(slice::Iter<'a, Value>),
+    Object(map::Iter<'a>),
+    /// The events of a raw value, read from its text.
+    Raw(vec::IntoIter<Event>),
+    /// A tagged value, which is described as an object with
+    /// a single member until that member is produced.
+    Ext(Option<(&'a String, &'a Value)>),
+}
+
+impl Value {
+    /// Returns an iterator over the events that describe
+    /// the value, in the order in which an
+    /// [`EventReader`] would produce them from its text.
+    /// Together with [`Value::from_events`], this lets
+    /// converters to and from other formats share one event
+    /// model.
+    pub fn to_events(&self) -> ValueEvents<'_> {
+        ValueEvents {
+            next: Some(self),
+            open: vec![],
+        }
+    }
+}
+
+impl Iterator for ValueEvents<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        loop {
+            if let Some(value) = self.next.take() {
+                return Some(match value {
+                    Value::String(string) => Event::String(string.clone()),
+                    // Numbers that are not finite are written as
+                    // null, as the serializer writes them.
+                    Value::Number(number) if !number.as_f64().is_finite() => Event::Null,
+                    Value::Number(number) => Event::Number(number.to_string()),
+                    Value::Boolean(bool) => Event::Boolean(*bool),
+                    Value::Null => Event::Null,
+                    Value::Array(array) => {
+                        self.open.push(Frame::Array(array.iter()));
+                        Event::StartArray
+                    }
+                    Value::Object(object) => {
+                        self.open.push(Frame::Object(object.iter()));
+                        Event::StartObject
+                    }
+                    Value::Raw(raw) => {
+                        let events: Vec<_> = EventReader::new(raw.as_bytes())
+                            .map_while(|event| event.ok())
+                            .collect();
+                        self.open.push(Frame::Raw(events.into_iter()));
+                        continue;
+                    }
+                    Value::Ext(tag, value) => {
+                        self.open.push(Frame::Ext(Some((tag, value))));
+                        Event::StartObject
+                    }
+                });
+            }
+
+            let event = match self.open.last_mut()? {
+                Frame::Array(elements) => match elements.next() {
+                    Some(element) => {
+                        self.next = Some(element);
+                        continue;
+                    }
+                    None => Event::EndArray,
+                },
+                Frame::Object(members) => match members.next() {
+                    Some((key, value)) => {
+                        self.next = Some(value);
+                        return Some(Event::Key(key.clone()));
+                    }
+                    None => Event::EndObject,
+                },
+                Frame::Ext(member) => match member.take() {
+                    Some((tag, value)) => {
+                        self.next = Some(value);
+                        return Some(Event::Key(tag.clone()));
+                    }
+                    None => Event::EndObject,
+                },
+                Frame::Raw(events) => match events.next() {
+                    Some(event) => return Some(event),
+                    None => {
+                        self.open.pop();
+                        continue;
+                    }
+                },
+            };
+            self.open.pop();
+            return Some(event);
+        }
+    }
+}
+
+/// Converts the text of a number event into a number,
+/// checking that it is valid JSON.
+fn number_from_text(text: &str) -> Result<Number> {
+    match tokenize(text)?.as_slice() {
+        [(Token::Number(lexeme), _)] => Ok(Number::from_lexeme(lexeme, &ParseOptions::default())),
+        _ => Err(Error::new(ErrorKind::InvalidNumber)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, parse_shallow, ErrorKind, Event, EventReader, Value};
+
+    #[test]
+    fn builds_values_from_events() {
+        let text = r#"{"a": [1, "x\"y", true, {}], "b": {"c": null}, "d": []}"#;
+        let events = EventReader::new(text.as_bytes()).map(Result::unwrap);
+        assert_eq!(Value::from_events(events).unwrap(), parse(text).unwrap());
+        assert_eq!(
+            Value::from_events([Event::Number("-1.5e3".into())]).unwrap(),
+            parse("-1.5e3").unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_streams() {
+        let streams = [
+            vec![],
+            vec![Event::StartArray],
+            vec![Event::StartArray, Event::EndObject],
+            vec![Event::StartArray, Event::Key("a".into()), Event::Null],
+            vec![Event::StartObject, Event::Null, Event::EndObject],
+            vec![Event::StartObject, Event::Key("a".into()), Event::EndObject],
+            vec![Event::Null, Event::Null],
+        ];
+        for events in streams {
+            let error = Value::from_events(events).unwrap_err();
+            assert_eq!(error.kind(), &ErrorKind::Syntax);
+        }
+
+        let error = Value::from_events([Event::Number("01".into())]).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::MalformedNumber(_)));
+        let error = Value::from_events([Event::Number("1 2".into())]).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidNumber);
+    }
+
+    #[test]
+    fn produces_events_from_values() {
+        let text = r#"{"a": [1, "x\"y", {}], "b": {"c": null}, "d": []}"#;
+        let value = parse(text).unwrap();
+        let expected: Vec<_> = EventReader::new(text.as_bytes())
+            .map(Result::unwrap)
+            .collect();
+        assert_eq!(value.to_events().collect::<Vec<_>>(), expected);
+        assert_eq!(Value::from_events(value.to_events()).unwrap(), value);
+        assert_eq!(
+            Value::TRUE.to_events().collect::<Vec<_>>(),
+            [Event::Boolean(true)]
+        );
+    }
+
+    #[test]
+    fn produces_events_from_raw_values() {
+        let text = r#"[{"a": [1]}, 2]"#;
+        let value = parse_shallow(text, 1).unwrap();
+        assert_eq!(
+            Value::from_events(value.to_events()).unwrap(),
+            parse(text).unwrap()
+        );
+    }
+}
@@ -0,0 +1,202 @@
+use std::env;
+
+use super::{
+    map::Map,
+    parse,
+    template::substitute,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// Expands environment variable references in the string
+/// values of a document. A reference takes the form
+/// `${NAME}` or `${NAME:-default}`, where the default is
+/// used when the variable is unset or empty. References
+/// may make up a whole string or appear inside one, and
+/// a literal `${` can be written as `$${`. It will return
+/// an error under the following conditions:
+///
+/// * A reference is not closed with `}`.
+/// * A reference without a default names a variable that
+///   is not set.
+pub fn expand_env(value: &mut Value) -> Result<()> {
+    expand_with(value, &|name| env::var(name).ok())
+}
+
+impl Value {
+    /// Converts the value into environment variables, one
+    /// per scalar or empty container, as by
+    /// [`Value::flatten`]. Names are the flattened keys in
+    /// uppercase, with characters other than ASCII letters,
+    /// digits, and underscores replaced by underscores.
+    /// String values are given as their contents, and other
+    /// values as JSON text.
+    pub fn to_env_vars(&self, separator: &str) -> Vec<(String, String)> {
+        let Value::Object(flat) = self.flatten(separator) else {
+            unreachable!()
+        };
+        flat.iter()
+            .map(|(key, value)| {
+                let name = key
+                    .chars()
+                    .map(|char| match char.is_ascii_alphanumeric() {
+                        true => char.to_ascii_uppercase(),
+                        false => '_',
+                    })
+                    .collect();
+                let value = match value {
+                    Value::String(string) => string.clone(),
+                    value => value.to_string(),
+                };
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// Builds a nested value from environment variables,
+    /// as by [`Value::unflatten`]. Names are converted to
+    /// lowercase keys. Values that are valid JSON, such as
+    /// `8080` or `true`, are parsed, and other values are
+    /// taken as strings. It will return an error under the
+    /// same conditions as `unflatten`.
+    pub fn from_env_vars(
+        vars: impl IntoIterator<Item = (String, String)>,
+        separator: &str,
+    ) -> Result<Value> {
+        let mut flat = Map::new();
+        for (name, value) in vars {
+            let value = parse(&value).unwrap_or(Value::String(value));
+            flat.insert(name.to_lowercase(), value);
+        }
+        Value::Object(flat).unflatten(separator)
+    }
+}
+
+fn expand_with(value: &mut Value, lookup: &dyn Fn(&str) -> Option<String>) -> Result<()> {
+    match value {
+        Value::String(string) => {
+            *string = substitute(string, |reference| resolve(reference, lookup))?;
+            Ok(())
+        }
+        Value::Array(array) => array
+            .iter_mut()
+            .try_for_each(|value| expand_with(value, lookup)),
+        Value::Object(object) => object
+            .values_mut()
+            .try_for_each(|value| expand_with(value, lookup)),
+        _ => Ok(()),
+    }
+}
+
+fn resolve(reference: &str, lookup: &dyn Fn(&str) -> Option<String>) -> Result<String> {
+    match reference.split_once(":-") {
+        Some((name, default)) => Ok(lookup(name)
+            .filter(|value| !value.is_empty())
+            .unwrap_or_else(|| default.to_owned())),
+        None => lookup(reference).ok_or_else(|| {
+            let name = format!("environment variable {}", reference);
+            Error::new(ErrorKind::NotFound(name))
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+
+    use crate::json::{parse, Value};
+
+    use super::{expand_env, expand_with};
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOST" => Some("localhost".to_owned()),
+            "EMPTY" => Some("".to_owned()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expands_whole_string_reference() {
+        let mut value = parse(r#"{"host": "${HOST}"}"#).unwrap();
+        expand_with(&mut value, &lookup).unwrap();
+        assert_eq!(value, parse(r#"{"host": "localhost"}"#).unwrap());
+    }
+
+    #[test]
+    fn expands_reference_inside_string() {
+        let mut value = parse(r#"["http://${HOST}:8080"]"#).unwrap();
+        expand_with(&mut value, &lookup).unwrap();
+        assert_eq!(value, parse(r#"["http://localhost:8080"]"#).unwrap());
+    }
+
+    #[test]
+    fn uses_default_for_unset_variable() {
+        let mut value = parse(r#""${PORT:-8080}""#).unwrap();
+        expand_with(&mut value, &lookup).unwrap();
+        assert_eq!(value, Value::String("8080".to_owned()));
+    }
+
+    #[test]
+    fn uses_default_for_empty_variable() {
+        let mut value = parse(r#""${EMPTY:-fallback}""#).unwrap();
+        expand_with(&mut value, &lookup).unwrap();
+        assert_eq!(value, Value::String("fallback".to_owned()));
+    }
+
+    #[test]
+    fn prefers_set_variable_over_default() {
+        let mut value = parse(r#""${HOST:-example.com}""#).unwrap();
+        expand_with(&mut value, &lookup).unwrap();
+        assert_eq!(value, Value::String("localhost".to_owned()));
+    }
+
+    #[test]
+    fn rejects_unset_variable_without_default() {
+        let mut value = parse(r#""${PORT}""#).unwrap();
+        assert!(expand_with(&mut value, &lookup).is_err());
+    }
+
+    #[test]
+    fn reads_process_environment() {
+        env::set_var("JSON_EXPAND_ENV_TEST", "value");
+        let mut value = parse(r#""${JSON_EXPAND_ENV_TEST}""#).unwrap();
+        expand_env(&mut value).unwrap();
+        assert_eq!(value, Value::String("value".to_owned()));
+    }
+
+    #[test]
+    fn converts_to_env_vars() {
+        let value =
+            parse(r#"{"server": {"port": 80, "host-name": "a b"}, "tags": ["x"]}"#).unwrap();
+        let vars = value.to_env_vars("_");
+        let expected = [
+            ("SERVER_PORT", "80"),
+            ("SERVER_HOST_NAME", "a b"),
+            ("TAGS_0", "x"),
+        ];
+        let expected: Vec<_> = expected
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect();
+        assert_eq!(vars, expected);
+    }
+
+    #[test]
+    fn converts_from_env_vars() {
+        let vars = [
+            ("SERVER__PORT", "80"),
+            ("SERVER__HOST", "say \"hi\""),
+            ("TAGS__0", "x"),
+            ("DEBUG", "true"),
+        ];
+        let vars = vars
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()));
+        let value = Value::from_env_vars(vars, "__").unwrap();
+        let expected = parse(
+            r#"{"server": {"port": 80, "host": "say \"hi\""}, "tags": ["x"], "debug": true}"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+    }
+}
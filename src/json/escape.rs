@@ -0,0 +1,221 @@
+use std::borrow::Cow;
+
+use super::{
+    options::LoneSurrogates,
+    types::{Error, ErrorKind, Result},
+};
+
+/// Escapes text for use as the contents of a JSON string,
+/// without the surrounding quotes. Quotes and backslashes
+/// are escaped, control characters that have a short
+/// escape, such as `\n`, use it, and all other control
+/// characters are written as `\u` escapes.
+pub fn escape_str(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    escape_into(text, &mut escaped);
+    escaped
+}
+
+/// Escapes text as by [`escape_str`], appending it to a
+/// string.
+pub(super) fn escape_into(text: &str, escaped: &mut String) {
+    for char in text.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\u{8}' => escaped.push_str("\\b"),
+            '\u{c}' => escaped.push_str("\\f"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            _ if char.is_control() => escaped.push_str(&format!("\\u{:04x}", char as u32)),
+            _ => escaped.push(char),
+        }
+    }
+}
+
+/// Reverses [`escape_str`], converting the contents of a
+/// JSON string, without the surrounding quotes, into the
+/// text it stands for. Surrogate pairs written as two `\u`
+/// escapes are combined into one character. It will return
+/// an error under the following conditions, with the offset
+/// of the problem in the text:
+///
+/// * The text contains a quote or control character that
+///   is not escaped.
+/// * The text contains a backslash that does not begin one
+///   of the escapes allowed by JSON.
+/// * A `\u` escape holds a surrogate that is not part of a
+///   pair.
+pub fn unescape_str(text: &str) -> Result<String> {
+    unescape_str_with(text, LoneSurrogates::Reject)
+}
+
+/// Converts the contents of a JSON string into the text it
+/// stands for, as [`unescape_str`] does, handling `\u`
+/// escapes of surrogates that are not part of a pair with
+/// the given policy.
+pub fn unescape_str_with(text: &str, lone_surrogates: LoneSurrogates) -> Result<String> {
+    let mut unescaped = String::with_capacity(text.len());
+    unescape_into(text, lone_surrogates, Some(&mut unescaped))?;
+    Ok(unescaped)
+}
+
+/// Checks that the contents of a JSON string are valid, as
+/// [`unescape_str_with`] does, without decoding them.
+pub(super) fn validate_str(text: &str, lone_surrogates: LoneSurrogates) -> Result<()> {
+    unescape_into(text, lone_surrogates, None)
+}
+
+/// Decodes the contents of a JSON string, appending the
+/// text they stand for to `unescaped` if given.
+fn unescape_into(
+    text: &str,
+    lone_surrogates: LoneSurrogates,
+    mut unescaped: Option<&mut String>,
+) -> Result<()> {
+    let mut chars = text.char_indices().peekable();
+    while let Some((offset, char)) = chars.next() {
+        let escape = match char {
+            '"' => return Err(Error::at(ErrorKind::InvalidString, offset)),
+            _ if char < ' ' => return Err(Error::at(ErrorKind::InvalidString, offset)),
+            '\\' => chars.next().map(|(_, char)| char),
+            _ => {
+                unescaped.iter_mut().for_each(|text| text.push(char));
+                continue;
+            }
+        };
+        let invalid = |len: usize| {
+            let end = (offset + len).min(text.len());
+            let escape = text.get(offset..end).unwrap_or(&text[offset..]);
+            Error::at(ErrorKind::InvalidEscape(escape.to_owned()), offset)
+        };
+        let char = match escape {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                let high = hex_escape(&text[offset + 2..]).ok_or_else(|| invalid(6))?;
+                skip(&mut chars, 4);
+                let low = text[offset + 6..]
+                    .strip_prefix("\\u")
+                    .and_then(hex_escape)
+                    .filter(|low| (0xdc00..=0xdfff).contains(low));
+                let code = match (high, low) {
+                    (0xd800..=0xdbff, Some(low)) => {
+                        skip(&mut chars, 6);
+                        0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+                    }
+                    (0xd800..=0xdfff, _) => match lone_surrogates {
+                        LoneSurrogates::Reject => return Err(invalid(6)),
+                        LoneSurrogates::Replace => 0xfffd,
+                    },
+                    (code, _) => code,
+                };
+                char::from_u32(code).ok_or_else(|| invalid(6))?
+            }
+            Some(char) => return Err(invalid(1 + char.len_utf8())),
+            None => return Err(invalid(1)),
+        };
+        unescaped.iter_mut().for_each(|text| text.push(char));
+    }
+    Ok(())
+}
+
+/// Unescapes the contents of a string that the tokenizer
+/// has already accepted, borrowing them if they hold no
+/// escapes. Lone surrogates can only remain if the
+/// tokenizer was asked to replace them, so they are
+/// replaced.
+///
+/// # Panics
+///
+/// Panics if the contents are not a valid string, which
+/// means they were not checked by the tokenizer.
+pub(super) fn unescape_valid(text: &str) -> Cow<'_, str> {
+    match text.contains('\\') {
+        true => match unescape_str_with(text, LoneSurrogates::Replace) {
+            Ok(text) => Cow::Owned(text),
+            Err(error) => panic!("string was not checked by the tokenizer: {}", error),
+        },
+        false => Cow::Borrowed(text),
+    }
+}
+
+/// Reads the four hex digits at the start of the text.
+fn hex_escape(text: &str) -> Option<u32> {
+    let digits = text.get(..4)?;
+    match digits.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+        true => u32::from_str_radix(digits, 16).ok(),
+        false => None,
+    }
+}
+
+fn skip(chars: &mut impl Iterator, count: usize) {
+    chars.take(count).for_each(drop);
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse_with, ErrorKind, LoneSurrogates, ParseOptions, Value};
+
+    use super::{escape_str, unescape_str, unescape_str_with};
+
+    #[test]
+    fn escapes_text() {
+        assert_eq!(escape_str("plain é"), "plain é");
+        assert_eq!(escape_str("a\"b\\c/"), r#"a\"b\\c/"#);
+        assert_eq!(escape_str("\n\t\r\u{8}\u{c}"), r"\n\t\r\b\f");
+        assert_eq!(escape_str("\u{0}\u{1f}\u{7f}"), r"\u0000\u001f\u007f");
+    }
+
+    #[test]
+    fn unescapes_text() {
+        assert_eq!(unescape_str(r#"a\"b\\c\/"#).unwrap(), "a\"b\\c/");
+        assert_eq!(unescape_str(r"\n\t\r\b\f").unwrap(), "\n\t\r\u{8}\u{c}");
+        assert_eq!(unescape_str(r"\u0041\u00e9é").unwrap(), "Aéé");
+        assert_eq!(unescape_str(r"\ud83d\uDE00!").unwrap(), "😀!");
+        for text in ["", "plain", "\"\\\n\u{1}é😀"] {
+            assert_eq!(unescape_str(&escape_str(text)).unwrap(), text);
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_escapes() {
+        let error = |text| unescape_str(text).unwrap_err();
+        let escape = |text: &str| ErrorKind::InvalidEscape(text.into());
+        assert_eq!(error(r"ab\x").kind(), &escape(r"\x"));
+        assert_eq!(error(r"ab\x").offset(), Some(2));
+        assert_eq!(error(r"\é").kind(), &escape(r"\é"));
+        assert_eq!(error(r"a\").kind(), &escape(r"\"));
+        assert_eq!(error(r"\u12").kind(), &escape(r"\u12"));
+        assert_eq!(error(r"\u12g4").kind(), &escape(r"\u12g4"));
+        assert_eq!(error(r"\uD800").kind(), &escape(r"\uD800"));
+        assert_eq!(error(r"\uD800A").kind(), &escape(r"\uD800"));
+        assert_eq!(error(r"x\uDC00").offset(), Some(1));
+        assert_eq!(error("a\"b").kind(), &ErrorKind::InvalidString);
+        assert_eq!(error("a\nb").offset(), Some(1));
+    }
+
+    #[test]
+    fn replaces_lone_surrogates_when_asked() {
+        let unescape = |text| unescape_str_with(text, LoneSurrogates::Replace).unwrap();
+        assert_eq!(unescape(r"a\uD800b"), "a\u{fffd}b");
+        assert_eq!(unescape(r"\uDC00\uD83D\uDE00"), "\u{fffd}😀");
+        assert_eq!(unescape(r"\uD83D\uD83D\uDE00"), "\u{fffd}😀");
+        assert_eq!(unescape(r"\uD800\n"), "\u{fffd}\n");
+
+        let options = ParseOptions {
+            lone_surrogates: LoneSurrogates::Replace,
+            ..Default::default()
+        };
+        let value = parse_with(r#"["\uD800"]"#, &options).unwrap();
+        assert_eq!(value, Value::Array(vec![Value::String("\u{fffd}".into())]));
+        assert!(parse_with(r#"["\uD800"]"#, &ParseOptions::default()).is_err());
+    }
+}
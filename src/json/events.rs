@@ -0,0 +1,391 @@
+use std::{
+    io::{self, BufReader, BufWriter, Bytes, Read, Write},
+    iter::Peekable,
+};
+
+use super::{
+    escape::escape_str,
+    tokenizer::tokenize,
+    types::{Error, ErrorKind, Result, Token},
+    utf8::decode,
+};
+
+/// An event in a stream of JSON text. Strings, keys, and
+/// numbers hold their text as it appears in the input,
+/// without quotes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Event {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key(String),
+    String(String),
+    Number(String),
+    Boolean(bool),
+    Null,
+}
+
+/// A reader that converts JSON text into a stream of
+/// events as it is read, without building values. Memory
+/// use is bounded by the nesting depth and the length of
+/// the longest string or number, so that documents larger
+/// than memory can be processed. The reader yields an error
+/// and then stops if the text is not valid JSON.
+pub struct EventReader<R: Read> {
+    bytes: Peekable<Bytes<BufReader<R>>>,
+    offset: usize,
+    open: Vec<Event>,
+    expect: Expect,
+    comma: Option<usize>,
+    done: bool,
+}
+
+/// The token expected next while reading events.
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    ValueOrClose,
+    Key,
+    KeyOrClose,
+    Colon,
+    CommaOrClose,
+    End,
+}
+
+impl<R: Read> EventReader<R> {
+    /// Creates a reader over JSON text.
+    pub fn new(reader: R) -> Self {
+        Self {
+            bytes: BufReader::new(reader).bytes().peekable(),
+            offset: 0,
+            open: vec![],
+            expect: Expect::Value,
+            comma: None,
+            done: false,
+        }
+    }
+
+    fn next_event(&mut self) -> Result<Option<Event>> {
+        loop {
+            while self.peek()?.is_some_and(|byte| byte.is_ascii_whitespace()) {
+                self.bump();
+            }
+
+            let start = self.offset;
+            let comma = self.comma.take();
+            let Some(byte) = self.peek()? else {
+                return match self.expect {
+                    Expect::End => Ok(None),
+                    _ => Err(Error::at(ErrorKind::Syntax, start)),
+                };
+            };
+
+            let event = match (self.expect, byte) {
+                (Expect::Colon, b':') => {
+                    self.bump();
+                    self.expect = Expect::Value;
+                    continue;
+                }
+                (Expect::CommaOrClose, b',') => {
+                    self.bump();
+                    self.comma = Some(start);
+                    self.expect = match self.open.last() {
+                        Some(Event::StartArray) => Expect::Value,
+                        _ => Expect::Key,
+                    };
+                    continue;
+                }
+                (Expect::Value | Expect::Key, b']' | b'}') if comma.is_some() => {
+                    return Err(Error::at(ErrorKind::TrailingComma, comma.unwrap()));
+                }
+                (
+                    Expect::ValueOrClose | Expect::KeyOrClose | Expect::CommaOrClose,
+                    close @ (b']' | b'}'),
+                ) => {
+                    let matches = match self.open.last() {
+                        Some(Event::StartArray) => {
+                            close == b']' && self.expect != Expect::KeyOrClose
+                        }
+                        _ => close == b'}' && self.expect != Expect::ValueOrClose,
+                    };
+                    if !matches {
+                        return Err(Error::at(ErrorKind::Syntax, start));
+                    }
+                    self.bump();
+                    self.open.pop();
+                    match close {
+                        b']' => Event::EndArray,
+                        _ => Event::EndObject,
+                    }
+                }
+                (Expect::Key | Expect::KeyOrClose, b'"') => {
+                    let key = self.string()?;
+                    self.expect = Expect::Colon;
+                    return Ok(Some(Event::Key(key)));
+                }
+                (Expect::Value | Expect::ValueOrClose, open @ (b'[' | b'{')) => {
+                    self.bump();
+                    let (event, expect) = match open {
+                        b'[' => (Event::StartArray, Expect::ValueOrClose),
+                        _ => (Event::StartObject, Expect::KeyOrClose),
+                    };
+                    self.open.push(event.clone());
+                    self.expect = expect;
+                    return Ok(Some(event));
+                }
+                (Expect::Value | Expect::ValueOrClose, b'"') => Event::String(self.string()?),
+                (Expect::Value | Expect::ValueOrClose, _) => self.scalar()?,
+                _ => return Err(Error::at(ErrorKind::Syntax, start)),
+            };
+
+            self.expect = match self.open.is_empty() {
+                true => Expect::End,
+                false => Expect::CommaOrClose,
+            };
+            return Ok(Some(event));
+        }
+    }
+
+    /// Reads a string, returning its contents.
+    fn string(&mut self) -> Result<String> {
+        let start = self.offset;
+        let mut bytes = vec![];
+        let mut escaped = false;
+        self.bump();
+        loop {
+            let byte = self
+                .peek()?
+                .ok_or_else(|| Error::at(ErrorKind::InvalidString, start))?;
+            self.bump();
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => break,
+                _ => {}
+            }
+            bytes.push(byte);
+        }
+
+        let text = format!("\"{}\"", offset(decode(&bytes), start + 1)?);
+        match token(&text, start)? {
+            Token::String(string) => Ok(string),
+            _ => Err(Error::at(ErrorKind::InvalidString, start)),
+        }
+    }
+
+    /// Reads a number or literal.
+    fn scalar(&mut self) -> Result<Event> {
+        let start = self.offset;
+        let mut bytes = vec![];
+        while let Some(byte) = self.peek()? {
+            if byte.is_ascii_whitespace() || b",:[]{}\"".contains(&byte) {
+                break;
+            }
+            self.bump();
+            bytes.push(byte);
+        }
+
+        match token(offset(decode(&bytes), start)?, start)? {
+            Token::Number(number) => Ok(Event::Number(number)),
+            Token::True => Ok(Event::Boolean(true)),
+            Token::False => Ok(Event::Boolean(false)),
+            Token::Null => Ok(Event::Null),
+            _ => Err(Error::at(ErrorKind::Syntax, start)),
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        match self.bytes.peek() {
+            None => Ok(None),
+            Some(Ok(byte)) => Ok(Some(*byte)),
+            Some(Err(error)) => Err(Error::at(ErrorKind::Io(error.to_string()), self.offset)),
+        }
+    }
+
+    fn bump(&mut self) {
+        self.bytes.next();
+        self.offset += 1;
+    }
+}
+
+impl<R: Read> Iterator for EventReader<R> {
+    type Item = Result<Event>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let event = self.next_event().transpose();
+        self.done = !matches!(event, Some(Ok(_)));
+        event
+    }
+}
+
+/// Tokenizes text that should hold exactly one token,
+/// reporting errors at their offset in the whole input.
+fn token(text: &str, start: usize) -> Result<Token> {
+    let mut tokens = offset(tokenize(text), start)?;
+    match tokens.len() {
+        1 => Ok(tokens.remove(0).0),
+        _ => Err(Error::at(ErrorKind::Syntax, start)),
+    }
+}
+
+/// Shifts the offset of an error by the offset at which
+/// the text it relates to starts.
+fn offset<T>(result: Result<T>, start: usize) -> Result<T> {
+    result.map_err(|error| match error.offset() {
+        Some(offset) => Error::at(error.kind().clone(), start + offset),
+        None => error,
+    })
+}
+
+/// A writer that converts a stream of events into compact
+/// JSON text. The events are assumed to be well-formed, as
+/// produced by an [`EventReader`].
+pub struct EventWriter<W: Write> {
+    writer: BufWriter<W>,
+    first: Vec<bool>,
+    after_key: bool,
+}
+
+impl<W: Write> EventWriter<W> {
+    /// Creates a writer that writes JSON text to `writer`.
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            first: vec![],
+            after_key: false,
+        }
+    }
+
+    /// Writes the text of an event.
+    pub fn write(&mut self, event: &Event) -> Result<()> {
+        let is_end = matches!(event, Event::EndObject | Event::EndArray);
+        if !is_end && !self.after_key {
+            if let Some(first) = self.first.last_mut() {
+                if !*first {
+                    self.writer.write_all(b",").map_err(io_error)?;
+                }
+                *first = false;
+            }
+        }
+        self.after_key = matches!(event, Event::Key(_));
+
+        let result = match event {
+            Event::StartObject | Event::StartArray => {
+                self.first.push(true);
+                let open = if *event == Event::StartObject {
+                    "{"
+                } else {
+                    "["
+                };
+                self.writer.write_all(open.as_bytes())
+            }
+            Event::EndObject | Event::EndArray => {
+                self.first.pop();
+                let close = if *event == Event::EndObject { "}" } else { "]" };
+                self.writer.write_all(close.as_bytes())
+            }
+            Event::Key(key) => write!(self.writer, "\"{}\":", escape_str(key)),
+            Event::String(string) => write!(self.writer, "\"{}\"", escape_str(string)),
+            Event::Number(number) => write!(self.writer, "{}", number),
+            Event::Boolean(bool) => write!(self.writer, "{}", bool),
+            Event::Null => write!(self.writer, "null"),
+        };
+        result.map_err(io_error)
+    }
+
+    /// Flushes any buffered text and returns the underlying
+    /// writer.
+    pub fn into_inner(self) -> Result<W> {
+        self.writer
+            .into_inner()
+            .map_err(|error| io_error(error.into_error()))
+    }
+}
+
+fn io_error(error: io::Error) -> Error {
+    Error::new(ErrorKind::Io(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::ErrorKind;
+
+    use super::{Event, EventReader, EventWriter};
+
+    fn events(text: &str) -> Vec<Event> {
+        EventReader::new(text.as_bytes())
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn reads_events() {
+        assert_eq!(
+            events(r#" {"a": [1, "x\"y", true], "b": {}, "c": null} "#),
+            [
+                Event::StartObject,
+                Event::Key("a".to_owned()),
+                Event::StartArray,
+                Event::Number("1".to_owned()),
+                Event::String("x\"y".to_owned()),
+                Event::Boolean(true),
+                Event::EndArray,
+                Event::Key("b".to_owned()),
+                Event::StartObject,
+                Event::EndObject,
+                Event::Key("c".to_owned()),
+                Event::Null,
+                Event::EndObject,
+            ]
+        );
+        assert_eq!(events("-1.5e3"), [Event::Number("-1.5e3".to_owned())]);
+    }
+
+    #[test]
+    fn reports_errors_with_offsets() {
+        for (text, kind, offset) in [
+            ("[1, tru]", ErrorKind::InvalidLiteral("true"), 4),
+            ("[1,]", ErrorKind::TrailingComma, 2),
+            (r#"{"a": }"#, ErrorKind::Syntax, 6),
+            ("[1 2]", ErrorKind::Syntax, 3),
+            (r#"{"a" 1}"#, ErrorKind::Syntax, 5),
+            ("[1}", ErrorKind::Syntax, 2),
+            (r#"["a\x"]"#, ErrorKind::InvalidEscape(r"\x".into()), 3),
+            (
+                "[+1]",
+                ErrorKind::MalformedNumber("leading '+' is not allowed"),
+                1,
+            ),
+            ("[1", ErrorKind::Syntax, 2),
+            ("1 2", ErrorKind::Syntax, 2),
+        ] {
+            let error = EventReader::new(text.as_bytes())
+                .find_map(Result::err)
+                .unwrap();
+            assert_eq!(error.kind(), &kind, "{}", text);
+            assert_eq!(error.offset(), Some(offset), "{}", text);
+        }
+    }
+
+    #[test]
+    fn stops_after_error() {
+        let mut reader = EventReader::new("[1, @, 2]".as_bytes());
+        assert!(reader.by_ref().any(|event| event.is_err()));
+        assert!(reader.next().is_none());
+    }
+
+    #[test]
+    fn writes_events_as_compact_text() {
+        let text = r#"{"a": [1, "x", {"b": false}], "c": [], "d": null}"#;
+        let mut writer = EventWriter::new(vec![]);
+        for event in events(text) {
+            writer.write(&event).unwrap();
+        }
+        let output = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(output, r#"{"a":[1,"x",{"b":false}],"c":[],"d":null}"#);
+    }
+}
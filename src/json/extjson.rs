@@ -0,0 +1,452 @@
+use super::{
+    map::Map,
+    options::{ExtHandler, ParseOptions},
+    types::Value,
+};
+
+const BASE64: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+const MILLIS_PER_DAY: i64 = 86_400_000;
+
+/// The contents of a `$binary` value: the BSON subtype and
+/// the decoded bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Binary {
+    pub subtype: u8,
+    pub bytes: Vec<u8>,
+}
+
+impl ExtHandler {
+    /// Returns an extension handler that recognizes the
+    /// `$oid`, `$date`, `$numberLong`, and `$binary` values
+    /// of MongoDB Extended JSON v2, as written by tools such
+    /// as `mongoexport`. Objects that use one of these keys
+    /// but do not have the expected shape are kept as
+    /// objects.
+    pub fn extended_json() -> Self {
+        ExtHandler::new(is_extended_json)
+    }
+}
+
+impl ParseOptions {
+    /// Returns options that parse MongoDB Extended JSON v2,
+    /// keeping its typed values as tagged values. See
+    /// [`ExtHandler::extended_json`].
+    pub fn extended_json() -> Self {
+        Self {
+            ext_handler: Some(ExtHandler::extended_json()),
+            ..Default::default()
+        }
+    }
+}
+
+impl Value {
+    /// Creates an `$oid` value from the hex text of an
+    /// object ID.
+    pub fn object_id(hex: &str) -> Value {
+        ext("$oid", Value::String(hex.to_owned()))
+    }
+
+    /// Creates a `$numberLong` value.
+    pub fn number_long(long: i64) -> Value {
+        ext("$numberLong", Value::String(long.to_string()))
+    }
+
+    /// Creates a `$date` value from milliseconds since the
+    /// Unix epoch. Dates from the year 1970 through 9999 are
+    /// written as ISO-8601 text, and others as a
+    /// `$numberLong`, as in relaxed Extended JSON.
+    pub fn date_millis(millis: i64) -> Value {
+        match (0..253_402_300_800_000).contains(&millis) {
+            true => ext("$date", Value::String(format_date(millis))),
+            false => ext("$date", Value::number_long(millis)),
+        }
+    }
+
+    /// Creates a `$binary` value from bytes and their BSON
+    /// subtype.
+    pub fn binary(subtype: u8, bytes: &[u8]) -> Value {
+        let mut members = Map::new();
+        members.insert("base64", Value::String(encode_base64(bytes)));
+        members.insert("subType", Value::String(format!("{:02x}", subtype)));
+        ext("$binary", Value::Object(members))
+    }
+
+    /// Returns the hex text of an `$oid` value.
+    pub fn as_object_id(&self) -> Option<&str> {
+        object_id_of(tagged(self, "$oid")?)
+    }
+
+    /// Returns the integer of a `$numberLong` value.
+    pub fn as_number_long(&self) -> Option<i64> {
+        number_long_of(tagged(self, "$numberLong")?)
+    }
+
+    /// Returns a `$date` value as milliseconds since the
+    /// Unix epoch, whether it is written as ISO-8601 text or
+    /// as a `$numberLong`.
+    pub fn as_date_millis(&self) -> Option<i64> {
+        date_millis_of(tagged(self, "$date")?)
+    }
+
+    /// Returns the subtype and decoded bytes of a `$binary`
+    /// value.
+    pub fn as_binary(&self) -> Option<Binary> {
+        binary_of(tagged(self, "$binary")?)
+    }
+}
+
+fn ext(tag: &str, value: Value) -> Value {
+    Value::Ext(tag.to_owned(), Box::new(value))
+}
+
+/// Returns the tagged value of an extension value with the
+/// given tag.
+fn tagged<'a>(value: &'a Value, tag: &str) -> Option<&'a Value> {
+    match value {
+        Value::Ext(found, value) if found == tag => Some(value),
+        _ => None,
+    }
+}
+
+/// Checks whether an object with a single member is one of
+/// the supported Extended JSON values, with the expected
+/// shape. Only the member's value is inspected, so nested
+/// values are neither copied nor walked.
+fn is_extended_json(tag: &str, value: &Value) -> bool {
+    match tag {
+        "$oid" => object_id_of(value)
+            .is_some_and(|hex| hex.len() == 24 && hex.bytes().all(|b| b.is_ascii_hexdigit())),
+        "$numberLong" => number_long_of(value).is_some(),
+        "$date" => date_millis_of(value).is_some(),
+        "$binary" => {
+            matches!(value, Value::Object(members) if members.len() == 2)
+                && binary_of(value).is_some()
+        }
+        _ => false,
+    }
+}
+
+/// Reads the value tagged with `$oid`.
+fn object_id_of(value: &Value) -> Option<&str> {
+    match value {
+        Value::String(hex) => Some(hex),
+        _ => None,
+    }
+}
+
+/// Reads the value tagged with `$numberLong`.
+fn number_long_of(value: &Value) -> Option<i64> {
+    match value {
+        Value::String(long) => long.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Reads the value tagged with `$date`.
+fn date_millis_of(value: &Value) -> Option<i64> {
+    match value {
+        Value::String(date) => parse_date(date),
+        long => long.as_number_long(),
+    }
+}
+
+/// Reads the value tagged with `$binary`.
+fn binary_of(value: &Value) -> Option<Binary> {
+    let Value::Object(members) = value else {
+        return None;
+    };
+    let (Some(Value::String(base64)), Some(Value::String(subtype))) =
+        (members.get("base64"), members.get("subType"))
+    else {
+        return None;
+    };
+    Some(Binary {
+        subtype: parse_subtype(subtype)?,
+        bytes: decode_base64(base64)?,
+    })
+}
+
+fn parse_subtype(subtype: &str) -> Option<u8> {
+    match subtype.len() {
+        1 | 2 => u8::from_str_radix(subtype, 16).ok(),
+        _ => None,
+    }
+}
+
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk
+            .iter()
+            .enumerate()
+            .fold(0, |group, (i, &byte)| group | (byte as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            match i <= chunk.len() {
+                true => text.push(BASE64[(group >> (18 - 6 * i) & 0x3f) as usize] as char),
+                false => text.push('='),
+            }
+        }
+    }
+    text
+}
+
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    if !text.len().is_multiple_of(4) {
+        return None;
+    }
+    let data = text.trim_end_matches('=');
+    if text.len() - data.len() > 2 {
+        return None;
+    }
+
+    let mut bytes = Vec::with_capacity(data.len() * 3 / 4);
+    for chunk in data.as_bytes().chunks(4) {
+        let mut group = 0;
+        for (i, &char) in chunk.iter().enumerate() {
+            let sextet = BASE64.iter().position(|&b| b == char)? as u32;
+            group |= sextet << (18 - 6 * i);
+        }
+        let len = chunk.len() * 3 / 4;
+        bytes.extend_from_slice(&group.to_be_bytes()[1..1 + len]);
+    }
+    Some(bytes)
+}
+
+/// Parses an ISO-8601 date and time, such as
+/// `2024-01-31T12:00:00.000Z` or `2024-01-31T12:00:00+01:00`,
+/// into milliseconds since the Unix epoch.
+fn parse_date(text: &str) -> Option<i64> {
+    let number = |range: std::ops::Range<usize>| -> Option<i64> {
+        let digits = text.get(range)?;
+        match digits.bytes().all(|b| b.is_ascii_digit()) {
+            true => digits.parse().ok(),
+            false => None,
+        }
+    };
+    let punct = |index: usize, chars: &[u8]| {
+        text.as_bytes()
+            .get(index)
+            .is_some_and(|b| chars.contains(b))
+    };
+
+    if !(punct(4, b"-") && punct(7, b"-") && punct(10, b"Tt") && punct(13, b":") && punct(16, b":"))
+    {
+        return None;
+    }
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let (hour, minute, second) = (number(11..13)?, number(14..16)?, number(17..19)?);
+    if !(1..=12).contains(&month)
+        || !(1..=days_in_month(year, month)).contains(&day)
+        || hour > 23
+        || minute > 59
+        || second > 59
+    {
+        return None;
+    }
+
+    let mut rest = &text[19..];
+    let mut millis = 0;
+    if let Some(fraction) = rest.strip_prefix('.') {
+        let len = fraction
+            .find(|char: char| !char.is_ascii_digit())
+            .unwrap_or(fraction.len());
+        if len == 0 {
+            return None;
+        }
+        let digits = format!("{:0<3}", &fraction[..len.min(3)]);
+        millis = digits.parse::<i64>().ok()?;
+        rest = &fraction[len..];
+    }
+
+    let offset = match rest.as_bytes() {
+        [b'Z' | b'z'] => 0,
+        [sign @ (b'+' | b'-'), h1, h2, b':', m1, m2] => {
+            let hours = std::str::from_utf8(&[*h1, *h2]).ok()?.parse::<i64>().ok()?;
+            let minutes = std::str::from_utf8(&[*m1, *m2]).ok()?.parse::<i64>().ok()?;
+            if hours > 23 || minutes > 59 {
+                return None;
+            }
+            let offset = (hours * 60 + minutes) * 60_000;
+            match sign {
+                b'+' => offset,
+                _ => -offset,
+            }
+        }
+        _ => return None,
+    };
+
+    let days = days_from_civil(year, month, day);
+    let time = ((hour * 60 + minute) * 60 + second) * 1000 + millis;
+    Some(days * MILLIS_PER_DAY + time - offset)
+}
+
+/// Formats milliseconds since the Unix epoch as an ISO-8601
+/// date and time in UTC, with milliseconds if there are
+/// any.
+fn format_date(millis: i64) -> String {
+    let (days, time) = (
+        millis.div_euclid(MILLIS_PER_DAY),
+        millis.rem_euclid(MILLIS_PER_DAY),
+    );
+    let (year, month, day) = civil_from_days(days);
+    let (seconds, millis) = (time / 1000, time % 1000);
+    let date = format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+        year,
+        month,
+        day,
+        seconds / 3600,
+        seconds / 60 % 60,
+        seconds % 60
+    );
+    match millis {
+        0 => format!("{}Z", date),
+        _ => format!("{}.{:03}Z", date, millis),
+    }
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    let is_leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    match month {
+        2 if is_leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Returns the number of days from the Unix epoch to a
+/// date in the proleptic Gregorian calendar.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Reverses [`days_from_civil`], returning the year, month,
+/// and day.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month_index + 2) / 5 + 1;
+    let month = if month_index < 10 {
+        month_index + 3
+    } else {
+        month_index - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse_with, to_string, ErrorKind, ParseOptions, Value};
+
+    use super::{decode_base64, encode_base64, Binary};
+
+    #[test]
+    fn parses_mongoexport_output() {
+        let text = concat!(
+            r#"{"_id":{"$oid":"5f1d7a2b9c1e4a3b2c1d0e0f"},"#,
+            r#""at":{"$date":"2020-07-26T12:30:45.123Z"},"#,
+            r#""old":{"$date":{"$numberLong":"-86400000"}},"#,
+            r#""n":{"$numberLong":"9007199254740993"},"#,
+            r#""data":{"$binary":{"base64":"AQID","subType":"00"}}}"#
+        );
+        let value = parse_with(text, &ParseOptions::extended_json()).unwrap();
+        let field = |key| value.pointer(&format!("/{}", key)).unwrap();
+
+        assert_eq!(
+            field("_id").as_object_id(),
+            Some("5f1d7a2b9c1e4a3b2c1d0e0f")
+        );
+        assert_eq!(field("at").as_date_millis(), Some(1_595_766_645_123));
+        assert_eq!(field("old").as_date_millis(), Some(-86_400_000));
+        assert_eq!(field("n").as_number_long(), Some(9_007_199_254_740_993));
+        let binary = Binary {
+            subtype: 0,
+            bytes: vec![1, 2, 3],
+        };
+        assert_eq!(field("data").as_binary(), Some(binary));
+        assert_eq!(to_string(&value), text);
+    }
+
+    #[test]
+    fn keeps_malformed_values_as_objects() {
+        let options = ParseOptions::extended_json();
+        for text in [
+            r#"{"$oid": "xyz"}"#,
+            r#"{"$numberLong": 1}"#,
+            r#"{"$date": "2020-02-30T00:00:00Z"}"#,
+            r#"{"$binary": {"base64": "A", "subType": "00"}}"#,
+            r#"{"$regularExpression": {"pattern": "a", "options": ""}}"#,
+        ] {
+            let value = parse_with(text, &options).unwrap();
+            assert!(matches!(value, Value::Object(_)), "{}", text);
+        }
+    }
+
+    #[test]
+    fn writes_extended_json() {
+        let value = Value::date_millis(1_595_766_645_000);
+        assert_eq!(to_string(&value), r#"{"$date":"2020-07-26T12:30:45Z"}"#);
+        let value = Value::date_millis(-1);
+        assert_eq!(to_string(&value), r#"{"$date":{"$numberLong":"-1"}}"#);
+        assert_eq!(value.as_date_millis(), Some(-1));
+
+        let value = Value::binary(4, b"hello");
+        assert_eq!(
+            to_string(&value),
+            r#"{"$binary":{"base64":"aGVsbG8=","subType":"04"}}"#
+        );
+        assert_eq!(value.as_binary().unwrap().bytes, b"hello");
+        assert_eq!(Value::object_id("ab").as_object_id(), Some("ab"));
+    }
+
+    #[test]
+    fn parses_dates_with_offsets() {
+        let date = |text: &str| Value::Ext("$date".into(), Box::new(text.into())).as_date_millis();
+        assert_eq!(date("1970-01-01T01:00:00+01:00"), Some(0));
+        assert_eq!(date("1970-01-01T00:00:00.5Z"), Some(500));
+        assert_eq!(date("2000-02-29T00:00:00Z"), Some(951_782_400_000));
+        assert_eq!(date("2000-02-29"), None);
+        assert_eq!(date("2000-02-29T00:00:00"), None);
+    }
+
+    #[test]
+    fn round_trips_base64() {
+        for bytes in [&b""[..], b"a", b"ab", b"abc", b"abcd"] {
+            assert_eq!(decode_base64(&encode_base64(bytes)).unwrap(), bytes);
+        }
+        assert_eq!(encode_base64(b"ab"), "YWI=");
+        assert!(decode_base64("Y===").is_none());
+        assert!(decode_base64("Y!I=").is_none());
+    }
+
+    #[test]
+    fn limits_depth_by_default() {
+        let deep = r#"{"$date":"#.repeat(100_000) + "1" + &"}".repeat(100_000);
+        let error = parse_with(&deep, &ParseOptions::extended_json()).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TooDeep { max: 128 });
+    }
+
+    #[test]
+    fn checks_shapes_without_walking_nested_values() {
+        let options = ParseOptions {
+            max_depth: None,
+            ..ParseOptions::extended_json()
+        };
+        let deep = r#"{"$date":"#.repeat(5_000) + "1" + &"}".repeat(5_000);
+        let value = parse_with(&deep, &options).unwrap();
+        assert!(matches!(value, Value::Object(_)));
+    }
+}
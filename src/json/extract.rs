@@ -0,0 +1,233 @@
+use super::{
+    map::Map,
+    pointer::JsonPointer,
+    require::MissingPaths,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// A type that can be extracted from a JSON value, as by
+/// the [`extract!`](crate::extract) macro.
+pub trait FromValue: Sized {
+    /// Converts a value, returning an error if it does not
+    /// have the right type or is out of range.
+    fn from_value(value: &Value) -> Result<Self>;
+
+    /// Returns the result of extracting a value that is
+    /// missing, if missing values are allowed.
+    fn from_missing() -> Option<Self> {
+        None
+    }
+}
+
+/// Pulls several typed fields out of a value in one
+/// statement. Each field names a type that implements
+/// [`FromValue`] and the JSON pointer to read it from. The
+/// macro evaluates to a `Result` holding a struct with one
+/// member per field. If any field is missing or has the
+/// wrong type, the error lists every such field. Fields of
+/// type `Option` may be missing or null.
+///
+/// ```ignore
+/// let fields = extract!(value, {
+///     id: u64 = "/id",
+///     name: String = "/user/name",
+///     tags: Vec<String> = "/tags",
+/// })?;
+/// println!("{} {}", fields.id, fields.name);
+/// ```
+#[macro_export]
+macro_rules! extract {
+    ($value:expr, { $($name:ident : $type:ty = $path:expr),* $(,)? }) => {{
+        #[allow(dead_code)]
+        struct Extracted {
+            $($name: $type,)*
+        }
+
+        let value: &$crate::json::Value = &$value;
+        let mut report = $crate::json::MissingPaths::default();
+        $(
+            let $name = $crate::json::extract_field::<$type>(
+                value,
+                $path,
+                stringify!($type),
+                &mut report,
+            );
+        )*
+        match report.missing.is_empty() && report.mismatched.is_empty() {
+            true => Ok(Extracted { $($name: $name.unwrap(),)* }),
+            false => Err(report),
+        }
+    }};
+}
+
+/// Extracts the value at a pointer for the `extract!`
+/// macro, recording it in the report if it is missing or
+/// cannot be converted.
+#[doc(hidden)]
+pub fn extract_field<T: FromValue>(
+    value: &Value,
+    path: &str,
+    type_name: &str,
+    report: &mut MissingPaths,
+) -> Option<T> {
+    let found = JsonPointer::parse(path)
+        .ok()
+        .and_then(|pointer| pointer.resolve(value));
+    let extracted = match found {
+        Some(found) => T::from_value(found).ok(),
+        None => match T::from_missing() {
+            Some(missing) => return Some(missing),
+            None => {
+                report.missing.push(path.to_owned());
+                return None;
+            }
+        },
+    };
+    if extracted.is_none() {
+        report
+            .mismatched
+            .push((path.to_owned(), type_name.to_owned()));
+    }
+    extracted
+}
+
+macro_rules! impl_from_value_for_int {
+    ($($method:ident => $int:ty),*) => {
+        $(
+            impl FromValue for $int {
+                fn from_value(value: &Value) -> Result<Self> {
+                    value.$method()
+                }
+            }
+        )*
+    };
+}
+
+impl_from_value_for_int!(
+    as_u8 => u8,
+    as_u16 => u16,
+    as_u32 => u32,
+    as_u64 => u64,
+    as_usize => usize,
+    as_i8 => i8,
+    as_i16 => i16,
+    as_i32 => i32,
+    as_i64 => i64,
+    as_isize => isize
+);
+
+impl FromValue for f64 {
+    fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Number(number) => Ok(number.as_f64()),
+            _ => Err(unexpected_type("number")),
+        }
+    }
+}
+
+impl FromValue for bool {
+    fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Boolean(bool) => Ok(*bool),
+            _ => Err(unexpected_type("boolean")),
+        }
+    }
+}
+
+impl FromValue for String {
+    fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::String(string) => Ok(string.clone()),
+            _ => Err(unexpected_type("string")),
+        }
+    }
+}
+
+impl FromValue for Map {
+    fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Object(object) => Ok(object.clone()),
+            _ => Err(unexpected_type("object")),
+        }
+    }
+}
+
+impl FromValue for Value {
+    fn from_value(value: &Value) -> Result<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl<T: FromValue> FromValue for Vec<T> {
+    fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Array(array) => array.iter().map(T::from_value).collect(),
+            _ => Err(unexpected_type("array")),
+        }
+    }
+}
+
+impl<T: FromValue> FromValue for Option<T> {
+    fn from_value(value: &Value) -> Result<Self> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_value(value).map(Some),
+        }
+    }
+
+    fn from_missing() -> Option<Self> {
+        Some(None)
+    }
+}
+
+fn unexpected_type(expected: &'static str) -> Error {
+    Error::new(ErrorKind::UnexpectedType(expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, MissingPaths};
+
+    const TEXT: &str = r#"{"id": 7, "user": {"name": "a"}, "tags": ["x", "y"], "score": null}"#;
+
+    #[test]
+    fn extracts_typed_fields() {
+        let value = parse(TEXT).unwrap();
+        let fields = crate::extract!(value, {
+            id: u64 = "/id",
+            name: String = "/user/name",
+            tags: Vec<String> = "/tags",
+            score: Option<f64> = "/score",
+            nickname: Option<String> = "/user/nickname",
+        })
+        .unwrap();
+        assert_eq!(fields.id, 7);
+        assert_eq!(fields.name, "a");
+        assert_eq!(fields.tags, ["x", "y"]);
+        assert_eq!(fields.score, None);
+        assert_eq!(fields.nickname, None);
+    }
+
+    #[test]
+    fn lists_every_failed_field() {
+        let value = parse(TEXT).unwrap();
+        let error = crate::extract!(&value, {
+            id: u8 = "/id",
+            name: bool = "/user/name",
+            email: String = "/user/email",
+            tags: Vec<u64> = "/tags",
+        })
+        .err()
+        .unwrap();
+        assert_eq!(
+            error,
+            MissingPaths {
+                missing: vec!["/user/email".to_owned()],
+                mismatched: vec![
+                    ("/user/name".to_owned(), "bool".to_owned()),
+                    ("/tags".to_owned(), "Vec<u64>".to_owned()),
+                ],
+            }
+        );
+    }
+}
@@ -0,0 +1,189 @@
+use std::{
+    ffi::OsStr,
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+use super::{
+    options::FormatOptions,
+    serializer::try_to_string_with,
+    types::{Error, ErrorKind, Result, Value},
+    utf8::decode,
+};
+
+/// Reads and parses a JSON file. It will return an error
+/// if the file cannot be read, is not valid UTF-8, or is
+/// not valid JSON.
+pub fn read_file(path: impl AsRef<Path>) -> Result<Value> {
+    let path = path.as_ref();
+    let bytes = fs::read(path).map_err(|error| io_error(path, error))?;
+    super::parse(decode(&bytes)?)
+}
+
+/// Writes a value to a JSON file so that the file is never
+/// left partially written. The text is written to a
+/// temporary file in the same directory, which is given
+/// the permissions of the file it replaces, flushed to
+/// disk, and then renamed over the destination. Readers
+/// see either the old file or the new one, even if the
+/// process crashes, and concurrent writers each publish
+/// their own text. It will return an error if the options
+/// reject a number in the value, or if any step fails, in
+/// which case the temporary file is removed.
+pub fn write_file_atomic(
+    path: impl AsRef<Path>,
+    value: &Value,
+    options: &FormatOptions,
+) -> Result<()> {
+    let path = path.as_ref();
+    let name = path
+        .file_name()
+        .ok_or_else(|| io_error(path, io::ErrorKind::InvalidInput.into()))?;
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+
+    let text = try_to_string_with(value, options)?;
+    let (temp, mut file) = create_temp(dir, name).map_err(|error| io_error(path, error))?;
+    let mut write = || -> io::Result<()> {
+        file.write_all(text.as_bytes())?;
+        if let Ok(metadata) = fs::metadata(path) {
+            file.set_permissions(metadata.permissions())?;
+        }
+        file.sync_all()?;
+        fs::rename(&temp, path)
+    };
+    if let Err(error) = write() {
+        let _ = fs::remove_file(&temp);
+        return Err(io_error(path, error));
+    }
+
+    // Persist the rename itself. Directories cannot be
+    // opened as files on every platform, so this is best
+    // effort.
+    if let Ok(dir) = File::open(dir) {
+        let _ = dir.sync_all();
+    }
+    Ok(())
+}
+
+/// Creates a temporary file for a destination file name in
+/// a directory. Every call uses a new name, and the file is
+/// only created if nothing has that name, so that writers in
+/// different threads or processes never share a file.
+fn create_temp(dir: &Path, name: &OsStr) -> io::Result<(PathBuf, File)> {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    loop {
+        let temp = dir.join(format!(
+            ".{}.{}.{}.tmp",
+            name.to_string_lossy(),
+            process::id(),
+            COUNTER.fetch_add(1, Ordering::Relaxed)
+        ));
+        match OpenOptions::new().write(true).create_new(true).open(&temp) {
+            Ok(file) => return Ok((temp, file)),
+            Err(error) if error.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+}
+
+fn io_error(path: &Path, error: io::Error) -> Error {
+    Error::new(ErrorKind::Io(format!("{}: {}", path.display(), error)))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{fs, path::PathBuf, process, thread};
+
+    use crate::json::{parse, ErrorKind, FormatOptions, Value};
+
+    use super::{read_file, write_file_atomic};
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("json-file-{}-{}", process::id(), name));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn writes_and_reads_files() {
+        let dir = temp_dir("round-trip");
+        let path = dir.join("config.json");
+        let value = parse(r#"{"a": [1, true]}"#).unwrap();
+
+        let options = FormatOptions {
+            indent: Some(2),
+            ..Default::default()
+        };
+        write_file_atomic(&path, &value, &options).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"a\": [\n    1,\n    true\n  ]\n}"
+        );
+        assert_eq!(read_file(&path).unwrap(), value);
+
+        write_file_atomic(&path, &parse("[]").unwrap(), &FormatOptions::default()).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[]");
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn reports_io_errors() {
+        let dir = temp_dir("errors");
+        let error = read_file(dir.join("missing.json")).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::Io(_)));
+
+        let path = dir.join("missing").join("config.json");
+        let error = write_file_atomic(&path, &parse("1").unwrap(), &FormatOptions::default());
+        assert!(matches!(error.unwrap_err().kind(), ErrorKind::Io(_)));
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn publishes_whole_files_from_concurrent_writers() {
+        let dir = temp_dir("concurrent");
+        let path = dir.join("config.json");
+        thread::scope(|scope| {
+            for i in 0..8 {
+                let path = &path;
+                scope.spawn(move || {
+                    let value = Value::Array(vec![Value::from(i); 1000]);
+                    for _ in 0..20 {
+                        write_file_atomic(path, &value, &FormatOptions::default()).unwrap();
+                    }
+                });
+            }
+        });
+
+        let value = read_file(&path).unwrap();
+        let Value::Array(elements) = &value else {
+            unreachable!()
+        };
+        assert!(elements.iter().all(|element| element == &elements[0]));
+        assert_eq!(elements.len(), 1000);
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = temp_dir("permissions");
+        let path = dir.join("config.json");
+        fs::write(&path, "1").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        write_file_atomic(&path, &parse("2").unwrap(), &FormatOptions::default()).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
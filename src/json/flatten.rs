@@ -0,0 +1,145 @@
+use super::{
+    map::Map,
+    pointer::parse_index,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+impl Value {
+    /// Flattens the value into an object with one member
+    /// per scalar or empty container, whose key is the path
+    /// to that value with its object keys and array indices
+    /// joined by the separator. `{"a": {"b": [1]}}` flattens
+    /// to `{"a.b.0": 1}` with a separator of `.`, for
+    /// example. A scalar flattens to a member with an empty
+    /// key.
+    pub fn flatten(&self, separator: &str) -> Value {
+        let mut flat = Map::new();
+        flatten_into(self, &mut String::new(), separator, &mut flat);
+        Value::Object(flat)
+    }
+
+    /// Rebuilds a nested value from an object produced by
+    /// [`Value::flatten`]. Keys are split on the separator,
+    /// and objects whose keys are exactly the indices from
+    /// zero up are converted into arrays. It will return an
+    /// error under the following conditions:
+    ///
+    /// * The value is not an object.
+    /// * A key refers to a value inside a member that is
+    ///   not an object, such as `a.b` when `a` is `1`.
+    pub fn unflatten(&self, separator: &str) -> Result<Value> {
+        let Value::Object(flat) = self else {
+            return Err(Error::new(ErrorKind::UnexpectedType("object")));
+        };
+
+        let mut root = Map::new();
+        for (key, value) in flat {
+            let conflict = || Error::new(ErrorKind::ConflictingKey(key.clone()));
+            let mut segments: Vec<_> = key.split(separator).collect();
+            let last = segments.pop().unwrap();
+
+            let mut object = &mut root;
+            for segment in segments {
+                object = match object.entry(segment).or_insert(Value::Object(Map::new())) {
+                    Value::Object(object) => object,
+                    _ => return Err(conflict()),
+                };
+            }
+            if object.insert(last, value.clone()).is_some() {
+                return Err(conflict());
+            }
+        }
+
+        let mut root = Value::Object(root);
+        restore_arrays(&mut root);
+        Ok(root)
+    }
+}
+
+fn flatten_into(value: &Value, path: &mut String, separator: &str, flat: &mut Map) {
+    let children: Vec<(String, &Value)> = match value {
+        Value::Object(object) => object.iter().map(|(k, v)| (k.clone(), v)).collect(),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(i, v)| (i.to_string(), v))
+            .collect(),
+        Value::Ext(tag, value) => vec![(tag.clone(), &**value)],
+        _ => vec![],
+    };
+    if children.is_empty() {
+        flat.insert(path.clone(), value.clone());
+        return;
+    }
+
+    for (key, child) in children {
+        let len = path.len();
+        if len > 0 {
+            path.push_str(separator);
+        }
+        path.push_str(&key);
+        flatten_into(child, path, separator, flat);
+        path.truncate(len);
+    }
+}
+
+/// Converts objects whose keys are the indices from zero
+/// up, in any order, into arrays.
+fn restore_arrays(value: &mut Value) {
+    let Value::Object(object) = value else {
+        return;
+    };
+    object.values_mut().for_each(restore_arrays);
+
+    let is_array = !object.is_empty()
+        && (0..object.len()).all(|index| object.contains_key(&index.to_string()))
+        && object.keys().all(|key| parse_index(key).is_some());
+    if is_array {
+        let mut object = std::mem::take(object);
+        let array = (0..object.len())
+            .map(|index| object.remove(&index.to_string()).unwrap())
+            .collect();
+        *value = Value::Array(array);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind};
+
+    #[test]
+    fn flattens_nested_values() {
+        let value = parse(r#"{"a": {"b": [1, {"c": null}]}, "d": {}, "e": []}"#).unwrap();
+        assert_eq!(
+            value.flatten("."),
+            parse(r#"{"a.b.0": 1, "a.b.1.c": null, "d": {}, "e": []}"#).unwrap()
+        );
+        assert_eq!(
+            parse("1").unwrap().flatten("/"),
+            parse(r#"{"": 1}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn unflattens_into_nested_values() {
+        let value =
+            parse(r#"{"a": {"b": [1, {"c": null}]}, "d": {}, "e": [], "f": {"1": 2}}"#).unwrap();
+        assert_eq!(value.flatten("__").unflatten("__").unwrap(), value);
+
+        let flat = parse(r#"{"x.1": "b", "x.0": "a"}"#).unwrap();
+        assert_eq!(
+            flat.unflatten(".").unwrap(),
+            parse(r#"{"x": ["a", "b"]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_conflicting_keys() {
+        for text in [r#"{"a": 1, "a.b": 2}"#, r#"{"a.b": 2, "a": 1}"#] {
+            let error = parse(text).unwrap().unflatten(".").unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::ConflictingKey(_)));
+        }
+        let error = parse("[]").unwrap().unflatten(".").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedType("object"));
+    }
+}
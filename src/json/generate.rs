@@ -0,0 +1,195 @@
+use super::{
+    map::Map,
+    schema::{Node, Schema, Type},
+    types::Value,
+};
+
+/// The types that a value may take when a schema does not
+/// constrain it.
+const ANY: [Type; 7] = [
+    Type::Null,
+    Type::Boolean,
+    Type::Integer,
+    Type::Number,
+    Type::String,
+    Type::Array,
+    Type::Object,
+];
+
+/// The maximum number of elements in a generated array or
+/// extra members in a generated object.
+const MAX_LEN: u64 = 4;
+
+/// A generator of synthetic JSON documents, for seeding
+/// test environments. Documents are generated either to
+/// conform to a [`Schema`] or at random. The generator is
+/// deterministic: the same seed always produces the same
+/// sequence of documents.
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+}
+
+impl Generator {
+    /// Creates a generator from a seed.
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed ^ 0x9e37_79b9_7f4a_7c15,
+        }
+    }
+
+    /// Generates a value that conforms to a schema. The
+    /// `default` of a schema is used when it has one.
+    /// Required properties are always present, and other
+    /// properties are present at random. Locations that the
+    /// schema does not constrain hold random values up to
+    /// two levels deep.
+    pub fn from_schema(&mut self, schema: &Schema) -> Value {
+        self.node(&schema.root)
+    }
+
+    /// Generates a random value nested up to `depth` levels
+    /// deep. A depth of zero only generates scalars.
+    pub fn random(&mut self, depth: usize) -> Value {
+        let types = match depth {
+            0 => &ANY[..5],
+            _ => &ANY[..],
+        };
+        let type_ = types[self.below(types.len() as u64) as usize];
+        self.value(type_, depth)
+    }
+
+    fn node(&mut self, node: &Node) -> Value {
+        if let Some(default) = &node.default {
+            return default.clone();
+        }
+        let type_ = match &node.types {
+            Some(types) if !types.is_empty() => types[self.below(types.len() as u64) as usize],
+            _ => return self.random(2),
+        };
+
+        match type_ {
+            Type::Object => {
+                let mut object = Map::new();
+                for (key, property) in &node.properties {
+                    if node.required.contains(key) || self.below(2) == 0 {
+                        object.insert(key.clone(), self.node(property));
+                    }
+                }
+                Value::Object(object)
+            }
+            Type::Array => {
+                let len = self.below(MAX_LEN + 1);
+                let array = (0..len)
+                    .map(|_| match &node.items {
+                        Some(items) => self.node(items),
+                        None => self.random(1),
+                    })
+                    .collect();
+                Value::Array(array)
+            }
+            type_ => self.value(type_, 0),
+        }
+    }
+
+    /// Generates a random value of a type, with containers
+    /// nested up to `depth` levels deep.
+    fn value(&mut self, type_: Type, depth: usize) -> Value {
+        match type_ {
+            Type::Null => Value::Null,
+            Type::Boolean => Value::Boolean(self.below(2) == 0),
+            Type::Integer => Value::Number((self.below(2000) as i64 - 1000).into()),
+            Type::Number => {
+                let cents = self.below(200_000) as i64 - 100_000;
+                Value::Number((cents as f64 / 100.0).into())
+            }
+            Type::String => Value::String(self.word()),
+            Type::Array => {
+                let len = self.below(MAX_LEN + 1);
+                let depth = depth.saturating_sub(1);
+                Value::Array((0..len).map(|_| self.random(depth)).collect())
+            }
+            Type::Object => {
+                let len = self.below(MAX_LEN + 1);
+                let depth = depth.saturating_sub(1);
+                let mut object = Map::new();
+                for _ in 0..len {
+                    let key = self.word();
+                    let value = self.random(depth);
+                    object.insert(key, value);
+                }
+                Value::Object(object)
+            }
+        }
+    }
+
+    /// Generates a word of three to eight lowercase letters.
+    fn word(&mut self) -> String {
+        let len = 3 + self.below(6);
+        (0..len)
+            .map(|_| (b'a' + self.below(26) as u8) as char)
+            .collect()
+    }
+
+    /// Returns a pseudo-random number below `bound`, using
+    /// the xorshift64* algorithm.
+    fn below(&mut self, bound: u64) -> u64 {
+        self.state ^= self.state >> 12;
+        self.state ^= self.state << 25;
+        self.state ^= self.state >> 27;
+        self.state.wrapping_mul(0x2545_f491_4f6c_dd1d) % bound
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Schema, Value};
+
+    use super::Generator;
+
+    fn depth(value: &Value) -> usize {
+        match value {
+            Value::Array(array) => 1 + array.iter().map(depth).max().unwrap_or(0),
+            Value::Object(object) => 1 + object.values().map(depth).max().unwrap_or(0),
+            _ => 0,
+        }
+    }
+
+    #[test]
+    fn generates_conforming_values() {
+        let schema = parse(
+            r#"{
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer"},
+                    "name": {"type": ["string", "null"]},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "kind": {"type": "string", "default": "user"}
+                },
+                "required": ["id", "tags", "kind"]
+            }"#,
+        )
+        .unwrap();
+        let schema = Schema::compile(&schema).unwrap();
+
+        let mut generator = Generator::new(7);
+        for _ in 0..50 {
+            let value = generator.from_schema(&schema);
+            assert!(schema.validate(&value).is_empty(), "{}", value);
+            assert_eq!(value.pointer("/kind"), Some(&Value::String("user".into())));
+            assert!(value.pointer("/id").is_some());
+        }
+    }
+
+    #[test]
+    fn generates_random_values_up_to_depth() {
+        let mut generator = Generator::new(1);
+        let values: Vec<_> = (0..50).map(|_| generator.random(3)).collect();
+        assert!(values.iter().all(|value| depth(value) <= 3));
+        assert!(values.iter().any(|value| depth(value) > 0));
+        assert!((0..20).all(|_| depth(&generator.random(0)) == 0));
+
+        let mut again = Generator::new(1);
+        assert_eq!(values[0], again.random(3));
+    }
+}
@@ -0,0 +1,208 @@
+use std::collections::HashSet;
+
+use super::{
+    pointer::{parse_index, JsonPointer},
+    types::Value,
+};
+
+impl Value {
+    /// Returns the pointer to every value in the document
+    /// in depth-first order, starting with the root.
+    pub fn paths(&self) -> Vec<JsonPointer> {
+        self.select_glob("**")
+            .into_iter()
+            .map(|(pointer, _)| pointer)
+            .collect()
+    }
+
+    /// Selects the values whose path matches a glob pattern.
+    /// The pattern is a dot-separated list of segments,
+    /// such as `servers.*.port`. A segment matches an object
+    /// key or array index with the same text, `*` matches
+    /// any single key or index, and `**` matches any number
+    /// of keys and indices, including none. The empty
+    /// pattern selects the root. Each selected value is
+    /// returned along with its pointer.
+    pub fn select_glob(&self, pattern: &str) -> Vec<(JsonPointer, &Value)> {
+        let segments = glob_segments(pattern);
+        let mut matches = vec![];
+        select(self, JsonPointer::root(), &segments, &mut matches);
+
+        let mut seen = HashSet::new();
+        matches.retain(|(pointer, _)| seen.insert(pointer.clone()));
+        matches
+    }
+}
+
+/// Splits a glob pattern into its segments, merging
+/// repeated `**` segments.
+pub(super) fn glob_segments(pattern: &str) -> Vec<&str> {
+    let mut segments: Vec<_> = match pattern {
+        "" => vec![],
+        _ => pattern.split('.').collect(),
+    };
+    segments.dedup_by(|a, b| *a == "**" && *b == "**");
+    segments
+}
+
+/// Checks whether the reference tokens of a path match the
+/// segments of a glob pattern.
+pub(super) fn glob_matches(segments: &[impl AsRef<str>], path: &[String]) -> bool {
+    match (segments.split_first(), path.split_first()) {
+        (None, _) => path.is_empty(),
+        (Some((segment, rest)), _) if segment.as_ref() == "**" => {
+            glob_matches(rest, path) || (!path.is_empty() && glob_matches(segments, &path[1..]))
+        }
+        (Some(_), None) => false,
+        (Some((segment, rest)), Some((token, path))) => {
+            (segment.as_ref() == "*" || segment.as_ref() == token) && glob_matches(rest, path)
+        }
+    }
+}
+
+fn select<'a>(
+    value: &'a Value,
+    pointer: JsonPointer,
+    segments: &[&str],
+    matches: &mut Vec<(JsonPointer, &'a Value)>,
+) {
+    let (segment, rest) = match segments.split_first() {
+        None => return matches.push((pointer, value)),
+        Some(split) => split,
+    };
+
+    match *segment {
+        "**" => {
+            select(value, pointer.clone(), rest, matches);
+            for (key, child) in children(value) {
+                select(child, pointer.join(key), segments, matches);
+            }
+        }
+        "*" => {
+            for (key, child) in children(value) {
+                select(child, pointer.join(key), rest, matches);
+            }
+        }
+        key => {
+            let child = match value {
+                Value::Object(object) => object.get(key),
+                Value::Array(array) => parse_index(key).and_then(|index| array.get(index)),
+                Value::Ext(tag, value) if tag == key => Some(&**value),
+                _ => None,
+            };
+            if let Some(child) = child {
+                select(child, pointer.join(key), rest, matches);
+            }
+        }
+    }
+}
+
+fn children(value: &Value) -> Vec<(String, &Value)> {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| (key.clone(), value))
+            .collect(),
+        Value::Array(array) => array
+            .iter()
+            .enumerate()
+            .map(|(index, value)| (index.to_string(), value))
+            .collect(),
+        Value::Ext(tag, value) => vec![(tag.clone(), &**value)],
+        _ => vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    use super::{glob_matches, glob_segments};
+
+    fn selected(value: &Value, pattern: &str) -> Vec<String> {
+        let mut pointers: Vec<_> = value
+            .select_glob(pattern)
+            .into_iter()
+            .map(|(pointer, _)| pointer.to_string())
+            .collect();
+        pointers.sort();
+        pointers
+    }
+
+    #[test]
+    fn selects_literal_path() {
+        let value = parse(r#"{"a": {"b": 1}}"#).unwrap();
+        let matches = value.select_glob("a.b");
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].0.to_string(), "/a/b");
+        assert_eq!(matches[0].1, &Value::Number(1.0.into()));
+    }
+
+    #[test]
+    fn selects_root_with_empty_pattern() {
+        let value = parse("[1]").unwrap();
+        assert_eq!(selected(&value, ""), [""]);
+    }
+
+    #[test]
+    fn matches_single_segment_wildcard() {
+        let value = parse(r#"{"servers": {"a": {"port": 1}, "b": {"port": 2}, "c": {}}}"#).unwrap();
+        assert_eq!(
+            selected(&value, "servers.*.port"),
+            ["/servers/a/port", "/servers/b/port"]
+        );
+    }
+
+    #[test]
+    fn matches_array_indices() {
+        let value = parse(r#"{"items": [{"id": 1}, {"id": 2}]}"#).unwrap();
+        assert_eq!(selected(&value, "items.1.id"), ["/items/1/id"]);
+        assert_eq!(
+            selected(&value, "items.*.id"),
+            ["/items/0/id", "/items/1/id"]
+        );
+    }
+
+    #[test]
+    fn matches_any_depth_wildcard() {
+        let value = parse(r#"{"port": 1, "a": {"port": 2, "b": [{"port": 3}]}}"#).unwrap();
+        assert_eq!(
+            selected(&value, "**.port"),
+            ["/a/b/0/port", "/a/port", "/port"]
+        );
+    }
+
+    #[test]
+    fn does_not_repeat_matches() {
+        let value = parse(r#"{"a": {"a": 1}}"#).unwrap();
+        assert_eq!(selected(&value, "**.**.a"), ["/a", "/a/a"]);
+        assert_eq!(selected(&value, "**.*.**"), ["/a", "/a/a"]);
+    }
+
+    #[test]
+    fn selects_nothing_for_missing_path() {
+        let value = parse(r#"{"a": 1}"#).unwrap();
+        assert!(value.select_glob("b.*").is_empty());
+        assert!(value.select_glob("a.b").is_empty());
+    }
+
+    #[test]
+    fn enumerates_all_paths() {
+        let value = parse(r#"{"a": [1, {"b": null}]}"#).unwrap();
+        let mut paths: Vec<_> = value.paths().iter().map(|p| p.to_string()).collect();
+        paths.sort();
+        assert_eq!(paths, ["", "/a", "/a/0", "/a/1", "/a/1/b"]);
+    }
+
+    #[test]
+    fn matches_paths_against_patterns() {
+        let path =
+            |text: &str| -> Vec<String> { text.split('/').skip(1).map(String::from).collect() };
+        assert!(glob_matches(&glob_segments("a.*.b"), &path("/a/0/b")));
+        assert!(glob_matches(&glob_segments("**.b"), &path("/a/0/b")));
+        assert!(glob_matches(&glob_segments("a.**"), &path("/a")));
+        assert!(glob_matches(&glob_segments(""), &path("")));
+        assert!(!glob_matches(&glob_segments("a.*"), &path("/a/0/b")));
+        assert!(!glob_matches(&glob_segments("a.b"), &path("/a")));
+    }
+}
@@ -0,0 +1,191 @@
+use super::{
+    map::Map,
+    pointer::JsonPointer,
+    serializer::to_canonical_string,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+impl Value {
+    /// Groups the elements of an array by the value found
+    /// at the `key` pointer within each element. Returns an
+    /// object that maps each distinct key to an array of the
+    /// elements that share it, in their original order.
+    /// String keys are used as they are, and other keys are
+    /// converted to their canonical text, so the number `1`
+    /// becomes the key `"1"`. Elements that are missing the
+    /// key are grouped with those whose key is null. It will
+    /// return an error under the following conditions:
+    ///
+    /// * The pointer is invalid.
+    /// * The value is not an array.
+    pub fn group_by(&self, key: &str) -> Result<Value> {
+        let key = JsonPointer::parse(key)?;
+        let mut groups = Map::new();
+
+        for element in elements(self)? {
+            let group = match key.resolve(element).unwrap_or(&Value::Null) {
+                Value::String(string) => string.clone(),
+                value => to_canonical_string(value),
+            };
+            if let Value::Array(members) =
+                groups.entry(group).or_insert_with(|| Value::Array(vec![]))
+            {
+                members.push(element.clone());
+            }
+        }
+
+        Ok(Value::Object(groups))
+    }
+
+    /// Returns the number of elements in an array. It will
+    /// return an error if the value is not an array.
+    pub fn count(&self) -> Result<usize> {
+        Ok(elements(self)?.len())
+    }
+
+    /// Adds up the numbers found at the `key` pointer within
+    /// the elements of an array. Elements that are missing
+    /// the key or have a null key are skipped. It will
+    /// return an error under the following conditions:
+    ///
+    /// * The pointer is invalid.
+    /// * The value is not an array.
+    /// * A key holds a value other than a number or null.
+    pub fn sum(&self, key: &str) -> Result<f64> {
+        selected(self, key)?
+            .into_iter()
+            .try_fold(0.0, |sum, value| match value {
+                Value::Number(number) => Ok(sum + number.as_f64()),
+                _ => Err(Error::new(ErrorKind::UnexpectedType("number"))),
+            })
+    }
+
+    /// Returns the smallest value found at the `key` pointer
+    /// within the elements of an array, as ordered by
+    /// [`Value::total_cmp`], or `None` if no element has the
+    /// key. Elements that are missing the key or have a null
+    /// key are skipped. It will return an error under the
+    /// following conditions:
+    ///
+    /// * The pointer is invalid.
+    /// * The value is not an array.
+    pub fn min(&self, key: &str) -> Result<Option<&Value>> {
+        Ok(selected(self, key)?
+            .into_iter()
+            .min_by(|a, b| a.total_cmp(b)))
+    }
+
+    /// Returns the largest value found at the `key` pointer
+    /// within the elements of an array, as ordered by
+    /// [`Value::total_cmp`], or `None` if no element has the
+    /// key. Elements that are missing the key or have a null
+    /// key are skipped. It will return an error under the
+    /// following conditions:
+    ///
+    /// * The pointer is invalid.
+    /// * The value is not an array.
+    pub fn max(&self, key: &str) -> Result<Option<&Value>> {
+        Ok(selected(self, key)?
+            .into_iter()
+            .max_by(|a, b| a.total_cmp(b)))
+    }
+}
+
+fn elements(value: &Value) -> Result<&[Value]> {
+    match value {
+        Value::Array(array) => Ok(array),
+        _ => Err(Error::new(ErrorKind::UnexpectedType("array"))),
+    }
+}
+
+fn selected<'a>(value: &'a Value, key: &str) -> Result<Vec<&'a Value>> {
+    let key = JsonPointer::parse(key)?;
+    Ok(elements(value)?
+        .iter()
+        .filter_map(|element| key.resolve(element))
+        .filter(|value| !matches!(value, Value::Null))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    const ORDERS: &str = r#"[
+        {"country": "US", "amount": 10},
+        {"country": "FR", "amount": 5},
+        {"country": "US", "amount": 2.5},
+        {"amount": 1},
+        {"country": "FR", "amount": null}
+    ]"#;
+
+    #[test]
+    fn groups_elements_by_key() {
+        let orders = parse(ORDERS).unwrap();
+        let groups = orders.group_by("/country").unwrap();
+        let expected = parse(
+            r#"{
+                "US": [{"country": "US", "amount": 10}, {"country": "US", "amount": 2.5}],
+                "FR": [{"country": "FR", "amount": 5}, {"country": "FR", "amount": null}],
+                "null": [{"amount": 1}]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(groups, expected);
+    }
+
+    #[test]
+    fn groups_by_non_string_keys() {
+        let value = parse(r#"[{"n": 1}, {"n": true}, {"n": 1}]"#).unwrap();
+        let groups = value.group_by("/n").unwrap();
+        assert_eq!(groups.pointer("/1").unwrap().count().unwrap(), 2);
+        assert_eq!(groups.pointer("/true").unwrap().count().unwrap(), 1);
+    }
+
+    #[test]
+    fn counts_elements() {
+        let orders = parse(ORDERS).unwrap();
+        assert_eq!(orders.count().unwrap(), 5);
+    }
+
+    #[test]
+    fn sums_numbers_at_key() {
+        let orders = parse(ORDERS).unwrap();
+        assert_eq!(orders.sum("/amount").unwrap(), 18.5);
+    }
+
+    #[test]
+    fn aggregates_groups() {
+        let orders = parse(ORDERS).unwrap();
+        let groups = orders.group_by("/country").unwrap();
+        assert_eq!(groups.pointer("/US").unwrap().sum("/amount").unwrap(), 12.5);
+    }
+
+    #[test]
+    fn rejects_sum_of_non_numbers() {
+        let value = parse(r#"[{"a": 1}, {"a": "2"}]"#).unwrap();
+        assert!(value.sum("/a").is_err());
+    }
+
+    #[test]
+    fn finds_min_and_max() {
+        let orders = parse(ORDERS).unwrap();
+        assert_eq!(
+            orders.min("/amount").unwrap(),
+            Some(&Value::Number(1.0.into()))
+        );
+        assert_eq!(
+            orders.max("/amount").unwrap(),
+            Some(&Value::Number(10.0.into()))
+        );
+        assert_eq!(orders.max("/missing").unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_non_array() {
+        let value = parse("{}").unwrap();
+        assert!(value.group_by("/a").is_err());
+        assert!(value.count().is_err());
+        assert!(value.min("/a").is_err());
+    }
+}
@@ -0,0 +1,244 @@
+use std::{
+    fmt::Display,
+    io::{self, Write},
+};
+
+use sha2::{Digest as _, Sha256};
+
+use super::{serializer::to_canonical_string, types::Value};
+
+/// An enumeration of the algorithms that can be used to
+/// fingerprint a JSON value.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HashAlgo {
+    /// 64-bit FNV-1a. Fast, but not collision resistant.
+    Fnv1a64,
+    /// SHA-256. Suitable when collisions must be avoided,
+    /// such as for cache keys shared between services.
+    Sha256,
+}
+
+/// The output of hashing a JSON value. It displays as a
+/// lowercase hexadecimal string.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Digest(Vec<u8>);
+
+impl Digest {
+    /// Returns the raw bytes of the digest.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Returns the digest as a lowercase hexadecimal string.
+    pub fn to_hex(&self) -> String {
+        self.0.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+}
+
+impl Display for Digest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_hex())
+    }
+}
+
+impl Value {
+    /// Hashes the canonical form of the value. The result
+    /// is stable across member order and formatting, so
+    /// it can be used to deduplicate payloads or as a
+    /// cache key.
+    pub fn structural_hash(&self, algo: HashAlgo) -> Digest {
+        let text = to_canonical_string(self);
+        match algo {
+            HashAlgo::Fnv1a64 => Digest(fnv1a64(text.as_bytes()).to_be_bytes().to_vec()),
+            HashAlgo::Sha256 => Digest(Sha256::digest(text.as_bytes()).to_vec()),
+        }
+    }
+}
+
+/// Returns a strong HTTP entity tag for a value: the
+/// quoted SHA-256 hash of its canonical form. Values that
+/// are structurally equal have the same tag, regardless of
+/// member order or formatting.
+pub fn etag(value: &Value) -> String {
+    to_canonical_string_with_etag(value).1
+}
+
+/// Serializes a value into canonical text along with the
+/// entity tag of that text, as returned by [`etag`], so
+/// that a canonical payload and its tag can be produced
+/// with a single serialization.
+pub fn to_canonical_string_with_etag(value: &Value) -> (String, String) {
+    let text = to_canonical_string(value);
+    let etag = format!("\"{}\"", Digest(Sha256::digest(text.as_bytes()).to_vec()));
+    (text, etag)
+}
+
+/// A writer that hashes everything written through it to
+/// another writer, so that a payload and its digest can be
+/// produced in one pass. It can wrap the output of any
+/// streaming writer, such as an [`EventWriter`](super::EventWriter)
+/// or [`concat_to_array`](super::concat_to_array). Only
+/// the bytes accepted by the inner writer are hashed.
+pub struct HashingWriter<W: Write> {
+    writer: W,
+    state: HashState,
+}
+
+enum HashState {
+    Fnv1a64(u64),
+    Sha256(Sha256),
+}
+
+impl<W: Write> HashingWriter<W> {
+    /// Wraps a writer, hashing with the given algorithm.
+    pub fn new(writer: W, algo: HashAlgo) -> Self {
+        let state = match algo {
+            HashAlgo::Fnv1a64 => HashState::Fnv1a64(FNV_OFFSET_BASIS),
+            HashAlgo::Sha256 => HashState::Sha256(Sha256::new()),
+        };
+        Self { writer, state }
+    }
+
+    /// Returns the inner writer and the digest of the bytes
+    /// written so far. The inner writer is not flushed.
+    pub fn finish(self) -> (W, Digest) {
+        let digest = match self.state {
+            HashState::Fnv1a64(hash) => Digest(hash.to_be_bytes().to_vec()),
+            HashState::Sha256(hasher) => Digest(hasher.finalize().to_vec()),
+        };
+        (self.writer, digest)
+    }
+}
+
+impl<W: Write> Write for HashingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        match &mut self.state {
+            HashState::Fnv1a64(hash) => *hash = fnv1a64_update(*hash, &buf[..written]),
+            HashState::Sha256(hasher) => hasher.update(&buf[..written]),
+        }
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf29ce484222325;
+
+fn fnv1a64(bytes: &[u8]) -> u64 {
+    fnv1a64_update(FNV_OFFSET_BASIS, bytes)
+}
+
+fn fnv1a64_update(hash: u64, bytes: &[u8]) -> u64 {
+    const PRIME: u64 = 0x100000001b3;
+
+    bytes
+        .iter()
+        .fold(hash, |hash, byte| (hash ^ *byte as u64).wrapping_mul(PRIME))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+
+    use crate::json::{parse, EventWriter, Value};
+
+    use super::{etag, to_canonical_string_with_etag, HashAlgo, HashingWriter};
+
+    #[test]
+    fn ignores_key_order() {
+        let a = parse(r#"{"a": 1, "b": 2}"#).unwrap();
+        let b = parse(r#"{"b": 2, "a": 1}"#).unwrap();
+        assert_eq!(
+            a.structural_hash(HashAlgo::Sha256),
+            b.structural_hash(HashAlgo::Sha256)
+        );
+    }
+
+    #[test]
+    fn ignores_formatting() {
+        let a = parse(r#"[1,2,{"a":null}]"#).unwrap();
+        let b = parse("[\n  1,\n  2,\n  { \"a\": null }\n]").unwrap();
+        assert_eq!(
+            a.structural_hash(HashAlgo::Fnv1a64),
+            b.structural_hash(HashAlgo::Fnv1a64)
+        );
+    }
+
+    #[test]
+    fn hashes_tagged_values_as_objects() {
+        let tagged = Value::Ext("$date".into(), Box::new(Value::from("2024-01-01")));
+        let object = parse(r#"{"$date": "2024-01-01"}"#).unwrap();
+        assert_eq!(
+            tagged.structural_hash(HashAlgo::Sha256),
+            object.structural_hash(HashAlgo::Sha256)
+        );
+        assert_eq!(etag(&tagged), etag(&object));
+    }
+
+    #[test]
+    fn distinguishes_different_values() {
+        let a = parse("[1, 2]").unwrap();
+        let b = parse("[2, 1]").unwrap();
+        assert_ne!(
+            a.structural_hash(HashAlgo::Sha256),
+            b.structural_hash(HashAlgo::Sha256)
+        );
+    }
+
+    #[test]
+    fn produces_sha256_of_canonical_form() {
+        let value = parse(r#"{"b": 1, "a": [true, null]}"#).unwrap();
+        assert_eq!(
+            value.structural_hash(HashAlgo::Sha256).to_hex(),
+            "51705a2c9eb3e7e410a58f696a770c3ac3885a0cf43eb7fc88f5e47c11d4d30d"
+        );
+    }
+
+    #[test]
+    fn produces_fnv1a64_of_canonical_form() {
+        let value = parse(r#"{"b": 1, "a": [true, null]}"#).unwrap();
+        assert_eq!(
+            value.structural_hash(HashAlgo::Fnv1a64).to_hex(),
+            "6a79a0d90cbae5e2"
+        );
+    }
+
+    #[test]
+    fn derives_strong_etags_from_canonical_text() {
+        let a = parse(r#"{"b": [1.0, "x"], "a": null}"#).unwrap();
+        let b = parse(r#"{"a":null,"b":[1,"x"]}"#).unwrap();
+        assert_eq!(etag(&a), etag(&b));
+        assert_ne!(etag(&a), etag(&parse("{}").unwrap()));
+
+        let (text, tag) = to_canonical_string_with_etag(&a);
+        assert_eq!(text, r#"{"a":null,"b":[1,"x"]}"#);
+        assert_eq!(tag, format!("\"{}\"", a.structural_hash(HashAlgo::Sha256)));
+        assert_eq!(tag.len(), 66);
+    }
+
+    #[test]
+    fn hashes_while_writing() {
+        let value = parse(r#"{"a": [1, "x"], "b": null}"#).unwrap();
+        for algo in [HashAlgo::Fnv1a64, HashAlgo::Sha256] {
+            let mut writer = EventWriter::new(HashingWriter::new(vec![], algo));
+            for event in value.to_events() {
+                writer.write(&event).unwrap();
+            }
+            let (bytes, digest) = writer.into_inner().unwrap().finish();
+            assert_eq!(bytes, br#"{"a":[1,"x"],"b":null}"#);
+            assert_eq!(digest, value.structural_hash(algo));
+        }
+
+        let mut writer = HashingWriter::new(vec![], HashAlgo::Sha256);
+        writer.write_all(b"[1,").unwrap();
+        writer.write_all(b"2]").unwrap();
+        let (_, digest) = writer.finish();
+        assert_eq!(
+            digest,
+            parse("[1, 2]").unwrap().structural_hash(HashAlgo::Sha256)
+        );
+    }
+}
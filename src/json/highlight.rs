@@ -0,0 +1,126 @@
+use super::{
+    tokenizer::{tokenize_with, TokenizeOptions},
+    types::{Result, Span, Token},
+};
+
+/// An enumeration of the classes of text that a JSON
+/// document is divided into for syntax highlighting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum TokenClass {
+    /// A string that names an object member.
+    Key,
+    /// A string that is a value.
+    String,
+    Number,
+    /// Either `true` or `false`.
+    Boolean,
+    Null,
+    /// One of `{}[],:`.
+    Punctuation,
+    Comment,
+}
+
+/// Classifies the tokens of a JSON text for syntax
+/// highlighting. Returns the span and class of each token
+/// in order of appearance, leaving out whitespace.
+/// Comments are accepted and classified so that JSONC
+/// documents can be highlighted as well. It will return an
+/// error if the text cannot be tokenized.
+pub fn highlight(text: &str) -> Result<Vec<(Span, TokenClass)>> {
+    let options = TokenizeOptions {
+        keep_trivia: true,
+        allow_comments: true,
+        ..Default::default()
+    };
+    let tokens = tokenize_with(text, &options)?;
+
+    Ok(tokens
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (token, span))| {
+            let class = match token {
+                Token::String(_) if is_key(&tokens[i + 1..]) => TokenClass::Key,
+                Token::String(_) => TokenClass::String,
+                Token::Number(_) => TokenClass::Number,
+                Token::True | Token::False => TokenClass::Boolean,
+                Token::Null => TokenClass::Null,
+                Token::Punct(_) => TokenClass::Punctuation,
+                Token::Comment(_) => TokenClass::Comment,
+                Token::Whitespace(_) => return None,
+            };
+            Some((*span, class))
+        })
+        .collect())
+}
+
+/// Returns whether a string is a key, given the tokens
+/// that follow it. A key is followed by a colon, possibly
+/// with whitespace or comments in between.
+fn is_key(following: &[(Token, Span)]) -> bool {
+    following
+        .iter()
+        .find(|(token, _)| !matches!(token, Token::Whitespace(_) | Token::Comment(_)))
+        .is_some_and(|(token, _)| token == &Token::Punct(':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::Span;
+
+    use super::{highlight, TokenClass::*};
+
+    #[test]
+    fn classifies_tokens() {
+        let text = r#"{"a": ["b", 1, true, null]}"#;
+        let classes: Vec<_> = highlight(text)
+            .unwrap()
+            .into_iter()
+            .map(|(_, class)| class)
+            .collect();
+        assert_eq!(
+            classes,
+            [
+                Punctuation,
+                Key,
+                Punctuation,
+                Punctuation,
+                String,
+                Punctuation,
+                Number,
+                Punctuation,
+                Boolean,
+                Punctuation,
+                Null,
+                Punctuation,
+                Punctuation,
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_spans() {
+        let text = r#"{ "key" : "value" }"#;
+        let spans = highlight(text).unwrap();
+        assert_eq!(spans[1], (Span { start: 2, end: 7 }, Key));
+        assert_eq!(spans[3], (Span { start: 10, end: 17 }, String));
+    }
+
+    #[test]
+    fn classifies_keys_separated_by_comments() {
+        let text = "{\"a\" /* c */ : \"b\"}";
+        let classes: Vec<_> = highlight(text)
+            .unwrap()
+            .into_iter()
+            .map(|(_, class)| class)
+            .collect();
+        assert_eq!(
+            classes,
+            [Punctuation, Key, Comment, Punctuation, String, Punctuation]
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_tokens() {
+        assert!(highlight("[tru]").is_err());
+    }
+}
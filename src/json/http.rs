@@ -0,0 +1,165 @@
+use std::borrow::Cow;
+
+use super::{
+    parse,
+    types::{Error, ErrorKind, Result, Value},
+    utf8::decode,
+};
+
+/// The record separator that begins each record of a JSON
+/// text sequence.
+const RECORD_SEPARATOR: char = '\u{1e}';
+
+/// Parses the body of an HTTP message according to its
+/// `Content-Type` header, returning the values it holds.
+/// The following media types are understood:
+///
+/// * `application/json`, and types with a `+json` suffix
+///   such as `application/problem+json`, hold one value.
+/// * `application/json-seq` holds a sequence of values,
+///   each preceded by a record separator (RFC 7464).
+/// * `application/x-ndjson` and `application/ndjson` hold
+///   one value per line, ignoring blank lines.
+///
+/// The `charset` parameter may be UTF-8 or UTF-16, and a
+/// byte order mark at the start of the body is skipped. It
+/// will return an error under the following conditions:
+///
+/// * The media type or charset is not supported.
+/// * The body cannot be decoded in the charset.
+/// * The body, or any record in a sequence, is not valid
+///   JSON.
+pub fn parse_http_body(content_type: &str, bytes: &[u8]) -> Result<Vec<Value>> {
+    let mut parts = content_type.split(';');
+    let media_type = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+    let charset = parts
+        .filter_map(|parameter| parameter.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .map(|(_, value)| value.trim().trim_matches('"').to_ascii_lowercase());
+
+    let text = decode_charset(bytes, charset.as_deref())?;
+    let text = text.strip_prefix('\u{feff}').unwrap_or(&text);
+
+    match media_type.as_str() {
+        "application/json" => Ok(vec![parse(text)?]),
+        "application/json-seq" => text.split(RECORD_SEPARATOR).skip(1).map(parse).collect(),
+        "application/x-ndjson" | "application/ndjson" => text
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(parse)
+            .collect(),
+        _ if media_type.starts_with("application/") && media_type.ends_with("+json") => {
+            Ok(vec![parse(text)?])
+        }
+        _ => Err(unsupported(content_type)),
+    }
+}
+
+/// Converts a body to text in the given charset, which
+/// defaults to UTF-8.
+fn decode_charset<'a>(bytes: &'a [u8], charset: Option<&str>) -> Result<Cow<'a, str>> {
+    let big_endian = match charset {
+        None | Some("utf-8" | "utf8") => return Ok(Cow::Borrowed(decode(bytes)?)),
+        Some("utf-16be") => true,
+        Some("utf-16le") => false,
+        Some("utf-16") => !bytes.starts_with(&[0xff, 0xfe]),
+        Some(charset) => return Err(unsupported(charset)),
+    };
+
+    if !bytes.len().is_multiple_of(2) {
+        return Err(Error::new(ErrorKind::InvalidUtf16));
+    }
+    let units = bytes.chunks(2).map(|pair| match big_endian {
+        true => u16::from_be_bytes([pair[0], pair[1]]),
+        false => u16::from_le_bytes([pair[0], pair[1]]),
+    });
+    char::decode_utf16(units)
+        .collect::<std::result::Result<String, _>>()
+        .map(Cow::Owned)
+        .map_err(|_| Error::new(ErrorKind::InvalidUtf16))
+}
+
+fn unsupported(content_type: &str) -> Error {
+    Error::new(ErrorKind::UnsupportedContentType(content_type.to_owned()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind, Value};
+
+    use super::parse_http_body;
+
+    fn values(texts: &[&str]) -> Vec<Value> {
+        texts.iter().map(|text| parse(text).unwrap()).collect()
+    }
+
+    #[test]
+    fn parses_json_bodies() {
+        let body = br#"{"a": [1]}"#;
+        assert_eq!(
+            parse_http_body("application/json", body).unwrap(),
+            values(&[r#"{"a": [1]}"#])
+        );
+        assert_eq!(
+            parse_http_body("Application/Problem+JSON; charset=\"UTF-8\"", body).unwrap(),
+            values(&[r#"{"a": [1]}"#])
+        );
+        assert_eq!(
+            parse_http_body("application/json", "\u{feff}[true]".as_bytes()).unwrap(),
+            values(&["[true]"])
+        );
+    }
+
+    #[test]
+    fn parses_sequences() {
+        let body = b"\x1e{\"a\": 1}\n\x1e[2]\n";
+        assert_eq!(
+            parse_http_body("application/json-seq", body).unwrap(),
+            values(&[r#"{"a": 1}"#, "[2]"])
+        );
+
+        let body = b"{\"a\": 1}\r\n\n[2]\n";
+        assert_eq!(
+            parse_http_body("application/x-ndjson", body).unwrap(),
+            values(&[r#"{"a": 1}"#, "[2]"])
+        );
+        assert!(parse_http_body("application/x-ndjson", b"")
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn decodes_utf16_bodies() {
+        let le: Vec<u8> = "[\"é\"]"
+            .encode_utf16()
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        let be: Vec<u8> = "[\"é\"]"
+            .encode_utf16()
+            .flat_map(u16::to_be_bytes)
+            .collect();
+        let expected = values(&["[\"é\"]"]);
+        assert_eq!(
+            parse_http_body("application/json; charset=utf-16le", &le).unwrap(),
+            expected
+        );
+        assert_eq!(
+            parse_http_body("application/json; charset=utf-16", &be).unwrap(),
+            expected
+        );
+
+        let error = parse_http_body("application/json; charset=utf-16le", &le[1..]).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidUtf16);
+    }
+
+    #[test]
+    fn rejects_unsupported_types() {
+        for content_type in ["text/plain", "application/json; charset=latin1"] {
+            let error = parse_http_body(content_type, b"1").unwrap_err();
+            assert!(matches!(error.kind(), ErrorKind::UnsupportedContentType(_)));
+        }
+
+        let error = parse_http_body("application/json-seq", b"\x1e1\x1e[").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedEnd);
+    }
+}
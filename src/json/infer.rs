@@ -0,0 +1,212 @@
+use super::{map::Map, schema::Type, types::Value};
+
+/// The order in which inferred types are listed.
+const TYPE_ORDER: [Type; 7] = [
+    Type::Null,
+    Type::Boolean,
+    Type::Integer,
+    Type::Number,
+    Type::String,
+    Type::Array,
+    Type::Object,
+];
+
+/// Infers a JSON Schema from sample values. Every sample is
+/// observed in turn, and the schema describes all of them:
+/// the types seen at each location, the properties of
+/// objects, and the items of arrays. A property is required
+/// if it appears in every object observed at its location.
+/// The schema uses the keywords supported by [`Schema`],
+/// so it can be compiled to validate further values.
+///
+/// [`Schema`]: super::Schema
+#[derive(Debug, Clone, Default)]
+pub struct SchemaInferrer {
+    root: Shape,
+}
+
+/// The observations made at one location in the samples.
+#[derive(Debug, Clone, Default)]
+struct Shape {
+    types: Vec<Type>,
+    /// The number of objects observed.
+    objects: usize,
+    /// The shape of each property, with the number of
+    /// objects that had it.
+    properties: Vec<(String, Shape, usize)>,
+    items: Option<Box<Shape>>,
+}
+
+impl SchemaInferrer {
+    /// Creates an inferrer that has not observed any
+    /// samples.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Merges the observations of a sample value into the
+    /// schema.
+    pub fn observe(&mut self, value: &Value) {
+        self.root.observe(value);
+    }
+
+    /// Returns the schema inferred from the samples so far,
+    /// marked as a JSON Schema draft 2020-12 document. A
+    /// schema inferred from no samples accepts any value.
+    pub fn to_schema(&self) -> Value {
+        let mut schema = Map::new();
+        schema.insert(
+            "$schema",
+            Value::String("https://json-schema.org/draft/2020-12/schema".to_owned()),
+        );
+        if let Value::Object(inferred) = self.root.to_schema() {
+            schema.extend(inferred);
+        }
+        Value::Object(schema)
+    }
+}
+
+/// Infers a JSON Schema that describes all of the sample
+/// values, as by [`SchemaInferrer`].
+pub fn infer_schema<'a>(samples: impl IntoIterator<Item = &'a Value>) -> Value {
+    let mut inferrer = SchemaInferrer::new();
+    samples
+        .into_iter()
+        .for_each(|sample| inferrer.observe(sample));
+    inferrer.to_schema()
+}
+
+impl Shape {
+    fn observe(&mut self, value: &Value) {
+        let type_ = match Type::Integer.matches(value) {
+            true => Type::Integer,
+            false => Type::of(value),
+        };
+        if !self.types.contains(&type_) {
+            self.types.push(type_);
+        }
+
+        match value {
+            Value::Object(_) | Value::Ext(..) => {
+                self.objects += 1;
+                for (key, value) in value.entries() {
+                    let position = match self.properties.iter().position(|(k, ..)| k == key) {
+                        Some(position) => position,
+                        None => {
+                            self.properties.push((key.clone(), Shape::default(), 0));
+                            self.properties.len() - 1
+                        }
+                    };
+                    let (_, shape, count) = &mut self.properties[position];
+                    shape.observe(value);
+                    *count += 1;
+                }
+            }
+            Value::Array(array) => {
+                let items = self.items.get_or_insert_with(Box::default);
+                array.iter().for_each(|element| items.observe(element));
+            }
+            _ => {}
+        }
+    }
+
+    fn to_schema(&self) -> Value {
+        let mut types: Vec<_> = TYPE_ORDER
+            .iter()
+            .filter(|type_| self.types.contains(type_))
+            .collect();
+        if types.contains(&&Type::Number) {
+            types.retain(|type_| **type_ != Type::Integer);
+        }
+
+        let mut schema = Map::new();
+        match types.as_slice() {
+            [] => {}
+            [type_] => {
+                schema.insert("type", Value::String(type_.name().to_owned()));
+            }
+            types => {
+                let names = types
+                    .iter()
+                    .map(|type_| Value::String(type_.name().to_owned()))
+                    .collect();
+                schema.insert("type", Value::Array(names));
+            }
+        }
+
+        if !self.properties.is_empty() {
+            let properties = self
+                .properties
+                .iter()
+                .map(|(key, shape, _)| (key.clone(), shape.to_schema()))
+                .collect();
+            schema.insert("properties", Value::Object(properties));
+
+            let required: Vec<_> = self
+                .properties
+                .iter()
+                .filter(|(_, _, count)| *count == self.objects)
+                .map(|(key, ..)| Value::String(key.clone()))
+                .collect();
+            if !required.is_empty() {
+                schema.insert("required", Value::Array(required));
+            }
+        }
+
+        if let Some(items) = &self.items {
+            schema.insert("items", items.to_schema());
+        }
+        Value::Object(schema)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Schema};
+
+    use super::infer_schema;
+
+    #[test]
+    fn infers_schema_from_samples() {
+        let samples = [
+            parse(r#"{"id": 1, "name": "a", "tags": ["x"], "score": 1}"#).unwrap(),
+            parse(r#"{"id": 2, "tags": [], "score": 1.5, "extra": null}"#).unwrap(),
+        ];
+        let schema = infer_schema(&samples);
+        let expected = parse(
+            r#"{
+                "$schema": "https://json-schema.org/draft/2020-12/schema",
+                "type": "object",
+                "properties": {
+                    "id": {"type": "integer"},
+                    "name": {"type": "string"},
+                    "tags": {"type": "array", "items": {"type": "string"}},
+                    "score": {"type": "number"},
+                    "extra": {"type": "null"}
+                },
+                "required": ["id", "tags", "score"]
+            }"#,
+        )
+        .unwrap();
+        assert_eq!(schema, expected);
+
+        let compiled = Schema::compile(&schema).unwrap();
+        assert!(samples
+            .iter()
+            .all(|sample| compiled.validate(sample).is_empty()));
+    }
+
+    #[test]
+    fn lists_every_observed_type() {
+        let samples = [
+            parse("1").unwrap(),
+            parse(r#""a""#).unwrap(),
+            parse("null").unwrap(),
+        ];
+        assert_eq!(
+            infer_schema(&samples).pointer("/type"),
+            Some(&parse(r#"["null", "integer", "string"]"#).unwrap())
+        );
+        assert_eq!(infer_schema([]).pointer("/type"), None);
+    }
+}
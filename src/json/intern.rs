@@ -0,0 +1,233 @@
+use std::{collections::HashMap, sync::Arc};
+
+use super::{
+    map::Map,
+    parse,
+    pointer::{parse_index, JsonPointer},
+    types::{Result, Value},
+};
+
+/// A JSON value whose identical strings and subtrees share
+/// memory, as built by an [`Interner`]. Cloning a shared
+/// value is cheap, since strings, arrays, and objects are
+/// reference counted.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SharedValue {
+    node: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Scalar(Value),
+    String(Arc<str>),
+    Array(Arc<[SharedValue]>),
+    Object(Arc<[(Arc<str>, SharedValue)]>),
+}
+
+impl SharedValue {
+    /// Returns the member of an object with the given key.
+    pub fn get(&self, key: &str) -> Option<&SharedValue> {
+        match &self.node {
+            Node::Object(members) => members
+                .iter()
+                .find(|(k, _)| &**k == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    /// Returns the element of an array at the given index.
+    pub fn get_index(&self, index: usize) -> Option<&SharedValue> {
+        match &self.node {
+            Node::Array(elements) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    /// Looks up a value by the text form of a JSON pointer.
+    /// Returns `None` if the pointer is invalid or refers to
+    /// a missing value.
+    pub fn pointer(&self, pointer: &str) -> Option<&SharedValue> {
+        JsonPointer::parse(pointer).ok()?.tokens().iter().try_fold(
+            self,
+            |value, token| match &value.node {
+                Node::Object(_) => value.get(token),
+                Node::Array(_) => value.get_index(parse_index(token)?),
+                _ => None,
+            },
+        )
+    }
+
+    /// Returns whether two values share the same memory,
+    /// rather than merely being equal. Values that are not
+    /// strings, arrays, or objects never share memory.
+    pub fn ptr_eq(&self, other: &SharedValue) -> bool {
+        match (&self.node, &other.node) {
+            (Node::String(a), Node::String(b)) => Arc::ptr_eq(a, b),
+            (Node::Array(a), Node::Array(b)) => Arc::ptr_eq(a, b),
+            (Node::Object(a), Node::Object(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    /// Converts the value into a plain value, copying every
+    /// shared string and subtree.
+    pub fn to_value(&self) -> Value {
+        match &self.node {
+            Node::Scalar(value) => value.clone(),
+            Node::String(string) => Value::String(string.to_string()),
+            Node::Array(elements) => {
+                Value::Array(elements.iter().map(SharedValue::to_value).collect())
+            }
+            Node::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_value()))
+                    .collect::<Map>(),
+            ),
+        }
+    }
+}
+
+/// A builder that converts values into [`SharedValue`]s,
+/// sharing every string and subtree that is identical to
+/// one it has seen before. This reduces the memory taken
+/// by highly redundant documents, such as exports that
+/// repeat the same small objects many times. Subtrees are
+/// shared across every value built by the same interner,
+/// which keeps an index of them until it is dropped.
+#[derive(Debug, Default)]
+pub struct Interner {
+    nodes: HashMap<Key, (usize, SharedValue)>,
+}
+
+/// Identifies a node by its contents. Containers are
+/// identified by the ids of their already interned
+/// children, so identical subtrees are found without
+/// comparing them in full.
+#[derive(Debug, PartialEq, Eq, Hash)]
+enum Key {
+    /// The debug form of a value that is not a string or
+    /// container, which distinguishes number
+    /// representations.
+    Scalar(String),
+    String(Arc<str>),
+    Array(Vec<usize>),
+    Object(Vec<(usize, usize)>),
+}
+
+impl Interner {
+    /// Creates an interner that has not seen any values.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Parses a JSON string into a shared value. It will
+    /// return an error under the same conditions as
+    /// `parse`.
+    pub fn parse(&mut self, text: &str) -> Result<SharedValue> {
+        Ok(self.intern(&parse(text)?))
+    }
+
+    /// Converts a value into a shared value.
+    pub fn intern(&mut self, value: &Value) -> SharedValue {
+        self.node(value).1
+    }
+
+    /// Returns the number of distinct strings, subtrees,
+    /// and scalars that the interner has seen.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns whether the interner has not seen any
+    /// values.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Interns a value, returning its id and shared form.
+    fn node(&mut self, value: &Value) -> (usize, SharedValue) {
+        let (key, node) = match value {
+            Value::String(string) => return self.string(string),
+            Value::Array(array) => {
+                let (ids, elements): (Vec<_>, Vec<_>) =
+                    array.iter().map(|element| self.node(element)).unzip();
+                (Key::Array(ids), Node::Array(elements.into()))
+            }
+            Value::Object(object) => {
+                let mut ids = vec![];
+                let mut members = vec![];
+                for (key, value) in object {
+                    let (key_id, key) = self.string(key);
+                    let (value_id, value) = self.node(value);
+                    let Node::String(key) = key.node else {
+                        unreachable!()
+                    };
+                    ids.push((key_id, value_id));
+                    members.push((key, value));
+                }
+                (Key::Object(ids), Node::Object(members.into()))
+            }
+            scalar => (
+                Key::Scalar(format!("{:?}", scalar)),
+                Node::Scalar(scalar.clone()),
+            ),
+        };
+        self.insert(key, node)
+    }
+
+    fn string(&mut self, string: &str) -> (usize, SharedValue) {
+        let string: Arc<str> = Arc::from(string);
+        self.insert(Key::String(string.clone()), Node::String(string))
+    }
+
+    /// Returns the node already interned under a key, or
+    /// interns the given node under a new id.
+    fn insert(&mut self, key: Key, node: Node) -> (usize, SharedValue) {
+        let id = self.nodes.len();
+        self.nodes
+            .entry(key)
+            .or_insert_with(|| (id, SharedValue { node }))
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::parse;
+
+    use super::Interner;
+
+    #[test]
+    fn shares_identical_subtrees() {
+        let text = r#"[{"kind": {"a": [1]}, "n": 1}, {"kind": {"a": [1]}, "n": 2}, {"a": [2]}]"#;
+        let mut interner = Interner::new();
+        let value = interner.parse(text).unwrap();
+        assert_eq!(value.to_value(), parse(text).unwrap());
+
+        let first = value.pointer("/0/kind").unwrap();
+        let second = value.pointer("/1/kind").unwrap();
+        assert!(first.ptr_eq(second));
+        assert!(!first.ptr_eq(value.pointer("/2").unwrap()));
+        assert!(!value
+            .get_index(0)
+            .unwrap()
+            .ptr_eq(value.get_index(1).unwrap()));
+        assert!(value
+            .pointer("/0/kind/a")
+            .unwrap()
+            .ptr_eq(value.pointer("/1/kind/a").unwrap()));
+    }
+
+    #[test]
+    fn shares_subtrees_across_values() {
+        let mut interner = Interner::new();
+        let a = interner.parse(r#"{"x": ["y"]}"#).unwrap();
+        let len = interner.len();
+        let b = interner.parse(r#"{"x": ["y"]}"#).unwrap();
+        assert!(a.ptr_eq(&b));
+        assert_eq!(interner.len(), len);
+        assert!(a.get("missing").is_none());
+    }
+}
@@ -0,0 +1,126 @@
+use std::slice;
+
+use super::{map, types::Value};
+
+impl Value {
+    /// Returns an iterator over the members of an object, as
+    /// key and value pairs in order. A tagged value has its
+    /// tag and value as its only member. Returns an empty
+    /// iterator if the value is not an object.
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &Value)> {
+        let (members, tagged) = match self {
+            Value::Object(object) => (Some(object.iter()), None),
+            Value::Ext(tag, value) => (None, Some((tag, value.as_ref()))),
+            _ => (None, None),
+        };
+        members.into_iter().flatten().chain(tagged)
+    }
+
+    /// Returns an iterator over the elements of an array in
+    /// order. Returns an empty iterator if the value is not
+    /// an array.
+    pub fn items(&self) -> impl Iterator<Item = &Value> {
+        let elements = match self {
+            Value::Array(array) => array.as_slice(),
+            _ => &[],
+        };
+        elements.iter()
+    }
+}
+
+/// An iterator over the children of a value: the elements
+/// of an array, the values of the members of an object, or
+/// the value of a tagged value, in order. Other values have
+/// no children.
+pub struct Children<'a>(Inner<'a>);
+
+enum Inner<'a> {
+    Elements(slice::Iter<'a, Value>),
+    Members(map::Iter<'a>),
+}
+
+impl<'a> Iterator for Children<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Inner::Elements(elements) => elements.next(),
+            Inner::Members(members) => members.next().map(|(_, value)| value),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        match &self.0 {
+            Inner::Elements(elements) => elements.size_hint(),
+            Inner::Members(members) => members.size_hint(),
+        }
+    }
+}
+
+impl DoubleEndedIterator for Children<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        match &mut self.0 {
+            Inner::Elements(elements) => elements.next_back(),
+            Inner::Members(members) => members.next_back().map(|(_, value)| value),
+        }
+    }
+}
+
+impl ExactSizeIterator for Children<'_> {}
+
+impl<'a> IntoIterator for &'a Value {
+    type Item = &'a Value;
+    type IntoIter = Children<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        match self {
+            Value::Array(array) => Children(Inner::Elements(array.iter())),
+            Value::Object(object) => Children(Inner::Members(object.iter())),
+            Value::Ext(_, value) => Children(Inner::Elements(slice::from_ref(&**value).iter())),
+            _ => Children(Inner::Elements([].iter())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    #[test]
+    fn iterates_over_elements_and_member_values() {
+        let value = parse("[1, 2, 3]").unwrap();
+        let mut sum = 0.0;
+        for child in &value {
+            if let Value::Number(number) = child {
+                sum += number.as_f64();
+            }
+        }
+        assert_eq!(sum, 6.0);
+
+        let value = parse(r#"{"a": null, "b": true}"#).unwrap();
+        let children: Vec<_> = (&value).into_iter().rev().collect();
+        assert_eq!(children, [&Value::Boolean(true), &Value::Null]);
+        assert_eq!((&value).into_iter().len(), 2);
+
+        let value = parse(r#""abc""#).unwrap();
+        assert_eq!((&value).into_iter().count(), 0);
+    }
+
+    #[test]
+    fn iterates_over_entries_of_objects() {
+        let value = parse(r#"{"a": 1, "b": 2}"#).unwrap();
+        let keys: Vec<_> = value.entries().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(keys, ["a", "b"]);
+        assert_eq!(parse("[1]").unwrap().entries().count(), 0);
+    }
+
+    #[test]
+    fn iterates_over_items_of_arrays() {
+        let value = parse("[1, null, 3]").unwrap();
+        assert_eq!(
+            value.items().filter(|item| **item == Value::Null).count(),
+            1
+        );
+        assert_eq!(parse(r#"{"a": 1}"#).unwrap().items().count(), 0);
+    }
+}
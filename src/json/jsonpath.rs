@@ -0,0 +1,222 @@
+use super::types::{Error, ErrorKind, Result, Value};
+
+/// One step of a JSONPath expression.
+#[derive(Debug, Clone, PartialEq)]
+enum Selector {
+    /// Selects the member of an object with a key.
+    Key(String),
+    /// Selects the element of an array at an index.
+    Index(usize),
+    /// Selects every member or element.
+    Wildcard,
+    /// Applies the rest of the path to the value and every
+    /// value nested within it.
+    Descendants,
+}
+
+impl Value {
+    /// Selects the values that match a JSONPath expression,
+    /// such as `$.items[*].price`, in document order. The
+    /// expression begins with `$`, the root, followed by
+    /// any number of the following steps:
+    ///
+    /// * `.key` or `['key']` selects a member of an object.
+    /// * `[0]` selects an element of an array.
+    /// * `.*` or `[*]` selects every member or element.
+    /// * `..` applies the step after it at any depth, as in
+    ///   `$..price`.
+    ///
+    /// Filters, slices, and unions are not supported. It
+    /// will return an error if the expression is invalid.
+    pub fn select_path(&self, path: &str) -> Result<Vec<&Value>> {
+        let selectors = parse_path(path)?;
+        let mut matches = vec![];
+        select(self, &selectors, &mut matches);
+        Ok(matches)
+    }
+}
+
+/// Parses a JSONPath expression into its selectors.
+fn parse_path(path: &str) -> Result<Vec<Selector>> {
+    let invalid = || Error::new(ErrorKind::InvalidJsonPath(path.to_owned()));
+    let mut rest = path.strip_prefix('$').ok_or_else(invalid)?;
+    let mut selectors = vec![];
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("..") {
+            selectors.push(Selector::Descendants);
+            rest = match after.starts_with('[') {
+                true => after,
+                false => {
+                    let (selector, after) = parse_name(after).ok_or_else(invalid)?;
+                    selectors.push(selector);
+                    after
+                }
+            };
+        } else if let Some(after) = rest.strip_prefix('.') {
+            let (selector, after) = parse_name(after).ok_or_else(invalid)?;
+            selectors.push(selector);
+            rest = after;
+        } else if let Some(after) = rest.strip_prefix('[') {
+            let (selector, after) = parse_bracket(after).ok_or_else(invalid)?;
+            selectors.push(selector);
+            rest = after;
+        } else {
+            return Err(invalid());
+        }
+    }
+    Ok(selectors)
+}
+
+/// Parses the name after a dot, which runs up to the next
+/// dot or bracket.
+fn parse_name(text: &str) -> Option<(Selector, &str)> {
+    let end = text.find(['.', '[']).unwrap_or(text.len());
+    let selector = match &text[..end] {
+        "" => return None,
+        "*" => Selector::Wildcard,
+        name => Selector::Key(name.to_owned()),
+    };
+    Some((selector, &text[end..]))
+}
+
+/// Parses the contents of brackets, after the opening one,
+/// which hold a wildcard, an index, or a quoted key.
+fn parse_bracket(text: &str) -> Option<(Selector, &str)> {
+    if let Some(rest) = text.strip_prefix("*]") {
+        return Some((Selector::Wildcard, rest));
+    }
+
+    let quote = match text.chars().next()? {
+        quote @ ('\'' | '"') => quote,
+        _ => {
+            let (index, rest) = text.split_once(']')?;
+            let index = match index.len() > 1 && index.starts_with('0') {
+                true => return None,
+                false => index.parse().ok()?,
+            };
+            return Some((Selector::Index(index), rest));
+        }
+    };
+
+    let mut key = String::new();
+    let mut chars = text[1..].char_indices();
+    while let Some((i, char)) = chars.next() {
+        match char {
+            '\\' => key.push(chars.next()?.1),
+            char if char == quote => {
+                let rest = text[1 + i + 1..].strip_prefix(']')?;
+                return Some((Selector::Key(key), rest));
+            }
+            char => key.push(char),
+        }
+    }
+    None
+}
+
+fn select<'a>(value: &'a Value, selectors: &[Selector], matches: &mut Vec<&'a Value>) {
+    let (selector, rest) = match selectors.split_first() {
+        None => return matches.push(value),
+        Some(split) => split,
+    };
+
+    match selector {
+        Selector::Key(key) => {
+            let child = match value {
+                Value::Object(object) => object.get(key),
+                Value::Ext(tag, value) if tag == key => Some(&**value),
+                _ => None,
+            };
+            if let Some(child) = child {
+                select(child, rest, matches);
+            }
+        }
+        Selector::Index(index) => {
+            if let Value::Array(array) = value {
+                if let Some(child) = array.get(*index) {
+                    select(child, rest, matches);
+                }
+            }
+        }
+        Selector::Wildcard => {
+            for child in children(value) {
+                select(child, rest, matches);
+            }
+        }
+        Selector::Descendants => {
+            select(value, rest, matches);
+            for child in children(value) {
+                select(child, selectors, matches);
+            }
+        }
+    }
+}
+
+fn children(value: &Value) -> Box<dyn Iterator<Item = &Value> + '_> {
+    match value {
+        Value::Object(object) => Box::new(object.values()),
+        Value::Array(array) => Box::new(array.iter()),
+        Value::Ext(_, value) => Box::new(std::iter::once(&**value)),
+        _ => Box::new(std::iter::empty()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind, Value};
+
+    use super::{parse_path, Selector};
+
+    const TEXT: &str = r#"{
+        "items": [{"price": 1, "tags": ["a"]}, {"price": 2}, {"name": "c"}],
+        "a.b": {"price": 3}
+    }"#;
+
+    fn selected(value: &Value, path: &str) -> Vec<String> {
+        let matches = value.select_path(path).unwrap();
+        matches.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_paths() {
+        assert_eq!(
+            parse_path("$.a[0]['b.c'][*]..d").unwrap(),
+            [
+                Selector::Key("a".into()),
+                Selector::Index(0),
+                Selector::Key("b.c".into()),
+                Selector::Wildcard,
+                Selector::Descendants,
+                Selector::Key("d".into()),
+            ]
+        );
+        assert_eq!(parse_path("$").unwrap(), []);
+        assert_eq!(
+            parse_path(r#"$["it's"]"#).unwrap(),
+            [Selector::Key("it's".into())]
+        );
+        for path in ["", "items", "$.", "$..", "$[01]", "$[a]", "$['a'", "$x"] {
+            let error = parse_path(path).unwrap_err();
+            assert!(
+                matches!(error.kind(), ErrorKind::InvalidJsonPath(_)),
+                "{}",
+                path
+            );
+        }
+    }
+
+    #[test]
+    fn selects_values() {
+        let value = parse(TEXT).unwrap();
+        assert_eq!(selected(&value, "$.items[*].price"), ["1", "2"]);
+        assert_eq!(selected(&value, "$.items[1].price"), ["2"]);
+        assert_eq!(selected(&value, "$['a.b'].price"), ["3"]);
+        assert_eq!(selected(&value, "$..price"), ["1", "2", "3"]);
+        assert_eq!(
+            selected(&value, "$..[0]"),
+            [r#"{"price":1,"tags":["a"]}"#, r#""a""#]
+        );
+        assert_eq!(selected(&value, "$.items.*.name"), [r#""c""#]);
+        assert!(selected(&value, "$.items[5]").is_empty());
+        assert_eq!(selected(&value, "$").len(), 1);
+    }
+}
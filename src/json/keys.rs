@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+
+use unicode_normalization::UnicodeNormalization;
+
+use super::{
+    map::Map,
+    options::ParseOptions,
+    types::{Error, ErrorKind, Result, Span, Token, Value},
+};
+
+impl Map {
+    /// Looks up a member by key, ignoring case. An exact
+    /// match is preferred over a match that differs in
+    /// case. Returns `None` if there is no matching member.
+    pub fn get_ci(&self, key: &str) -> Option<&Value> {
+        self.get(key).or_else(|| {
+            let key = key.to_lowercase();
+            self.iter()
+                .find(|(other, _)| other.to_lowercase() == key)
+                .map(|(_, value)| value)
+        })
+    }
+}
+
+impl Value {
+    /// Looks up an object member by key, ignoring case, as
+    /// [`Map::get_ci`] does. Returns `None` if the value is
+    /// not an object or has no matching member.
+    pub fn get_ci(&self, key: &str) -> Option<&Value> {
+        match self {
+            Value::Object(object) => object.get_ci(key),
+            _ => None,
+        }
+    }
+}
+
+/// Normalizes the object keys in a token list according to
+/// the parse options, and passes them to the key handler.
+/// A key is a string token that is followed by a colon. It
+/// will return an error if the key handler rejects a key.
+pub(super) fn normalize_keys(tokens: &mut [(Token, Span)], options: &ParseOptions) -> Result<()> {
+    if !options.nfc_keys && !options.lowercase_keys && options.key_handler.is_none() {
+        return Ok(());
+    }
+
+    for i in 1..tokens.len() {
+        if tokens[i].0 != Token::Punct(':') {
+            continue;
+        }
+
+        let (token, span) = &mut tokens[i - 1];
+        if let Token::String(key) = token {
+            if options.nfc_keys {
+                *key = key.nfc().collect();
+            }
+            if options.lowercase_keys {
+                *key = key.to_lowercase();
+            }
+            if let Some(handler) = &options.key_handler {
+                *key = handler
+                    .handle(std::mem::take(key))
+                    .map_err(|reason| Error::at(ErrorKind::RejectedKey(reason), span.start))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that no key appears more than once in an object
+/// of a token list that has already been normalized. It
+/// will return an error carrying the offset of the second
+/// occurrence of a key otherwise.
+pub(super) fn reject_duplicate_keys(tokens: &[(Token, Span)]) -> Result<()> {
+    let mut objects: Vec<Option<HashSet<&str>>> = vec![];
+    for (i, (token, span)) in tokens.iter().enumerate() {
+        match token {
+            Token::Punct('{') => objects.push(Some(HashSet::new())),
+            Token::Punct('[') => objects.push(None),
+            Token::Punct('}' | ']') => {
+                objects.pop();
+            }
+            Token::String(key)
+                if tokens.get(i + 1).map(|(token, _)| token) == Some(&Token::Punct(':')) =>
+            {
+                if let Some(Some(keys)) = objects.last_mut() {
+                    if !keys.insert(key) {
+                        return Err(Error::at(ErrorKind::DuplicateKey(key.clone()), span.start));
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, parse_with, ErrorKind, KeyHandler, ParseOptions, Value};
+
+    #[test]
+    fn finds_key_ignoring_case() {
+        let value = parse(r#"{"Content-Type": "text/plain"}"#).unwrap();
+        assert_eq!(
+            value.get_ci("content-type"),
+            Some(&Value::String("text/plain".to_owned()))
+        );
+    }
+
+    #[test]
+    fn prefers_exact_match() {
+        let value = parse(r#"{"KEY": 1, "key": 2}"#).unwrap();
+        assert_eq!(value.get_ci("key"), Some(&Value::Number(2.0.into())));
+    }
+
+    #[test]
+    fn finds_key_in_map_ignoring_case() {
+        let value = parse(r#"{"Accept": "*/*", "accept": "text/plain"}"#).unwrap();
+        let Value::Object(object) = value else {
+            panic!("expected an object");
+        };
+        assert_eq!(object.get_ci("ACCEPT"), Some(&Value::from("*/*")));
+        assert_eq!(object.get_ci("accept"), Some(&Value::from("text/plain")));
+        assert_eq!(object.get_ci("Host"), None);
+    }
+
+    #[test]
+    fn finds_nothing_for_missing_key_or_non_object() {
+        let value = parse(r#"{"a": 1}"#).unwrap();
+        assert_eq!(value.get_ci("b"), None);
+        assert_eq!(parse("[]").unwrap().get_ci("a"), None);
+    }
+
+    #[test]
+    fn lowercases_keys_when_requested() {
+        let options = ParseOptions {
+            lowercase_keys: true,
+            ..Default::default()
+        };
+        let value = parse_with(r#"{"Accept": {"X-Id": "ABC"}}"#, &options).unwrap();
+        assert_eq!(value, parse(r#"{"accept": {"x-id": "ABC"}}"#).unwrap());
+    }
+
+    #[test]
+    fn normalizes_keys_to_nfc_when_requested() {
+        let options = ParseOptions {
+            nfc_keys: true,
+            ..Default::default()
+        };
+        let value = parse_with("{\"cafe\u{0301}\": \"cafe\u{0301}\"}", &options).unwrap();
+        assert_eq!(value, parse("{\"caf\u{e9}\": \"cafe\u{0301}\"}").unwrap());
+    }
+
+    #[test]
+    fn leaves_keys_untouched_by_default() {
+        let value = parse_with(r#"{"Key": 1}"#, &ParseOptions::default()).unwrap();
+        assert!(value.pointer("/Key").is_some());
+    }
+
+    #[test]
+    fn passes_keys_to_handler() {
+        let options = ParseOptions {
+            lowercase_keys: true,
+            key_handler: Some(KeyHandler::new(|key| match key.starts_with('$') {
+                true => Err(format!("{} is reserved", key)),
+                false => Ok(key.replace('-', "_")),
+            })),
+            ..Default::default()
+        };
+        let value = parse_with(r#"{"X-Id": {"a-b": "c-d"}}"#, &options).unwrap();
+        assert_eq!(value, parse(r#"{"x_id": {"a_b": "c-d"}}"#).unwrap());
+
+        let error = parse_with(r#"{"a": {"$ref": 1}}"#, &options).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::RejectedKey("$ref is reserved".to_owned())
+        );
+        assert_eq!(error.offset(), Some(7));
+    }
+}
@@ -0,0 +1,147 @@
+use super::{
+    options::ParseOptions,
+    types::{Error, ErrorKind, Result, Span, Token},
+};
+
+/// Checks a token list against the limits on the number of
+/// tokens, the depth of nesting, and the length of strings
+/// set by the parse options. Whitespace and comments are
+/// not counted as tokens. It will return an error carrying
+/// the offset of the first token past a limit otherwise.
+pub(super) fn enforce_limits(tokens: &[(Token, Span)], options: &ParseOptions) -> Result<()> {
+    let mut count = 0;
+    let mut depth: usize = 0;
+    for (token, span) in tokens {
+        match token {
+            Token::Whitespace(_) | Token::Comment(_) => continue,
+            Token::Punct('[' | '{') => depth += 1,
+            // Unbalanced closers are left for the parser to
+            // reject.
+            Token::Punct(']' | '}') => depth = depth.saturating_sub(1),
+            Token::String(_) => {
+                // Strings are measured as written, with their
+                // escapes, rather than as decoded.
+                let length = span.end - span.start - 2;
+                if let Some(max) = options.max_string_length.filter(|&max| length > max) {
+                    return Err(Error::at(
+                        ErrorKind::StringTooLong { length, max },
+                        span.start,
+                    ));
+                }
+            }
+            _ => {}
+        }
+
+        count += 1;
+        if let Some(max) = options.max_tokens.filter(|&max| count > max) {
+            return Err(Error::at(ErrorKind::TooManyTokens { max }, span.start));
+        }
+        if let Some(max) = options.max_depth.filter(|&max| depth > max) {
+            return Err(Error::at(ErrorKind::TooDeep { max }, span.start));
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{
+        parse, parse_cst, parse_spanned, parse_with, Document, ErrorKind, Interner, KeyOrder,
+        ParseOptions,
+    };
+
+    #[test]
+    fn enforces_limits() {
+        let options = ParseOptions {
+            max_size: Some(20),
+            max_tokens: Some(7),
+            max_depth: Some(2),
+            max_string_length: Some(3),
+            ..Default::default()
+        };
+        assert!(parse_with(r#"[["abc"], 1]"#, &options).is_ok());
+
+        let cases = [
+            ("[[[1]]]", ErrorKind::TooDeep { max: 2 }, 2),
+            (
+                r#"["abcd"]"#,
+                ErrorKind::StringTooLong { length: 4, max: 3 },
+                1,
+            ),
+            ("[1, 2, 3, 4]", ErrorKind::TooManyTokens { max: 7 }, 10),
+            (
+                "[                  1]",
+                ErrorKind::InputTooLarge { size: 21, max: 20 },
+                20,
+            ),
+        ];
+        for (text, kind, offset) in cases {
+            let error = parse_with(text, &options).unwrap_err();
+            assert_eq!((error.kind(), error.offset()), (&kind, Some(offset)));
+        }
+    }
+
+    #[test]
+    fn hardened_options_reject_hostile_input() {
+        let options = ParseOptions::hardened();
+        assert!(parse_with(r#"{"a": [1, "b", null]}"#, &options).is_ok());
+
+        let deep = "[".repeat(65) + &"]".repeat(65);
+        let error = parse_with(&deep, &options).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TooDeep { max: 64 });
+
+        let error = parse_with(r#"{"a": 1, "a": 2}"#, &options).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::DuplicateKey("a".into()));
+
+        let error = parse_with("None", &options).unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::UnexpectedCharacter { .. }
+        ));
+    }
+
+    #[test]
+    fn leaves_unbalanced_closers_to_parser() {
+        let options = ParseOptions {
+            max_depth: Some(2),
+            ..Default::default()
+        };
+        for text in ["]", "[]]", "1]"] {
+            let error = parse_with(text, &options).unwrap_err();
+            assert!(!matches!(error.kind(), ErrorKind::TooDeep { .. }));
+            assert!(crate::json::parse(text).is_err());
+        }
+    }
+
+    #[test]
+    fn limits_depth_by_default() {
+        let nested = |depth| "[".repeat(depth) + &"]".repeat(depth);
+        assert!(parse(&nested(128)).is_ok());
+        assert!(parse_cst(&nested(128)).is_ok());
+        assert!(Document::parse(&nested(128)).is_ok());
+
+        let deep = nested(100_000);
+        let too_deep = ErrorKind::TooDeep { max: 128 };
+        let sorted = ParseOptions {
+            key_order: KeyOrder::Sorted,
+            ..Default::default()
+        };
+        let errors = [
+            parse(&deep).unwrap_err(),
+            parse_with(&deep, &sorted).unwrap_err(),
+            parse_spanned(&deep).unwrap_err(),
+            parse_cst(&deep).unwrap_err(),
+            Document::parse(&deep).unwrap_err(),
+            Interner::new().parse(&deep).unwrap_err(),
+        ];
+        for error in errors {
+            assert_eq!((error.kind(), error.offset()), (&too_deep, Some(128)));
+        }
+
+        let options = ParseOptions {
+            max_depth: None,
+            ..Default::default()
+        };
+        assert!(parse_with(&nested(1000), &options).is_ok());
+    }
+}
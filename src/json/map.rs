@@ -0,0 +1,429 @@
+use std::collections::HashMap;
+
+use super::types::Value;
+
+/// The members of a JSON object. Members are kept in the
+/// order in which they were inserted, and can be looked up
+/// by key in constant time. Two maps compare equal when
+/// they have the same members, regardless of their order.
+#[derive(Clone, Default)]
+pub struct Map {
+    entries: Vec<(String, Value)>,
+    index: HashMap<String, usize>,
+}
+
+impl Map {
+    /// Creates an empty map.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an empty map with space for at least
+    /// `capacity` members.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            entries: Vec::with_capacity(capacity),
+            index: HashMap::with_capacity(capacity),
+        }
+    }
+
+    /// Reserves space for at least `additional` more
+    /// members.
+    pub fn reserve(&mut self, additional: usize) {
+        self.entries.reserve(additional);
+        self.index.reserve(additional);
+    }
+
+    /// Returns the number of members the map can hold
+    /// without reallocating.
+    pub fn capacity(&self) -> usize {
+        self.entries.capacity().min(self.index.capacity())
+    }
+
+    /// Returns the number of members in the map.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns whether the map has no members.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Returns the value of the member with the given key.
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        self.index
+            .get(key)
+            .map(|&position| &self.entries[position].1)
+    }
+
+    /// Returns the value of the member with the given key
+    /// for modification.
+    pub fn get_mut(&mut self, key: &str) -> Option<&mut Value> {
+        self.index
+            .get(key)
+            .map(|&position| &mut self.entries[position].1)
+    }
+
+    /// Returns the member at the given position.
+    pub fn get_index(&self, position: usize) -> Option<(&String, &Value)> {
+        self.entries.get(position).map(|(key, value)| (key, value))
+    }
+
+    /// Returns the position of the member with the given
+    /// key.
+    pub fn index_of(&self, key: &str) -> Option<usize> {
+        self.index.get(key).copied()
+    }
+
+    /// Returns whether the map has a member with the given
+    /// key.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.index.contains_key(key)
+    }
+
+    /// Inserts a member. If the map already has a member
+    /// with the same key, its value is replaced in place and
+    /// the old value is returned. Otherwise, the member is
+    /// added at the end.
+    pub fn insert(&mut self, key: impl Into<String>, value: Value) -> Option<Value> {
+        let key = key.into();
+        match self.index.get(&key) {
+            Some(&position) => Some(std::mem::replace(&mut self.entries[position].1, value)),
+            None => {
+                self.index.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+                None
+            }
+        }
+    }
+
+    /// Removes the member with the given key and returns its
+    /// value. The order of the remaining members is kept.
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        let position = self.index.remove(key)?;
+        let (_, value) = self.entries.remove(position);
+        for later in self.index.values_mut().filter(|later| **later > position) {
+            *later -= 1;
+        }
+        Some(value)
+    }
+
+    /// Returns the entry for the given key, for in-place
+    /// insertion or modification.
+    pub fn entry(&mut self, key: impl Into<String>) -> Entry<'_> {
+        Entry {
+            map: self,
+            key: key.into(),
+        }
+    }
+
+    /// Keeps only the members for which `keep` returns true.
+    /// The order of the remaining members is kept.
+    pub fn retain(&mut self, mut keep: impl FnMut(&String, &mut Value) -> bool) {
+        let index = &mut self.index;
+        let mut position = 0;
+        self.entries.retain_mut(|(key, value)| {
+            let kept = keep(key, value);
+            match kept {
+                true => {
+                    *index.get_mut(key.as_str()).unwrap() = position;
+                    position += 1;
+                }
+                false => {
+                    index.remove(key.as_str());
+                }
+            }
+            kept
+        });
+    }
+
+    /// Sorts the members by key.
+    pub fn sort_keys(&mut self) {
+        self.entries.sort_by(|a, b| a.0.cmp(&b.0));
+        for (position, (key, _)) in self.entries.iter().enumerate() {
+            *self.index.get_mut(key.as_str()).unwrap() = position;
+        }
+    }
+
+    /// Removes all members.
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.index.clear();
+    }
+
+    /// Returns an iterator over the members in order.
+    pub fn iter(&self) -> Iter<'_> {
+        Iter(self.entries.iter())
+    }
+
+    /// Returns an iterator over the members in order, with
+    /// mutable values.
+    pub fn iter_mut(&mut self) -> IterMut<'_> {
+        IterMut(self.entries.iter_mut())
+    }
+
+    /// Returns an iterator over the keys in order.
+    pub fn keys(&self) -> impl Iterator<Item = &String> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    /// Returns an iterator over the values in order.
+    pub fn values(&self) -> impl Iterator<Item = &Value> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    /// Returns an iterator over the values in order, for
+    /// modification.
+    pub fn values_mut(&mut self) -> impl Iterator<Item = &mut Value> {
+        self.iter_mut().map(|(_, value)| value)
+    }
+}
+
+/// An entry in a map, which may or may not have a value.
+pub struct Entry<'a> {
+    map: &'a mut Map,
+    key: String,
+}
+
+impl<'a> Entry<'a> {
+    /// Returns the key of the entry.
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Returns the value of the entry, inserting `default`
+    /// first if there is none.
+    pub fn or_insert(self, default: Value) -> &'a mut Value {
+        self.or_insert_with(|| default)
+    }
+
+    /// Returns the value of the entry, inserting the result
+    /// of `default` first if there is none.
+    pub fn or_insert_with(self, default: impl FnOnce() -> Value) -> &'a mut Value {
+        if !self.map.contains_key(&self.key) {
+            self.map.insert(self.key.clone(), default());
+        }
+        self.map.get_mut(&self.key).unwrap()
+    }
+
+    /// Modifies the value of the entry, if there is one.
+    pub fn and_modify(self, modify: impl FnOnce(&mut Value)) -> Self {
+        if let Some(value) = self.map.get_mut(&self.key) {
+            modify(value);
+        }
+        self
+    }
+}
+
+/// An iterator over the members of a map.
+pub struct Iter<'a>(std::slice::Iter<'a, (String, Value)>);
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Iter<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back().map(|(key, value)| (key, value))
+    }
+}
+
+impl ExactSizeIterator for Iter<'_> {}
+
+/// An iterator over the members of a map, with mutable
+/// values.
+pub struct IterMut<'a>(std::slice::IterMut<'a, (String, Value)>);
+
+impl<'a> Iterator for IterMut<'a> {
+    type Item = (&'a String, &'a mut Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next().map(|(key, value)| (&*key, value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for IterMut<'_> {}
+
+/// An owning iterator over the members of a map.
+pub struct IntoIter(std::vec::IntoIter<(String, Value)>);
+
+impl Iterator for IntoIter {
+    type Item = (String, Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl ExactSizeIterator for IntoIter {}
+
+impl IntoIterator for Map {
+    type Item = (String, Value);
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> Self::IntoIter {
+        IntoIter(self.entries.into_iter())
+    }
+}
+
+impl<'a> IntoIterator for &'a Map {
+    type Item = (&'a String, &'a Value);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a mut Map {
+    type Item = (&'a String, &'a mut Value);
+    type IntoIter = IterMut<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter_mut()
+    }
+}
+
+impl<K: Into<String>> FromIterator<(K, Value)> for Map {
+    fn from_iter<T: IntoIterator<Item = (K, Value)>>(iter: T) -> Self {
+        let mut map = Map::new();
+        map.extend(iter);
+        map
+    }
+}
+
+impl<K: Into<String>> Extend<(K, Value)> for Map {
+    fn extend<T: IntoIterator<Item = (K, Value)>>(&mut self, iter: T) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Into<String>, const N: usize> From<[(K, Value); N]> for Map {
+    fn from(members: [(K, Value); N]) -> Self {
+        members.into_iter().collect()
+    }
+}
+
+impl PartialEq for Map {
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
+
+impl std::fmt::Debug for Map {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, to_string, Value};
+
+    use super::Map;
+
+    fn map(text: &str) -> Map {
+        match parse(text).unwrap() {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        }
+    }
+
+    fn keys(map: &Map) -> Vec<&str> {
+        map.keys().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn keeps_insertion_order() {
+        let map = map(r#"{"b": 1, "a": 2, "c": 3}"#);
+        assert_eq!(keys(&map), ["b", "a", "c"]);
+        assert_eq!(to_string(&Value::Object(map)), r#"{"b":1,"a":2,"c":3}"#);
+    }
+
+    #[test]
+    fn keeps_last_duplicate_value_at_first_position() {
+        let map = map(r#"{"a": 1, "b": 2, "a": 3}"#);
+        assert_eq!(keys(&map), ["a", "b"]);
+        assert_eq!(map.get("a"), Some(&Value::Number(3.into())));
+    }
+
+    #[test]
+    fn replaces_values_in_place() {
+        let mut map = map(r#"{"a": 1, "b": 2}"#);
+        assert_eq!(map.insert("a", Value::Null), Some(Value::Number(1.into())));
+        assert_eq!(keys(&map), ["a", "b"]);
+        assert_eq!(map.get("a"), Some(&Value::Null));
+    }
+
+    #[test]
+    fn removes_members_keeping_order() {
+        let mut map = map(r#"{"a": 1, "b": 2, "c": 3}"#);
+        assert_eq!(map.remove("b"), Some(Value::Number(2.into())));
+        assert_eq!(map.remove("b"), None);
+        assert_eq!(keys(&map), ["a", "c"]);
+        assert_eq!(map.get("c"), Some(&Value::Number(3.into())));
+    }
+
+    #[test]
+    fn inserts_through_entries() {
+        let mut map = Map::new();
+        *map.entry("a").or_insert(Value::Number(0.into())) = Value::Boolean(true);
+        map.entry("a")
+            .and_modify(|value| *value = Value::Null)
+            .or_insert(Value::Boolean(false));
+        assert_eq!(map.get("a"), Some(&Value::Null));
+    }
+
+    #[test]
+    fn retains_and_sorts_members() {
+        let mut map = map(r#"{"d": 1, "b": null, "c": 2, "a": 3}"#);
+        map.retain(|_, value| *value != Value::Null);
+        map.sort_keys();
+        assert_eq!(keys(&map), ["a", "c", "d"]);
+        assert_eq!(map.get("d"), Some(&Value::Number(1.into())));
+    }
+
+    #[test]
+    fn keeps_positions_after_removals() {
+        let mut map = Map::new();
+        for i in 0..10 {
+            map.insert(i.to_string(), Value::from(i));
+        }
+        map.remove("2");
+        map.retain(|key, _| key != "5");
+        map.remove("0");
+        for (position, key) in ["1", "3", "4", "6", "7", "8", "9"].iter().enumerate() {
+            assert_eq!(map.index_of(key), Some(position));
+            assert_eq!(map.get_index(position).unwrap().0, key);
+        }
+        assert_eq!(map.index_of("5"), None);
+    }
+
+    #[test]
+    fn ignores_order_in_comparisons() {
+        assert_eq!(map(r#"{"a": 1, "b": 2}"#), map(r#"{"b": 2, "a": 1}"#));
+        assert_ne!(map(r#"{"a": 1}"#), map(r#"{"a": 1, "b": 2}"#));
+    }
+}
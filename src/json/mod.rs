@@ -1,17 +1,204 @@
+mod build;
+mod cache;
+mod codegen;
+mod coerce;
+mod column;
+mod compare;
+mod concat;
+mod config;
+mod containers;
+mod cst;
+mod cursor;
+mod dedup;
+mod document;
+mod dom;
+mod env;
+mod escape;
+mod events;
+#[cfg(feature = "extjson")]
+mod extjson;
+mod extract;
+mod file;
+mod flatten;
+mod generate;
+mod glob;
+mod group;
+mod hash;
+mod highlight;
+mod http;
+mod infer;
+mod intern;
+mod iter;
+mod jsonpath;
+mod keys;
+mod limits;
+mod map;
+pub mod ndjson;
+mod nullable;
+mod number;
+mod numeric;
+mod options;
+mod parallel;
 mod parser;
+mod partial;
+mod peek;
+pub mod pipeline;
+mod pointer;
+mod refs;
+mod require;
+mod reuse;
+mod schema;
+mod serializer;
+mod shallow;
+mod snapshot;
+mod sniff;
+mod sort;
+mod spanned;
+mod split;
+mod stats;
+mod template;
 mod tokenizer;
+mod truncate;
 mod types;
+mod update;
+mod utf8;
+mod validate;
+mod watch;
 
-use parser::parse as parse_internal;
-use tokenizer::tokenize;
-use types::{Error, Result};
+use keys::{normalize_keys, reject_duplicate_keys};
+use limits::enforce_limits;
+use number::{reject_lossy_numbers, reject_oversized_numbers};
+use parser::{parse as parse_internal, reject_trailing_commas};
 
-pub use self::types::Value;
+pub use self::build::{ArrayBuilder, KeyPolicy, MapKey, ObjectBuilder};
+pub use self::codegen::{generate_rust_types, to_rust_literal, RustTypesOptions};
+pub use self::compare::{semantic_eq, CompareOptions};
+pub use self::concat::concat_to_array;
+pub use self::config::{load_config, ConfigOptions};
+pub use self::containers::{Array, Object};
+pub use self::cst::{parse_cst, Comment, CommentPosition, Cst, CstNode};
+pub use self::cursor::Cursor;
+pub use self::document::Document;
+pub use self::dom::ValueEvents;
+pub use self::env::expand_env;
+pub use self::escape::{escape_str, unescape_str, unescape_str_with};
+pub use self::events::{Event, EventReader, EventWriter};
+#[cfg(feature = "extjson")]
+pub use self::extjson::Binary;
+pub use self::extract::{extract_field, FromValue};
+pub use self::file::{read_file, write_file_atomic};
+pub use self::generate::Generator;
+pub use self::hash::{etag, to_canonical_string_with_etag, Digest, HashAlgo, HashingWriter};
+pub use self::highlight::{highlight, TokenClass};
+pub use self::http::parse_http_body;
+pub use self::infer::{infer_schema, SchemaInferrer};
+pub use self::intern::{Interner, SharedValue};
+pub use self::iter::Children;
+pub use self::map::Map;
+pub use self::nullable::Nullable;
+#[cfg(feature = "decimal")]
+pub use self::number::Decimal;
+pub use self::number::{parse_number_lexeme, Number};
+pub use self::options::{
+    ExtHandler, FormatOptions, KeyHandler, KeyOrder, LoneSurrogates, NumberFormat, NumberPolicy,
+    ParseOptions,
+};
+pub use self::parallel::par_parse_array;
+pub use self::partial::parse_partial;
+pub use self::peek::peek_fields;
+pub use self::pointer::{escape_token, unescape_token, JsonPointer};
+pub use self::refs::{resolve_refs, ResolveOptions};
+pub use self::require::{require, require_typed, MissingPaths};
+pub use self::schema::{Schema, ValidationError};
+pub use self::serializer::{
+    format_number, to_canonical_string, to_string, to_string_pretty, to_string_with,
+    try_to_string_with,
+};
+pub use self::shallow::parse_shallow;
+pub use self::snapshot::Snapshot;
+pub use self::sniff::{sniff, Encoding, Sniff, ValueKind};
+pub use self::sort::Order;
+pub use self::spanned::SpannedValue;
+pub use self::split::split_array;
+pub use self::stats::{aggregate, Stats};
+pub use self::template::render;
+pub use self::tokenizer::{tokenize, tokenize_recovering, tokenize_with, TokenizeOptions};
+pub use self::truncate::{truncate_json_string, TruncateOptions};
+pub use self::types::{Colored, Diagnostic, Error, ErrorKind, Result, Span, Token, Value};
+pub use self::update::UpdateRules;
+pub use self::utf8::{parse_reader, parse_slice};
+pub use self::validate::{validate, ValidateOptions};
+pub use self::watch::WatchedValue;
 
 /// Parse a JSON string.
 pub fn parse(text: &str) -> Result<Value> {
-    match tokenize(text) {
-        Err(_) => Err(Error),
-        Ok(tokens) => parse_internal(tokens),
+    parse_with(text, &ParseOptions::default())
+}
+
+/// Parse a JSON string with the given options. Errors
+/// carry the line and column at which they occurred.
+pub fn parse_with(text: &str, options: &ParseOptions) -> Result<Value> {
+    parse_text(text, options).map_err(|error| error.locate(text))
+}
+
+fn parse_text(text: &str, options: &ParseOptions) -> Result<Value> {
+    if let Some(max) = options.max_size.filter(|&max| text.len() > max) {
+        let size = text.len();
+        return Err(Error::at(ErrorKind::InputTooLarge { size, max }, max));
+    }
+
+    let tokenize_options = TokenizeOptions {
+        python_literals: options.python_literals,
+        lone_surrogates: options.lone_surrogates,
+        ..Default::default()
+    };
+    let mut tokens = tokenize_with(text, &tokenize_options)?;
+    reject_trailing_commas(&tokens)?;
+    enforce_limits(&tokens, options)?;
+
+    if options.max_number_length.is_some() || options.max_exponent.is_some() {
+        reject_oversized_numbers(&tokens, options)?;
     }
+
+    if options.reject_lossy_numbers {
+        reject_lossy_numbers(&tokens, options)?;
+    }
+
+    normalize_keys(&mut tokens, options)?;
+    if options.reject_duplicate_keys {
+        reject_duplicate_keys(&tokens)?;
+    }
+    let mut value = parse_internal(tokens, options)?;
+
+    if options.key_order == KeyOrder::Sorted {
+        value.sort_keys();
+    }
+    Ok(value)
+}
+
+/// Parse a JSON string into a value that records the span
+/// of source text taken up by every nested value.
+pub fn parse_spanned(text: &str) -> Result<SpannedValue> {
+    Ok(Document::parse(text)?.to_spanned())
+}
+
+/// Parse a JSON string into an existing value, reusing the
+/// strings, arrays, and objects it already holds where the
+/// new value has the same shape. This avoids reallocating
+/// buffers when repeatedly parsing messages of a similar
+/// shape. The value is left untouched if parsing fails.
+pub fn parse_into(text: &str, value: &mut Value) -> Result<()> {
+    reuse::assign(value, parse(text)?);
+    Ok(())
+}
+
+/// Scans a JSON string for objects that contain the same
+/// key more than once. Such documents parse successfully,
+/// but other parsers may disagree about which value a
+/// duplicated key refers to. Every occurrence of a
+/// duplicated key is reported with a pointer to its member
+/// and the span of the key. It will return an error if the
+/// text is not valid JSON.
+pub fn find_duplicate_keys(text: &str) -> Result<Vec<(JsonPointer, Span)>> {
+    Ok(Document::parse(text)?.duplicate_keys())
 }
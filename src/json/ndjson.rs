@@ -0,0 +1,336 @@
+//! Streaming operations on newline-delimited JSON, where
+//! each line of the input holds one record. Records are
+//! read and written one line at a time, so inputs of any
+//! length can be processed in bounded memory.
+
+use std::{
+    collections::HashSet,
+    hash::{DefaultHasher, Hash, Hasher},
+    io::{BufRead, BufReader, Read, Write},
+};
+
+use super::{
+    parse,
+    pointer::JsonPointer,
+    serializer::to_canonical_string,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// How [`dedup_with`] remembers the keys it has seen.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum DedupMemory {
+    /// Every key is remembered exactly, so no record is
+    /// dropped unless its key was seen before. Memory grows
+    /// with the number of distinct keys.
+    #[default]
+    Exact,
+    /// Keys are remembered in a Bloom filter sized for the
+    /// given number of distinct keys and rate of false
+    /// positives, such as 0.001. Memory is fixed, but a
+    /// record may be dropped although its key is new, more
+    /// often once the capacity is exceeded.
+    Bloom {
+        capacity: usize,
+        false_positive_rate: f64,
+    },
+}
+
+/// The number of records kept and dropped by [`dedup`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DedupStats {
+    pub kept: usize,
+    pub dropped: usize,
+}
+
+/// Copies the records of newline-delimited JSON text to a
+/// writer, dropping each record whose value at the key
+/// pointer is structurally equal to that of an earlier
+/// record. Records that lack the value are always kept.
+/// Kept records are written as they appeared, and blank
+/// lines are skipped. It will return an error under the
+/// following conditions:
+///
+/// * The pointer is invalid.
+/// * A record is not valid JSON, in which case the error
+///   carries an offset from the start of the input, or
+///   the offset of the record if the parser gave none.
+/// * Reading or writing fails.
+pub fn dedup(reader: impl Read, key_pointer: &str, writer: impl Write) -> Result<DedupStats> {
+    dedup_with(reader, key_pointer, writer, DedupMemory::Exact)
+}
+
+/// Drops records with duplicate keys, as by [`dedup`],
+/// remembering keys as set by `memory`.
+pub fn dedup_with(
+    reader: impl Read,
+    key_pointer: &str,
+    mut writer: impl Write,
+    memory: DedupMemory,
+) -> Result<DedupStats> {
+    let pointer = JsonPointer::parse(key_pointer)?;
+    let mut seen = match memory {
+        DedupMemory::Exact => Seen::Exact(HashSet::new()),
+        DedupMemory::Bloom {
+            capacity,
+            false_positive_rate,
+        } => Seen::Bloom(BloomFilter::new(capacity, false_positive_rate)),
+    };
+
+    let mut stats = DedupStats::default();
+    for record in Records::new(reader) {
+        let (start, record) = record?;
+        let value = parse_record(&record, start)?;
+
+        let is_new = match pointer.resolve(&value) {
+            Some(key) => seen.insert(&to_canonical_string(key)),
+            None => true,
+        };
+        match is_new {
+            true => {
+                writer.write_all(record.as_bytes()).map_err(io_error)?;
+                writer.write_all(b"\n").map_err(io_error)?;
+                stats.kept += 1;
+            }
+            false => stats.dropped += 1,
+        }
+    }
+    writer.flush().map_err(io_error)?;
+    Ok(stats)
+}
+
+/// Reads newline-delimited JSON text in batches of `n`
+/// records, as wanted by bulk-insert APIs. Every batch
+/// holds `n` records except the last, which holds the rest.
+/// Blank lines are skipped. The iterator stops after the
+/// first error, which is returned under the conditions
+/// listed for [`dedup`].
+///
+/// # Panics
+///
+/// Panics if `n` is zero.
+pub fn batches<R: Read>(reader: R, n: usize) -> Batches<R> {
+    assert!(n != 0, "batch size must be greater than zero");
+    Batches {
+        records: Records::new(reader),
+        n,
+        done: false,
+    }
+}
+
+/// An iterator over batches of records, created by
+/// [`batches`].
+pub struct Batches<R> {
+    records: Records<R>,
+    n: usize,
+    done: bool,
+}
+
+impl<R: Read> Iterator for Batches<R> {
+    type Item = Result<Vec<Value>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let mut batch = Vec::with_capacity(self.n);
+        while batch.len() < self.n {
+            let value = match self.records.next() {
+                None => break,
+                Some(record) => record.and_then(|(start, record)| parse_record(&record, start)),
+            };
+            match value {
+                Ok(value) => batch.push(value),
+                Err(error) => {
+                    self.done = true;
+                    return Some(Err(error));
+                }
+            }
+        }
+        self.done = batch.len() < self.n;
+        (!batch.is_empty()).then_some(Ok(batch))
+    }
+}
+
+/// An iterator over the non-blank lines of a reader, with
+/// the offset at which each starts.
+struct Records<R> {
+    reader: BufReader<R>,
+    offset: usize,
+}
+
+impl<R: Read> Records<R> {
+    fn new(reader: R) -> Self {
+        Self {
+            reader: BufReader::new(reader),
+            offset: 0,
+        }
+    }
+}
+
+impl<R: Read> Iterator for Records<R> {
+    type Item = Result<(usize, String)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let mut line = String::new();
+            let read = match self.reader.read_line(&mut line) {
+                Ok(0) => return None,
+                Ok(read) => read,
+                Err(error) => return Some(Err(io_error(error))),
+            };
+            let start = self.offset;
+            self.offset += read;
+            let record = line.trim_end_matches(['\n', '\r']);
+            if !record.trim().is_empty() {
+                return Some(Ok((start, record.to_owned())));
+            }
+        }
+    }
+}
+
+/// Parses a record, reporting errors at their offset in
+/// the whole input.
+fn parse_record(record: &str, start: usize) -> Result<Value> {
+    parse(record)
+        .map_err(|error| Error::at(error.kind().clone(), start + error.offset().unwrap_or(0)))
+}
+
+/// The keys seen so far.
+enum Seen {
+    Exact(HashSet<String>),
+    Bloom(BloomFilter),
+}
+
+impl Seen {
+    /// Records a key, returning whether it was new.
+    fn insert(&mut self, key: &str) -> bool {
+        match self {
+            Seen::Exact(keys) => keys.insert(key.to_owned()),
+            Seen::Bloom(filter) => filter.insert(key),
+        }
+    }
+}
+
+/// A fixed-size set that can tell for certain that a key
+/// was not inserted, but may wrongly report that it was.
+struct BloomFilter {
+    bits: Vec<u64>,
+    len: u64,
+    hashes: u64,
+}
+
+impl BloomFilter {
+    /// Creates a filter with the optimal number of bits and
+    /// hash functions for the capacity and rate of false
+    /// positives.
+    fn new(capacity: usize, false_positive_rate: f64) -> Self {
+        let capacity = capacity.max(1) as f64;
+        let rate = false_positive_rate.clamp(f64::MIN_POSITIVE, 0.5);
+        let ln2 = std::f64::consts::LN_2;
+        let len = (-capacity * rate.ln() / (ln2 * ln2)).ceil().max(64.0) as u64;
+        let hashes = ((len as f64 / capacity) * ln2).round().max(1.0) as u64;
+        Self {
+            bits: vec![0; len.div_ceil(64) as usize],
+            len,
+            hashes,
+        }
+    }
+
+    /// Sets the bits of a key, returning whether any of
+    /// them was unset.
+    fn insert(&mut self, key: &str) -> bool {
+        // Double hashing derives every hash function from
+        // two independent hashes.
+        let first = hash(key, 0);
+        let second = hash(key, 1) | 1;
+        let mut is_new = false;
+        for i in 0..self.hashes {
+            let bit = first.wrapping_add(i.wrapping_mul(second)) % self.len;
+            let (word, mask) = ((bit / 64) as usize, 1 << (bit % 64));
+            is_new |= self.bits[word] & mask == 0;
+            self.bits[word] |= mask;
+        }
+        is_new
+    }
+}
+
+fn hash(key: &str, seed: u8) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    seed.hash(&mut hasher);
+    key.hash(&mut hasher);
+    hasher.finish()
+}
+
+fn io_error(error: std::io::Error) -> Error {
+    Error::new(ErrorKind::Io(error.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::ErrorKind;
+
+    use super::{batches, dedup, dedup_with, DedupMemory, DedupStats};
+
+    const TEXT: &str = "{\"id\": 1, \"n\": \"a\"}\n\n{\"id\": 2}\n{\"n\": \"b\"}\r\n{\"id\":1.0}\n{\"id\": {\"b\": 1, \"a\": 2}}\n{\"id\": {\"a\": 2, \"b\": 1}}";
+
+    #[test]
+    fn drops_records_with_seen_keys() {
+        let mut output = vec![];
+        let stats = dedup(TEXT.as_bytes(), "/id", &mut output).unwrap();
+        assert_eq!(
+            stats,
+            DedupStats {
+                kept: 4,
+                dropped: 2
+            }
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "{\"id\": 1, \"n\": \"a\"}\n{\"id\": 2}\n{\"n\": \"b\"}\n{\"id\": {\"b\": 1, \"a\": 2}}\n"
+        );
+    }
+
+    #[test]
+    fn drops_records_with_bloom_filter() {
+        let text: String = (0..1000)
+            .chain(0..1000)
+            .map(|id| format!("{{\"id\": {}}}\n", id))
+            .collect();
+        let memory = DedupMemory::Bloom {
+            capacity: 1000,
+            false_positive_rate: 0.001,
+        };
+        let stats = dedup_with(text.as_bytes(), "/id", vec![], memory).unwrap();
+        assert_eq!(stats.kept + stats.dropped, 2000);
+        assert!((995..=1000).contains(&stats.kept), "{:?}", stats);
+    }
+
+    #[test]
+    fn reads_records_in_batches() {
+        let sizes: Vec<_> = batches(TEXT.as_bytes(), 2)
+            .map(|batch| batch.unwrap().len())
+            .collect();
+        assert_eq!(sizes, [2, 2, 2]);
+        let sizes: Vec<_> = batches(TEXT.as_bytes(), 4)
+            .map(|batch| batch.unwrap().len())
+            .collect();
+        assert_eq!(sizes, [4, 2]);
+        assert_eq!(batches("\n".as_bytes(), 4).count(), 0);
+    }
+
+    #[test]
+    fn stops_batches_at_first_error() {
+        let mut batches = batches("1\n2\n[\n3\n".as_bytes(), 2);
+        assert_eq!(batches.next().unwrap().unwrap().len(), 2);
+        assert_eq!(batches.next().unwrap().unwrap_err().offset(), Some(5));
+        assert!(batches.next().is_none());
+    }
+
+    #[test]
+    fn reports_offsets_of_invalid_records() {
+        let error = dedup("{}\n{\"a\": }\n".as_bytes(), "/id", vec![]).unwrap_err();
+        assert_eq!(error.offset(), Some(9));
+        let error = dedup("{}".as_bytes(), "id", vec![]).unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidPointer(_)));
+    }
+}
@@ -0,0 +1,89 @@
+use super::types::{Error, ErrorKind, Result, Value};
+
+impl Value {
+    /// Converts the value into an option, with null as
+    /// `None`.
+    pub fn opt(self) -> Option<Value> {
+        match self {
+            Value::Null => None,
+            value => Some(value),
+        }
+    }
+
+    /// Borrows the value as an option, with null as `None`.
+    pub fn as_opt(&self) -> Option<&Value> {
+        match self {
+            Value::Null => None,
+            value => Some(value),
+        }
+    }
+}
+
+/// Conversions for values that may be absent, such as the
+/// result of a lookup, which distinguish between a value
+/// that is absent (`None`), null (`Some(None)`), and
+/// present (`Some(Some(_))`). This matters for partial
+/// updates, where an absent field is left unchanged but a
+/// null field is cleared.
+pub trait Nullable<'a> {
+    /// Converts the value into its tri-state form.
+    fn as_nullable(&self) -> Option<Option<&'a Value>>;
+
+    /// Converts the value into its tri-state form as a
+    /// string. It will return an error if the value is
+    /// present but is not a string.
+    fn as_nullable_str(&self) -> Result<Option<Option<&'a str>>>;
+}
+
+impl<'a> Nullable<'a> for Option<&'a Value> {
+    fn as_nullable(&self) -> Option<Option<&'a Value>> {
+        self.map(Value::as_opt)
+    }
+
+    fn as_nullable_str(&self) -> Result<Option<Option<&'a str>>> {
+        match self {
+            None => Ok(None),
+            Some(Value::Null) => Ok(Some(None)),
+            Some(Value::String(string)) => Ok(Some(Some(string))),
+            Some(_) => Err(Error::new(ErrorKind::UnexpectedType("string"))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    use super::Nullable;
+
+    #[test]
+    fn converts_null_to_none() {
+        assert_eq!(Value::Null.opt(), None);
+        assert_eq!(Value::Boolean(true).opt(), Some(Value::Boolean(true)));
+        assert_eq!(Value::Null.as_opt(), None);
+    }
+
+    #[test]
+    fn distinguishes_absent_null_and_present() {
+        let value = parse(r#"{"name": "a", "nickname": null, "age": 3}"#).unwrap();
+        assert_eq!(
+            value.pointer("/name").as_nullable_str().unwrap(),
+            Some(Some("a"))
+        );
+        assert_eq!(
+            value.pointer("/nickname").as_nullable_str().unwrap(),
+            Some(None)
+        );
+        assert_eq!(value.pointer("/email").as_nullable_str().unwrap(), None);
+        assert_eq!(
+            value.pointer("/age").as_nullable(),
+            Some(Some(&Value::Number(3.into())))
+        );
+    }
+
+    #[test]
+    fn rejects_present_non_string() {
+        let value = parse(r#"{"age": 3}"#).unwrap();
+        assert!(value.pointer("/age").as_nullable_str().is_err());
+    }
+}
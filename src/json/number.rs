@@ -0,0 +1,761 @@
+use std::{cmp::Ordering, fmt::Display, sync::Arc};
+
+use super::{
+    cache::{invalid, Reader},
+    options::{FormatOptions, NumberFormat, NumberPolicy, ParseOptions},
+    tokenizer::tokenize,
+    types::{Error, ErrorKind, Result, Span, Token},
+};
+
+/// A JSON number. By default, numbers are stored as
+/// double-precision floats. Integers can instead be kept
+/// exactly as 128-bit integers, and with the `decimal`
+/// feature, other numbers can be kept exactly as
+/// fixed-point decimals. Numbers compare equal when they
+/// have the same value, regardless of how they are stored.
+/// Numbers parsed in round-trip mode also keep the text
+/// they were parsed from, so that it can be written back.
+#[derive(Debug, Clone)]
+pub struct Number(N, Option<Arc<str>>);
+
+#[derive(Debug, Clone, Copy)]
+enum N {
+    Float(f64),
+    Int(i128),
+    /// Only used for integers that are too large for `i128`.
+    UInt(u128),
+    #[cfg(feature = "decimal")]
+    Decimal(Decimal),
+}
+
+impl Number {
+    fn new(n: N) -> Number {
+        Number(n, None)
+    }
+
+    /// Converts a number lexeme that has already been
+    /// validated by the tokenizer into a number, choosing
+    /// the representation according to the parse options.
+    /// The lexeme is kept in round-trip mode.
+    pub(super) fn from_lexeme(lexeme: &str, options: &ParseOptions) -> Number {
+        let mut number = Number::from_lexeme_value(lexeme, options);
+        if options.round_trip {
+            number.1 = Some(lexeme.into());
+        }
+        number
+    }
+
+    fn from_lexeme_value(lexeme: &str, options: &ParseOptions) -> Number {
+        let is_integer = !lexeme.contains(['.', 'e', 'E']);
+
+        if options.exact_integers && is_integer {
+            if let Ok(int) = lexeme.parse() {
+                return Number::new(N::Int(int));
+            }
+            if let Ok(uint) = lexeme.parse() {
+                return Number::new(N::UInt(uint));
+            }
+        }
+
+        #[cfg(feature = "decimal")]
+        if options.exact_decimals {
+            if let Some(decimal) = Decimal::parse(lexeme) {
+                return Number::new(N::Decimal(decimal));
+            }
+        }
+
+        Number::new(N::Float(lexeme.parse().expect("Number lexeme was invalid")))
+    }
+
+    /// Returns the text the number was parsed from, if it
+    /// was parsed in round-trip mode. The text is exactly
+    /// as written, such as `1.50` or `1E+2`.
+    pub fn lexeme(&self) -> Option<&str> {
+        self.1.as_deref()
+    }
+
+    /// Returns the number as a double-precision float,
+    /// rounding if necessary.
+    pub fn as_f64(&self) -> f64 {
+        match self.0 {
+            N::Float(float) => float,
+            N::Int(int) => int as f64,
+            N::UInt(uint) => uint as f64,
+            #[cfg(feature = "decimal")]
+            N::Decimal(decimal) => decimal.to_f64(),
+        }
+    }
+
+    /// Returns the number as a signed integer, or `None` if
+    /// it has a fractional part or is out of range.
+    pub fn as_i128(&self) -> Option<i128> {
+        match self.0 {
+            N::Float(float) => float_to_int(float),
+            N::Int(int) => Some(int),
+            N::UInt(uint) => uint.try_into().ok(),
+            #[cfg(feature = "decimal")]
+            N::Decimal(decimal) => decimal.to_i128(),
+        }
+    }
+
+    /// Returns the number as an unsigned integer, or `None`
+    /// if it has a fractional part or is out of range.
+    pub fn as_u128(&self) -> Option<u128> {
+        match self.0 {
+            N::UInt(uint) => Some(uint),
+            _ => self.as_i128()?.try_into().ok(),
+        }
+    }
+
+    /// Returns the number as a fixed-point decimal, or
+    /// `None` if it is not stored as one.
+    #[cfg(feature = "decimal")]
+    pub fn as_decimal(&self) -> Option<Decimal> {
+        match self.0 {
+            N::Decimal(decimal) => Some(decimal),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the number is stored as an exact
+    /// integer rather than as a float or decimal.
+    pub fn is_exact_integer(&self) -> bool {
+        matches!(self.0, N::Int(_) | N::UInt(_))
+    }
+
+    /// Compares two numbers under a total order. Integers
+    /// are compared exactly, and other numbers are compared
+    /// by their float values, as ordered by `f64::total_cmp`.
+    pub fn total_cmp(&self, other: &Number) -> Ordering {
+        match (self.exact_int(), other.exact_int()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            _ => self.as_f64().total_cmp(&other.as_f64()),
+        }
+    }
+
+    /// Writes the number in the given notation.
+    pub(super) fn format(&self, format: NumberFormat) -> String {
+        let plain = self.to_string();
+        let is_scientific = match format {
+            NumberFormat::Plain => false,
+            NumberFormat::PlainUpTo(digits) => {
+                plain.bytes().filter(u8::is_ascii_digit).count() > digits
+            }
+            NumberFormat::ScientificAbove(threshold) => self.as_f64().abs() >= threshold,
+        };
+
+        if !is_scientific {
+            return plain;
+        }
+
+        match self.0 {
+            N::Float(float) => format!("{:e}", float),
+            N::Int(int) => format!("{:e}", int),
+            N::UInt(uint) => format!("{:e}", uint),
+            #[cfg(feature = "decimal")]
+            N::Decimal(_) => plain,
+        }
+    }
+
+    /// Writes the number with trailing fractional zeros
+    /// removed and negative zero written as zero, so that
+    /// numbers which compare equal are written equally.
+    pub(super) fn to_canonical_string(&self) -> String {
+        match self.0 {
+            N::Float(float) if float == 0.0 => "0".to_owned(),
+            #[cfg(feature = "decimal")]
+            N::Decimal(decimal) => decimal.normalize().to_string(),
+            _ => self.to_string(),
+        }
+    }
+
+    /// Writes the number to a binary cache as a byte that
+    /// identifies its representation, followed by the
+    /// little-endian bytes of that representation.
+    pub(super) fn write_cache(&self, bytes: &mut Vec<u8>) {
+        match self.0 {
+            N::Float(float) => {
+                bytes.push(0);
+                bytes.extend_from_slice(&float.to_le_bytes());
+            }
+            N::Int(int) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&int.to_le_bytes());
+            }
+            N::UInt(uint) => {
+                bytes.push(2);
+                bytes.extend_from_slice(&uint.to_le_bytes());
+            }
+            #[cfg(feature = "decimal")]
+            N::Decimal(decimal) => {
+                bytes.push(3);
+                bytes.extend_from_slice(&decimal.mantissa.to_le_bytes());
+                bytes.extend_from_slice(&decimal.scale.to_le_bytes());
+            }
+        }
+    }
+
+    /// Reads a number written by `write_cache`.
+    pub(super) fn read_cache(reader: &mut Reader) -> Result<Number> {
+        match reader.byte()? {
+            0 => Ok(Number::new(N::Float(f64::from_le_bytes(reader.array()?)))),
+            1 => Ok(Number::new(N::Int(i128::from_le_bytes(reader.array()?)))),
+            2 => Ok(Number::new(N::UInt(u128::from_le_bytes(reader.array()?)))),
+            #[cfg(feature = "decimal")]
+            3 => Ok(Number::new(N::Decimal(Decimal {
+                mantissa: i128::from_le_bytes(reader.array()?),
+                scale: u32::from_le_bytes(reader.array()?),
+            }))),
+            #[cfg(not(feature = "decimal"))]
+            3 => Err(invalid("decimal numbers require the `decimal` feature")),
+            _ => Err(invalid("unknown number tag")),
+        }
+    }
+
+    /// Returns the policy of the format options that
+    /// applies to the number, if any.
+    pub(super) fn policy(&self, options: &FormatOptions) -> NumberPolicy {
+        const MAX_SAFE: u128 = (1 << 53) - 1;
+        let (negative_zero, beyond_safe, subnormal) = match self.0 {
+            N::Float(float) => (
+                float == 0.0 && float.is_sign_negative(),
+                float.abs() > MAX_SAFE as f64,
+                float.is_subnormal(),
+            ),
+            N::Int(int) => (false, int.unsigned_abs() > MAX_SAFE, false),
+            N::UInt(uint) => (false, uint > MAX_SAFE, false),
+            #[cfg(feature = "decimal")]
+            N::Decimal(decimal) => (false, decimal.to_f64().abs() > MAX_SAFE as f64, false),
+        };
+
+        if negative_zero {
+            options.negative_zero
+        } else if beyond_safe {
+            options.beyond_safe_integers
+        } else if subnormal {
+            options.subnormals
+        } else {
+            NumberPolicy::AsIs
+        }
+    }
+
+    fn exact_int(&self) -> Option<ExactInt> {
+        match self.0 {
+            N::Int(int) => Some(ExactInt::Int(int)),
+            N::UInt(uint) => Some(ExactInt::UInt(uint)),
+            _ => None,
+        }
+    }
+}
+
+/// Parses the text of a single JSON number, such as one
+/// embedded in a protocol header, with the same rules the
+/// parser applies to numbers in a document. The number
+/// keeps its text, so it can be written back exactly. It
+/// will return an error if the text is anything other than
+/// one number, including a number with surrounding
+/// whitespace.
+pub fn parse_number_lexeme(text: &str) -> Result<Number> {
+    let tokens = tokenize(text)?;
+    match tokens.as_slice() {
+        [(Token::Number(lexeme), span)] if span.start == 0 && span.end == text.len() => {
+            let options = ParseOptions {
+                round_trip: true,
+                ..Default::default()
+            };
+            Ok(Number::from_lexeme(lexeme, &options))
+        }
+        _ => Err(Error::at(ErrorKind::InvalidNumber, 0)),
+    }
+}
+
+/// Checks that every number in a token list can be
+/// represented exactly in the numeric type chosen by the
+/// parse options. It will return an error carrying the
+/// offending lexeme and its offset otherwise.
+pub(super) fn reject_lossy_numbers(tokens: &[(Token, Span)], options: &ParseOptions) -> Result<()> {
+    for (token, span) in tokens {
+        if let Token::Number(lexeme) = token {
+            let number = Number::from_lexeme(lexeme, options);
+            if decompose(lexeme) != decompose(&number.to_string()) {
+                return Err(Error::at(
+                    ErrorKind::LossyNumber(lexeme.clone()),
+                    span.start,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns an error if any number token is longer, or has
+/// an exponent of larger magnitude, than the options allow.
+pub(super) fn reject_oversized_numbers(
+    tokens: &[(Token, Span)],
+    options: &ParseOptions,
+) -> Result<()> {
+    for (token, span) in tokens {
+        let Token::Number(lexeme) = token else {
+            continue;
+        };
+
+        if let Some(max) = options.max_number_length {
+            if lexeme.len() > max {
+                let length = lexeme.len();
+                return Err(Error::at(
+                    ErrorKind::NumberTooLong { length, max },
+                    span.start,
+                ));
+            }
+        }
+
+        if let (Some(max), Some((_, exponent))) =
+            (options.max_exponent, lexeme.split_once(['e', 'E']))
+        {
+            let digits = exponent.trim_start_matches(['+', '-']);
+            if digits
+                .parse::<u32>()
+                .map_or(true, |magnitude| magnitude > max)
+            {
+                let exponent = exponent.to_owned();
+                return Err(Error::at(
+                    ErrorKind::ExponentTooLarge { exponent, max },
+                    span.start,
+                ));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Breaks the text of a number into its sign, significant
+/// digits, and decimal exponent, so that texts with the
+/// same value decompose equally. For example, `-1.50e1`
+/// and `-15` both decompose into `(true, "15", 0)`.
+/// Returns `None` if the text is not a finite number.
+fn decompose(text: &str) -> Option<(bool, String, i64)> {
+    let (negative, text) = match text.strip_prefix('-') {
+        Some(text) => (true, text),
+        None => (false, text),
+    };
+
+    let (significand, exponent) = match text.split_once(['e', 'E']) {
+        None => (text, 0),
+        Some((significand, exponent)) => (significand, exponent.parse::<i64>().ok()?),
+    };
+
+    let (int, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+    let digits = format!("{}{}", int, fraction);
+    if digits.is_empty() || !digits.bytes().all(|byte| byte.is_ascii_digit()) {
+        return None;
+    }
+
+    let digits = digits.trim_start_matches('0');
+    let significant = digits.trim_end_matches('0');
+    if significant.is_empty() {
+        return Some((false, String::new(), 0));
+    }
+
+    let trailing_zeros = (digits.len() - significant.len()) as i64;
+    let exponent = exponent
+        .saturating_sub(fraction.len() as i64)
+        .saturating_add(trailing_zeros);
+    Some((negative, significant.to_owned(), exponent))
+}
+
+/// An exact integer that orders correctly across the full
+/// range of both `i128` and `u128`.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum ExactInt {
+    Int(i128),
+    UInt(u128),
+}
+
+fn float_to_int(float: f64) -> Option<i128> {
+    let in_range = float >= i128::MIN as f64 && float < i128::MAX as f64;
+    (float.fract() == 0.0 && in_range).then_some(float as i128)
+}
+
+impl PartialEq for Number {
+    fn eq(&self, other: &Self) -> bool {
+        match (self.0, other.0) {
+            #[cfg(feature = "decimal")]
+            (N::Decimal(a), N::Decimal(b)) => a.normalize() == b.normalize(),
+            _ if self.is_exact_integer() || other.is_exact_integer() => {
+                let is_integer = self.as_i128().is_some() || self.as_u128().is_some();
+                is_integer && self.as_i128() == other.as_i128() && self.as_u128() == other.as_u128()
+            }
+            _ => self.as_f64() == other.as_f64(),
+        }
+    }
+}
+
+impl Display for Number {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            N::Float(float) => write!(f, "{}", float),
+            N::Int(int) => write!(f, "{}", int),
+            N::UInt(uint) => write!(f, "{}", uint),
+            #[cfg(feature = "decimal")]
+            N::Decimal(decimal) => write!(f, "{}", decimal),
+        }
+    }
+}
+
+impl From<f64> for Number {
+    fn from(float: f64) -> Self {
+        Number::new(N::Float(float))
+    }
+}
+
+impl From<f32> for Number {
+    fn from(float: f32) -> Self {
+        Number::new(N::Float(float.into()))
+    }
+}
+
+macro_rules! impl_from_int {
+    ($($int:ty),*) => {
+        $(
+            impl From<$int> for Number {
+                fn from(int: $int) -> Self {
+                    Number::new(N::Int(int.into()))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_int!(i8, i16, i32, i64, i128, u8, u16, u32, u64);
+
+impl From<u128> for Number {
+    fn from(uint: u128) -> Self {
+        match uint.try_into() {
+            Ok(int) => Number::new(N::Int(int)),
+            Err(_) => Number::new(N::UInt(uint)),
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl From<Decimal> for Number {
+    fn from(decimal: Decimal) -> Self {
+        Number::new(N::Decimal(decimal))
+    }
+}
+
+/// A fixed-point decimal number, made up of an integer
+/// mantissa and a scale that counts the digits after the
+/// decimal point. For example, `10.50` has a mantissa of
+/// 1050 and a scale of 2. The scale is preserved, so
+/// trailing zeros survive a round trip.
+#[cfg(feature = "decimal")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Decimal {
+    mantissa: i128,
+    scale: u32,
+}
+
+#[cfg(feature = "decimal")]
+impl Decimal {
+    /// Creates a decimal with the value `mantissa / 10^scale`.
+    pub fn new(mantissa: i128, scale: u32) -> Self {
+        Self { mantissa, scale }
+    }
+
+    /// Returns the integer mantissa of the decimal.
+    pub fn mantissa(&self) -> i128 {
+        self.mantissa
+    }
+
+    /// Returns the number of digits after the decimal point.
+    pub fn scale(&self) -> u32 {
+        self.scale
+    }
+
+    /// Returns the decimal as a double-precision float,
+    /// rounding if necessary.
+    pub fn to_f64(&self) -> f64 {
+        self.to_string().parse().expect("Decimal text was invalid")
+    }
+
+    /// Returns the decimal as an integer, or `None` if it
+    /// has a non-zero fractional part.
+    pub fn to_i128(&self) -> Option<i128> {
+        let normalized = self.normalize();
+        (normalized.scale == 0).then_some(normalized.mantissa)
+    }
+
+    /// Returns an equal decimal without trailing fractional
+    /// zeros.
+    pub fn normalize(&self) -> Self {
+        let mut decimal = *self;
+        while decimal.scale > 0 && decimal.mantissa % 10 == 0 {
+            decimal.mantissa /= 10;
+            decimal.scale -= 1;
+        }
+        decimal
+    }
+
+    /// Parses a JSON number lexeme exactly. Returns `None`
+    /// if the lexeme is invalid or its digits do not fit in
+    /// the mantissa.
+    fn parse(lexeme: &str) -> Option<Self> {
+        let (significand, exponent) = match lexeme.split_once(['e', 'E']) {
+            None => (lexeme, 0),
+            Some((significand, exponent)) => (significand, exponent.parse::<i64>().ok()?),
+        };
+
+        let (int, fraction) = significand.split_once('.').unwrap_or((significand, ""));
+        let mantissa: i128 = format!("{}{}", int, fraction).parse().ok()?;
+        let scale = (fraction.len() as i64).checked_sub(exponent)?;
+
+        if scale >= 0 {
+            Some(Self::new(mantissa, scale.try_into().ok()?))
+        } else {
+            let factor = 10i128.checked_pow((-scale).try_into().ok()?)?;
+            Some(Self::new(mantissa.checked_mul(factor)?, 0))
+        }
+    }
+}
+
+#[cfg(feature = "decimal")]
+impl Display for Decimal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let digits = self.mantissa.unsigned_abs().to_string();
+        let scale = self.scale as usize;
+        let sign = if self.mantissa < 0 { "-" } else { "" };
+
+        if scale == 0 {
+            return write!(f, "{}{}", sign, digits);
+        }
+
+        let digits = format!("{:0>width$}", digits, width = scale + 1);
+        let (int, fraction) = digits.split_at(digits.len() - scale);
+        write!(f, "{}{}.{}", sign, int, fraction)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::json::{parse_with, ErrorKind, ParseOptions, Value};
+
+    use super::{parse_number_lexeme, Number};
+
+    fn exact() -> ParseOptions {
+        ParseOptions {
+            exact_integers: true,
+            ..Default::default()
+        }
+    }
+
+    fn number(text: &str, options: &ParseOptions) -> Number {
+        match parse_with(text, options).unwrap() {
+            Value::Number(number) => number,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn parses_floats_by_default() {
+        let number = number("9007199254740993", &ParseOptions::default());
+        assert!(!number.is_exact_integer());
+        assert_eq!(number.as_f64(), 9007199254740992.0);
+    }
+
+    #[test]
+    fn keeps_large_integers_exactly() {
+        let number = number("9007199254740993", &exact());
+        assert_eq!(number.as_i128(), Some(9007199254740993));
+        assert_eq!(number.to_string(), "9007199254740993");
+    }
+
+    #[test]
+    fn keeps_negative_integers_exactly() {
+        let number = number("-170141183460469231731687303715884105728", &exact());
+        assert_eq!(number.as_i128(), Some(i128::MIN));
+        assert_eq!(number.as_u128(), None);
+    }
+
+    #[test]
+    fn keeps_unsigned_integers_exactly() {
+        let number = number("340282366920938463463374607431768211455", &exact());
+        assert_eq!(number.as_u128(), Some(u128::MAX));
+        assert_eq!(number.as_i128(), None);
+    }
+
+    #[test]
+    fn falls_back_to_float_beyond_integer_range() {
+        let number = number("340282366920938463463374607431768211456", &exact());
+        assert!(!number.is_exact_integer());
+    }
+
+    #[test]
+    fn parses_fractions_as_floats_in_exact_mode() {
+        let number = number("1.5", &exact());
+        assert!(!number.is_exact_integer());
+        assert_eq!(number.as_f64(), 1.5);
+    }
+
+    #[test]
+    fn converts_integral_floats_to_integers() {
+        assert_eq!(Number::from(3.0).as_i128(), Some(3));
+        assert_eq!(Number::from(3.5).as_i128(), None);
+        assert_eq!(Number::from(-3.0).as_u128(), None);
+    }
+
+    #[test]
+    fn compares_across_representations() {
+        assert_eq!(Number::from(1), Number::from(1.0));
+        assert_ne!(
+            Number::from(9007199254740993i64),
+            Number::from(9007199254740992i64)
+        );
+        assert_eq!(Number::from(-0.0), Number::from(0));
+    }
+
+    #[test]
+    fn orders_exact_integers_exactly() {
+        let a = Number::from(u128::MAX);
+        let b = Number::from(u128::MAX - 1);
+        assert_eq!(a.total_cmp(&b), Ordering::Greater);
+        assert_eq!(Number::from(-1).total_cmp(&a), Ordering::Less);
+    }
+
+    fn strict(exact_integers: bool) -> ParseOptions {
+        ParseOptions {
+            exact_integers,
+            reject_lossy_numbers: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn accepts_numbers_that_round_trip() {
+        let value = parse_with("[0.1, 1.50, -0, 1e3, 9007199254740992]", &strict(false));
+        assert!(value.is_ok());
+    }
+
+    #[test]
+    fn rejects_integers_beyond_float_precision() {
+        let error = parse_with("[1, 9007199254740993]", &strict(false)).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::LossyNumber("9007199254740993".to_owned())
+        );
+        assert_eq!(error.offset(), Some(4));
+    }
+
+    #[test]
+    fn rejects_overflowing_floats() {
+        let error = parse_with("1e400", &strict(false)).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::LossyNumber("1e400".to_owned()));
+    }
+
+    #[test]
+    fn rejects_excess_fraction_digits() {
+        assert!(parse_with("0.30000000000000000001", &strict(false)).is_err());
+    }
+
+    #[test]
+    fn accepts_large_integers_in_exact_mode() {
+        assert!(parse_with("9007199254740993", &strict(true)).is_ok());
+        assert!(parse_with("1e400", &strict(true)).is_err());
+    }
+
+    #[test]
+    fn rejects_numbers_longer_than_limit() {
+        let options = ParseOptions {
+            max_number_length: Some(5),
+            ..Default::default()
+        };
+        assert!(parse_with("[-1.25, 1e10]", &options).is_ok());
+        let error = parse_with("[1, 123456]", &options).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::NumberTooLong { length: 6, max: 5 }
+        );
+        assert_eq!(error.offset(), Some(4));
+    }
+
+    #[test]
+    fn rejects_exponents_beyond_limit() {
+        let options = ParseOptions {
+            max_exponent: Some(308),
+            ..Default::default()
+        };
+        assert!(parse_with("[1e308, 1E-308, 5e+0]", &options).is_ok());
+        for (text, exponent) in [
+            ("1e999999", "999999"),
+            ("1E-309", "-309"),
+            ("1e+99999999999999999999", "+99999999999999999999"),
+        ] {
+            let error = parse_with(text, &options).unwrap_err();
+            assert_eq!(
+                error.kind(),
+                &ErrorKind::ExponentTooLarge {
+                    exponent: exponent.to_owned(),
+                    max: 308
+                }
+            );
+        }
+    }
+
+    #[cfg(feature = "decimal")]
+    mod decimal {
+        use crate::json::{number::Decimal, to_canonical_string, ParseOptions};
+
+        use super::{number, Number};
+
+        fn decimal() -> ParseOptions {
+            ParseOptions {
+                exact_decimals: true,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn keeps_cents_exactly() {
+            let number = number("10.50", &decimal());
+            assert_eq!(number.as_decimal(), Some(Decimal::new(1050, 2)));
+            assert_eq!(number.to_string(), "10.50");
+        }
+
+        #[test]
+        fn parses_exponents_exactly() {
+            assert_eq!(number("1.5e2", &decimal()).to_string(), "150");
+            assert_eq!(number("-25e-3", &decimal()).to_string(), "-0.025");
+        }
+
+        #[test]
+        fn compares_decimals_by_value() {
+            assert_eq!(number("1.10", &decimal()), number("1.1", &decimal()));
+            assert_eq!(number("0.5", &decimal()), Number::from(0.5));
+        }
+
+        #[test]
+        fn writes_canonical_decimals_without_trailing_zeros() {
+            let value = crate::json::parse_with("[1.50, 2.00]", &decimal()).unwrap();
+            assert_eq!(to_canonical_string(&value), "[1.5,2]");
+        }
+    }
+
+    #[test]
+    fn parses_single_number_lexemes() {
+        let number = parse_number_lexeme("-1.50e+2").unwrap();
+        assert_eq!(number.as_f64(), -150.0);
+        assert_eq!(number.lexeme(), Some("-1.50e+2"));
+
+        let error = parse_number_lexeme("01").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::MalformedNumber(_)));
+
+        for text in ["", " 1", "1 ", "1,2", "\"1\"", "true"] {
+            let error = parse_number_lexeme(text).unwrap_err();
+            assert_eq!(error.kind(), &ErrorKind::InvalidNumber, "{:?}", text);
+        }
+    }
+}
@@ -0,0 +1,110 @@
+use super::{
+    number::Number,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+macro_rules! impl_as_int {
+    ($($method:ident => $int:ty),*) => {
+        impl Value {
+            $(
+                #[doc = concat!(
+                    "Returns the value as a `", stringify!($int), "`. It will\n",
+                    "return an error under the following conditions:\n",
+                    "\n",
+                    "* The value is not a number.\n",
+                    "* The number has a fractional part.\n",
+                    "* The number is out of range for `", stringify!($int), "`.",
+                )]
+                pub fn $method(&self) -> Result<$int> {
+                    to_int(self, stringify!($int))
+                }
+            )*
+        }
+    };
+}
+
+impl_as_int!(
+    as_u8 => u8,
+    as_u16 => u16,
+    as_u32 => u32,
+    as_u64 => u64,
+    as_usize => usize,
+    as_i8 => i8,
+    as_i16 => i16,
+    as_i32 => i32,
+    as_i64 => i64,
+    as_isize => isize
+);
+
+/// Converts a number value to an integer type, checking
+/// that no information is lost.
+fn to_int<T>(value: &Value, target: &'static str) -> Result<T>
+where
+    T: TryFrom<i128> + TryFrom<u128>,
+{
+    let number = match value {
+        Value::Number(number) => number,
+        _ => return Err(Error::new(ErrorKind::UnexpectedType("number"))),
+    };
+
+    let converted = match (number.as_i128(), number.as_u128()) {
+        (Some(int), _) => T::try_from(int).ok(),
+        (None, Some(uint)) => T::try_from(uint).ok(),
+        (None, None) if has_fraction(number) => {
+            return Err(Error::new(ErrorKind::NotAnInteger(number.to_string())))
+        }
+        (None, None) => None,
+    };
+
+    converted.ok_or_else(|| {
+        Error::new(ErrorKind::OutOfRange {
+            number: number.to_string(),
+            target,
+        })
+    })
+}
+
+fn has_fraction(number: &Number) -> bool {
+    let float = number.as_f64();
+    float.is_finite() && float.fract() != 0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind, Value};
+
+    #[test]
+    fn converts_integers_in_range() {
+        assert_eq!(parse("8080").unwrap().as_u16().unwrap(), 8080);
+        assert_eq!(parse("-5").unwrap().as_i32().unwrap(), -5);
+        assert_eq!(parse("3.0").unwrap().as_usize().unwrap(), 3);
+        assert_eq!(parse("1e3").unwrap().as_u64().unwrap(), 1000);
+    }
+
+    #[test]
+    fn rejects_out_of_range() {
+        let error = parse("70000").unwrap().as_u16().unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::OutOfRange {
+                number: "70000".to_owned(),
+                target: "u16"
+            }
+        );
+        assert_eq!(error.to_string(), "70000 is out of range for u16");
+        assert!(parse("-1").unwrap().as_u32().is_err());
+        assert!(parse("1e40").unwrap().as_i64().is_err());
+    }
+
+    #[test]
+    fn rejects_fractions() {
+        let error = parse("1.5").unwrap().as_i8().unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::NotAnInteger("1.5".to_owned()));
+    }
+
+    #[test]
+    fn rejects_non_numbers() {
+        let error = Value::String("1".to_owned()).as_u8().unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedType("number"));
+    }
+}
@@ -0,0 +1,340 @@
+use std::{fmt::Debug, sync::Arc};
+
+use super::types::Value;
+
+/// The depth to which arrays and objects may be nested by
+/// default. Operations such as cloning, comparing, and
+/// dropping values recurse into nested values, so deeper
+/// documents could overflow the stack.
+pub(super) const DEFAULT_MAX_DEPTH: usize = 128;
+
+/// Options that control how JSON text is parsed. The
+/// default options parse text exactly as specified at
+/// https://www.json.org, except that arrays and objects
+/// may be nested at most 128 levels deep.
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    /// Whether object keys are converted to Unicode
+    /// Normalization Form C, so that keys which differ only
+    /// in their encoding of accented characters match.
+    pub nfc_keys: bool,
+    /// Whether object keys are converted to lowercase.
+    pub lowercase_keys: bool,
+    /// Whether integers are kept exactly as 128-bit
+    /// integers rather than converted to floats. Integers
+    /// outside the range of both `i128` and `u128` are
+    /// still converted to floats.
+    pub exact_integers: bool,
+    /// Whether numbers are kept exactly as fixed-point
+    /// decimals rather than converted to floats. Numbers
+    /// whose digits do not fit in a decimal are still
+    /// converted to floats. Exact integers take precedence
+    /// when both options are set.
+    #[cfg(feature = "decimal")]
+    pub exact_decimals: bool,
+    /// Whether parsing fails when a number cannot be
+    /// represented exactly in the numeric type chosen by
+    /// the options above, rather than being rounded. A
+    /// float represents a number exactly if it prints back
+    /// as the same value, so `0.1` is accepted while
+    /// `9007199254740993` and `1e400` are rejected.
+    pub reject_lossy_numbers: bool,
+    /// The maximum number of characters in the text of a
+    /// number, including its sign, point, and exponent.
+    /// When `None`, numbers may be of any length.
+    pub max_number_length: Option<usize>,
+    /// The maximum magnitude of the exponent written in a
+    /// number, so that `1e400` is rejected when the maximum
+    /// is less than 400. When `None`, exponents may be of
+    /// any magnitude.
+    pub max_exponent: Option<u32>,
+    /// The maximum length of the text in bytes. When
+    /// `None`, text may be of any length.
+    pub max_size: Option<usize>,
+    /// The maximum number of tokens in the text, counting
+    /// each punctuation symbol, string, number, and literal.
+    /// When `None`, text may have any number of tokens.
+    pub max_tokens: Option<usize>,
+    /// The maximum depth to which arrays and objects are
+    /// nested, so that `[[1]]` is rejected when the maximum
+    /// is less than 2. It is 128 by default. When `None`,
+    /// values may be nested to any depth, and values nested
+    /// many thousands of levels deep can overflow the stack
+    /// when they are cloned, compared, or dropped.
+    pub max_depth: Option<usize>,
+    /// The maximum length in bytes of a string as written
+    /// in the text, excluding its quotes. When `None`,
+    /// strings may be of any length.
+    pub max_string_length: Option<usize>,
+    /// Whether parsing fails when a key appears more than
+    /// once in an object, rather than the last member with
+    /// the key being kept. Keys are compared after the
+    /// normalizations below.
+    pub reject_duplicate_keys: bool,
+    /// Whether the Python literals `True`, `False`, and
+    /// `None` and single-quoted strings are accepted, so
+    /// that the output of `str(dict)` in Python can be
+    /// parsed. See [`TokenizeOptions`](super::TokenizeOptions).
+    pub python_literals: bool,
+    /// How `\u` escapes of surrogates that are not part of a
+    /// pair, such as `"\uD800"`, are handled.
+    pub lone_surrogates: LoneSurrogates,
+    /// Whether numbers keep the text they were parsed from,
+    /// so that serializing in round-trip mode writes them
+    /// exactly as they appeared. See
+    /// [`FormatOptions::round_trip`].
+    pub round_trip: bool,
+    /// The order of the members of parsed objects.
+    pub key_order: KeyOrder,
+    /// A hook that is called with every object key after
+    /// the normalizations above, and returns the key to use
+    /// instead or the reason the key is rejected.
+    pub key_handler: Option<KeyHandler>,
+    /// A hook that is called with the key and value of every
+    /// object that has a single member, and returns whether
+    /// the object is a tagged value, such as `{"$date":
+    /// "..."}`. Tagged values are kept as [`Value::Ext`].
+    ///
+    /// [`Value::Ext`]: super::Value::Ext
+    pub ext_handler: Option<ExtHandler>,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        Self {
+            nfc_keys: false,
+            lowercase_keys: false,
+            exact_integers: false,
+            #[cfg(feature = "decimal")]
+            exact_decimals: false,
+            reject_lossy_numbers: false,
+            max_number_length: None,
+            max_exponent: None,
+            max_size: None,
+            max_tokens: None,
+            max_depth: Some(DEFAULT_MAX_DEPTH),
+            max_string_length: None,
+            reject_duplicate_keys: false,
+            python_literals: false,
+            lone_surrogates: LoneSurrogates::default(),
+            round_trip: false,
+            key_order: KeyOrder::default(),
+            key_handler: None,
+            ext_handler: None,
+        }
+    }
+}
+
+/// A function that transforms or rejects object keys as
+/// they are parsed, such as to intern keys or to reject
+/// keys that are not allowed, without a second pass over
+/// the parsed value.
+#[derive(Clone)]
+pub struct KeyHandler(Arc<dyn Fn(String) -> Result<String, String> + Send + Sync>);
+
+/// A function that recognizes tagged values as they are
+/// parsed, so that conventions such as MongoDB Extended
+/// JSON can be supported without being built into the
+/// parser.
+#[derive(Clone)]
+pub struct ExtHandler(Arc<IsTagged>);
+
+/// Returns whether an object with a single member, given
+/// its key and value, is a tagged value.
+type IsTagged = dyn Fn(&str, &Value) -> bool + Send + Sync;
+
+impl ParseOptions {
+    /// Returns options suited to parsing untrusted input,
+    /// such as request bodies in a web service. They limit
+    /// the size of the text to 1 MiB, the number of tokens
+    /// to 100,000, the depth to 64, strings to 64 KiB, and
+    /// numbers to 100 characters with exponents of at most
+    /// 400. Duplicate keys are rejected, since parsers that
+    /// keep different members can be made to disagree, and
+    /// no extensions to JSON are accepted. Individual limits
+    /// can be changed with struct update syntax:
+    ///
+    /// ```
+    /// # use json::json::ParseOptions;
+    /// let options = ParseOptions {
+    ///     max_size: Some(16 << 20),
+    ///     ..ParseOptions::hardened()
+    /// };
+    /// ```
+    pub fn hardened() -> Self {
+        Self {
+            max_number_length: Some(100),
+            max_exponent: Some(400),
+            max_size: Some(1 << 20),
+            max_tokens: Some(100_000),
+            max_depth: Some(64),
+            max_string_length: Some(64 << 10),
+            reject_duplicate_keys: true,
+            python_literals: false,
+            ..Default::default()
+        }
+    }
+}
+
+impl KeyHandler {
+    /// Creates a key handler from a function that returns
+    /// the key to use, or the reason the key is rejected.
+    pub fn new(handler: impl Fn(String) -> Result<String, String> + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    pub(super) fn handle(&self, key: String) -> Result<String, String> {
+        (self.0)(key)
+    }
+}
+
+impl ExtHandler {
+    /// Creates an extension handler from a function that
+    /// returns whether an object with a single member, given
+    /// its key and value, is a tagged value.
+    pub fn new(handler: impl Fn(&str, &Value) -> bool + Send + Sync + 'static) -> Self {
+        Self(Arc::new(handler))
+    }
+
+    /// Creates an extension handler that recognizes objects
+    /// whose single key is one of the given tags, whatever
+    /// their value.
+    pub fn tags(tags: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        let tags: Vec<String> = tags.into_iter().map(Into::into).collect();
+        Self::new(move |tag, _| tags.iter().any(|known| known == tag))
+    }
+
+    pub(super) fn handle(&self, tag: &str, value: &Value) -> bool {
+        (self.0)(tag, value)
+    }
+}
+
+impl Debug for ExtHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "ExtHandler")
+    }
+}
+
+impl Debug for KeyHandler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "KeyHandler")
+    }
+}
+
+/// Options that control how JSON values are written as
+/// text. The default options write compact text.
+#[derive(Debug, Clone, Default)]
+pub struct FormatOptions {
+    /// The number of spaces by which nested values are
+    /// indented. When `None`, values are written on a single
+    /// line without insignificant whitespace.
+    pub indent: Option<usize>,
+    /// The notation in which numbers are written.
+    pub number_format: NumberFormat,
+    /// The order in which object members are written.
+    pub key_order: KeyOrder,
+    /// Whether the keys of each object are padded so that
+    /// the colons of its members line up. Only applies to
+    /// indented text.
+    pub align_colons: bool,
+    /// Whether arrays whose elements are all arrays of
+    /// scalars, such as matrices, are written with one row
+    /// per line and each element right-aligned in its
+    /// column. Only applies to indented text.
+    pub align_columns: bool,
+    /// How negative zero is written.
+    pub negative_zero: NumberPolicy,
+    /// How numbers whose magnitude is at least 2^53 are
+    /// written. Such integers may not be represented exactly
+    /// by JavaScript and other consumers that read numbers
+    /// as doubles.
+    pub beyond_safe_integers: NumberPolicy,
+    /// How subnormal floats, such as `5e-324`, are written.
+    pub subnormals: NumberPolicy,
+    /// Whether numbers parsed in round-trip mode are written
+    /// exactly as they appeared rather than in the number
+    /// format above, and members are written in their stored
+    /// order whatever the key order above. Together with
+    /// [`ParseOptions::round_trip`], this reproduces text that
+    /// is already written in the target style, so that
+    /// automated edits to a file only change what they touch.
+    /// Strings and object keys are not kept as written, so
+    /// they are written with the writer's escapes, and an
+    /// object that repeats a key keeps only its last member.
+    /// [`parse_cst`](super::parse_cst) keeps all of the text
+    /// exactly as written.
+    pub round_trip: bool,
+    /// The maximum length of the text in bytes, enforced by
+    /// [`try_to_string_with`](super::try_to_string_with).
+    /// Writing stops with an error before anything that
+    /// would grow the text past it is written, so that a
+    /// small value that shares large parts cannot be
+    /// amplified into an enormous response. When `None`,
+    /// text may be of any length.
+    pub max_output_bytes: Option<usize>,
+}
+
+/// An enumeration of the ways in which a `\u` escape of a
+/// UTF-16 surrogate that is not part of a pair, which
+/// stands for no character, can be handled.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LoneSurrogates {
+    /// The escape is rejected with an error.
+    #[default]
+    Reject,
+    /// The escape is decoded as the replacement character
+    /// U+FFFD.
+    Replace,
+}
+
+/// An enumeration of the ways in which numbers that some
+/// consumers handle poorly can be written.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NumberPolicy {
+    /// The number is written as is.
+    #[default]
+    AsIs,
+    /// Writing the number fails with an error, from the
+    /// functions that can fail, such as
+    /// [`try_to_string_with`](super::try_to_string_with).
+    /// Functions that cannot fail write the number as is.
+    Error,
+    /// The number is written as a string holding its text,
+    /// such as `"-0"`.
+    Stringify,
+}
+
+/// An enumeration of the orders in which object members
+/// can be kept. Objects always keep their members in a
+/// well-defined order, so that parsing, modifying, and
+/// serializing a document is reproducible.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyOrder {
+    /// Members are kept in the order in which they appear
+    /// in the text or were inserted. A member whose key
+    /// appears more than once keeps its first position.
+    #[default]
+    Insertion,
+    /// Members are kept in ascending order of their keys,
+    /// compared as strings.
+    Sorted,
+}
+
+/// An enumeration of the notations in which numbers can be
+/// written. Fixed-point decimals are always written in
+/// plain decimal notation, so that they keep their scale.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum NumberFormat {
+    /// Numbers are always written in plain decimal
+    /// notation, such as `1000000000000000000000`.
+    #[default]
+    Plain,
+    /// Numbers are written in plain decimal notation when
+    /// that takes at most the given number of digits, and
+    /// in scientific notation, such as `1e21`, otherwise.
+    PlainUpTo(usize),
+    /// Numbers whose magnitude is at least the given
+    /// threshold are written in scientific notation, and
+    /// other numbers in plain decimal notation.
+    ScientificAbove(f64),
+}
@@ -0,0 +1,192 @@
+use std::thread;
+
+use super::{
+    parse,
+    types::{Error, ErrorKind, Result, Value},
+    utf8::decode,
+};
+
+/// Parses a JSON text whose top-level value is an array,
+/// using multiple threads. The text is first scanned for
+/// the byte offsets of the array's elements, without
+/// tokenizing them, and the elements are then split into
+/// one chunk per available thread and parsed in parallel.
+/// This is experimental and only pays off for very large
+/// arrays. It will return an error under the following
+/// conditions:
+///
+/// * The bytes are not valid UTF-8.
+/// * The top-level value is not an array.
+/// * Any element is not valid JSON.
+pub fn par_parse_array(bytes: &[u8]) -> Result<Value> {
+    let text = decode(bytes)?;
+    let elements = split_elements(text).map_err(|error| error.locate(text))?;
+
+    let threads = thread::available_parallelism().map_or(1, usize::from);
+    let chunk_len = elements.len().div_ceil(threads).max(1);
+    let chunks: Vec<Result<Vec<Value>>> = thread::scope(|scope| {
+        let handles: Vec<_> = elements
+            .chunks(chunk_len)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    chunk
+                        .iter()
+                        .map(|&(start, end)| parse_element(text, start, end))
+                        .collect()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    });
+
+    let mut array = Vec::with_capacity(elements.len());
+    for chunk in chunks {
+        array.extend(chunk?);
+    }
+    Ok(Value::Array(array))
+}
+
+/// Parses the element found between two byte offsets,
+/// reporting errors at their offset, line, and column in
+/// the whole text.
+fn parse_element(text: &str, start: usize, end: usize) -> Result<Value> {
+    parse(&text[start..end]).map_err(|error| match error.offset() {
+        Some(offset) => Error::at(error.kind().clone(), start + offset).locate(text),
+        None => error,
+    })
+}
+
+/// Builds a structural index of a top-level array: the
+/// byte ranges of its elements, found by tracking string
+/// and nesting boundaries. The elements themselves are
+/// only validated when they are parsed.
+fn split_elements(text: &str) -> Result<Vec<(usize, usize)>> {
+    let bytes = text.as_bytes();
+    let open = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(bytes.len());
+    if bytes.get(open) != Some(&b'[') {
+        return Err(Error::at(ErrorKind::UnexpectedType("array"), open));
+    }
+
+    let mut elements = vec![];
+    let mut start = open + 1;
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut close = None;
+
+    for (index, &byte) in bytes.iter().enumerate().skip(open + 1) {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' if depth > 0 => depth -= 1,
+            b',' if depth == 0 => {
+                elements.push((start, index));
+                start = index + 1;
+            }
+            b']' => {
+                close = Some(index);
+                break;
+            }
+            b'}' => return Err(Error::at(ErrorKind::Syntax, index)),
+            _ => {}
+        }
+    }
+
+    let close = close.ok_or_else(|| Error::at(ErrorKind::Syntax, text.len()))?;
+    if let Some(trailing) = bytes[close + 1..]
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+    {
+        return Err(Error::at(ErrorKind::Syntax, close + 1 + trailing));
+    }
+
+    let last_is_empty = text[start..close].trim().is_empty();
+    match (elements.is_empty(), last_is_empty) {
+        (true, true) => {}
+        (false, true) => return Err(Error::at(ErrorKind::TrailingComma, start - 1)),
+        (_, false) => elements.push((start, close)),
+    }
+
+    Ok(elements)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind, Value};
+
+    use super::par_parse_array;
+
+    #[test]
+    fn matches_sequential_parse() {
+        let text = (0..20)
+            .map(|index| {
+                format!(
+                    r#"{{"id": {}, "tags": ["a,]", "b\"]"], "nested": [[{}]]}}"#,
+                    index, index
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let text = format!("[{}]", text);
+        assert_eq!(
+            par_parse_array(text.as_bytes()).unwrap(),
+            parse(&text).unwrap()
+        );
+    }
+
+    #[test]
+    fn accepts_empty_array() {
+        assert_eq!(par_parse_array(b" [ ] ").unwrap(), Value::Array(vec![]));
+    }
+
+    #[test]
+    fn reports_errors_at_document_offsets() {
+        let error = par_parse_array(b"[1, 2, tru]").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidLiteral("true"));
+        assert_eq!(error.offset(), Some(7));
+
+        let error = par_parse_array(b"[1,\n 2,\n  tru]").unwrap_err();
+        assert_eq!(error.offset(), Some(10));
+        assert_eq!(error.line(), Some(3));
+        assert_eq!(error.column(), Some(3));
+
+        let error = par_parse_array(b"[1,\n 2,\n ]").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TrailingComma);
+        assert_eq!(error.line(), Some(2));
+    }
+
+    #[test]
+    fn rejects_trailing_comma() {
+        let error = par_parse_array(b"[1, 2, ]").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TrailingComma);
+        assert_eq!(error.offset(), Some(5));
+    }
+
+    #[test]
+    fn rejects_non_array() {
+        let error = par_parse_array(br#"{"a": 1}"#).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedType("array"));
+    }
+
+    #[test]
+    fn rejects_unclosed_array() {
+        assert!(par_parse_array(b"[1, [2]").is_err());
+        assert!(par_parse_array(b"[1] 2").is_err());
+    }
+}
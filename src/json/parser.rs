@@ -1,17 +1,16 @@
-use std::{collections::HashMap, iter};
-
-use super::types::{Error, Result, Token, Value};
-
-struct Node {
-    value: Value,
-    len: usize,
-}
+use super::{
+    map::Map,
+    number::Number,
+    options::ParseOptions,
+    types::{Error, ErrorKind, Result, Span, Token, Value},
+};
 
 /// Parses an input token list into a JSON value.
 /// It will return an error under the following
-/// conditions:
+/// conditions, carrying the offset of the token at fault:
 ///
 /// * The token list is empty.
+/// * The token list contains whitespace or comments.
 /// * The token list contains a punctuation mark
 ///   in an unexpected position.
 /// * An array does not have matching brackets.
@@ -19,176 +18,222 @@ struct Node {
 /// * An object does not have matching braces.
 /// * An object is missing necessary commas.
 /// * An object is missing necessary keys, colons, values.
+/// * Tokens follow the value.
 ///
 /// This implementation matches the specification declared
 /// at https://www.json.org.
-pub fn parse(tokens: Vec<Token>) -> Result<Value> {
-    let json = json(&tokens)?;
-    (json.len == tokens.len())
-        .then_some(json.value)
-        .ok_or(Error)
-}
-
-fn json(tokens: &[Token]) -> Result<Node> {
-    element(tokens)
-}
-
-fn value(tokens: &[Token]) -> Result<Node> {
-    match tokens.get(0) {
-        None => Err(Error),
-        Some(token) => match token {
-            Token::String(string) => Ok(Node {
-                value: Value::String(string.clone()),
-                len: 1,
-            }),
-            Token::Number(number) => Ok(Node {
-                value: Value::Number(*number),
-                len: 1,
-            }),
-            Token::True => Ok(Node {
-                value: Value::Boolean(true),
-                len: 1,
-            }),
-            Token::False => Ok(Node {
-                value: Value::Boolean(false),
-                len: 1,
-            }),
-            Token::Null => Ok(Node {
-                value: Value::Null,
-                len: 1,
-            }),
-            Token::Punct(punct) => match punct {
-                '[' => array(tokens),
-                '{' => object(tokens),
-                _ => Err(Error),
-            },
-        },
+pub fn parse(tokens: Vec<(Token, Span)>, options: &ParseOptions) -> Result<Value> {
+    let (tokens, spans): (Vec<_>, Vec<_>) = tokens.into_iter().unzip();
+    // Errors below carry the number of tokens that were
+    // left when they occurred in place of an offset, which
+    // is converted here into the offset of the token at
+    // fault, or the end of the last token if none was left.
+    let locate = |error: Error| {
+        let left = error.offset().unwrap_or(0);
+        let offset = match spans.get(tokens.len() - left) {
+            Some(span) => span.start,
+            None => spans.last().map_or(0, |span| span.end),
+        };
+        Error::at(error.kind().clone(), offset)
+    };
+
+    let (value, len) = json(&tokens, options).map_err(locate)?;
+    match len == tokens.len() {
+        true => Ok(value),
+        false => Err(locate(Error::at(
+            ErrorKind::TrailingData,
+            tokens.len() - len,
+        ))),
     }
 }
 
-fn object(tokens: &[Token]) -> Result<Node> {
-    let begins_with_open_brace = matches!(tokens.get(0), Some(Token::Punct(char)) if char == &'{');
-    let followed_by_close_brace = matches!(tokens.get(1), Some(Token::Punct(char)) if char == &'}');
-
-    if !begins_with_open_brace {
-        return Err(Error);
+/// Rejects a comma that directly precedes the closing
+/// bracket or brace of an array or object, so that it can
+/// be reported with a targeted hint rather than as a
+/// generic syntax error.
+pub(super) fn reject_trailing_commas(tokens: &[(Token, Span)]) -> Result<()> {
+    let trailing = tokens.windows(2).find(|pair| {
+        matches!(
+            (&pair[0].0, &pair[1].0),
+            (Token::Punct(','), Token::Punct(']' | '}'))
+        )
+    });
+
+    match trailing {
+        Some(pair) => Err(Error::at(ErrorKind::TrailingComma, pair[0].1.start)),
+        None => Ok(()),
     }
+}
 
-    if begins_with_open_brace && followed_by_close_brace {
-        return Ok(Node {
-            value: Value::Object(HashMap::new()),
-            len: 2,
-        });
+/// Creates an error for the first of the remaining tokens,
+/// which is not what the grammar expects, or for the end of
+/// the tokens if none remain.
+fn unexpected(tokens: &[Token], expected: &'static str) -> Error {
+    match tokens.is_empty() {
+        true => Error::at(ErrorKind::UnexpectedEnd, 0),
+        false => Error::at(ErrorKind::UnexpectedToken { expected }, tokens.len()),
     }
-
-    let members = members(&tokens[1..])?;
-    let mem_len = members
-        .iter()
-        .fold(members.len() - 1, |acc, mem| acc + mem.1.len + 2);
-
-    let followed_by_close_brace =
-        matches!(tokens.get(mem_len + 1), Some(Token::Punct(char)) if char == &'}');
-
-    followed_by_close_brace
-        .then_some(Node {
-            value: Value::Object(
-                members
-                    .into_iter()
-                    .map(|mem| (mem.0, mem.1.value))
-                    .collect(),
-            ),
-            len: mem_len + 2,
-        })
-        .ok_or(Error)
 }
 
-fn members(tokens: &[Token]) -> Result<HashMap<String, Node>> {
-    let member = member(tokens)?;
-
-    let followed_by_comma =
-        matches!(tokens.get(member.1.len + 2), Some(Token::Punct(char)) if char == &',');
-
-    if !followed_by_comma {
-        return Ok(HashMap::from([member]));
-    }
+/// Returns the tokens from the given index onwards, which
+/// is empty if the index is past the end.
+fn rest(tokens: &[Token], index: usize) -> &[Token] {
+    tokens.get(index..).unwrap_or_default()
+}
 
-    match members(&tokens[member.1.len + 2 + 1..]) {
-        Err(_) => Ok(HashMap::from([member])),
-        Ok(members) => Ok(HashMap::from([member]).into_iter().chain(members).collect()),
-    }
+/// An array or object whose members are being parsed,
+/// which keeps the members parsed so far. An object also
+/// keeps the key of the member whose value is next.
+enum Frame {
+    Array(Vec<Value>),
+    Object(Map, String),
 }
 
-fn member(tokens: &[Token]) -> Result<(String, Node)> {
-    if let Some(Token::String(string)) = tokens.get(0) {
-        let followed_by_colon = matches!(tokens.get(1), Some(Token::Punct(char)) if char == &':');
-        if !followed_by_colon {
-            return Err(Error);
+/// Parses the value at the start of the tokens, returning
+/// it along with the number of tokens it spans. Nested
+/// arrays and objects are kept on an explicit stack rather
+/// than the call stack, so that depth is limited only by
+/// memory and the limits in the options, which are
+/// enforced before parsing.
+fn json(tokens: &[Token], options: &ParseOptions) -> Result<(Value, usize)> {
+    let mut stack = vec![];
+    let mut index = 0;
+    loop {
+        let mut value = match tokens.get(index) {
+            Some(Token::Punct('[')) => {
+                if let Some(Token::Punct(']')) = tokens.get(index + 1) {
+                    index += 2;
+                    Value::Array(vec![])
+                } else {
+                    stack.push(Frame::Array(vec![]));
+                    index += 1;
+                    continue;
+                }
+            }
+            Some(Token::Punct('{')) => {
+                if let Some(Token::Punct('}')) = tokens.get(index + 1) {
+                    index += 2;
+                    Value::Object(Map::new())
+                } else {
+                    let key = key(rest(tokens, index + 1))?;
+                    stack.push(Frame::Object(Map::new(), key));
+                    index += 3;
+                    continue;
+                }
+            }
+            _ => {
+                let value = scalar(rest(tokens, index), options)?;
+                index += 1;
+                value
+            }
+        };
+
+        // Adds the value to the enclosing array or object,
+        // closing as many of them as end after it.
+        loop {
+            let Some(frame) = stack.pop() else {
+                return Ok((value, index));
+            };
+            match frame {
+                Frame::Array(mut elements) => {
+                    elements.push(value);
+                    match tokens.get(index) {
+                        Some(Token::Punct(',')) => {
+                            stack.push(Frame::Array(elements));
+                            index += 1;
+                            break;
+                        }
+                        Some(Token::Punct(']')) => {
+                            value = Value::Array(elements);
+                            index += 1;
+                        }
+                        _ => return Err(unexpected(rest(tokens, index), "`,` or `]`")),
+                    }
+                }
+                Frame::Object(mut members, key) => {
+                    members.insert(key, value);
+                    match tokens.get(index) {
+                        Some(Token::Punct(',')) => {
+                            let key = self::key(rest(tokens, index + 1))?;
+                            stack.push(Frame::Object(members, key));
+                            index += 3;
+                            break;
+                        }
+                        Some(Token::Punct('}')) => {
+                            value = object(members, options);
+                            index += 1;
+                        }
+                        _ => return Err(unexpected(rest(tokens, index), "`,` or `}`")),
+                    }
+                }
+            }
         }
-
-        let element = element(&tokens[2..])?;
-        return Ok((string.clone(), element));
     }
-
-    Err(Error)
 }
 
-fn array(tokens: &[Token]) -> Result<Node> {
-    let begins_with_open_bracket =
-        matches!(tokens.get(0), Some(Token::Punct(char)) if char == &'[');
-
-    let followed_by_close_bracket =
-        matches!(tokens.get(1), Some(Token::Punct(char)) if char == &']');
-
-    if !begins_with_open_bracket {
-        return Err(Error);
-    }
-
-    if begins_with_open_bracket && followed_by_close_bracket {
-        return Ok(Node {
-            value: Value::Array(vec![]),
-            len: 2,
-        });
+/// Converts parsed members into an object, or into a tagged
+/// value if the extension handler of the options
+/// recognizes them as one.
+fn object(members: Map, options: &ParseOptions) -> Value {
+    let Some(handler) = &options.ext_handler else {
+        return Value::Object(members);
+    };
+    match members.get_index(0) {
+        Some((tag, value)) if members.len() == 1 && handler.handle(tag, value) => {
+            let (tag, value) = members.into_iter().next().unwrap();
+            Value::Ext(tag, Box::new(value))
+        }
+        _ => Value::Object(members),
     }
-
-    let elements = elements(&tokens[1..])?;
-    let elem_len = elements
-        .iter()
-        .fold(elements.len() - 1, |acc, elem| acc + elem.len);
-
-    let followed_by_close_bracket =
-        matches!(tokens.get(elem_len + 1), Some(Token::Punct(char)) if char == &']');
-
-    followed_by_close_bracket
-        .then_some(Node {
-            value: Value::Array(elements.into_iter().map(|elem| elem.value).collect()),
-            len: elem_len + 2,
-        })
-        .ok_or(Error)
 }
 
-fn elements(tokens: &[Token]) -> Result<Vec<Node>> {
-    let element = element(tokens)?;
-
-    let followed_by_comma =
-        matches!(tokens.get(element.len), Some(Token::Punct(char)) if char == &',');
-
-    if !followed_by_comma {
-        return Ok(vec![element]);
-    }
-
-    match elements(&tokens[element.len + 1..]) {
-        Err(_) => Ok(vec![element]),
-        Ok(elements) => Ok(iter::once(element).chain(elements).collect()),
+/// Parses the key and colon at the start of a member.
+fn key(tokens: &[Token]) -> Result<String> {
+    let Some(Token::String(key)) = tokens.first() else {
+        return Err(unexpected(tokens, "a string key"));
+    };
+    match tokens.get(1) {
+        Some(Token::Punct(':')) => Ok(key.clone()),
+        _ => Err(unexpected(rest(tokens, 1), "`:`")),
     }
 }
 
-fn element(tokens: &[Token]) -> Result<Node> {
-    value(tokens)
+/// Parses the scalar value at the start of the tokens.
+fn scalar(tokens: &[Token], options: &ParseOptions) -> Result<Value> {
+    match tokens.first() {
+        Some(Token::String(string)) => Ok(Value::String(string.clone())),
+        Some(Token::Number(number)) => Ok(Value::Number(Number::from_lexeme(number, options))),
+        Some(Token::True) => Ok(Value::Boolean(true)),
+        Some(Token::False) => Ok(Value::Boolean(false)),
+        Some(Token::Null) => Ok(Value::Null),
+        _ => Err(unexpected(tokens, "a value")),
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use super::{parse, Token::*};
+    use crate::json::{ErrorKind, ParseOptions};
+
+    use super::{Span, Token::*, Value};
+
+    /// Parses tokens that are each given a span of one byte,
+    /// so that error offsets are token indices.
+    fn parse(tokens: Vec<super::Token>) -> super::Result<Value> {
+        let tokens = tokens
+            .into_iter()
+            .enumerate()
+            .map(|(i, token)| {
+                (
+                    token,
+                    Span {
+                        start: i,
+                        end: i + 1,
+                    },
+                )
+            })
+            .collect();
+        super::parse(tokens, &ParseOptions::default())
+    }
 
     #[test]
     fn rejects_empty_input() {
@@ -210,7 +255,7 @@ mod tests {
 
     #[test]
     fn accepts_number() {
-        let tokens = vec![Number(0.0)];
+        let tokens = vec![Number("0".to_owned())];
         assert!(parse(tokens).is_ok());
     }
 
@@ -268,7 +313,7 @@ mod tests {
             Punct('['),
             String("".to_owned()),
             Punct(','),
-            Number(0.0),
+            Number("0".to_owned()),
             Punct(','),
             Null,
             Punct(']'),
@@ -339,7 +384,7 @@ mod tests {
             Punct(','),
             String("goodbye".to_owned()),
             Punct(':'),
-            Number(0.0),
+            Number("0".to_owned()),
             Punct(','),
             String("morning".to_owned()),
             Punct(':'),
@@ -372,9 +417,82 @@ mod tests {
         assert!(parse(tokens).is_err());
     }
 
+    #[test]
+    fn reports_trailing_comma() {
+        let error = crate::json::parse("[1, 2,]").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TrailingComma);
+        assert_eq!(error.offset(), Some(5));
+
+        let error = crate::json::parse(r#"{"a": 1, }"#).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TrailingComma);
+    }
+
     #[test]
     fn rejects_object_with_missing_value() {
         let tokens = vec![Punct('{'), String("".to_owned()), Punct(':'), Punct('}')];
         assert!(parse(tokens).is_err());
     }
+
+    #[test]
+    fn reports_position_of_unexpected_tokens() {
+        let error = |tokens| {
+            let error = parse(tokens).unwrap_err();
+            (error.kind().clone(), error.offset().unwrap())
+        };
+        let expected = |expected| ErrorKind::UnexpectedToken { expected };
+        assert_eq!(error(vec![]), (ErrorKind::UnexpectedEnd, 0));
+        assert_eq!(error(vec![Punct(']')]), (expected("a value"), 0));
+        assert_eq!(
+            error(vec![Punct('['), Null, Null, Punct(']')]),
+            (expected("`,` or `]`"), 2)
+        );
+        assert_eq!(
+            error(vec![Punct('['), Null, Punct(','), Punct(':')]),
+            (expected("a value"), 3)
+        );
+        assert_eq!(error(vec![Punct('['), Null]), (ErrorKind::UnexpectedEnd, 2));
+        assert_eq!(
+            error(vec![Punct('{'), String("a".into()), Null]),
+            (expected("`:`"), 2)
+        );
+        assert_eq!(
+            error(vec![Punct('{'), Null, Punct(':'), Null, Punct('}')]),
+            (expected("a string key"), 1)
+        );
+        assert_eq!(
+            error(vec![
+                Punct('{'),
+                String("a".into()),
+                Punct(':'),
+                Null,
+                Punct(','),
+                String("b".into()),
+                Null,
+            ]),
+            (expected("`:`"), 6)
+        );
+        assert_eq!(
+            error(vec![Punct('{'), String("a".into()), Punct(':'), Null, Null]),
+            (expected("`,` or `}`"), 4)
+        );
+        assert_eq!(error(vec![Null, Null]), (ErrorKind::TrailingData, 1));
+    }
+
+    #[test]
+    fn parses_deeply_nested_arrays() {
+        let depth = 10_000;
+        let tokens = [vec![Punct('['); depth], vec![Punct(']'); depth]].concat();
+        let mut value = &parse(tokens).unwrap();
+        for _ in 1..depth {
+            value = match value {
+                Value::Array(elements) => &elements[0],
+                _ => unreachable!(),
+            };
+        }
+        assert_eq!(value, &Value::Array(vec![]));
+
+        let tokens = vec![Punct('['); 100_000];
+        let error = parse(tokens).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedEnd);
+    }
 }
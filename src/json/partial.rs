@@ -0,0 +1,108 @@
+use super::{
+    parse,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// Parses the first complete JSON value in a string,
+/// returning it along with the text that follows it. Text
+/// after the value is not examined, so that JSON can be
+/// read from the start of a larger message, such as a log
+/// line with a suffix or a stream of concatenated values.
+/// Whitespace before the value is skipped, but whitespace
+/// after it is left in the remainder. It will return an
+/// error under the same conditions as `parse`, applied to
+/// the first value.
+pub fn parse_partial(text: &str) -> Result<(Value, &str)> {
+    let end = value_end(text)?;
+    Ok((parse(&text[..end])?, &text[end..]))
+}
+
+/// Returns the byte offset at which the first value in a
+/// text ends, found by tracking string and nesting
+/// boundaries. The value itself is only validated when it
+/// is parsed.
+fn value_end(text: &str) -> Result<usize> {
+    let bytes = text.as_bytes();
+    let start = bytes
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .ok_or_else(|| Error::at(ErrorKind::Syntax, text.len()))?;
+
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (index, &byte) in bytes.iter().enumerate().skip(start) {
+        if in_string {
+            match byte {
+                _ if escaped => escaped = false,
+                b'\\' => escaped = true,
+                b'"' if depth == 0 => return Ok(index + 1),
+                b'"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+
+        match byte {
+            b'"' | b'[' | b'{' if depth == 0 && index > start => return Ok(index),
+            b'"' => in_string = true,
+            b'[' | b'{' => depth += 1,
+            b']' | b'}' if depth > 1 => depth -= 1,
+            b']' | b'}' if depth == 1 => return Ok(index + 1),
+            _ if depth > 0 => {}
+            b' ' | b'\n' | b'\r' | b'\t' | b',' | b':' | b']' | b'}' => return Ok(index),
+            _ => {}
+        }
+    }
+
+    match depth == 0 && !in_string {
+        true => Ok(text.len()),
+        false => Err(Error::at(ErrorKind::Syntax, text.len())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind, Value};
+
+    use super::parse_partial;
+
+    #[test]
+    fn returns_remaining_text() {
+        let (value, rest) = parse_partial(r#" {"a": ["}", 1]} trailing"#).unwrap();
+        assert_eq!(value, parse(r#"{"a": ["}", 1]}"#).unwrap());
+        assert_eq!(rest, " trailing");
+    }
+
+    #[test]
+    fn parses_concatenated_values() {
+        let mut text = r#"[1]{"b":2}"c\"" 3"d" true"#;
+        let mut values = vec![];
+        while !text.trim().is_empty() {
+            let (value, rest) = parse_partial(text).unwrap();
+            values.push(value.to_string());
+            text = rest;
+        }
+        assert_eq!(
+            values,
+            ["[1]", r#"{"b":2}"#, r#""c\"""#, "3", r#""d""#, "true"]
+        );
+    }
+
+    #[test]
+    fn returns_empty_remainder_for_whole_value() {
+        let (value, rest) = parse_partial("42").unwrap();
+        assert_eq!(value, Value::Number(42.into()));
+        assert_eq!(rest, "");
+    }
+
+    #[test]
+    fn rejects_incomplete_values() {
+        for text in ["", "  ", "[1, 2", r#"{"a": "b"#, "[1 2] rest"] {
+            assert!(parse_partial(text).is_err(), "{}", text);
+        }
+        let error = parse_partial("[1,] rest").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TrailingComma);
+    }
+}
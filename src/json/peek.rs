@@ -0,0 +1,134 @@
+use super::{
+    events::{Event, EventReader, EventWriter},
+    parse,
+    pointer::JsonPointer,
+    types::{Error, ErrorKind, Result, Value},
+    utf8::decode,
+};
+
+/// Reads the values of top-level object members, named by
+/// JSON pointers such as `/type`, without parsing the whole
+/// text. The text is scanned only until every requested
+/// member has been found, so that the envelope fields of a
+/// large message can be read to route it. Values are
+/// returned in the order of the pointers, with `None` for
+/// members that are absent. If a key is duplicated, the
+/// first occurrence is returned. Text after the last
+/// requested member is not validated. It will return an
+/// error under the following conditions:
+///
+/// * A pointer does not refer to a top-level member.
+/// * The text up to the last requested member is not valid
+///   JSON.
+/// * The top-level value is not an object.
+pub fn peek_fields(text: &str, pointers: &[&str]) -> Result<Vec<Option<Value>>> {
+    let keys = pointers
+        .iter()
+        .map(|pointer| match JsonPointer::parse(pointer)?.tokens() {
+            [key] => Ok(key.clone()),
+            _ => Err(Error::new(ErrorKind::InvalidPointer(pointer.to_string()))),
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    let mut values = vec![None; keys.len()];
+    let mut events = EventReader::new(text.as_bytes());
+    if next(&mut events)? != Event::StartObject {
+        return Err(Error::new(ErrorKind::UnexpectedType("object")));
+    }
+
+    while values.iter().any(Option::is_none) {
+        let key = match next(&mut events)? {
+            Event::Key(key) => key,
+            _ => break,
+        };
+        let requested = keys.contains(&key);
+        let value = read_value(&mut events, requested)?;
+        for (k, slot) in keys.iter().zip(&mut values) {
+            if *k == key && slot.is_none() {
+                *slot = value.clone();
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Returns the next event, treating the end of the text as
+/// a syntax error since the reader reports premature ends
+/// itself.
+fn next(events: &mut EventReader<&[u8]>) -> Result<Event> {
+    events
+        .next()
+        .unwrap_or_else(|| Err(Error::new(ErrorKind::Syntax)))
+}
+
+/// Reads the events of one value, converting them into a
+/// value if it is wanted and skipping them otherwise.
+fn read_value(events: &mut EventReader<&[u8]>, wanted: bool) -> Result<Option<Value>> {
+    let mut writer = wanted.then(|| EventWriter::new(vec![]));
+    let mut depth = 0;
+    loop {
+        let event = next(events)?;
+        match event {
+            Event::StartObject | Event::StartArray => depth += 1,
+            Event::EndObject | Event::EndArray => depth -= 1,
+            _ => {}
+        }
+        if let Some(writer) = &mut writer {
+            writer.write(&event)?;
+        }
+        if depth == 0 {
+            break;
+        }
+    }
+
+    match writer {
+        Some(writer) => {
+            let bytes = writer.into_inner()?;
+            Ok(Some(parse(decode(&bytes)?)?))
+        }
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind};
+
+    use super::peek_fields;
+
+    #[test]
+    fn reads_requested_fields() {
+        let text = r#"{"type": "order", "body": {"items": [1, 2]}, "version": 2}"#;
+        let values = peek_fields(text, &["/version", "/type", "/missing"]).unwrap();
+        assert_eq!(
+            values,
+            [
+                Some(parse("2").unwrap()),
+                Some(parse(r#""order""#).unwrap()),
+                None
+            ]
+        );
+
+        let values = peek_fields(text, &["/body"]).unwrap();
+        assert_eq!(values, [Some(parse(r#"{"items": [1, 2]}"#).unwrap())]);
+    }
+
+    #[test]
+    fn stops_after_last_requested_field() {
+        let text = r#"{"type": "order", "body": [1, 2"#;
+        let values = peek_fields(text, &["/type"]).unwrap();
+        assert_eq!(values, [Some(parse(r#""order""#).unwrap())]);
+
+        let error = peek_fields(text, &["/type", "/version"]).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::Syntax);
+    }
+
+    #[test]
+    fn rejects_invalid_requests() {
+        let error = peek_fields("[1]", &["/type"]).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedType("object"));
+
+        let error = peek_fields("{}", &["/a/b"]).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidPointer("/a/b".into()));
+    }
+}
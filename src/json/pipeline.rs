@@ -0,0 +1,455 @@
+//! Streaming transformations of JSON text. A [`Pipeline`]
+//! reads events from its input, passes them through a
+//! series of [`Stage`]s, and writes the events that come
+//! out of the last stage as compact JSON text. Stages see
+//! one event at a time, so documents can be reshaped
+//! without building values.
+//!
+//! Stages that refer to parts of a document do so with
+//! glob patterns, as used by
+//! [`Value::select_glob`](super::Value::select_glob): a
+//! dot-separated list of segments, such as
+//! `servers.*.port`, where `*` matches any single key or
+//! index and `**` matches any number of them.
+
+use std::io::{Read, Write};
+
+use super::{
+    events::{Event, EventReader, EventWriter},
+    glob::{glob_matches, glob_segments},
+    pointer::parse_index,
+    types::Result,
+};
+
+/// A transformation applied to a stream of events.
+pub trait Stage {
+    /// Processes an event at a path, passing the events
+    /// that continue down the pipeline to `emit`. The path
+    /// of a key is the path of its member, and the path of
+    /// the end of a container is the path of the container.
+    fn process(&mut self, event: Event, path: &Path, emit: &mut dyn FnMut(Event));
+}
+
+/// The location of an event within a document.
+#[derive(Debug, Clone, Default)]
+pub struct Path {
+    tokens: Vec<String>,
+    open: Vec<Container>,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Container {
+    Object,
+    Array(usize),
+}
+
+/// How an event moves the path once it is processed.
+enum Step {
+    Open(Container),
+    Key,
+    Close,
+}
+
+impl Path {
+    /// Returns the reference tokens of the path.
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// Returns the index of the current value within its
+    /// array, if it is an array element.
+    pub fn index(&self) -> Option<usize> {
+        match self.open.last() {
+            Some(Container::Array(_)) => parse_index(self.tokens.last()?),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the path matches a glob pattern.
+    pub fn matches(&self, pattern: &[impl AsRef<str>]) -> bool {
+        glob_matches(pattern, &self.tokens)
+    }
+
+    /// Moves to the path of an event before it is
+    /// processed.
+    fn enter(&mut self, event: &Event) -> Step {
+        match event {
+            Event::Key(key) => {
+                self.tokens.push(key.clone());
+                return Step::Key;
+            }
+            Event::EndObject | Event::EndArray => {
+                self.open.pop();
+                return Step::Close;
+            }
+            _ => {}
+        }
+
+        if let Some(Container::Array(next)) = self.open.last_mut() {
+            self.tokens.push(next.to_string());
+            *next += 1;
+        }
+        match event {
+            Event::StartObject => Step::Open(Container::Object),
+            Event::StartArray => Step::Open(Container::Array(0)),
+            _ => Step::Close,
+        }
+    }
+
+    /// Moves past an event once it is processed.
+    fn leave(&mut self, step: Step) {
+        match step {
+            Step::Open(container) => self.open.push(container),
+            Step::Key => {}
+            Step::Close if !self.open.is_empty() => {
+                self.tokens.pop();
+            }
+            Step::Close => {}
+        }
+    }
+}
+
+/// A series of stages that transforms JSON text as it is
+/// read. Stages are added with chained calls to `stage`
+/// and applied in order.
+#[derive(Default)]
+pub struct Pipeline {
+    stages: Vec<(Box<dyn Stage>, Path)>,
+}
+
+impl Pipeline {
+    /// Creates a pipeline without any stages, which copies
+    /// its input as compact JSON text.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a stage at the end of the pipeline.
+    pub fn stage(mut self, stage: impl Stage + 'static) -> Self {
+        self.stages.push((Box::new(stage), Path::default()));
+        self
+    }
+
+    /// Reads JSON text from `reader`, passes it through the
+    /// stages, and writes the result to `writer`. It will
+    /// return an error if the input is not valid JSON or if
+    /// reading or writing fails. Text may already have been
+    /// written when an error occurs.
+    pub fn run(&mut self, reader: impl Read, writer: impl Write) -> Result<()> {
+        let mut writer = EventWriter::new(writer);
+        let mut result = Ok(());
+        for event in EventReader::new(reader) {
+            feed(&mut self.stages, event?, &mut |event| {
+                if result.is_ok() {
+                    result = writer.write(&event);
+                }
+            });
+            result.clone()?;
+        }
+        writer.into_inner()?;
+        Ok(())
+    }
+}
+
+/// Passes an event through a series of stages, sending the
+/// events that come out of the last one to `sink`.
+fn feed(stages: &mut [(Box<dyn Stage>, Path)], event: Event, sink: &mut dyn FnMut(Event)) {
+    match stages.split_first_mut() {
+        None => sink(event),
+        Some(((stage, path), rest)) => {
+            let step = path.enter(&event);
+            stage.process(event, path, &mut |event| feed(rest, event, sink));
+            path.leave(step);
+        }
+    }
+}
+
+/// Tracks a value that is being skipped, so that the
+/// events of its contents are skipped as well.
+#[derive(Debug, Default)]
+struct Skip {
+    depth: usize,
+}
+
+impl Skip {
+    /// Consumes an event if it belongs to a skipped value.
+    fn consume(&mut self, event: &Event) -> bool {
+        if self.depth == 0 {
+            return false;
+        }
+        match event {
+            Event::StartObject | Event::StartArray => self.depth += 1,
+            Event::EndObject | Event::EndArray => self.depth -= 1,
+            _ => {}
+        }
+        true
+    }
+
+    /// Starts skipping a value, given its first event.
+    fn start(&mut self, event: &Event) {
+        if matches!(event, Event::StartObject | Event::StartArray) {
+            self.depth = 1;
+        }
+    }
+}
+
+/// A stage that keeps only the values whose paths match
+/// any of a set of patterns, along with the containers
+/// and keys that lead to them. A container is written only
+/// once a value inside it is kept, so memory use is
+/// bounded by the nesting depth. If nothing matches, nothing
+/// is written.
+pub struct Select {
+    patterns: Vec<Vec<String>>,
+    pending: Vec<Event>,
+    written: Vec<bool>,
+    inside: usize,
+}
+
+impl Select {
+    /// Creates a stage that keeps the values matching any
+    /// of the given patterns.
+    pub fn new(patterns: &[&str]) -> Self {
+        Self {
+            patterns: patterns.iter().map(|pattern| segments(pattern)).collect(),
+            pending: vec![],
+            written: vec![],
+            inside: 0,
+        }
+    }
+
+    /// Forgets the key of a member that is not kept.
+    fn discard_key(&mut self) {
+        if matches!(self.pending.last(), Some(Event::Key(_))) {
+            self.pending.pop();
+        }
+    }
+}
+
+impl Stage for Select {
+    fn process(&mut self, event: Event, path: &Path, emit: &mut dyn FnMut(Event)) {
+        if self.inside > 0 {
+            match event {
+                Event::StartObject | Event::StartArray => self.inside += 1,
+                Event::EndObject | Event::EndArray => self.inside -= 1,
+                _ => {}
+            }
+            return emit(event);
+        }
+
+        match event {
+            Event::Key(_) => self.pending.push(event),
+            Event::EndObject | Event::EndArray => {
+                match self.written.pop() {
+                    Some(true) => emit(event),
+                    _ => {
+                        self.pending.pop();
+                        self.discard_key();
+                    }
+                };
+            }
+            _ if self.patterns.iter().any(|pattern| path.matches(pattern)) => {
+                for pending in self.pending.drain(..) {
+                    emit(pending);
+                }
+                self.written.fill(true);
+                if matches!(event, Event::StartObject | Event::StartArray) {
+                    self.inside = 1;
+                }
+                emit(event);
+            }
+            Event::StartObject | Event::StartArray => {
+                self.pending.push(event);
+                self.written.push(false);
+            }
+            _ => self.discard_key(),
+        }
+    }
+}
+
+/// A stage that renames the members whose paths match a
+/// pattern.
+pub struct Rename {
+    pattern: Vec<String>,
+    key: String,
+}
+
+impl Rename {
+    /// Creates a stage that gives the members matching a
+    /// pattern a new key.
+    pub fn new(pattern: &str, key: impl Into<String>) -> Self {
+        Self {
+            pattern: segments(pattern),
+            key: key.into(),
+        }
+    }
+}
+
+impl Stage for Rename {
+    fn process(&mut self, event: Event, path: &Path, emit: &mut dyn FnMut(Event)) {
+        match event {
+            Event::Key(_) if path.matches(&self.pattern) => emit(Event::Key(self.key.clone())),
+            event => emit(event),
+        }
+    }
+}
+
+/// A stage that removes the members and elements whose
+/// paths match any of a set of patterns.
+pub struct Remove {
+    patterns: Vec<Vec<String>>,
+    skip: Skip,
+}
+
+impl Remove {
+    /// Creates a stage that removes the values matching
+    /// any of the given patterns.
+    pub fn new(patterns: &[&str]) -> Self {
+        Self {
+            patterns: patterns.iter().map(|pattern| segments(pattern)).collect(),
+            skip: Skip::default(),
+        }
+    }
+}
+
+impl Stage for Remove {
+    fn process(&mut self, event: Event, path: &Path, emit: &mut dyn FnMut(Event)) {
+        if self.skip.consume(&event) {
+            return;
+        }
+        match self.patterns.iter().any(|pattern| path.matches(pattern)) {
+            true => self.skip.start(&event),
+            false => emit(event),
+        }
+    }
+}
+
+/// A stage that truncates every array to at most a given
+/// number of elements.
+pub struct Limit {
+    max: usize,
+    skip: Skip,
+}
+
+impl Limit {
+    /// Creates a stage that keeps at most `max` elements of
+    /// each array.
+    pub fn new(max: usize) -> Self {
+        Self {
+            max,
+            skip: Skip::default(),
+        }
+    }
+}
+
+impl Stage for Limit {
+    fn process(&mut self, event: Event, path: &Path, emit: &mut dyn FnMut(Event)) {
+        if self.skip.consume(&event) {
+            return;
+        }
+        let is_value = !matches!(event, Event::Key(_) | Event::EndObject | Event::EndArray);
+        match is_value && path.index().is_some_and(|index| index >= self.max) {
+            true => self.skip.start(&event),
+            false => emit(event),
+        }
+    }
+}
+
+fn segments(pattern: &str) -> Vec<String> {
+    glob_segments(pattern)
+        .into_iter()
+        .map(String::from)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::ErrorKind;
+
+    use super::{Limit, Pipeline, Remove, Rename, Select};
+
+    const TEXT: &str = r#"{
+        "id": 1,
+        "user": {"name": "a", "email": "a@example.com"},
+        "items": [{"sku": "x", "qty": 1}, {"sku": "y", "qty": 2}, {"sku": "z", "qty": 3}]
+    }"#;
+
+    fn run(pipeline: Pipeline, text: &str) -> String {
+        let mut output = vec![];
+        let mut pipeline = pipeline;
+        pipeline.run(text.as_bytes(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn copies_input_without_stages() {
+        assert_eq!(
+            run(Pipeline::new(), r#"{"a": [1, {"b": null}]}"#),
+            r#"{"a":[1,{"b":null}]}"#
+        );
+    }
+
+    #[test]
+    fn selects_paths() {
+        let pipeline = Pipeline::new().stage(Select::new(&["user.name", "items.*.sku"]));
+        assert_eq!(
+            run(pipeline, TEXT),
+            r#"{"user":{"name":"a"},"items":[{"sku":"x"},{"sku":"y"},{"sku":"z"}]}"#
+        );
+
+        let pipeline = Pipeline::new().stage(Select::new(&["*.*.x"]));
+        assert_eq!(
+            run(pipeline, r#"[[1, 2, {"x": 1}], [3]]"#),
+            r#"[[{"x":1}]]"#
+        );
+
+        let pipeline = Pipeline::new().stage(Select::new(&["user"]));
+        assert_eq!(
+            run(pipeline, TEXT),
+            r#"{"user":{"name":"a","email":"a@example.com"}}"#
+        );
+    }
+
+    #[test]
+    fn renames_keys() {
+        let pipeline = Pipeline::new()
+            .stage(Rename::new("user.name", "username"))
+            .stage(Rename::new("items.*.qty", "quantity"))
+            .stage(Select::new(&["user.username", "items.0"]));
+        assert_eq!(
+            run(pipeline, TEXT),
+            r#"{"user":{"username":"a"},"items":[{"sku":"x","quantity":1}]}"#
+        );
+    }
+
+    #[test]
+    fn removes_fields() {
+        let pipeline = Pipeline::new().stage(Remove::new(&["user.email", "items.*.qty", "id"]));
+        assert_eq!(
+            run(pipeline, TEXT),
+            r#"{"user":{"name":"a"},"items":[{"sku":"x"},{"sku":"y"},{"sku":"z"}]}"#
+        );
+    }
+
+    #[test]
+    fn limits_array_length() {
+        let pipeline = Pipeline::new()
+            .stage(Limit::new(2))
+            .stage(Remove::new(&["user"]));
+        assert_eq!(
+            run(pipeline, TEXT),
+            r#"{"id":1,"items":[{"sku":"x","qty":1},{"sku":"y","qty":2}]}"#
+        );
+        assert_eq!(run(Pipeline::new().stage(Limit::new(0)), "[[1], 2]"), "[]");
+    }
+
+    #[test]
+    fn reports_invalid_input() {
+        let mut output = vec![];
+        let error = Pipeline::new()
+            .stage(Limit::new(1))
+            .run("[1, 2".as_bytes(), &mut output)
+            .unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::Syntax);
+    }
+}
@@ -0,0 +1,382 @@
+use std::{fmt::Display, str::FromStr};
+
+use super::{
+    map::Map,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// A JSON pointer, as specified by RFC 6901, which
+/// identifies a single value within a document. A pointer
+/// is a list of reference tokens, each of which names an
+/// object member or an array index. In text form, every
+/// token is preceded by `/`, with `~` written as `~0` and
+/// `/` written as `~1`. The empty pointer refers to the
+/// whole document.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+impl JsonPointer {
+    /// Returns the pointer that refers to the whole document.
+    pub fn root() -> Self {
+        Self::default()
+    }
+
+    /// Parses the text form of a pointer. It will return an
+    /// error under the following conditions:
+    ///
+    /// * The text is not empty and does not begin with `/`.
+    /// * The text contains `~` not followed by `0` or `1`.
+    pub fn parse(text: &str) -> Result<Self> {
+        if text.is_empty() {
+            return Ok(Self::root());
+        }
+
+        let invalid = || Error::new(ErrorKind::InvalidPointer(text.to_owned()));
+        let tokens = text.strip_prefix('/').ok_or_else(invalid)?;
+        Ok(Self {
+            tokens: tokens
+                .split('/')
+                .map(unescape_token)
+                .collect::<Result<_>>()
+                .map_err(|_| invalid())?,
+        })
+    }
+
+    /// Returns the unescaped reference tokens of the pointer.
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// Returns whether the pointer refers to the whole
+    /// document.
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// Appends an unescaped reference token to the pointer.
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    /// Removes the last reference token from the pointer
+    /// and returns it.
+    pub fn pop(&mut self) -> Option<String> {
+        self.tokens.pop()
+    }
+
+    /// Returns a copy of the pointer extended by an
+    /// unescaped reference token.
+    pub fn join(&self, token: impl Into<String>) -> Self {
+        let mut pointer = self.clone();
+        pointer.push(token);
+        pointer
+    }
+
+    /// Returns the pointer to the value that contains the
+    /// value referred to by this pointer, or `None` for the
+    /// root pointer.
+    pub fn parent(&self) -> Option<Self> {
+        let (_, parent) = self.tokens.split_last()?;
+        Some(Self {
+            tokens: parent.to_vec(),
+        })
+    }
+
+    /// Returns the last reference token of the pointer, or
+    /// `None` for the root pointer.
+    pub fn last(&self) -> Option<&str> {
+        self.tokens.last().map(String::as_str)
+    }
+
+    /// Looks up the value referred to by the pointer.
+    pub fn resolve<'a>(&self, value: &'a Value) -> Option<&'a Value> {
+        self.tokens
+            .iter()
+            .try_fold(value, |value, token| match value {
+                Value::Object(object) => object.get(token),
+                Value::Array(array) => array.get(parse_index(token)?),
+                Value::Ext(tag, value) if tag == token => Some(&**value),
+                _ => None,
+            })
+    }
+
+    /// Looks up the value referred to by the pointer for
+    /// modification.
+    pub fn resolve_mut<'a>(&self, value: &'a mut Value) -> Option<&'a mut Value> {
+        self.tokens
+            .iter()
+            .try_fold(value, |value, token| match value {
+                Value::Object(object) => object.get_mut(token),
+                Value::Array(array) => array.get_mut(parse_index(token)?),
+                Value::Ext(tag, value) if tag == token => Some(&mut **value),
+                _ => None,
+            })
+    }
+}
+
+impl Display for JsonPointer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.tokens
+            .iter()
+            .try_for_each(|token| write!(f, "/{}", escape_token(token)))
+    }
+}
+
+impl FromStr for JsonPointer {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self> {
+        Self::parse(text)
+    }
+}
+
+impl Value {
+    /// Looks up a value by the text form of a JSON pointer,
+    /// such as `/servers/0/port`. Returns `None` if the
+    /// pointer is invalid or refers to a missing value.
+    pub fn pointer(&self, pointer: &str) -> Option<&Value> {
+        JsonPointer::parse(pointer).ok()?.resolve(self)
+    }
+
+    /// Looks up a value for modification by the text form
+    /// of a JSON pointer. Returns `None` if the pointer is
+    /// invalid or refers to a missing value.
+    pub fn pointer_mut(&mut self, pointer: &str) -> Option<&mut Value> {
+        JsonPointer::parse(pointer).ok()?.resolve_mut(self)
+    }
+
+    /// Looks up a value for modification by the text form
+    /// of a JSON pointer, inserting `default` if it is
+    /// missing. The root is never missing. Missing
+    /// containers along the path are created as well, as
+    /// arrays if they are indexed by an array index or `-`,
+    /// and as objects otherwise. Nulls along the path are
+    /// treated as missing containers. An index equal to the
+    /// length of an array, or `-`, refers to a new element at
+    /// the end. It will return an error under the following
+    /// conditions, in which case the containers created so
+    /// far are kept:
+    ///
+    /// * The pointer is invalid.
+    /// * The path passes through a string, number, or
+    ///   boolean.
+    /// * The path indexes into an array with a token that is
+    ///   not an array index, or with an index past the end.
+    pub fn pointer_or_insert(&mut self, pointer: &str, default: Value) -> Result<&mut Value> {
+        let pointer = JsonPointer::parse(pointer)?;
+        let mut default = Some(default);
+        let mut value = self;
+
+        for (position, token) in pointer.tokens().iter().enumerate() {
+            if matches!(value, Value::Null) {
+                *value = container_for(token);
+            }
+
+            let missing = match position == pointer.tokens().len() - 1 {
+                true => default.take().unwrap(),
+                false => Value::Null,
+            };
+            value = match value {
+                Value::Object(object) => object.entry(token.clone()).or_insert(missing),
+                Value::Array(array) => {
+                    let index = match token.as_str() {
+                        "-" => array.len(),
+                        token => parse_index(token)
+                            .filter(|&index| index <= array.len())
+                            .ok_or_else(|| {
+                                Error::new(ErrorKind::InvalidPointer(pointer.to_string()))
+                            })?,
+                    };
+                    if index == array.len() {
+                        array.push(missing);
+                    }
+                    &mut array[index]
+                }
+                _ => return Err(Error::new(ErrorKind::UnexpectedType("object or array"))),
+            };
+        }
+
+        Ok(value)
+    }
+}
+
+/// Returns an empty container that can be indexed by a
+/// reference token.
+fn container_for(token: &str) -> Value {
+    match token == "-" || parse_index(token).is_some() {
+        true => Value::Array(vec![]),
+        false => Value::Object(Map::new()),
+    }
+}
+
+/// Escapes a reference token for use in the text form of
+/// a pointer, replacing `~` with `~0` and `/` with `~1`.
+pub fn escape_token(token: &str) -> String {
+    token.replace('~', "~0").replace('/', "~1")
+}
+
+/// Reverses [`escape_token`]. It will return an error if
+/// the token contains `~` not followed by `0` or `1`.
+pub fn unescape_token(token: &str) -> Result<String> {
+    let mut unescaped = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(char) = chars.next() {
+        match char {
+            '~' => match chars.next() {
+                Some('0') => unescaped.push('~'),
+                Some('1') => unescaped.push('/'),
+                _ => return Err(Error::new(ErrorKind::InvalidPointer(token.to_owned()))),
+            },
+            _ => unescaped.push(char),
+        }
+    }
+    Ok(unescaped)
+}
+
+/// Parses a reference token as an array index. Indices may
+/// not have leading zeros, as required by RFC 6901.
+pub(super) fn parse_index(token: &str) -> Option<usize> {
+    let is_canonical = token == "0" || !token.starts_with('0');
+    let is_numeric = !token.is_empty() && token.bytes().all(|byte| byte.is_ascii_digit());
+    (is_canonical && is_numeric)
+        .then(|| token.parse().ok())
+        .flatten()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    use super::{escape_token, unescape_token, JsonPointer};
+
+    #[test]
+    fn parses_root_pointer() {
+        let pointer = JsonPointer::parse("").unwrap();
+        assert!(pointer.is_root());
+    }
+
+    #[test]
+    fn parses_tokens() {
+        let pointer = JsonPointer::parse("/a/0/").unwrap();
+        assert_eq!(pointer.tokens(), ["a", "0", ""]);
+    }
+
+    #[test]
+    fn unescapes_tokens() {
+        let pointer = JsonPointer::parse("/a~1b/c~0d/~01").unwrap();
+        assert_eq!(pointer.tokens(), ["a/b", "c~d", "~1"]);
+    }
+
+    #[test]
+    fn rejects_missing_leading_slash() {
+        assert!(JsonPointer::parse("a/b").is_err());
+    }
+
+    #[test]
+    fn rejects_invalid_escape() {
+        assert!(JsonPointer::parse("/a~2").is_err());
+        assert!(JsonPointer::parse("/a~").is_err());
+    }
+
+    #[test]
+    fn displays_escaped_tokens() {
+        let mut pointer = JsonPointer::root();
+        pointer.push("a/b");
+        pointer.push("c~d");
+        assert_eq!(pointer.to_string(), "/a~1b/c~0d");
+    }
+
+    #[test]
+    fn round_trips_through_text() {
+        let text = "/a~1b/~0/1";
+        assert_eq!(JsonPointer::parse(text).unwrap().to_string(), text);
+    }
+
+    #[test]
+    fn returns_parent() {
+        let pointer = JsonPointer::parse("/a/b").unwrap();
+        assert_eq!(pointer.parent().unwrap().to_string(), "/a");
+        assert!(JsonPointer::root().parent().is_none());
+    }
+
+    #[test]
+    fn escapes_and_unescapes_tokens() {
+        assert_eq!(escape_token("~/"), "~0~1");
+        assert_eq!(unescape_token("~0~1").unwrap(), "~/");
+    }
+
+    #[test]
+    fn resolves_values() {
+        let value = parse(r#"{"a": [{"b/c": 1}]}"#).unwrap();
+        assert_eq!(value.pointer("/a/0/b~1c"), Some(&Value::Number(1.0.into())));
+        assert_eq!(value.pointer(""), Some(&value));
+    }
+
+    #[test]
+    fn does_not_resolve_missing_values() {
+        let value = parse(r#"{"a": [1]}"#).unwrap();
+        assert!(value.pointer("/b").is_none());
+        assert!(value.pointer("/a/1").is_none());
+        assert!(value.pointer("/a/-").is_none());
+        assert!(value.pointer("/a/00").is_none());
+        assert!(value.pointer("a").is_none());
+    }
+
+    #[test]
+    fn resolves_values_for_modification() {
+        let mut value = parse(r#"{"a": [1]}"#).unwrap();
+        *value.pointer_mut("/a/0").unwrap() = Value::Null;
+        assert_eq!(value, parse(r#"{"a": [null]}"#).unwrap());
+    }
+
+    #[test]
+    fn inserts_missing_path() {
+        let mut value = parse("{}").unwrap();
+        *value.pointer_or_insert("/a/b/0/c", Value::Null).unwrap() = Value::Boolean(true);
+        assert_eq!(value, parse(r#"{"a": {"b": [{"c": true}]}}"#).unwrap());
+    }
+
+    #[test]
+    fn rejects_index_past_end() {
+        let mut value = parse(r#"{"a": [1]}"#).unwrap();
+        assert!(value.pointer_or_insert("/a/2", Value::Null).is_err());
+        assert!(value
+            .pointer_or_insert("/a/18446744073709551615", Value::Null)
+            .is_err());
+        assert!(value.pointer_or_insert("/b/1", Value::Null).is_err());
+        assert_eq!(value, parse(r#"{"a": [1], "b": []}"#).unwrap());
+    }
+
+    #[test]
+    fn returns_existing_value() {
+        let mut value = parse(r#"{"a": [1, null]}"#).unwrap();
+        let existing = value.pointer_or_insert("/a/0", Value::Null).unwrap();
+        assert_eq!(existing, &Value::Number(1.into()));
+        let existing = value
+            .pointer_or_insert("/a/1", Value::Boolean(true))
+            .unwrap();
+        assert_eq!(existing, &Value::Null);
+    }
+
+    #[test]
+    fn appends_with_dash() {
+        let mut value = parse(r#"{"a": [1]}"#).unwrap();
+        value
+            .pointer_or_insert("/a/-", Value::Number(2.into()))
+            .unwrap();
+        value
+            .pointer_or_insert("/b/-", Value::Number(3.into()))
+            .unwrap();
+        assert_eq!(value, parse(r#"{"a": [1, 2], "b": [3]}"#).unwrap());
+    }
+
+    #[test]
+    fn rejects_path_through_scalar() {
+        let mut value = parse(r#"{"a": 1}"#).unwrap();
+        assert!(value.pointer_or_insert("/a/b", Value::Null).is_err());
+        let mut value = parse(r#"{"a": []}"#).unwrap();
+        assert!(value.pointer_or_insert("/a/b", Value::Null).is_err());
+    }
+}
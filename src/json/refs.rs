@@ -0,0 +1,223 @@
+use std::{
+    collections::HashMap,
+    path::{Path, PathBuf},
+};
+
+use super::{
+    file::read_file,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// Options that control how references are resolved by
+/// [`resolve_refs`].
+#[derive(Debug, Clone, Default)]
+pub struct ResolveOptions {
+    /// The directory against which references to other
+    /// files, such as `common.json#/definitions/id`, are
+    /// resolved. References within a referenced file are
+    /// resolved against the directory of that file. When
+    /// `None`, references to other files are an error.
+    pub base_dir: Option<PathBuf>,
+}
+
+/// Replaces every object with a `$ref` member, such as
+/// `{"$ref": "#/definitions/x"}`, with the value it refers
+/// to. The part of a reference after `#` is a JSON pointer
+/// into the document, or into the file named before it.
+/// Other members of a reference object are discarded, and
+/// referenced values are themselves resolved. The value is
+/// left unchanged if an error is returned, which happens
+/// under the following conditions:
+///
+/// * A reference does not refer to a value.
+/// * A reference refers, directly or indirectly, to a
+///   value that contains itself.
+/// * A reference names another file, and either
+///   `base_dir` is not set or the file cannot be read.
+pub fn resolve_refs(value: &mut Value, options: &ResolveOptions) -> Result<()> {
+    let mut resolver = Resolver {
+        options,
+        files: HashMap::new(),
+        stack: vec![],
+    };
+    let root = value.clone();
+    let mut resolved = value.clone();
+    resolver.resolve(&mut resolved, &root, None)?;
+    *value = resolved;
+    Ok(())
+}
+
+struct Resolver<'a> {
+    options: &'a ResolveOptions,
+    /// The files read so far, by path.
+    files: HashMap<PathBuf, Value>,
+    /// The references being resolved, as file and pointer,
+    /// so that cycles are detected.
+    stack: Vec<(Option<PathBuf>, String)>,
+}
+
+impl Resolver<'_> {
+    /// Resolves the references within a value that belongs
+    /// to the given root, which was read from the given
+    /// file, or is the input document if there is none.
+    fn resolve(&mut self, value: &mut Value, root: &Value, file: Option<&Path>) -> Result<()> {
+        let reference = match value {
+            Value::Object(object) => match object.get("$ref") {
+                Some(Value::String(reference)) => reference.clone(),
+                _ => {
+                    return object
+                        .values_mut()
+                        .try_for_each(|value| self.resolve(value, root, file))
+                }
+            },
+            Value::Array(array) => {
+                return array
+                    .iter_mut()
+                    .try_for_each(|value| self.resolve(value, root, file))
+            }
+            _ => return Ok(()),
+        };
+
+        let (path, fragment) = reference.split_once('#').unwrap_or((&reference, ""));
+        let pointer = percent_decode(fragment)
+            .ok_or_else(|| Error::new(ErrorKind::UnresolvedRef(reference.clone())))?;
+        let target_file = match path {
+            "" => file.map(Path::to_path_buf),
+            path => Some(self.path(path, file, &reference)?),
+        };
+
+        let key = (target_file.clone(), pointer.clone());
+        if self.stack.contains(&key) {
+            return Err(Error::new(ErrorKind::CyclicRef(reference)));
+        }
+
+        let target_root = match &target_file {
+            Some(target_file) if Some(target_file.as_path()) != file => self.file(target_file)?,
+            _ => root.clone(),
+        };
+        let mut target = target_root
+            .pointer(&pointer)
+            .cloned()
+            .ok_or_else(|| Error::new(ErrorKind::UnresolvedRef(reference.clone())))?;
+
+        self.stack.push(key);
+        self.resolve(&mut target, &target_root, target_file.as_deref())?;
+        self.stack.pop();
+        *value = target;
+        Ok(())
+    }
+
+    /// Returns the path of a file named by a reference made
+    /// within the given file.
+    fn path(&self, path: &str, file: Option<&Path>, reference: &str) -> Result<PathBuf> {
+        let dir = match file {
+            Some(file) => file.parent().map(Path::to_path_buf),
+            None => self.options.base_dir.clone(),
+        };
+        let dir = dir.ok_or_else(|| Error::new(ErrorKind::UnresolvedRef(reference.to_owned())))?;
+        Ok(dir.join(path))
+    }
+
+    /// Returns the value in a file, reading it the first
+    /// time it is referenced.
+    fn file(&mut self, path: &Path) -> Result<Value> {
+        if let Some(value) = self.files.get(path) {
+            return Ok(value.clone());
+        }
+        let value = read_file(path)?;
+        self.files.insert(path.to_path_buf(), value.clone());
+        Ok(value)
+    }
+}
+
+/// Decodes the `%XX` escapes in the fragment of a
+/// reference, which are used for characters that are not
+/// allowed in URIs. Returns `None` if an escape is invalid.
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = vec![];
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use crate::json::{parse, ErrorKind};
+
+    use super::{resolve_refs, ResolveOptions};
+
+    #[test]
+    fn inlines_internal_refs() {
+        let mut value = parse(
+            r##"{
+                "definitions": {
+                    "id": {"type": "integer"},
+                    "user": {"properties": {"id": {"$ref": "#/definitions/id"}}},
+                    "a b": [1]
+                },
+                "items": [{"$ref": "#/definitions/user", "title": "x"}, {"$ref": "#/definitions/a%20b/0"}]
+            }"##,
+        )
+        .unwrap();
+        resolve_refs(&mut value, &ResolveOptions::default()).unwrap();
+        assert_eq!(
+            value.pointer("/items").unwrap(),
+            &parse(r#"[{"properties": {"id": {"type": "integer"}}}, 1]"#).unwrap()
+        );
+        assert_eq!(
+            value.pointer("/definitions/user/properties/id").unwrap(),
+            &parse(r#"{"type": "integer"}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn rejects_cycles_and_missing_targets() {
+        let text = r##"{"a": {"b": {"$ref": "#/a"}}, "c": {"$ref": "#/a"}}"##;
+        let mut value = parse(text).unwrap();
+        let error = resolve_refs(&mut value, &ResolveOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::CyclicRef("#/a".into()));
+        assert_eq!(value, parse(text).unwrap());
+
+        for reference in ["#/missing", "other.json#/a"] {
+            let mut value = parse(&format!(r#"{{"$ref": "{}"}}"#, reference)).unwrap();
+            let error = resolve_refs(&mut value, &ResolveOptions::default()).unwrap_err();
+            assert_eq!(error.kind(), &ErrorKind::UnresolvedRef(reference.into()));
+        }
+    }
+
+    #[test]
+    fn resolves_refs_to_other_files() {
+        let dir = std::env::temp_dir().join(format!("json-refs-{}", std::process::id()));
+        fs::create_dir_all(dir.join("common")).unwrap();
+        fs::write(
+            dir.join("common/types.json"),
+            r##"{"id": {"$ref": "#/int"}, "int": {"type": "integer"}, "name": {"$ref": "names.json"}}"##,
+        )
+        .unwrap();
+        fs::write(dir.join("common/names.json"), r#"{"type": "string"}"#).unwrap();
+
+        let mut value =
+            parse(r#"[{"$ref": "common/types.json#/id"}, {"$ref": "common/types.json#/name"}]"#)
+                .unwrap();
+        let options = ResolveOptions {
+            base_dir: Some(dir.clone()),
+        };
+        resolve_refs(&mut value, &options).unwrap();
+        assert_eq!(
+            value,
+            parse(r#"[{"type": "integer"}, {"type": "string"}]"#).unwrap()
+        );
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
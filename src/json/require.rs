@@ -0,0 +1,118 @@
+use std::fmt::Display;
+
+use super::{pointer::JsonPointer, schema::Type, types::Value};
+
+/// The paths that a value was required to have but did
+/// not, as reported by [`require`] and [`require_typed`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MissingPaths {
+    /// The paths that do not refer to a value, including
+    /// paths that are not valid JSON pointers.
+    pub missing: Vec<String>,
+    /// The paths that refer to a value of the wrong type,
+    /// each with the name of the expected type.
+    pub mismatched: Vec<(String, String)>,
+}
+
+/// Checks that a value has a value at each of the given
+/// JSON pointers. This is a cheap sanity check for inputs
+/// whose full shape does not need to be validated. All
+/// paths are checked, and those that are missing are
+/// reported together.
+pub fn require(value: &Value, paths: &[&str]) -> Result<(), MissingPaths> {
+    let paths: Vec<_> = paths.iter().map(|&path| (path, None)).collect();
+    check(value, &paths)
+}
+
+/// Checks that a value has a value of the given type at
+/// each of the given JSON pointers. Types are named as by
+/// the `type` keyword of JSON Schema, such as `"integer"`
+/// or `"object"`. All paths are checked, and those that are
+/// missing or have the wrong type are reported together.
+pub fn require_typed(value: &Value, paths: &[(&str, &str)]) -> Result<(), MissingPaths> {
+    let paths: Vec<_> = paths
+        .iter()
+        .map(|&(path, type_)| (path, Some(type_)))
+        .collect();
+    check(value, &paths)
+}
+
+fn check(value: &Value, paths: &[(&str, Option<&str>)]) -> Result<(), MissingPaths> {
+    let mut report = MissingPaths::default();
+
+    for &(path, type_) in paths {
+        let found = JsonPointer::parse(path)
+            .ok()
+            .and_then(|pointer| pointer.resolve(value));
+        match (found, type_) {
+            (None, _) => report.missing.push(path.to_owned()),
+            (Some(found), Some(name)) => {
+                if !Type::named(name).is_some_and(|type_| type_.matches(found)) {
+                    report.mismatched.push((path.to_owned(), name.to_owned()));
+                }
+            }
+            (Some(_), None) => {}
+        }
+    }
+
+    match report.missing.is_empty() && report.mismatched.is_empty() {
+        true => Ok(()),
+        false => Err(report),
+    }
+}
+
+impl Display for MissingPaths {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut problems = vec![];
+        if !self.missing.is_empty() {
+            problems.push(format!("missing {}", self.missing.join(", ")));
+        }
+        for (path, type_) in &self.mismatched {
+            problems.push(format!("expected {} at {}", type_, path));
+        }
+        write!(f, "{}", problems.join("; "))
+    }
+}
+
+impl std::error::Error for MissingPaths {}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::parse;
+
+    use super::{require, require_typed, MissingPaths};
+
+    const TEXT: &str = r#"{"id": 7, "user": {"name": "a"}, "items": [1]}"#;
+
+    #[test]
+    fn accepts_present_paths() {
+        let value = parse(TEXT).unwrap();
+        assert_eq!(require(&value, &["/id", "/user/name", "/items/0"]), Ok(()));
+        assert_eq!(require(&value, &[]), Ok(()));
+    }
+
+    #[test]
+    fn reports_all_missing_paths() {
+        let value = parse(TEXT).unwrap();
+        let error = require(&value, &["/id", "/user/email", "/items/1", "bad"]).unwrap_err();
+        assert_eq!(error.missing, ["/user/email", "/items/1", "bad"]);
+        assert_eq!(error.to_string(), "missing /user/email, /items/1, bad");
+    }
+
+    #[test]
+    fn checks_types() {
+        let value = parse(TEXT).unwrap();
+        let paths = [("/id", "integer"), ("/user", "object"), ("/items", "array")];
+        assert_eq!(require_typed(&value, &paths), Ok(()));
+
+        let error = require_typed(&value, &[("/id", "string"), ("/name", "string")]).unwrap_err();
+        assert_eq!(
+            error,
+            MissingPaths {
+                missing: vec!["/name".to_owned()],
+                mismatched: vec![("/id".to_owned(), "string".to_owned())],
+            }
+        );
+        assert_eq!(error.to_string(), "missing /name; expected string at /id");
+    }
+}
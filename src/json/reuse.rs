@@ -0,0 +1,81 @@
+use super::types::Value;
+
+/// Moves `source` into `target`, keeping the strings,
+/// arrays, and objects already allocated by `target`
+/// wherever the two values have the same shape. Buffers
+/// that are kept only grow, so that repeatedly assigning
+/// values of a similar shape settles into reusing them.
+pub(super) fn assign(target: &mut Value, source: Value) {
+    match (target, source) {
+        (Value::String(target), Value::String(source)) => {
+            target.clear();
+            target.push_str(&source);
+        }
+        (Value::Array(target), Value::Array(source)) => {
+            target.truncate(source.len());
+            let mut source = source.into_iter();
+            for (target, source) in target.iter_mut().zip(source.by_ref()) {
+                assign(target, source);
+            }
+            target.extend(source);
+        }
+        (Value::Object(target), Value::Object(mut source)) => {
+            target.retain(|key, _| source.contains_key(key));
+            for (key, target) in target.iter_mut() {
+                assign(target, source.remove(key).unwrap());
+            }
+            target.extend(source);
+        }
+        (target, source) => *target = source,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, parse_into, Value};
+
+    #[test]
+    fn replaces_previous_value() {
+        let mut value = parse(r#"{"a": [1, 2, 3], "b": "x"}"#).unwrap();
+        parse_into(r#"{"a": [4], "c": null}"#, &mut value).unwrap();
+        assert_eq!(value, parse(r#"{"a": [4], "c": null}"#).unwrap());
+    }
+
+    #[test]
+    fn replaces_values_of_different_type() {
+        let mut value = parse(r#"[1, "two", {}]"#).unwrap();
+        parse_into(r#"["one", 2, [], true]"#, &mut value).unwrap();
+        assert_eq!(value, parse(r#"["one", 2, [], true]"#).unwrap());
+    }
+
+    #[test]
+    fn reuses_existing_buffers() {
+        let mut value = parse(r#"{"items": ["aaaaaaaa", "bbbbbbbb", "cccccccc"]}"#).unwrap();
+        let (array_ptr, string_ptr) = match &value.pointer("/items").unwrap() {
+            Value::Array(array) => match &array[0] {
+                Value::String(string) => (array.as_ptr(), string.as_ptr()),
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        };
+
+        parse_into(r#"{"items": ["dddd", "eeee"]}"#, &mut value).unwrap();
+        match &value.pointer("/items").unwrap() {
+            Value::Array(array) => match &array[0] {
+                Value::String(string) => {
+                    assert_eq!(array.as_ptr(), array_ptr);
+                    assert_eq!(string.as_ptr(), string_ptr);
+                }
+                _ => unreachable!(),
+            },
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn leaves_value_untouched_on_error() {
+        let mut value = parse("[1]").unwrap();
+        assert!(parse_into("[2,", &mut value).is_err());
+        assert_eq!(value, parse("[1]").unwrap());
+    }
+}
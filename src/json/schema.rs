@@ -0,0 +1,427 @@
+use std::fmt::Display;
+
+use super::{
+    document::Document,
+    map::Map,
+    pointer::JsonPointer,
+    types::{Error, ErrorKind, Result, Span, Value},
+};
+
+/// A compiled JSON Schema. Compiling checks the schema
+/// document once and converts it into a form that can be
+/// applied to many values. The supported keywords are
+/// `type`, `properties`, `required`, `items`, and
+/// `default`; other keywords are ignored.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Schema {
+    pub(super) root: Node,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(super) struct Node {
+    pub(super) types: Option<Vec<Type>>,
+    pub(super) properties: Vec<(String, Node)>,
+    pub(super) required: Vec<String>,
+    pub(super) items: Option<Box<Node>>,
+    pub(super) default: Option<Value>,
+}
+
+/// A value that does not conform to a schema.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValidationError {
+    /// The location of the offending value.
+    pub pointer: JsonPointer,
+    /// A description of how the value does not conform.
+    pub message: String,
+    /// The span of the offending value in the source text,
+    /// if the value was validated from text.
+    pub span: Option<Span>,
+}
+
+/// A type named by the `type` keyword.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(super) enum Type {
+    Null,
+    Boolean,
+    Integer,
+    Number,
+    String,
+    Array,
+    Object,
+}
+
+impl Schema {
+    /// Compiles a schema document. It will return an error
+    /// under the following conditions:
+    ///
+    /// * The schema, or a nested schema, is not an object.
+    /// * A supported keyword has a value of the wrong type.
+    /// * The `type` keyword names an unknown type.
+    pub fn compile(schema: &Value) -> Result<Self> {
+        Ok(Self {
+            root: compile_node(schema, "#")?,
+        })
+    }
+
+    /// Inserts the `default` value of every property that
+    /// is missing from an object, recursively. Defaults are
+    /// inserted in the order the properties are declared,
+    /// and are themselves filled in from their own schema.
+    /// Values that do not have the shape the schema expects
+    /// are left untouched.
+    pub fn apply_defaults(&self, value: &mut Value) {
+        apply_defaults(&self.root, value);
+    }
+
+    /// Validates a value against the schema, returning an
+    /// error for every value that does not conform. The
+    /// errors are returned in document order.
+    pub fn validate(&self, value: &Value) -> Vec<ValidationError> {
+        let mut errors = vec![];
+        validate(&self.root, value, &mut JsonPointer::root(), &mut errors);
+        errors
+    }
+
+    /// Parses a JSON string and validates it against the
+    /// schema, like `validate`, but with the span of each
+    /// offending value in the text attached to its error.
+    /// It will return an error if the text is not valid
+    /// JSON.
+    pub fn validate_text(&self, text: &str) -> Result<Vec<ValidationError>> {
+        let document = Document::parse(text)?;
+        let mut errors = self.validate(&document.to_value());
+        for error in &mut errors {
+            error.span = document.span_of(&error.pointer);
+        }
+        Ok(errors)
+    }
+}
+
+fn compile_node(schema: &Value, path: &str) -> Result<Node> {
+    let object = match schema {
+        Value::Object(object) => object,
+        _ => return Err(invalid(path, "a schema must be an object")),
+    };
+
+    Ok(Node {
+        types: object
+            .get("type")
+            .map(|types| compile_types(types, path))
+            .transpose()?,
+        properties: match object.get("properties") {
+            None => vec![],
+            Some(Value::Object(properties)) => properties
+                .iter()
+                .map(|(key, schema)| {
+                    let path = format!("{}/properties/{}", path, key);
+                    Ok((key.clone(), compile_node(schema, &path)?))
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => return Err(invalid(path, "`properties` must be an object")),
+        },
+        required: match object.get("required") {
+            None => vec![],
+            Some(Value::Array(required)) => required
+                .iter()
+                .map(|key| match key {
+                    Value::String(key) => Ok(key.clone()),
+                    _ => Err(invalid(path, "`required` must contain strings")),
+                })
+                .collect::<Result<_>>()?,
+            Some(_) => return Err(invalid(path, "`required` must be an array")),
+        },
+        items: object
+            .get("items")
+            .map(|items| compile_node(items, &format!("{}/items", path)).map(Box::new))
+            .transpose()?,
+        default: object.get("default").cloned(),
+    })
+}
+
+fn compile_types(types: &Value, path: &str) -> Result<Vec<Type>> {
+    let compile_type = |name: &Value| match name {
+        Value::String(name) => {
+            Type::named(name).ok_or_else(|| invalid(path, "`type` names an unknown type"))
+        }
+        _ => Err(invalid(path, "`type` must be a string or array of strings")),
+    };
+
+    match types {
+        Value::Array(types) => types.iter().map(compile_type).collect(),
+        name => Ok(vec![compile_type(name)?]),
+    }
+}
+
+fn apply_defaults(node: &Node, value: &mut Value) {
+    match value {
+        Value::Object(object) => apply_object_defaults(node, object),
+        Value::Array(array) => {
+            if let Some(items) = &node.items {
+                array
+                    .iter_mut()
+                    .for_each(|element| apply_defaults(items, element));
+            }
+        }
+        _ => {}
+    }
+}
+
+fn apply_object_defaults(node: &Node, object: &mut Map) {
+    for (key, property) in &node.properties {
+        if let (false, Some(default)) = (object.contains_key(key), &property.default) {
+            object.insert(key.clone(), default.clone());
+        }
+        if let Some(member) = object.get_mut(key) {
+            apply_defaults(property, member);
+        }
+    }
+}
+
+fn validate(
+    node: &Node,
+    value: &Value,
+    pointer: &mut JsonPointer,
+    errors: &mut Vec<ValidationError>,
+) {
+    let mut report = |message: String| {
+        errors.push(ValidationError {
+            pointer: pointer.clone(),
+            message,
+            span: None,
+        })
+    };
+
+    if let Some(types) = &node.types {
+        if !types.iter().any(|type_| type_.matches(value)) {
+            let expected = types.iter().map(Type::name).collect::<Vec<_>>();
+            report(format!(
+                "expected {}, found {}",
+                expected.join(" or "),
+                Type::of(value).name()
+            ));
+            return;
+        }
+    }
+
+    match value {
+        Value::Object(_) | Value::Ext(..) => {
+            let member = |key: &str| match value {
+                Value::Object(object) => object.get(key),
+                Value::Ext(tag, tagged) if tag == key => Some(&**tagged),
+                _ => None,
+            };
+            for key in node.required.iter().filter(|key| member(key).is_none()) {
+                report(format!("missing required member \"{}\"", key));
+            }
+            for (key, property) in &node.properties {
+                if let Some(member) = member(key) {
+                    pointer.push(key.clone());
+                    validate(property, member, pointer, errors);
+                    pointer.pop();
+                }
+            }
+        }
+        Value::Array(array) => {
+            if let Some(items) = &node.items {
+                for (index, element) in array.iter().enumerate() {
+                    pointer.push(index.to_string());
+                    validate(items, element, pointer, errors);
+                    pointer.pop();
+                }
+            }
+        }
+        _ => {}
+    }
+}
+
+impl Type {
+    /// Returns the type with the given name, as used by the
+    /// `type` keyword.
+    pub(super) fn named(name: &str) -> Option<Type> {
+        match name {
+            "null" => Some(Type::Null),
+            "boolean" => Some(Type::Boolean),
+            "integer" => Some(Type::Integer),
+            "number" => Some(Type::Number),
+            "string" => Some(Type::String),
+            "array" => Some(Type::Array),
+            "object" => Some(Type::Object),
+            _ => None,
+        }
+    }
+
+    pub(super) fn of(value: &Value) -> Type {
+        match value {
+            Value::Null => Type::Null,
+            Value::Boolean(_) => Type::Boolean,
+            Value::Number(_) => Type::Number,
+            Value::String(_) => Type::String,
+            Value::Array(_) => Type::Array,
+            Value::Object(_) | Value::Ext(..) => Type::Object,
+            Value::Raw(raw) if raw.starts_with('[') => Type::Array,
+            Value::Raw(_) => Type::Object,
+        }
+    }
+
+    pub(super) fn matches(&self, value: &Value) -> bool {
+        match (self, value) {
+            (Type::Integer, Value::Number(number)) => {
+                number.as_i128().is_some() || number.as_u128().is_some()
+            }
+            (type_, value) => *type_ == Type::of(value),
+        }
+    }
+
+    pub(super) fn name(&self) -> &'static str {
+        match self {
+            Type::Null => "null",
+            Type::Boolean => "boolean",
+            Type::Integer => "integer",
+            Type::Number => "number",
+            Type::String => "string",
+            Type::Array => "array",
+            Type::Object => "object",
+        }
+    }
+}
+
+impl Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{}: {}", self.pointer, self.message)?;
+        if let Some(span) = self.span {
+            write!(f, " at bytes {}..{}", span.start, span.end)?;
+        }
+        Ok(())
+    }
+}
+
+fn invalid(path: &str, reason: &str) -> Error {
+    Error::new(ErrorKind::InvalidSchema(format!("{} at {}", reason, path)))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, to_string, Span, Value};
+
+    use super::Schema;
+
+    fn schema(text: &str) -> Schema {
+        Schema::compile(&parse(text).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn fills_missing_members() {
+        let schema = schema(
+            r#"{"type": "object", "properties": {
+                "host": {"type": "string", "default": "localhost"},
+                "port": {"type": "integer", "default": 80}
+            }}"#,
+        );
+        let mut value = parse(r#"{"port": 8080}"#).unwrap();
+        schema.apply_defaults(&mut value);
+        assert_eq!(
+            value,
+            parse(r#"{"port": 8080, "host": "localhost"}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn fills_nested_members_recursively() {
+        let schema = schema(
+            r#"{"properties": {
+                "server": {"default": {}, "properties": {
+                    "tls": {"default": {"enabled": false}, "properties": {
+                        "port": {"default": 443}
+                    }}
+                }},
+                "routes": {"items": {"properties": {"method": {"default": "GET"}}}}
+            }}"#,
+        );
+        let mut value = parse(r#"{"routes": [{"path": "/"}, {"method": "POST"}]}"#).unwrap();
+        schema.apply_defaults(&mut value);
+        let expected = parse(
+            r#"{"routes": [{"path": "/", "method": "GET"}, {"method": "POST"}],
+                "server": {"tls": {"enabled": false, "port": 443}}}"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn inserts_in_declaration_order() {
+        let schema = schema(r#"{"properties": {"b": {"default": 1}, "a": {"default": 2}}}"#);
+        let mut value = parse("{}").unwrap();
+        schema.apply_defaults(&mut value);
+        assert_eq!(to_string(&value), r#"{"b":1,"a":2}"#);
+    }
+
+    #[test]
+    fn leaves_mismatched_values_untouched() {
+        let schema = schema(r#"{"properties": {"a": {"default": 1}}}"#);
+        let mut value = Value::Array(vec![]);
+        schema.apply_defaults(&mut value);
+        assert_eq!(value, Value::Array(vec![]));
+    }
+
+    #[test]
+    fn rejects_invalid_schemas() {
+        for text in [
+            "1",
+            r#"{"type": "float"}"#,
+            r#"{"properties": []}"#,
+            r#"{"required": [1]}"#,
+            r#"{"items": true}"#,
+        ] {
+            assert!(Schema::compile(&parse(text).unwrap()).is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn reports_validation_errors() {
+        let schema = schema(
+            r#"{"type": "object", "required": ["name", "port"], "properties": {
+                "port": {"type": "integer"},
+                "tags": {"items": {"type": ["string", "null"]}}
+            }}"#,
+        );
+        let value = parse(r#"{"port": 1.5, "tags": ["a", null, 3]}"#).unwrap();
+        let errors: Vec<_> = schema
+            .validate(&value)
+            .iter()
+            .map(ToString::to_string)
+            .collect();
+        assert_eq!(
+            errors,
+            [
+                r#"#: missing required member "name""#,
+                "#/port: expected integer, found number",
+                "#/tags/2: expected string or null, found number",
+            ]
+        );
+    }
+
+    #[test]
+    fn accepts_conforming_values() {
+        let schema = schema(r#"{"type": "array", "items": {"type": "integer"}}"#);
+        assert!(schema.validate(&parse("[1, 2.0, -3]").unwrap()).is_empty());
+    }
+
+    #[test]
+    fn localizes_errors_in_source_text() {
+        let schema = schema(r#"{"properties": {"port": {"type": "number"}}}"#);
+        let errors = schema.validate_text("{\n  \"port\": \"80\"\n}").unwrap();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].span, Some(Span { start: 12, end: 16 }));
+        assert_eq!(
+            errors[0].to_string(),
+            "#/port: expected number, found string at bytes 12..16"
+        );
+    }
+
+    #[test]
+    fn reports_syntax_errors_with_offsets() {
+        let schema = schema("{}");
+        let error = schema.validate_text("[1, @]").unwrap_err();
+        assert_eq!(error.offset(), Some(4));
+    }
+}
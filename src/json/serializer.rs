@@ -0,0 +1,872 @@
+use colored::Colorize;
+
+use super::{
+    escape::escape_into,
+    number::Number,
+    options::{FormatOptions, KeyOrder, NumberPolicy},
+    parse,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// Serializes a JSON value into compact text, with no
+/// insignificant whitespace.
+pub fn to_string(value: &Value) -> String {
+    to_string_with(value, &FormatOptions::default())
+}
+
+/// Serializes a JSON value into indented text, with each
+/// array element and object member on its own line.
+pub fn to_string_pretty(value: &Value) -> String {
+    let options = FormatOptions {
+        indent: Some(2),
+        ..Default::default()
+    };
+    to_string_with(value, &options)
+}
+
+/// Serializes a JSON value into text with the given
+/// options. Object members are written in the order in
+/// which the object stores them, unless the options ask
+/// for them to be sorted. This never fails: numbers that
+/// the options reject with [`NumberPolicy::Error`] are
+/// written as is, numbers that are not finite are written
+/// as `null`, and the maximum output length is not
+/// enforced. Use [`try_to_string_with`] to enforce them.
+pub fn to_string_with(value: &Value, options: &FormatOptions) -> String {
+    let mut writer = Writer::new(options, false);
+    writer.write(value);
+    writer.text
+}
+
+/// Serializes a JSON value into text with the given
+/// options, as `to_string_with` does. It will return an
+/// error under the following conditions:
+///
+/// * The options reject a number in the value.
+/// * A number in the value is not finite, unless it is
+///   written as it appeared in round-trip mode.
+/// * The text is longer than the options allow.
+pub fn try_to_string_with(value: &Value, options: &FormatOptions) -> Result<String> {
+    let mut writer = Writer::new(options, false);
+    writer.limit = options.max_output_bytes;
+    writer.write(value);
+    if let Some(max) = options.max_output_bytes.filter(|_| writer.too_long) {
+        return Err(Error::new(ErrorKind::OutputTooLarge { max }));
+    }
+    match writer.rejected {
+        Some(number) => Err(Error::new(ErrorKind::RejectedNumber(number))),
+        None => Ok(writer.text),
+    }
+}
+
+/// Writes a single number as the serializer would write it
+/// with the given options, so that protocol code which
+/// embeds numbers outside of a document writes them in the
+/// same way. It will return an error if the options reject
+/// the number. A number the options stringify is returned
+/// as a quoted string.
+pub fn format_number(number: &Number, options: &FormatOptions) -> Result<String> {
+    let mut writer = Writer::new(options, false);
+    writer.write_number(number);
+    match writer.rejected {
+        Some(number) => Err(Error::new(ErrorKind::RejectedNumber(number))),
+        None => Ok(writer.text),
+    }
+}
+
+/// Writes a JSON value to a formatter as it is serialized,
+/// with terminal colors if asked, so that the text of a
+/// large value is never held in memory all at once.
+pub(super) fn write_fmt(
+    value: &Value,
+    options: &FormatOptions,
+    colored: bool,
+    out: &mut dyn std::fmt::Write,
+) -> std::fmt::Result {
+    let mut writer = Writer::new(options, false);
+    writer.colored = colored;
+    writer.write_to(value, Some(out))
+}
+
+/// Serializes a JSON value into its canonical text form.
+/// The canonical form contains no insignificant whitespace
+/// and lists object members in ascending key order, so
+/// two values that differ only in member order or layout
+/// produce identical text. Negative zero is written as
+/// zero so that values which compare equal also serialize
+/// equally, and numbers that are not finite are written as
+/// `null`. Raw text is written as the canonical form of the
+/// value it holds, so that a shallowly parsed value is
+/// written as the fully parsed value would be.
+pub fn to_canonical_string(value: &Value) -> String {
+    let options = FormatOptions::default();
+    let mut writer = Writer::new(&options, true);
+    writer.write(value);
+    writer.text
+}
+
+struct Writer<'a> {
+    options: &'a FormatOptions,
+    canonical: bool,
+    colored: bool,
+    text: String,
+    /// The number of bytes of text already flushed.
+    flushed: usize,
+    /// The maximum length of the text, which is only
+    /// enforced when writing can fail.
+    limit: Option<usize>,
+    /// Whether writing stopped because the text would have
+    /// grown past the limit.
+    too_long: bool,
+    /// The first number rejected by the options.
+    rejected: Option<String>,
+}
+
+/// The length of text at which a writer flushes it.
+const FLUSH_BYTES: usize = 1 << 16;
+
+/// A container that has been opened in the text, with the
+/// items that remain to be written into it. The writer
+/// keeps a stack of these rather than recursing, so that
+/// deeply nested values do not overflow the call stack.
+struct Open<'v> {
+    items: std::vec::IntoIter<Item<'v>>,
+    close: char,
+    is_empty: bool,
+}
+
+/// An item of an open container.
+enum Item<'v> {
+    Element(&'v Value),
+    /// An object member, with the padding after its key.
+    Member(&'v String, &'v Value, usize),
+    /// A row of a table, already written.
+    Row(String),
+}
+
+impl<'a> Writer<'a> {
+    fn new(options: &'a FormatOptions, canonical: bool) -> Self {
+        Self {
+            options,
+            canonical,
+            colored: false,
+            text: String::new(),
+            flushed: 0,
+            limit: None,
+            too_long: false,
+            rejected: None,
+        }
+    }
+
+    fn write(&mut self, value: &Value) {
+        self.write_to(value, None)
+            .expect("writing into a string does not fail");
+    }
+
+    /// Writes a value, flushing the text to `out` as it grows
+    /// if given.
+    fn write_to(
+        &mut self,
+        value: &Value,
+        mut out: Option<&mut dyn std::fmt::Write>,
+    ) -> std::fmt::Result {
+        let mut stack = vec![];
+        self.write_value(value, &mut stack);
+        while let Some(open) = stack.last_mut() {
+            if let Some(out) = out
+                .as_deref_mut()
+                .filter(|_| self.text.len() >= FLUSH_BYTES)
+            {
+                self.flush(out)?;
+            }
+            let Some(item) = open.items.next() else {
+                let (is_empty, close) = (open.is_empty, open.close);
+                stack.pop();
+                if !is_empty {
+                    self.write_newline(stack.len());
+                }
+                self.push(close);
+                continue;
+            };
+            if self.too_long {
+                break;
+            }
+            if !std::mem::replace(&mut open.is_empty, false) {
+                self.push(',');
+            }
+            self.write_newline(stack.len());
+            match item {
+                Item::Element(value) => self.write_value(value, &mut stack),
+                Item::Member(key, value, padding) => {
+                    self.write_key(key);
+                    self.push_str(&" ".repeat(padding));
+                    self.push(':');
+                    if self.options.indent.is_some() {
+                        self.push(' ');
+                    }
+                    self.write_value(value, &mut stack);
+                }
+                Item::Row(row) => self.push_str(&row),
+            }
+        }
+        match out {
+            Some(out) => self.flush(out),
+            None => Ok(()),
+        }
+    }
+
+    fn flush(&mut self, out: &mut dyn std::fmt::Write) -> std::fmt::Result {
+        out.write_str(&self.text)?;
+        self.flushed += self.text.len();
+        self.text.clear();
+        Ok(())
+    }
+
+    /// Writes a scalar value, or opens a container and
+    /// pushes it onto the stack for its items to be written.
+    fn write_value<'v>(&mut self, value: &'v Value, stack: &mut Vec<Open<'v>>) {
+        if self.too_long {
+            return;
+        }
+        match value {
+            Value::String(string) => self.write_string(string),
+            Value::Number(number) => self.write_number(number),
+            Value::Boolean(bool) => self.push_str(if *bool { "true" } else { "false" }),
+            Value::Null if self.colored => self.push_str(&"null".dimmed().to_string()),
+            Value::Null => self.push_str("null"),
+            Value::Raw(raw) if self.canonical => self.write_canonical_raw(raw),
+            Value::Raw(raw) => self.push_str(raw),
+            Value::Ext(tag, value) => self.open_object(vec![(tag, value)], stack),
+            Value::Object(object) => self.open_object(object.iter().collect(), stack),
+            Value::Array(array) => {
+                let items = match self.columns(array) {
+                    Some(rows) => rows.into_iter().map(Item::Row).collect(),
+                    None => array.iter().map(Item::Element).collect(),
+                };
+                self.open('[', ']', items, stack);
+            }
+        }
+    }
+
+    /// Writes raw text in canonical form by parsing it and
+    /// writing the value it holds. Text that is not valid
+    /// JSON has no canonical form and is written as is.
+    fn write_canonical_raw(&mut self, raw: &str) {
+        let Ok(value) = parse(raw) else {
+            return self.push_str(raw);
+        };
+        let mut writer = Writer::new(self.options, true);
+        writer.limit = self.limit;
+        writer.write(&value);
+        self.too_long |= writer.too_long;
+        self.rejected = self.rejected.take().or(writer.rejected);
+        self.push_str(&writer.text);
+    }
+
+    fn open_object<'v>(
+        &mut self,
+        mut members: Vec<(&'v String, &'v Value)>,
+        stack: &mut Vec<Open<'v>>,
+    ) {
+        if self.canonical
+            || (self.options.key_order == KeyOrder::Sorted && !self.options.round_trip)
+        {
+            members.sort_by(|a, b| a.0.cmp(b.0));
+        }
+        let width = match self.aligned(self.options.align_colons) {
+            true => members.iter().map(|(key, _)| key.chars().count()).max(),
+            false => None,
+        };
+        let items = members
+            .into_iter()
+            .map(|(key, value)| {
+                let padding = width.map_or(0, |width| width - key.chars().count());
+                Item::Member(key, value, padding)
+            })
+            .collect();
+        self.open('{', '}', items, stack);
+    }
+
+    fn open<'v>(
+        &mut self,
+        open: char,
+        close: char,
+        items: Vec<Item<'v>>,
+        stack: &mut Vec<Open<'v>>,
+    ) {
+        self.push(open);
+        stack.push(Open {
+            items: items.into_iter(),
+            close,
+            is_empty: true,
+        });
+    }
+
+    /// Returns whether `len` more bytes of text fit within
+    /// the limit. If they do not, the text is marked as too
+    /// long and nothing more is written.
+    fn fits(&mut self, len: usize) -> bool {
+        if self
+            .limit
+            .is_some_and(|max| self.flushed + self.text.len() + len > max)
+        {
+            self.too_long = true;
+        }
+        !self.too_long
+    }
+
+    fn push(&mut self, char: char) {
+        if self.fits(char.len_utf8()) {
+            self.text.push(char);
+        }
+    }
+
+    fn push_str(&mut self, text: &str) {
+        if self.fits(text.len()) {
+            self.text.push_str(text);
+        }
+    }
+
+    /// Returns whether an alignment option applies, which
+    /// requires the text to be indented.
+    fn aligned(&self, option: bool) -> bool {
+        option && self.options.indent.is_some()
+    }
+
+    /// Writes the elements of an array of scalars as the
+    /// rows of a table, padding each element to the width of
+    /// its column. Returns `None` if columns are not aligned
+    /// or the array is not a table.
+    fn columns(&mut self, array: &[Value]) -> Option<Vec<String>> {
+        if !self.aligned(self.options.align_columns) || array.is_empty() {
+            return None;
+        }
+
+        let mut rows = vec![];
+        let mut widths: Vec<usize> = vec![];
+        for row in array {
+            let Value::Array(row) = row else {
+                return None;
+            };
+            let mut cells = vec![];
+            for (column, value) in row.iter().enumerate() {
+                if matches!(
+                    value,
+                    Value::Array(_) | Value::Object(_) | Value::Raw(_) | Value::Ext(..)
+                ) {
+                    return None;
+                }
+                let mut writer = Writer::new(self.options, self.canonical);
+                writer.limit = self.limit;
+                writer.write(value);
+                self.too_long |= writer.too_long;
+                self.rejected = self.rejected.take().or(writer.rejected);
+                match widths.get_mut(column) {
+                    Some(width) => *width = (*width).max(writer.text.chars().count()),
+                    None => widths.push(writer.text.chars().count()),
+                }
+                cells.push(writer.text);
+            }
+            rows.push(cells);
+        }
+        Some(rows.iter().map(|row| write_row(row, &widths)).collect())
+    }
+
+    fn write_newline(&mut self, depth: usize) {
+        if let Some(indent) = self
+            .options
+            .indent
+            .filter(|indent| self.fits(1 + indent * depth))
+        {
+            self.text.push('\n');
+            self.text.push_str(&" ".repeat(indent * depth));
+        }
+    }
+
+    fn write_string(&mut self, string: &str) {
+        match self.colored {
+            true => self.push_str(&quote(string).green().to_string()),
+            false => self.write_quoted(string),
+        }
+    }
+
+    fn write_key(&mut self, key: &str) {
+        match self.colored {
+            true => self.push_str(&quote(key).blue().to_string()),
+            false => self.write_quoted(key),
+        }
+    }
+
+    fn write_quoted(&mut self, string: &str) {
+        // The escaped string is at least as long as the
+        // string, and is only written if that much fits.
+        if self.fits(string.len() + 2) {
+            self.text.push('"');
+            escape_into(string, &mut self.text);
+            self.text.push('"');
+            self.fits(0);
+        }
+    }
+
+    fn write_number(&mut self, number: &Number) {
+        let lexeme = number
+            .lexeme()
+            .filter(|_| self.options.round_trip && !self.canonical);
+        if lexeme.is_none() && !number.as_f64().is_finite() {
+            // Infinities and NaN, such as the value of an
+            // overflowing literal like `1e400`, have no JSON
+            // text of their own.
+            self.rejected.get_or_insert_with(|| number.to_string());
+            return self.push_str("null");
+        }
+        if self.canonical {
+            return self.push_str(&number.to_canonical_string());
+        }
+
+        let text = match lexeme {
+            Some(lexeme) => lexeme.to_owned(),
+            None => number.format(self.options.number_format),
+        };
+        match number.policy(self.options) {
+            NumberPolicy::AsIs => self.push_str(&text),
+            NumberPolicy::Stringify => self.write_string(&text),
+            NumberPolicy::Error => {
+                self.rejected.get_or_insert_with(|| number.to_string());
+                self.push_str(&text);
+            }
+        }
+    }
+}
+
+/// Writes a row of a table on one line, padding each
+/// element to the width of its column.
+fn write_row(row: &[String], widths: &[usize]) -> String {
+    let mut text = String::from('[');
+    for (column, cell) in row.iter().enumerate() {
+        if column > 0 {
+            text.push_str(", ");
+        }
+        let padding = widths[column] - cell.chars().count();
+        text.push_str(&" ".repeat(padding));
+        text.push_str(cell);
+    }
+    text.push(']');
+    text
+}
+
+fn quote(string: &str) -> String {
+    let mut text = String::from('"');
+    escape_into(string, &mut text);
+    text.push('"');
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::parse;
+
+    use crate::json::{
+        etag, parse_shallow, parse_with, ErrorKind, ExtHandler, FormatOptions, HashAlgo, KeyOrder,
+        NumberFormat, NumberPolicy, ParseOptions, Value,
+    };
+
+    use super::{
+        format_number, to_canonical_string, to_string, to_string_pretty, to_string_with,
+        try_to_string_with, Writer,
+    };
+
+    #[test]
+    fn removes_whitespace() {
+        let value = parse(r#"[ 1, true, null, "a b" ]"#).unwrap();
+        assert_eq!(to_canonical_string(&value), r#"[1,true,null,"a b"]"#);
+    }
+
+    #[test]
+    fn sorts_object_keys() {
+        let value = parse(r#"{"b": 1, "a": {"d": 2, "c": 3}}"#).unwrap();
+        assert_eq!(to_canonical_string(&value), r#"{"a":{"c":3,"d":2},"b":1}"#);
+    }
+
+    #[test]
+    fn writes_negative_zero_as_zero() {
+        let value = parse("-0").unwrap();
+        assert_eq!(to_canonical_string(&value), "0");
+    }
+
+    #[test]
+    fn writes_numbers_in_shortest_form() {
+        let value = parse("[1.0, 1.5e2, -0.25]").unwrap();
+        assert_eq!(to_canonical_string(&value), "[1,150,-0.25]");
+    }
+
+    #[test]
+    fn writes_compact_text() {
+        let value = parse("[ 1, [ ], { }, { \"a\" : [ null ] } ]").unwrap();
+        assert_eq!(to_string(&value), r#"[1,[],{},{"a":[null]}]"#);
+    }
+
+    #[test]
+    fn decodes_and_escapes_strings() {
+        let value = parse(r#"{"a\tb": ["\n", "\u00e9\/", "\ud83d\ude00", "\u0001"]}"#).unwrap();
+        assert_eq!(
+            value,
+            Value::object()
+                .set(
+                    "a\tb",
+                    vec!["\n".into(), "é/".into(), "😀".into(), "\u{1}".into()]
+                )
+                .build()
+        );
+        assert_eq!(to_string(&value), r#"{"a\tb":["\n","é/","😀","\u0001"]}"#);
+        assert!(parse(r#""\ud800""#).is_err());
+    }
+
+    #[test]
+    fn writes_pretty_text() {
+        let value = parse(r#"{"a": [1, {}, []]}"#).unwrap();
+        assert_eq!(
+            to_string_pretty(&value),
+            "{\n  \"a\": [\n    1,\n    {},\n    []\n  ]\n}"
+        );
+    }
+
+    #[test]
+    fn writes_numbers_in_plain_notation_by_default() {
+        let value = parse("[1e21, 1.5e-7]").unwrap();
+        assert_eq!(to_string(&value), "[1000000000000000000000,0.00000015]");
+    }
+
+    #[test]
+    fn writes_numbers_in_plain_notation_up_to_digit_limit() {
+        let options = FormatOptions {
+            number_format: NumberFormat::PlainUpTo(6),
+            ..Default::default()
+        };
+        let value = parse("[123456, 1234567, 0.00001, 0.000001, -1.5e10]").unwrap();
+        assert_eq!(
+            to_string_with(&value, &options),
+            "[123456,1.234567e6,0.00001,1e-6,-1.5e10]"
+        );
+    }
+
+    #[test]
+    fn writes_numbers_in_scientific_notation_above_threshold() {
+        let options = FormatOptions {
+            number_format: NumberFormat::ScientificAbove(1e6),
+            ..Default::default()
+        };
+        let value = parse("[999999, 1000000, -2500000, 0.0000001, 0]").unwrap();
+        assert_eq!(
+            to_string_with(&value, &options),
+            "[999999,1e6,-2.5e6,0.0000001,0]"
+        );
+    }
+
+    #[test]
+    fn keeps_insertion_order_from_parse_to_output() {
+        let mut value = parse(r#"{"z": 1, "a": 2, "m": {"y": 3, "b": 4}}"#).unwrap();
+        if let Value::Object(object) = &mut value {
+            object.insert("c", Value::Null);
+        }
+        assert_eq!(
+            to_string(&value),
+            r#"{"z":1,"a":2,"m":{"y":3,"b":4},"c":null}"#
+        );
+    }
+
+    #[test]
+    fn sorts_keys_when_requested() {
+        let value = parse(r#"{"z": 1, "a": {"y": 2, "b": 3}}"#).unwrap();
+        let options = FormatOptions {
+            key_order: KeyOrder::Sorted,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            r#"{"a":{"b":3,"y":2},"z":1}"#
+        );
+
+        let options = ParseOptions {
+            key_order: KeyOrder::Sorted,
+            ..Default::default()
+        };
+        let value = parse_with(r#"{"z": 1, "a": [{"y": 2, "b": 3}]}"#, &options).unwrap();
+        assert_eq!(to_string(&value), r#"{"a":[{"b":3,"y":2}],"z":1}"#);
+    }
+
+    #[test]
+    fn aligns_colons_when_requested() {
+        let value = parse(r#"{"a": 1, "long": {"bb": 2, "c": 3}}"#).unwrap();
+        let options = FormatOptions {
+            indent: Some(2),
+            align_colons: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            "{\n  \"a\"   : 1,\n  \"long\": {\n    \"bb\": 2,\n    \"c\" : 3\n  }\n}"
+        );
+        assert_eq!(
+            to_string_with(
+                &value,
+                &FormatOptions {
+                    indent: None,
+                    ..options
+                }
+            ),
+            r#"{"a":1,"long":{"bb":2,"c":3}}"#
+        );
+    }
+
+    #[test]
+    fn aligns_columns_when_requested() {
+        let options = FormatOptions {
+            indent: Some(2),
+            align_columns: true,
+            ..Default::default()
+        };
+        let value = parse(r#"{"m": [[1, -20, 3], [400, 5], []]}"#).unwrap();
+        assert_eq!(
+            to_string_with(&value, &options),
+            "{\n  \"m\": [\n    [  1, -20, 3],\n    [400,   5],\n    []\n  ]\n}"
+        );
+
+        let value = parse(r#"[[1], [[2]]]"#).unwrap();
+        assert_eq!(
+            to_string_with(&value, &options),
+            "[\n  [\n    1\n  ],\n  [\n    [2]\n  ]\n]"
+        );
+
+        let tagged = Value::Ext("$n".into(), Box::new(Value::from(2)));
+        let value = Value::Array(vec![Value::Array(vec![tagged])]);
+        assert_eq!(
+            to_string_with(&value, &options),
+            "[\n  [\n    {\n      \"$n\": 2\n    }\n  ]\n]"
+        );
+    }
+
+    #[test]
+    fn applies_number_policies() {
+        let value = parse("[-0, 9007199254740993, 1]").unwrap();
+        assert_eq!(to_string(&value), "[-0,9007199254740992,1]");
+
+        let options = FormatOptions {
+            negative_zero: NumberPolicy::Stringify,
+            beyond_safe_integers: NumberPolicy::Stringify,
+            subnormals: NumberPolicy::Stringify,
+            number_format: NumberFormat::PlainUpTo(16),
+            ..Default::default()
+        };
+        let value = parse("[-0, 9007199254740993, 1e-320, 1]").unwrap();
+        assert_eq!(
+            try_to_string_with(&value, &options).unwrap(),
+            r#"["-0","9007199254740992","1e-320",1]"#
+        );
+
+        let options = FormatOptions {
+            beyond_safe_integers: NumberPolicy::Error,
+            ..Default::default()
+        };
+        let exact = ParseOptions {
+            exact_integers: true,
+            ..Default::default()
+        };
+        let value = parse_with("[9007199254740991, -9007199254740992]", &exact).unwrap();
+        let error = try_to_string_with(&value, &options).unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::RejectedNumber("-9007199254740992".into())
+        );
+        assert_eq!(
+            to_string_with(&value, &options),
+            "[9007199254740991,-9007199254740992]"
+        );
+        assert_eq!(
+            to_canonical_string(&value),
+            "[9007199254740991,-9007199254740992]"
+        );
+    }
+
+    #[test]
+    fn reproduces_text_in_round_trip_mode() {
+        let parse_options = ParseOptions {
+            round_trip: true,
+            ..Default::default()
+        };
+        let text = r#"{"b":[1.50,1E+2,-0.0,12345678901234567890],"a":"é\""}"#;
+        let value = parse_with(text, &parse_options).unwrap();
+        let options = FormatOptions {
+            key_order: KeyOrder::Sorted,
+            round_trip: true,
+            ..Default::default()
+        };
+        assert_eq!(to_string_with(&value, &options), text);
+        assert_eq!(
+            to_string(&value),
+            r#"{"b":[1.5,100,-0,12345678901234567000],"a":"é\""}"#
+        );
+
+        let text = "{\n  \"a\": [\n    1.0,\n    2e3\n  ]\n}";
+        let value = parse_with(text, &parse_options).unwrap();
+        let options = FormatOptions {
+            indent: Some(2),
+            round_trip: true,
+            ..Default::default()
+        };
+        assert_eq!(to_string_with(&value, &options), text);
+        assert_eq!(value, parse("{\"a\": [1, 2000]}").unwrap());
+    }
+
+    #[test]
+    fn writes_strings_with_writer_escapes_in_round_trip_mode() {
+        let parse_options = ParseOptions {
+            round_trip: true,
+            ..Default::default()
+        };
+        let options = FormatOptions {
+            round_trip: true,
+            ..Default::default()
+        };
+        let write = |text| to_string_with(&parse_with(text, &parse_options).unwrap(), &options);
+        assert_eq!(write(r#"["a/b","\n",1.0]"#), r#"["a/b","\n",1.0]"#);
+        assert_eq!(write(r#"["a\/b","\u000a"]"#), r#"["a/b","\n"]"#);
+        assert_eq!(write(r#"{"\u0061\/":"\/"}"#), r#"{"a/":"/"}"#);
+        assert_eq!(write(r#"{"a":1,"a":2}"#), r#"{"a":2}"#);
+    }
+
+    #[test]
+    fn stops_at_maximum_output_length() {
+        let options = |max| FormatOptions {
+            max_output_bytes: Some(max),
+            ..Default::default()
+        };
+        let value = parse(r#"{"a": [1, 2]}"#).unwrap();
+        assert_eq!(
+            try_to_string_with(&value, &options(13)).unwrap(),
+            r#"{"a":[1,2]}"#
+        );
+        let error = try_to_string_with(&value, &options(10)).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::OutputTooLarge { max: 10 });
+
+        let value = Value::Array(vec![Value::String("x".repeat(1000)); 1000]);
+        let error = try_to_string_with(&value, &options(4096)).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::OutputTooLarge { max: 4096 });
+        assert_eq!(to_string_with(&value, &options(4096)).len(), 1_003_001);
+    }
+
+    #[test]
+    fn checks_maximum_output_length_before_writing() {
+        let options = FormatOptions::default();
+        let value = parse(r#"{"a": [1, 2]}"#).unwrap();
+        let mut writer = Writer::new(&options, false);
+        writer.limit = Some(11);
+        writer.write(&value);
+        assert_eq!(
+            (writer.text.as_str(), writer.too_long),
+            (r#"{"a":[1,2]}"#, false)
+        );
+
+        let huge = "x".repeat(1_000_000);
+        for value in [
+            Value::String(huge.as_str().into()),
+            Value::Raw(huge.clone()),
+        ] {
+            let mut writer = Writer::new(&options, false);
+            writer.limit = Some(100);
+            writer.write(&Value::Array(vec![Value::Null, value]));
+            assert_eq!((writer.text.as_str(), writer.too_long), ("[null,", true));
+        }
+    }
+
+    #[test]
+    fn formats_single_numbers() {
+        let Value::Number(number) = &parse("-0.0").unwrap() else {
+            unreachable!()
+        };
+        let options = FormatOptions {
+            negative_zero: NumberPolicy::Stringify,
+            ..Default::default()
+        };
+        assert_eq!(format_number(number, &options).unwrap(), r#""-0""#);
+
+        let options = FormatOptions {
+            negative_zero: NumberPolicy::Error,
+            ..Default::default()
+        };
+        let error = format_number(number, &options).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::RejectedNumber("-0".to_owned()));
+    }
+
+    #[test]
+    fn round_trips_tagged_values() {
+        let options = ParseOptions {
+            ext_handler: Some(ExtHandler::tags(["$date"])),
+            ..Default::default()
+        };
+        let text = r#"[{"$date":"2024-01-01"},{"$date":1,"x":2},{"$oid":"5f"}]"#;
+        let value = parse_with(text, &options).unwrap();
+        let Value::Array(elements) = &value else {
+            unreachable!()
+        };
+        assert_eq!(
+            elements[0],
+            Value::Ext("$date".into(), Box::new(Value::from("2024-01-01")))
+        );
+        assert!(matches!(elements[1], Value::Object(_)));
+        assert!(matches!(elements[2], Value::Object(_)));
+        assert_eq!(to_string(&value), text);
+        assert_eq!(to_string(&parse(text).unwrap()), text);
+
+        let pretty = to_string_pretty(&elements[0]);
+        assert_eq!(pretty, "{\n  \"$date\": \"2024-01-01\"\n}");
+        assert_eq!(
+            value.to_events().count(),
+            parse(text).unwrap().to_events().count()
+        );
+
+        assert_eq!(value.pointer("/0/$date"), Some(&Value::from("2024-01-01")));
+        assert_eq!((&elements[0]).into_iter().count(), 1);
+        assert_eq!(elements[0].entries().count(), 1);
+    }
+
+    #[test]
+    fn rejects_non_finite_numbers() {
+        let value = parse("[1e400, -1e400]").unwrap();
+        assert_eq!(to_string(&value), "[null,null]");
+        assert_eq!(to_canonical_string(&value), "[null,null]");
+        let error = try_to_string_with(&value, &FormatOptions::default()).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::RejectedNumber("inf".to_owned()));
+
+        let options = ParseOptions {
+            round_trip: true,
+            ..Default::default()
+        };
+        let value = parse_with("[1e400, -1e400]", &options).unwrap();
+        let options = FormatOptions {
+            round_trip: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            try_to_string_with(&value, &options).unwrap(),
+            "[1e400,-1e400]"
+        );
+        assert!(parse(&to_string(&value)).is_ok());
+    }
+
+    #[test]
+    fn canonicalizes_raw_text() {
+        let text = r#"{"b": [1.50, {"d": -0.0, "c": 2}], "a": "\u0041"}"#;
+        let full = parse(text).unwrap();
+        for depth in 0..3 {
+            let shallow = parse_shallow(text, depth).unwrap();
+            assert_eq!(to_canonical_string(&shallow), to_canonical_string(&full));
+            assert_eq!(
+                shallow.structural_hash(HashAlgo::Sha256),
+                full.structural_hash(HashAlgo::Sha256)
+            );
+            assert_eq!(etag(&shallow), etag(&full));
+        }
+        assert_eq!(to_string(&parse_shallow(text, 0).unwrap()), text);
+        assert_eq!(to_canonical_string(&Value::Raw("[1,".into())), "[1,");
+    }
+}
@@ -0,0 +1,92 @@
+use super::{
+    document::Document,
+    parse,
+    types::{Result, Value},
+};
+
+/// Parses a JSON string, converting only the top
+/// `max_depth` levels of the tree into values. Arrays and
+/// objects nested any deeper are kept as [`Value::Raw`]
+/// slices of the text, which can be parsed on demand with
+/// [`Value::materialize`]. This is useful for reading the
+/// envelope fields of a large message without building the
+/// rest of it. The whole text is still validated, so it
+/// will return an error under the same conditions as
+/// `parse`.
+pub fn parse_shallow(text: &str, max_depth: usize) -> Result<Value> {
+    Ok(Document::parse(text)?.to_shallow_value(max_depth))
+}
+
+impl Value {
+    /// Parses every [`Value::Raw`] within the value in
+    /// place, so that the value holds no unparsed text. It
+    /// will return an error if any raw text is not valid
+    /// JSON, in which case the value may be partially
+    /// materialized.
+    pub fn materialize(&mut self) -> Result<()> {
+        match self {
+            Value::Raw(raw) => *self = parse(raw)?,
+            Value::Array(array) => {
+                for element in array {
+                    element.materialize()?;
+                }
+            }
+            Value::Object(object) => {
+                for value in object.values_mut() {
+                    value.materialize()?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, to_string, ErrorKind, Value};
+
+    use super::parse_shallow;
+
+    #[test]
+    fn keeps_deep_containers_as_text() {
+        let text = r#"{"type": "event", "body": {"items": [1, 2]}, "tags": [ "a" ]}"#;
+        let value = parse_shallow(text, 1).unwrap();
+        let Value::Object(object) = &value else {
+            unreachable!()
+        };
+        assert_eq!(object.get("type").unwrap(), &Value::String("event".into()));
+        assert_eq!(
+            object.get("body").unwrap(),
+            &Value::Raw(r#"{"items": [1, 2]}"#.into())
+        );
+        assert_eq!(
+            object.get("tags").unwrap(),
+            &Value::Raw(r#"[ "a" ]"#.into())
+        );
+        assert_eq!(
+            to_string(&value),
+            r#"{"type":"event","body":{"items": [1, 2]},"tags":[ "a" ]}"#
+        );
+
+        assert_eq!(parse_shallow(text, 0).unwrap(), Value::Raw(text.into()));
+        assert_eq!(parse_shallow(text, 3).unwrap(), parse(text).unwrap());
+        assert_eq!(parse_shallow("[]", 0).unwrap(), Value::Raw("[]".into()));
+        assert_eq!(parse_shallow("1", 0).unwrap(), parse("1").unwrap());
+    }
+
+    #[test]
+    fn materializes_raw_values() {
+        let text = r#"[{"a": [true]}, [[null]]]"#;
+        let mut value = parse_shallow(text, 2).unwrap();
+        assert_ne!(value, parse(text).unwrap());
+        value.materialize().unwrap();
+        assert_eq!(value, parse(text).unwrap());
+    }
+
+    #[test]
+    fn validates_whole_text() {
+        let error = parse_shallow(r#"{"a": [1 }"#, 1).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::Syntax);
+    }
+}
@@ -0,0 +1,67 @@
+use super::types::{Result, Value};
+
+/// A saved state of a value, taken by [`Value::snapshot`]
+/// and restored by [`Value::restore`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot(Value);
+
+impl Value {
+    /// Saves the current state of the value, so that a
+    /// batch of edits can later be undone by restoring it.
+    /// Values do not share their contents, so the snapshot
+    /// is a full copy of the value.
+    pub fn snapshot(&self) -> Snapshot {
+        Snapshot(self.clone())
+    }
+
+    /// Returns the value to a state saved by `snapshot`,
+    /// discarding any edits made since.
+    pub fn restore(&mut self, snapshot: Snapshot) {
+        *self = snapshot.0;
+    }
+
+    /// Applies a batch of edits atomically. If the edits
+    /// return an error, the value is restored to its state
+    /// before the batch, so that no step of a failed batch
+    /// takes effect, and the error is returned.
+    pub fn transaction<T>(&mut self, edit: impl FnOnce(&mut Value) -> Result<T>) -> Result<T> {
+        let snapshot = self.snapshot();
+        edit(self).inspect_err(|_| self.restore(snapshot))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind, Value};
+
+    #[test]
+    fn restores_snapshots() {
+        let mut value = parse(r#"{"a": [1, 2]}"#).unwrap();
+        let snapshot = value.snapshot();
+        *value.pointer_mut("/a/0").unwrap() = Value::Null;
+        value.pointer_or_insert("/b", Value::TRUE).unwrap();
+        assert_eq!(value, parse(r#"{"a": [null, 2], "b": true}"#).unwrap());
+
+        value.restore(snapshot);
+        assert_eq!(value, parse(r#"{"a": [1, 2]}"#).unwrap());
+    }
+
+    #[test]
+    fn rolls_back_failed_transactions() {
+        let mut value = parse(r#"{"a": 1}"#).unwrap();
+        let error = value
+            .transaction(|value| {
+                value.pointer_or_insert("/b", Value::TRUE)?;
+                value.pointer_or_insert("/a/x", Value::Null)?;
+                Ok(())
+            })
+            .unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::UnexpectedType(_)));
+        assert_eq!(value, parse(r#"{"a": 1}"#).unwrap());
+
+        value
+            .transaction(|value| value.pointer_or_insert("/b", Value::TRUE).map(drop))
+            .unwrap();
+        assert_eq!(value, parse(r#"{"a": 1, "b": true}"#).unwrap());
+    }
+}
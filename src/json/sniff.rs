@@ -0,0 +1,191 @@
+/// The number of bytes that [`sniff`] inspects at most.
+const SNIFF_LIMIT: usize = 1024;
+
+/// What [`sniff`] found out about some bytes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Sniff {
+    /// Whether the bytes begin like a JSON text, that is,
+    /// with optional whitespace followed by the start of a
+    /// value. The rest of the bytes are not checked.
+    pub looks_like_json: bool,
+    /// The encoding of the bytes, as given by a byte order
+    /// mark or guessed from the pattern of zero bytes.
+    pub encoding: Encoding,
+    /// Whether the bytes begin with a byte order mark.
+    pub has_bom: bool,
+    /// The kind of the first value, if the bytes look like
+    /// JSON.
+    pub first_value_kind: Option<ValueKind>,
+}
+
+/// An enumeration of the encodings of JSON text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Encoding {
+    Utf8,
+    Utf16Le,
+    Utf16Be,
+    Utf32Le,
+    Utf32Be,
+}
+
+/// An enumeration of the kinds of JSON values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueKind {
+    Object,
+    Array,
+    String,
+    Number,
+    Boolean,
+    Null,
+}
+
+/// Cheaply inspects the first bytes of an input, such as
+/// one of many inputs of mixed formats, to tell whether it
+/// is likely to be JSON. The encoding is detected from a
+/// byte order mark, or else from the zero bytes among the
+/// first four, which JSON text always allows since it
+/// begins with ASCII characters (RFC 4627). Then leading
+/// whitespace is skipped and the first character is used
+/// to tell the kind of the first value. Only the first
+/// kilobyte is inspected, and nothing is parsed, so an
+/// input that looks like JSON may still be invalid.
+pub fn sniff(bytes: &[u8]) -> Sniff {
+    let bytes = &bytes[..bytes.len().min(SNIFF_LIMIT)];
+    let (encoding, bom) = detect_encoding(bytes);
+    let has_bom = bom > 0;
+
+    let width = match encoding {
+        Encoding::Utf8 => 1,
+        Encoding::Utf16Le | Encoding::Utf16Be => 2,
+        Encoding::Utf32Le | Encoding::Utf32Be => 4,
+    };
+    let chars: Vec<char> = bytes[bom..]
+        .chunks_exact(width)
+        .map(|unit| {
+            let code = match encoding {
+                Encoding::Utf8 => unit[0] as u32,
+                Encoding::Utf16Le => u16::from_le_bytes([unit[0], unit[1]]) as u32,
+                Encoding::Utf16Be => u16::from_be_bytes([unit[0], unit[1]]) as u32,
+                Encoding::Utf32Le => u32::from_le_bytes([unit[0], unit[1], unit[2], unit[3]]),
+                Encoding::Utf32Be => u32::from_be_bytes([unit[0], unit[1], unit[2], unit[3]]),
+            };
+            // Only ASCII characters matter for sniffing, so
+            // anything else is replaced.
+            char::from_u32(code)
+                .filter(char::is_ascii)
+                .unwrap_or(char::REPLACEMENT_CHARACTER)
+        })
+        .skip_while(|char| matches!(char, ' ' | '\t' | '\n' | '\r'))
+        .take(5)
+        .collect();
+
+    let first_value_kind = first_value_kind(&chars);
+    Sniff {
+        looks_like_json: first_value_kind.is_some(),
+        encoding,
+        has_bom,
+        first_value_kind,
+    }
+}
+
+/// Returns the encoding of the bytes and the length of
+/// their byte order mark, which is zero if there is none.
+fn detect_encoding(bytes: &[u8]) -> (Encoding, usize) {
+    match bytes {
+        [0xef, 0xbb, 0xbf, ..] => (Encoding::Utf8, 3),
+        [0xff, 0xfe, 0, 0, ..] => (Encoding::Utf32Le, 4),
+        [0, 0, 0xfe, 0xff, ..] => (Encoding::Utf32Be, 4),
+        [0xff, 0xfe, ..] => (Encoding::Utf16Le, 2),
+        [0xfe, 0xff, ..] => (Encoding::Utf16Be, 2),
+        [0, 0, 0, _, ..] => (Encoding::Utf32Be, 0),
+        [_, 0, 0, 0, ..] => (Encoding::Utf32Le, 0),
+        [0, _, ..] => (Encoding::Utf16Be, 0),
+        [_, 0, ..] => (Encoding::Utf16Le, 0),
+        _ => (Encoding::Utf8, 0),
+    }
+}
+
+/// Returns the kind of the value that the characters
+/// begin, if they begin a value. Literals must match as
+/// far as the characters go.
+fn first_value_kind(chars: &[char]) -> Option<ValueKind> {
+    let matches = |literal: &str| {
+        literal
+            .chars()
+            .zip(chars)
+            .all(|(expected, &char)| expected == char)
+    };
+    match chars.first()? {
+        '{' => Some(ValueKind::Object),
+        '[' => Some(ValueKind::Array),
+        '"' => Some(ValueKind::String),
+        '-' | '0'..='9' => Some(ValueKind::Number),
+        't' if matches("true") => Some(ValueKind::Boolean),
+        'f' if matches("false") => Some(ValueKind::Boolean),
+        'n' if matches("null") => Some(ValueKind::Null),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{sniff, Encoding, Sniff, ValueKind};
+
+    fn utf16(text: &str, big_endian: bool) -> Vec<u8> {
+        text.encode_utf16()
+            .flat_map(|unit| match big_endian {
+                true => unit.to_be_bytes(),
+                false => unit.to_le_bytes(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn detects_kind_of_first_value() {
+        let cases = [
+            ("  {\"a\": 1}", Some(ValueKind::Object)),
+            ("\n[", Some(ValueKind::Array)),
+            ("\"x", Some(ValueKind::String)),
+            ("-1", Some(ValueKind::Number)),
+            ("fals", Some(ValueKind::Boolean)),
+            ("null", Some(ValueKind::Null)),
+            ("nope", None),
+            ("<html>", None),
+            ("   ", None),
+        ];
+        for (text, kind) in cases {
+            let expected = Sniff {
+                looks_like_json: kind.is_some(),
+                encoding: Encoding::Utf8,
+                has_bom: false,
+                first_value_kind: kind,
+            };
+            assert_eq!(sniff(text.as_bytes()), expected, "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn detects_encoding() {
+        let sniffed = sniff(b"\xef\xbb\xbf[1]");
+        assert_eq!((sniffed.encoding, sniffed.has_bom), (Encoding::Utf8, true));
+
+        let sniffed = sniff(&utf16(" {}", true));
+        assert_eq!(sniffed.encoding, Encoding::Utf16Be);
+        assert_eq!(sniffed.first_value_kind, Some(ValueKind::Object));
+
+        let sniffed = sniff(&utf16("\u{feff}1", false));
+        assert_eq!(
+            (sniffed.encoding, sniffed.has_bom),
+            (Encoding::Utf16Le, true)
+        );
+        assert_eq!(sniffed.first_value_kind, Some(ValueKind::Number));
+
+        let bytes: Vec<u8> = "[]"
+            .chars()
+            .flat_map(|c| (c as u32).to_le_bytes())
+            .collect();
+        let sniffed = sniff(&bytes);
+        assert_eq!(sniffed.encoding, Encoding::Utf32Le);
+        assert_eq!(sniffed.first_value_kind, Some(ValueKind::Array));
+    }
+}
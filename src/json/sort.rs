@@ -0,0 +1,198 @@
+use std::cmp::Ordering;
+
+use super::{
+    pointer::JsonPointer,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// The direction in which values are sorted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Order {
+    Asc,
+    Desc,
+}
+
+impl Value {
+    /// Sorts the members of every object in the value by
+    /// key, recursively.
+    pub fn sort_keys(&mut self) {
+        match self {
+            Value::Object(object) => {
+                object.sort_keys();
+                object.values_mut().for_each(Value::sort_keys);
+            }
+            Value::Array(array) => array.iter_mut().for_each(Value::sort_keys),
+            Value::Ext(_, value) => value.sort_keys(),
+            _ => {}
+        }
+    }
+
+    /// Compares two values under a total order, so that
+    /// values of any type can be sorted together. Values of
+    /// different types are ordered null, booleans, numbers,
+    /// strings, arrays, objects, then tagged values. Arrays
+    /// are compared element by element, objects are compared
+    /// member by member in ascending key order, and tagged
+    /// values are compared by tag, then by value.
+    pub fn total_cmp(&self, other: &Value) -> Ordering {
+        match (self, other) {
+            (Value::Boolean(a), Value::Boolean(b)) => a.cmp(b),
+            (Value::Number(a), Value::Number(b)) => a.total_cmp(b),
+            (Value::String(a), Value::String(b)) => a.cmp(b),
+            (Value::Array(a), Value::Array(b)) => a
+                .iter()
+                .zip(b)
+                .map(|(a, b)| a.total_cmp(b))
+                .find(|ordering| ordering.is_ne())
+                .unwrap_or_else(|| a.len().cmp(&b.len())),
+            (Value::Object(a), Value::Object(b)) => {
+                let mut a: Vec<_> = a.iter().collect();
+                let mut b: Vec<_> = b.iter().collect();
+                a.sort_by(|x, y| x.0.cmp(y.0));
+                b.sort_by(|x, y| x.0.cmp(y.0));
+                a.iter()
+                    .zip(&b)
+                    .map(|(a, b)| a.0.cmp(b.0).then_with(|| a.1.total_cmp(b.1)))
+                    .find(|ordering| ordering.is_ne())
+                    .unwrap_or_else(|| a.len().cmp(&b.len()))
+            }
+            (Value::Ext(a, x), Value::Ext(b, y)) => a.cmp(b).then_with(|| x.total_cmp(y)),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+
+    /// Sorts the array found at the `array` pointer by the
+    /// value found at the `key` pointer within each of its
+    /// elements, as ordered by [`Value::total_cmp`].
+    /// Elements that are missing the key are placed last,
+    /// whatever the order. The sort is stable. It will
+    /// return an error under the following conditions:
+    ///
+    /// * Either pointer is invalid.
+    /// * The `array` pointer does not refer to an array.
+    pub fn sort_array_by_path(&mut self, array: &str, key: &str, order: Order) -> Result<()> {
+        let key = JsonPointer::parse(key)?;
+        self.sort_array_by(array, |a, b| match (key.resolve(a), key.resolve(b)) {
+            (Some(a), Some(b)) => match order {
+                Order::Asc => a.total_cmp(b),
+                Order::Desc => b.total_cmp(a),
+            },
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        })
+    }
+
+    /// Sorts the array found at the `array` pointer with a
+    /// comparator function. The sort is stable. It will
+    /// return an error under the following conditions:
+    ///
+    /// * The pointer is invalid.
+    /// * The pointer does not refer to an array.
+    pub fn sort_array_by<F>(&mut self, array: &str, compare: F) -> Result<()>
+    where
+        F: FnMut(&Value, &Value) -> Ordering,
+    {
+        match JsonPointer::parse(array)?.resolve_mut(self) {
+            Some(Value::Array(array)) => {
+                array.sort_by(compare);
+                Ok(())
+            }
+            _ => Err(Error::new(ErrorKind::UnexpectedType("array"))),
+        }
+    }
+}
+
+fn rank(value: &Value) -> u8 {
+    match value {
+        Value::Null => 0,
+        Value::Boolean(_) => 1,
+        Value::Number(_) => 2,
+        Value::String(_) => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+        Value::Raw(raw) if raw.starts_with('[') => 4,
+        Value::Raw(_) => 5,
+        Value::Ext(..) => 6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cmp::Ordering;
+
+    use crate::json::{parse, Value};
+
+    use super::Order;
+
+    #[test]
+    fn orders_values_of_different_types() {
+        let Value::Array(values) = parse(r#"[null, false, 0, "", [], {}]"#).unwrap() else {
+            unreachable!()
+        };
+        for pair in values.windows(2) {
+            assert_eq!(pair[0].total_cmp(&pair[1]), Ordering::Less);
+        }
+    }
+
+    #[test]
+    fn orders_arrays_element_by_element() {
+        let a = parse("[1, 2]").unwrap();
+        let b = parse("[1, 3]").unwrap();
+        let c = parse("[1, 2, 0]").unwrap();
+        assert_eq!(a.total_cmp(&b), Ordering::Less);
+        assert_eq!(a.total_cmp(&c), Ordering::Less);
+    }
+
+    #[test]
+    fn orders_objects_independently_of_member_order() {
+        let a = parse(r#"{"a": 1, "b": 2}"#).unwrap();
+        let b = parse(r#"{"b": 2, "a": 1}"#).unwrap();
+        assert_eq!(a.total_cmp(&b), Ordering::Equal);
+    }
+
+    #[test]
+    fn sorts_by_key_ascending() {
+        let mut value = parse(r#"{"items": [{"price": 3}, {"price": 1}, {"price": 2}]}"#).unwrap();
+        value
+            .sort_array_by_path("/items", "/price", Order::Asc)
+            .unwrap();
+        let expected = parse(r#"{"items": [{"price": 1}, {"price": 2}, {"price": 3}]}"#).unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn sorts_by_key_descending() {
+        let mut value = parse(r#"[{"p": {"q": 1}}, {"p": {"q": 3}}, {"p": {"q": 2}}]"#).unwrap();
+        value.sort_array_by_path("", "/p/q", Order::Desc).unwrap();
+        let expected = parse(r#"[{"p": {"q": 3}}, {"p": {"q": 2}}, {"p": {"q": 1}}]"#).unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn places_missing_keys_last() {
+        let mut value = parse(r#"[{}, {"a": 2}, {"b": 0}, {"a": 1}]"#).unwrap();
+        value.sort_array_by_path("", "/a", Order::Desc).unwrap();
+        let expected = parse(r#"[{"a": 2}, {"a": 1}, {}, {"b": 0}]"#).unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn sorts_with_comparator() {
+        let mut value = parse(r#"["ccc", "a", "bb"]"#).unwrap();
+        value.sort_array_by("", |a, b| b.total_cmp(a)).unwrap();
+        let expected = parse(r#"["ccc", "bb", "a"]"#).unwrap();
+        assert_eq!(value, expected);
+    }
+
+    #[test]
+    fn rejects_non_array_target() {
+        let mut value = parse(r#"{"items": {}}"#).unwrap();
+        assert!(value
+            .sort_array_by_path("/items", "/a", Order::Asc)
+            .is_err());
+        assert!(value
+            .sort_array_by_path("/missing", "/a", Order::Asc)
+            .is_err());
+    }
+}
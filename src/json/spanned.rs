@@ -0,0 +1,165 @@
+use super::{
+    map::Map,
+    pointer::{parse_index, JsonPointer},
+    types::{Span, Value},
+};
+
+/// A JSON value that records the span of source text it
+/// was parsed from, as do all of its nested values and
+/// object keys. This allows semantic errors found after
+/// parsing, such as a member with the wrong type, to be
+/// reported at a precise location.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SpannedValue {
+    span: Span,
+    node: Node,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Scalar(Value),
+    Array(Vec<SpannedValue>),
+    Object(Vec<Member>),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct Member {
+    key: String,
+    key_span: Span,
+    value: SpannedValue,
+}
+
+impl SpannedValue {
+    pub(super) fn scalar(value: Value, span: Span) -> Self {
+        Self {
+            span,
+            node: Node::Scalar(value),
+        }
+    }
+
+    pub(super) fn array(elements: Vec<SpannedValue>, span: Span) -> Self {
+        Self {
+            span,
+            node: Node::Array(elements),
+        }
+    }
+
+    pub(super) fn object(members: Vec<(String, Span, SpannedValue)>, span: Span) -> Self {
+        let members = members
+            .into_iter()
+            .map(|(key, key_span, value)| Member {
+                key,
+                key_span,
+                value,
+            })
+            .collect();
+        Self {
+            span,
+            node: Node::Object(members),
+        }
+    }
+
+    /// Returns the span of source text taken up by the
+    /// value.
+    pub fn span(&self) -> Span {
+        self.span
+    }
+
+    /// Returns the member of an object with the given key.
+    /// If the key appears more than once, the last member
+    /// is returned, matching the behavior of `parse`.
+    pub fn get(&self, key: &str) -> Option<&SpannedValue> {
+        self.member(key).map(|member| &member.value)
+    }
+
+    /// Returns the span of the key of the member of an
+    /// object with the given key, including its quotes.
+    pub fn key_span(&self, key: &str) -> Option<Span> {
+        self.member(key).map(|member| member.key_span)
+    }
+
+    /// Returns the element of an array at the given index.
+    pub fn get_index(&self, index: usize) -> Option<&SpannedValue> {
+        match &self.node {
+            Node::Array(elements) => elements.get(index),
+            _ => None,
+        }
+    }
+
+    /// Looks up a value by the text form of a JSON pointer.
+    /// Returns `None` if the pointer is invalid or refers to
+    /// a missing value.
+    pub fn pointer(&self, pointer: &str) -> Option<&SpannedValue> {
+        JsonPointer::parse(pointer).ok()?.tokens().iter().try_fold(
+            self,
+            |value, token| match &value.node {
+                Node::Object(_) => value.get(token),
+                Node::Array(_) => value.get_index(parse_index(token)?),
+                Node::Scalar(_) => None,
+            },
+        )
+    }
+
+    /// Converts the value into a plain value, without spans.
+    pub fn to_value(&self) -> Value {
+        match &self.node {
+            Node::Scalar(value) => value.clone(),
+            Node::Array(elements) => {
+                Value::Array(elements.iter().map(SpannedValue::to_value).collect())
+            }
+            Node::Object(members) => Value::Object(
+                members
+                    .iter()
+                    .map(|member| (member.key.clone(), member.value.to_value()))
+                    .collect::<Map>(),
+            ),
+        }
+    }
+
+    fn member(&self, key: &str) -> Option<&Member> {
+        match &self.node {
+            Node::Object(members) => members.iter().rev().find(|member| member.key == key),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, parse_spanned, Span, Value};
+
+    const TEXT: &str = "{\n  \"name\": \"a\",\n  \"ports\": [80, 443]\n}";
+
+    #[test]
+    fn records_spans_of_all_values() {
+        let value = parse_spanned(TEXT).unwrap();
+        assert_eq!(value.span(), Span { start: 0, end: 39 });
+        assert_eq!(
+            value.get("name").unwrap().span(),
+            Span { start: 12, end: 15 }
+        );
+        assert_eq!(value.key_span("ports"), Some(Span { start: 19, end: 26 }));
+        assert_eq!(
+            value.pointer("/ports/1").unwrap().span(),
+            Span { start: 33, end: 36 }
+        );
+    }
+
+    #[test]
+    fn converts_to_plain_values() {
+        let value = parse_spanned(TEXT).unwrap();
+        assert_eq!(value.to_value(), parse(TEXT).unwrap());
+        assert_eq!(
+            value.pointer("/ports/0").unwrap().to_value(),
+            Value::Number(80.into())
+        );
+    }
+
+    #[test]
+    fn returns_none_for_missing_values() {
+        let value = parse_spanned(TEXT).unwrap();
+        assert!(value.pointer("/ports/2").is_none());
+        assert!(value.pointer("/name/0").is_none());
+        assert!(value.get_index(0).is_none());
+    }
+}
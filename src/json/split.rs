@@ -0,0 +1,149 @@
+use std::io::{self, Read, Write};
+
+use super::{
+    events::{Event, EventReader, EventWriter},
+    types::{Error, ErrorKind, Result},
+};
+
+/// Streams a JSON text whose top-level value is an array
+/// into several outputs, each holding a JSON array of up to
+/// `chunk_size` consecutive elements. Outputs are created on
+/// demand by calling `create` with the index of the chunk,
+/// starting at zero, so that only one element is held in
+/// memory at a time. Returns the number of chunks written,
+/// which is zero for an empty array. It will return an
+/// error under the following conditions:
+///
+/// * The text is not valid JSON.
+/// * The top-level value is not an array.
+/// * Creating or writing an output fails.
+///
+/// # Panics
+///
+/// Panics if `chunk_size` is zero.
+pub fn split_array<W: Write>(
+    reader: impl Read,
+    chunk_size: usize,
+    mut create: impl FnMut(usize) -> io::Result<W>,
+) -> Result<usize> {
+    assert!(chunk_size > 0, "chunk size must be positive");
+
+    let mut events = EventReader::new(reader);
+    if events.next().transpose()? != Some(Event::StartArray) {
+        return Err(Error::new(ErrorKind::UnexpectedType("array")));
+    }
+
+    let mut chunks = 0;
+    let mut chunk: Option<EventWriter<W>> = None;
+    let mut elements = 0;
+    let mut depth = 0;
+
+    for event in events.by_ref() {
+        let event = event?;
+        if depth == 0 && event == Event::EndArray {
+            break;
+        }
+
+        if depth == 0 {
+            if elements % chunk_size == 0 {
+                if let Some(full) = chunk.take() {
+                    finish(full)?;
+                }
+                let writer =
+                    create(chunks).map_err(|error| Error::new(ErrorKind::Io(error.to_string())))?;
+                let mut writer = EventWriter::new(writer);
+                writer.write(&Event::StartArray)?;
+                chunk = Some(writer);
+                chunks += 1;
+            }
+            elements += 1;
+        }
+
+        match event {
+            Event::StartObject | Event::StartArray => depth += 1,
+            Event::EndObject | Event::EndArray => depth -= 1,
+            _ => {}
+        }
+        chunk.as_mut().unwrap().write(&event)?;
+    }
+
+    if let Some(Err(error)) = events.next() {
+        return Err(error);
+    }
+    if let Some(last) = chunk {
+        finish(last)?;
+    }
+    Ok(chunks)
+}
+
+/// Closes the array of a chunk and flushes it.
+fn finish<W: Write>(mut chunk: EventWriter<W>) -> Result<()> {
+    chunk.write(&Event::EndArray)?;
+    chunk.into_inner()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, io::Write, rc::Rc};
+
+    use crate::json::ErrorKind;
+
+    use super::split_array;
+
+    /// A writer whose output can be read after it is
+    /// dropped.
+    struct Output(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for Output {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0.borrow_mut().write(buf)
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn split(text: &str, chunk_size: usize) -> Vec<String> {
+        let mut outputs = vec![];
+        let chunks = split_array(text.as_bytes(), chunk_size, |index| {
+            assert_eq!(index, outputs.len());
+            let output = Rc::new(RefCell::new(vec![]));
+            outputs.push(output.clone());
+            Ok(Output(output))
+        })
+        .unwrap();
+        assert_eq!(chunks, outputs.len());
+        outputs
+            .iter()
+            .map(|output| String::from_utf8(output.borrow().clone()).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn splits_into_chunks() {
+        let text = r#"[1, {"a": [2, 3]}, "x", [4], null]"#;
+        assert_eq!(
+            split(text, 2),
+            [r#"[1,{"a":[2,3]}]"#, r#"["x",[4]]"#, "[null]"]
+        );
+        assert_eq!(split(text, 5), [r#"[1,{"a":[2,3]},"x",[4],null]"#]);
+    }
+
+    #[test]
+    fn writes_nothing_for_empty_array() {
+        assert!(split(" [ ] ", 3).is_empty());
+    }
+
+    #[test]
+    fn rejects_non_arrays() {
+        let error = split_array(r#"{"a": 1}"#.as_bytes(), 1, |_| Ok(vec![])).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedType("array"));
+
+        for text in ["[1, 2", "[1] 2"] {
+            let error = split_array(text.as_bytes(), 1, |_| Ok(vec![])).unwrap_err();
+            assert_eq!(error.kind(), &ErrorKind::Syntax);
+        }
+    }
+}
@@ -0,0 +1,94 @@
+use super::types::{Result, Value};
+
+/// Numeric statistics over a set of values, computed by
+/// [`aggregate`].
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Stats {
+    /// The number of numbers.
+    pub count: usize,
+    /// The sum of the numbers, or zero if there are none.
+    pub sum: f64,
+    /// The smallest number, if there is any.
+    pub min: Option<f64>,
+    /// The largest number, if there is any.
+    pub max: Option<f64>,
+    /// The arithmetic mean of the numbers, if there is any.
+    pub mean: Option<f64>,
+}
+
+impl Stats {
+    /// Converts the statistics to an object, with `null`
+    /// for the statistics that are missing.
+    pub fn to_value(&self) -> Value {
+        let optional = |stat: Option<f64>| stat.map_or(Value::Null, Value::from);
+        Value::object()
+            .set("count", self.count as u64)
+            .set("sum", self.sum)
+            .set("min", optional(self.min))
+            .set("max", optional(self.max))
+            .set("mean", optional(self.mean))
+            .build()
+    }
+}
+
+/// Computes statistics over the numbers selected by a
+/// JSONPath expression, such as `$.items[*].price`, as by
+/// [`Value::select_path`]. Selected values that are not
+/// numbers are ignored. It will return an error if the
+/// expression is invalid.
+pub fn aggregate(value: &Value, path: &str) -> Result<Stats> {
+    let mut stats = Stats::default();
+    for value in value.select_path(path)? {
+        let Value::Number(number) = value else {
+            continue;
+        };
+        let number = number.as_f64();
+        stats.count += 1;
+        stats.sum += number;
+        stats.min = Some(stats.min.map_or(number, |min| min.min(number)));
+        stats.max = Some(stats.max.map_or(number, |max| max.max(number)));
+    }
+    stats.mean = (stats.count > 0).then(|| stats.sum / stats.count as f64);
+    Ok(stats)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind};
+
+    use super::{aggregate, Stats};
+
+    #[test]
+    fn aggregates_selected_numbers() {
+        let value =
+            parse(r#"{"items": [{"price": 4}, {"price": "1"}, {"price": -1.5}, {}]}"#).unwrap();
+        let stats = aggregate(&value, "$.items[*].price").unwrap();
+        assert_eq!(
+            stats,
+            Stats {
+                count: 2,
+                sum: 2.5,
+                min: Some(-1.5),
+                max: Some(4.0),
+                mean: Some(1.25),
+            }
+        );
+        assert_eq!(
+            stats.to_value().to_string(),
+            r#"{"count":2,"sum":2.5,"min":-1.5,"max":4,"mean":1.25}"#
+        );
+    }
+
+    #[test]
+    fn aggregates_nothing() {
+        let value = parse("[]").unwrap();
+        let stats = aggregate(&value, "$[*]").unwrap();
+        assert_eq!(stats, Stats::default());
+        assert_eq!(
+            stats.to_value().to_string(),
+            r#"{"count":0,"sum":0,"min":null,"max":null,"mean":null}"#
+        );
+        let error = aggregate(&value, "items").unwrap_err();
+        assert!(matches!(error.kind(), ErrorKind::InvalidJsonPath(_)));
+    }
+}
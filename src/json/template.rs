@@ -0,0 +1,172 @@
+use super::{
+    serializer::to_canonical_string,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// Renders a template document by replacing `${path}`
+/// placeholders with values from a context document. The
+/// path is a dot-separated list of object keys and array
+/// indices, such as `${servers.0.host}`. A string that
+/// consists of a single placeholder is replaced by the
+/// referenced value itself, whatever its type. Otherwise,
+/// each placeholder is replaced by the text of the value
+/// it references. A literal `${` can be written as `$${`.
+/// It will return an error under the following conditions:
+///
+/// * A placeholder is not closed with `}`.
+/// * A placeholder references a path that does not exist
+///   in the context document.
+pub fn render(template: &Value, vars: &Value) -> Result<Value> {
+    match template {
+        Value::String(string) => render_string(string, vars),
+        Value::Array(array) => Ok(Value::Array(
+            array
+                .iter()
+                .map(|value| render(value, vars))
+                .collect::<Result<_>>()?,
+        )),
+        Value::Object(object) => Ok(Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| Ok((key.clone(), render(value, vars)?)))
+                .collect::<Result<_>>()?,
+        )),
+        _ => Ok(template.clone()),
+    }
+}
+
+fn render_string(string: &str, vars: &Value) -> Result<Value> {
+    if let Some(path) = whole_placeholder(string) {
+        return lookup(vars, path).cloned();
+    }
+
+    let rendered = substitute(string, |path| {
+        lookup(vars, path).map(|value| match value {
+            Value::String(string) => string.clone(),
+            value => to_canonical_string(value),
+        })
+    })?;
+
+    Ok(Value::String(rendered))
+}
+
+fn whole_placeholder(string: &str) -> Option<&str> {
+    string
+        .strip_prefix("${")
+        .and_then(|body| body.strip_suffix('}'))
+        .filter(|path| !path.contains('}'))
+}
+
+fn lookup<'a>(vars: &'a Value, path: &str) -> Result<&'a Value> {
+    path.split('.')
+        .try_fold(vars, |value, segment| match value {
+            Value::Object(object) => object.get(segment),
+            Value::Array(array) => segment
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| array.get(index)),
+            _ => None,
+        })
+        .ok_or_else(|| Error::new(ErrorKind::NotFound(format!("variable {}", path))))
+}
+
+/// Replaces each `${name}` placeholder in a string with the
+/// text produced by `resolve` for its name, and each `$${`
+/// with a literal `${`.
+pub(super) fn substitute(
+    string: &str,
+    mut resolve: impl FnMut(&str) -> Result<String>,
+) -> Result<String> {
+    let mut output = String::with_capacity(string.len());
+    let mut rest = string;
+
+    while let Some(start) = rest.find('$') {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        if let Some(after) = rest.strip_prefix("$${") {
+            output.push_str("${");
+            rest = after;
+        } else if let Some(body) = rest.strip_prefix("${") {
+            let end = body
+                .find('}')
+                .ok_or_else(|| Error::new(ErrorKind::UnclosedPlaceholder))?;
+            output.push_str(&resolve(&body[..end])?);
+            rest = &body[end + 1..];
+        } else {
+            output.push('$');
+            rest = &rest[1..];
+        }
+    }
+
+    output.push_str(rest);
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    use super::render;
+
+    #[test]
+    fn replaces_whole_value_placeholder() {
+        let template = parse(r#"{"port": "${server.port}"}"#).unwrap();
+        let vars = parse(r#"{"server": {"port": 8080}}"#).unwrap();
+        let expected = parse(r#"{"port": 8080}"#).unwrap();
+        assert_eq!(render(&template, &vars).unwrap(), expected);
+    }
+
+    #[test]
+    fn interpolates_placeholders_inside_strings() {
+        let template = parse(r#"["http://${host}:${port}/"]"#).unwrap();
+        let vars = parse(r#"{"host": "localhost", "port": 80}"#).unwrap();
+        let expected = parse(r#"["http://localhost:80/"]"#).unwrap();
+        assert_eq!(render(&template, &vars).unwrap(), expected);
+    }
+
+    #[test]
+    fn resolves_array_indices() {
+        let template = parse(r#""${hosts.1}""#).unwrap();
+        let vars = parse(r#"{"hosts": ["a", "b"]}"#).unwrap();
+        let expected = parse(r#""b""#).unwrap();
+        assert_eq!(render(&template, &vars).unwrap(), expected);
+    }
+
+    #[test]
+    fn interpolates_structured_values_as_json() {
+        let template = parse(r#""tags=${tags}!""#).unwrap();
+        let vars = parse(r#"{"tags": ["a", 1]}"#).unwrap();
+        let expected = Value::String(String::from(r#"tags=["a",1]!"#));
+        assert_eq!(render(&template, &vars).unwrap(), expected);
+    }
+
+    #[test]
+    fn keeps_escaped_placeholders() {
+        let template = parse(r#""cost: $5, $${literal}""#).unwrap();
+        let vars = parse("{}").unwrap();
+        let expected = parse(r#""cost: $5, ${literal}""#).unwrap();
+        assert_eq!(render(&template, &vars).unwrap(), expected);
+    }
+
+    #[test]
+    fn leaves_other_values_untouched() {
+        let template = parse(r#"[1, true, null, "plain"]"#).unwrap();
+        let vars = parse("{}").unwrap();
+        assert_eq!(render(&template, &vars).unwrap(), template);
+    }
+
+    #[test]
+    fn rejects_missing_variable() {
+        let template = parse(r#""${missing}""#).unwrap();
+        let vars = parse("{}").unwrap();
+        assert!(render(&template, &vars).is_err());
+    }
+
+    #[test]
+    fn rejects_unclosed_placeholder() {
+        let template = parse(r#""${host""#).unwrap();
+        let vars = parse(r#"{"host": "a"}"#).unwrap();
+        assert!(render(&template, &vars).is_err());
+    }
+}
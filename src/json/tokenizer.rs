@@ -1,9 +1,13 @@
-use std::iter;
+use std::cell::{Cell, RefCell};
 
 use lazy_static::lazy_static;
 use regex::Regex;
 
-use super::types::{Error, Result, Token};
+use super::{
+    escape::{unescape_str_with, validate_str},
+    options::LoneSurrogates,
+    types::{Diagnostic, Error, ErrorKind, Result, Span, Token},
+};
 
 lazy_static! {
     static ref STRING_RE: Regex =
@@ -11,107 +15,546 @@ lazy_static! {
             .expect("String regex was invalid");
     static ref NUMBER_RE: Regex =
         Regex::new("^-?([0-9]+)(\\.[0-9]+)?([eE][+-]?[0-9]+)?").expect("Number regex was invalid");
-    static ref LEADING_ZERO_RE: Regex =
-        Regex::new("^-?0+[1-9]").expect("Leading zero regex was invalid");
+}
+
+/// Options that control how text is tokenized. The
+/// default options tokenize text exactly as specified at
+/// https://www.json.org.
+#[derive(Debug, Clone, Default)]
+pub struct TokenizeOptions {
+    /// Whether whitespace and comments are emitted as
+    /// tokens rather than discarded. Consecutive whitespace
+    /// characters are emitted as a single token.
+    pub keep_trivia: bool,
+    /// Whether JavaScript-style line (`// ...`) and block
+    /// (`/* ... */`) comments are accepted, as in JSONC.
+    pub allow_comments: bool,
+    /// Whether the Python literals `True`, `False`, and
+    /// `None` and single-quoted strings are accepted, as
+    /// printed by `str(dict)` in Python. They are converted
+    /// into the equivalent JSON tokens.
+    pub python_literals: bool,
+    /// How `\u` escapes of surrogates that are not part of
+    /// a pair are handled in strings.
+    pub lone_surrogates: LoneSurrogates,
+    /// The maximum number of diagnostics reported when
+    /// recovering from errors. Further problems are counted
+    /// but not recorded, and are summarized by a final
+    /// [`ErrorKind::MoreProblems`] diagnostic. When `None`,
+    /// every problem is reported.
+    pub max_errors: Option<usize>,
 }
 
 /// Converts an input text into a list of tokens.
-/// Strings are parsed without any transformations
-/// and numbers are parsed to double-precision floats.
+/// Escapes in strings are decoded, so that string tokens
+/// hold the text that the strings stand for, while numbers
+/// are parsed without any transformations.
 /// In addition, the following punctuation symbols will be
 /// parsed into individual tokens: `{}[],:`. The method
 /// ignores whitespace. It will return an error under the
 /// following conditions:
 ///
-/// * A segment beginning with '"' does not match a string.
+/// * A segment beginning with '"' does not match a string,
+///   or holds a `\u` escape of a lone surrogate that the
+///   options do not replace.
 /// * A segment beginning with 't' does not match `true`.
 /// * A segment beginning with 'f' does not match `false`.
 /// * A segment beginning with 'n' does not match `null`.
 /// * A segment beginning with '-' or a digit does not match a number.
+/// * A segment beginning with '+' or '.' followed by a digit.
 ///
 /// This implementation matches the specification declared
 /// at https://www.json.org.
-pub fn tokenize(text: &str) -> Result<Vec<Token>> {
+///
+/// Each token is paired with the span of text it was
+/// parsed from, and errors carry the offset of the segment
+/// that could not be parsed.
+pub fn tokenize(text: &str) -> Result<Vec<(Token, Span)>> {
+    tokenize_with(text, &TokenizeOptions::default())
+}
+
+/// Converts an input text into a list of tokens with the
+/// given options. See [`tokenize`] for details. When
+/// comments are allowed, it will also return an error if a
+/// segment beginning with '/' does not match a comment.
+pub fn tokenize_with(text: &str, options: &TokenizeOptions) -> Result<Vec<(Token, Span)>> {
+    let input = Input {
+        len: text.len(),
+        options,
+        diagnostics: None,
+        omitted: Cell::new(None),
+    };
+    scan(text, &input)
+}
+
+/// Converts an input text into a list of tokens with the
+/// given options, recovering from errors. When a segment
+/// cannot be parsed, its span is recorded and tokenization
+/// resumes at the next plausible token boundary: the end of
+/// the line for a malformed string, or the next whitespace
+/// or punctuation character otherwise. Returns the tokens
+/// that could be parsed along with a diagnostic for each
+/// problem, both in order of appearance, up to the maximum
+/// number of errors in the options.
+pub fn tokenize_recovering(
+    text: &str,
+    options: &TokenizeOptions,
+) -> (Vec<(Token, Span)>, Vec<Diagnostic>) {
+    let input = Input {
+        len: text.len(),
+        options,
+        diagnostics: Some(RefCell::new(vec![])),
+        omitted: Cell::new(None),
+    };
+    let tokens = scan(text, &input).expect("Recovering tokenizer returned an error");
+    let mut diagnostics = input.diagnostics.unwrap_or_default().into_inner();
+    if let Some((start, count)) = input.omitted.get() {
+        diagnostics.push(Diagnostic {
+            kind: ErrorKind::MoreProblems(count),
+            span: Span {
+                start,
+                end: text.len(),
+            },
+        });
+    }
+    (tokens, diagnostics)
+}
+
+/// Checks that an input text is made of valid tokens, as
+/// [`tokenize`] does, and returns the span of each token
+/// without building it. Strings are checked but not
+/// decoded, and the kind of each token is told by the first
+/// character of its span. It will return an error under the
+/// same conditions as `tokenize`.
+pub(super) fn scan_spans(text: &str) -> Result<Vec<Span>> {
+    let input = Input {
+        len: text.len(),
+        options: &TokenizeOptions::default(),
+        diagnostics: None,
+        omitted: Cell::new(None),
+    };
+    let mut spans = vec![];
+    let mut rest = text;
+    while !rest.is_empty() {
+        let start = input.len - rest.len();
+        let (is_token, len) = match rest.as_bytes()[0] {
+            b'"' => (true, string_len(rest, &input)?),
+            b'-' | b'0'..=b'9' => (true, number_len(rest, &input)?),
+            _ => {
+                let (token, len) = scan_token(rest, &input)?;
+                (token.is_some(), len)
+            }
+        };
+        if is_token {
+            spans.push(Span {
+                start,
+                end: start + len,
+            });
+        }
+        rest = &rest[len..];
+    }
+    Ok(spans)
+}
+
+/// Describes the input text that is being tokenized.
+struct Input<'a> {
+    /// The length of the whole input text in bytes.
+    len: usize,
+    options: &'a TokenizeOptions,
+    /// The problems found so far, if recovering from errors.
+    diagnostics: Option<RefCell<Vec<Diagnostic>>>,
+    /// The offset of the first problem that was not
+    /// recorded because of the error limit, and the number
+    /// of such problems.
+    omitted: Cell<Option<(usize, usize)>>,
+}
+
+/// Tokenizes the remaining `text` of an input text in a
+/// single pass. When recovering from errors, a segment
+/// that cannot be parsed is recorded and skipped.
+fn scan(text: &str, input: &Input) -> Result<Vec<(Token, Span)>> {
+    let mut tokens = vec![];
+    let mut rest = text;
+    while !rest.is_empty() {
+        let start = input.len - rest.len();
+        let len = match (scan_token(rest, input), &input.diagnostics) {
+            (Ok((token, len)), _) => {
+                let span = Span {
+                    start,
+                    end: start + len,
+                };
+                tokens.extend(token.map(|token| (token, span)));
+                len
+            }
+            (Err(error), Some(diagnostics)) => {
+                let len = skip_len(rest);
+                let span = Span {
+                    start,
+                    end: start + len,
+                };
+                record(error, span, &mut diagnostics.borrow_mut(), input);
+                len
+            }
+            (Err(error), None) => return Err(error),
+        };
+        rest = &rest[len..];
+    }
+    Ok(tokens)
+}
+
+/// Records a problem found while recovering from errors,
+/// or counts it if the error limit has been reached.
+fn record(error: Error, span: Span, diagnostics: &mut Vec<Diagnostic>, input: &Input) {
+    if input
+        .options
+        .max_errors
+        .is_some_and(|max| diagnostics.len() >= max)
+    {
+        let (first, count) = input.omitted.get().unwrap_or((span.start, 0));
+        input.omitted.set(Some((first, count + 1)));
+    } else {
+        diagnostics.push(Diagnostic {
+            kind: error.kind().clone(),
+            span,
+        });
+    }
+}
+
+/// Returns the length of the malformed segment at the start
+/// of `text`, up to the next plausible token boundary.
+fn skip_len(text: &str) -> usize {
+    let is_string = text.starts_with('"');
+    let is_boundary = |char| match is_string {
+        true => matches!(char, '\n' | '\r'),
+        false => matches!(
+            char,
+            ' ' | '\n' | '\r' | '\t' | '{' | '}' | '[' | ']' | ',' | ':' | '"'
+        ),
+    };
+
+    let first_len = text.chars().next().map_or(0, char::len_utf8);
+    text[first_len..]
+        .find(is_boundary)
+        .map_or(text.len(), |len| first_len + len)
+}
+
+/// The token matched at the start of a text, or `None` if
+/// it is trivia that is skipped, and its length in bytes.
+type Match = (Option<Token>, usize);
+
+/// Tokenizes the token at the start of `text`.
+fn scan_token(text: &str, input: &Input) -> Result<Match> {
     match text.chars().next() {
-        None => Ok(vec![]),
+        None => Ok((None, 0)),
         Some(char) => match char {
-            '"' => Ok(match_string(text)?),
-            't' => Ok(match_true(text)?),
-            'f' => Ok(match_false(text)?),
-            'n' => Ok(match_null(text)?),
-            '-' | '0'..='9' => Ok(match_number(text)?),
-            ' ' | '\n' | '\r' | '\t' => Ok(match_whitespace(text)?),
-            '{' | '}' | '[' | ']' | ',' | ':' => Ok(match_punct(text)?),
-            _ => Err(Error),
+            '"' => match_string(text, input),
+            't' => match_true(text, input),
+            'f' => match_false(text, input),
+            'n' => match_null(text, input),
+            '-' | '0'..='9' => match_number(text, input),
+            '+' | '.' if text[1..].starts_with(|char: char| char.is_ascii_digit()) => {
+                match_number(text, input)
+            }
+            ' ' | '\n' | '\r' | '\t' => match_whitespace(text, input),
+            '{' | '}' | '[' | ']' | ',' | ':' => match_punct(text),
+            '/' if input.options.allow_comments => match_comment(text, input),
+            'T' | 'F' | 'N' if input.options.python_literals => match_python_literal(text, input),
+            '\'' if input.options.python_literals => match_single_quoted_string(text, input),
+            _ => Err(unexpected_character(text, input)),
+        },
+    }
+}
+
+/// Emits a token parsed from the first `token_len` bytes of
+/// a text.
+fn emit(token: Token, token_len: usize) -> Result<Match> {
+    Ok((Some(token), token_len))
+}
+
+/// Emits a whitespace or comment token of the given kind
+/// if trivia is kept, or skips over it without building the
+/// token otherwise.
+fn emit_trivia(kind: fn(String) -> Token, trivia: &str, input: &Input) -> Result<Match> {
+    let token = input.options.keep_trivia.then(|| kind(trivia.to_owned()));
+    Ok((token, trivia.len()))
+}
+
+fn error(kind: ErrorKind, text: &str, input: &Input) -> Error {
+    Error::at(kind, input.len - text.len())
+}
+
+/// Creates an error for a character that cannot begin a
+/// token, with a hint for characters and words that
+/// commonly appear in text that is almost, but not quite,
+/// JSON.
+fn unexpected_character(text: &str, input: &Input) -> Error {
+    let char = text.chars().next().unwrap();
+    let hint = match word(text) {
+        "True" => Some("use `true` instead of `True`"),
+        "False" => Some("use `false` instead of `False`"),
+        "None" => Some("use `null` instead of `None`"),
+        "undefined" => Some("use `null` instead of `undefined`"),
+        "NaN" | "Infinity" => Some("NaN and Infinity are not valid JSON numbers"),
+        _ if is_unquoted_key(text) => Some("keys must be quoted"),
+        _ => match char {
+            '\'' => Some("single quotes are not valid JSON; use double quotes"),
+            '/' if !input.options.allow_comments => Some("comments are not valid JSON"),
+            '/' => Some("comments begin with `//` or `/*`"),
+            '=' => Some("use ':' to separate keys from values"),
+            '+' => Some("numbers may not begin with '+'"),
+            '.' => Some("numbers must have a digit before the decimal point"),
+            '\u{feff}' => Some("byte order marks are not valid JSON"),
+            _ => None,
         },
+    };
+    error(ErrorKind::UnexpectedCharacter { char, hint }, text, input)
+}
+
+/// Creates an error for a segment that begins like the
+/// given literal but does not match it. A segment that
+/// looks like an unquoted key is reported as such.
+fn invalid_literal(literal: &'static str, text: &str, input: &Input) -> Error {
+    match is_unquoted_key(text) {
+        true => unexpected_character(text, input),
+        false => error(ErrorKind::InvalidLiteral(literal), text, input),
     }
 }
 
-fn match_whitespace(text: &str) -> Result<Vec<Token>> {
-    tokenize(&text[1..])
+/// Returns the identifier-like word at the start of `text`.
+fn word(text: &str) -> &str {
+    let len = text
+        .find(|char: char| !(char.is_alphanumeric() || matches!(char, '_' | '$')))
+        .unwrap_or(text.len());
+    &text[..len]
+}
+
+/// Checks whether `text` begins with a word followed by a
+/// colon, such as `name: "value"`.
+fn is_unquoted_key(text: &str) -> bool {
+    let word = word(text);
+    !word.is_empty() && text[word.len()..].trim_start().starts_with(':')
+}
+
+fn match_whitespace(text: &str, input: &Input) -> Result<Match> {
+    let len = text
+        .find(|char| !matches!(char, ' ' | '\n' | '\r' | '\t'))
+        .unwrap_or(text.len());
+    emit_trivia(Token::Whitespace, &text[..len], input)
+}
+
+fn match_comment(text: &str, input: &Input) -> Result<Match> {
+    let len = if text.starts_with("//") {
+        text.find(['\n', '\r']).unwrap_or(text.len())
+    } else if let Some(body) = text.strip_prefix("/*") {
+        match body.find("*/") {
+            None => return Err(error(ErrorKind::UnterminatedComment, text, input)),
+            Some(end) => end + 4,
+        }
+    } else {
+        return Err(unexpected_character(text, input));
+    };
+    emit_trivia(Token::Comment, &text[..len], input)
 }
 
-fn match_punct(text: &str) -> Result<Vec<Token>> {
-    Ok(iter::once(Token::Punct(text.chars().next().unwrap()))
-        .chain(tokenize(&text[1..])?)
-        .collect())
+fn match_punct(text: &str) -> Result<Match> {
+    emit(Token::Punct(text.chars().next().unwrap()), 1)
 }
 
-fn match_true(text: &str) -> Result<Vec<Token>> {
-    match text.strip_prefix("true") {
-        None => Err(Error),
-        Some(substring) => Ok(iter::once(Token::True)
-            .chain(tokenize(substring)?)
-            .collect()),
+fn match_true(text: &str, input: &Input) -> Result<Match> {
+    match text.starts_with("true") {
+        false => Err(invalid_literal("true", text, input)),
+        true => emit(Token::True, 4),
     }
 }
 
-fn match_false(text: &str) -> Result<Vec<Token>> {
-    match text.strip_prefix("false") {
-        None => Err(Error),
-        Some(substring) => Ok(iter::once(Token::False)
-            .chain(tokenize(substring)?)
-            .collect()),
+fn match_false(text: &str, input: &Input) -> Result<Match> {
+    match text.starts_with("false") {
+        false => Err(invalid_literal("false", text, input)),
+        true => emit(Token::False, 5),
     }
 }
 
-fn match_null(text: &str) -> Result<Vec<Token>> {
-    match text.strip_prefix("null") {
-        None => Err(Error),
-        Some(substring) => Ok(iter::once(Token::Null)
-            .chain(tokenize(substring)?)
-            .collect()),
+fn match_null(text: &str, input: &Input) -> Result<Match> {
+    match text.starts_with("null") {
+        false => Err(invalid_literal("null", text, input)),
+        true => emit(Token::Null, 4),
     }
 }
 
-fn match_number(text: &str) -> Result<Vec<Token>> {
+fn match_number(text: &str, input: &Input) -> Result<Match> {
+    let len = number_len(text, input)?;
+    emit(Token::Number(text[..len].to_owned()), len)
+}
+
+/// Returns the length of the number at the start of `text`.
+fn number_len(text: &str, input: &Input) -> Result<usize> {
+    if let Some(reason) = malformed_number(text) {
+        return Err(error(ErrorKind::MalformedNumber(reason), text, input));
+    }
     match NUMBER_RE.find(text) {
-        None => Err(Error),
-        Some(mat) => match LEADING_ZERO_RE.find(mat.as_str()) {
-            Some(_) => Err(Error),
-            None => Ok(iter::once(Token::Number(mat.as_str().parse().unwrap()))
-                .chain(tokenize(&text[mat.end()..])?)
-                .collect()),
-        },
+        None => Err(error(ErrorKind::InvalidNumber, text, input)),
+        Some(mat) => Ok(mat.end()),
+    }
+}
+
+/// Checks the number at the start of `text` for common
+/// mistakes, such as `+1`, `.5`, `5.`, and `01`, returning
+/// the reason it is malformed if one is found.
+fn malformed_number(text: &str) -> Option<&'static str> {
+    let digits = |text: &str| {
+        text.find(|char: char| !char.is_ascii_digit())
+            .unwrap_or(text.len())
+    };
+
+    if text.starts_with('+') {
+        return Some("leading '+' is not allowed");
+    }
+    let text = text.strip_prefix('-').unwrap_or(text);
+    if text.starts_with('.') {
+        return Some("fraction requires a digit before the decimal point");
+    }
+
+    let int = digits(text);
+    if int == 0 {
+        return Some("'-' must be followed by a digit");
+    }
+    if int > 1 && text.starts_with('0') {
+        return Some("leading zeros are not allowed");
     }
+
+    let mut text = &text[int..];
+    if let Some(fraction) = text.strip_prefix('.') {
+        let len = digits(fraction);
+        if len == 0 {
+            return Some("fraction requires digits after the decimal point");
+        }
+        text = &fraction[len..];
+    }
+    if let Some(exponent) = text.strip_prefix(['e', 'E']) {
+        let exponent = exponent.strip_prefix(['+', '-']).unwrap_or(exponent);
+        if digits(exponent) == 0 {
+            return Some("exponent requires digits");
+        }
+    }
+    None
 }
 
-fn match_string(text: &str) -> Result<Vec<Token>> {
+fn match_string(text: &str, input: &Input) -> Result<Match> {
     match STRING_RE.find(text) {
-        None => Err(Error),
-        Some(mat) => Ok(
-            iter::once(Token::String(text[1..mat.end() - 1].to_string()))
-                .chain(tokenize(&text[mat.end()..])?)
-                .collect(),
-        ),
+        None => Err(invalid_string(text, input)),
+        Some(mat) => {
+            match unescape_str_with(&text[1..mat.end() - 1], input.options.lone_surrogates) {
+                Ok(string) => emit(Token::String(string), mat.end()),
+                Err(error) => Err(shift(error, text, input)),
+            }
+        }
+    }
+}
+
+/// Returns the length of the string at the start of `text`,
+/// checking its escapes without decoding them.
+fn string_len(text: &str, input: &Input) -> Result<usize> {
+    match STRING_RE.find(text) {
+        None => Err(invalid_string(text, input)),
+        Some(mat) => match validate_str(&text[1..mat.end() - 1], input.options.lone_surrogates) {
+            Ok(()) => Ok(mat.end()),
+            Err(error) => Err(shift(error, text, input)),
+        },
+    }
+}
+
+/// Creates an error for a segment beginning with '"' that
+/// is not a valid string, at the offset of the problem: the
+/// start of the segment if the closing quote is missing, or
+/// the invalid escape or character within it.
+fn invalid_string(text: &str, input: &Input) -> Error {
+    let mut chars = text.char_indices().skip(1);
+    let end = loop {
+        match chars.next() {
+            None => return error(ErrorKind::UnterminatedString, text, input),
+            Some((index, '"')) => break index,
+            Some((_, '\\')) => {
+                chars.next();
+            }
+            Some(_) => {}
+        }
+    };
+    match unescape_str_with(&text[1..end], input.options.lone_surrogates) {
+        Err(error) => shift(error, text, input),
+        Ok(_) => error(ErrorKind::InvalidString, text, input),
+    }
+}
+
+/// Shifts the offset of an error in the contents of the
+/// string at the start of `text` to the whole input.
+fn shift(error: Error, text: &str, input: &Input) -> Error {
+    let start = input.len - text.len() + 1;
+    Error::at(error.kind().clone(), start + error.offset().unwrap_or(0))
+}
+
+fn match_python_literal(text: &str, input: &Input) -> Result<Match> {
+    match word(text) {
+        "True" => emit(Token::True, 4),
+        "False" => emit(Token::False, 5),
+        "None" => emit(Token::Null, 4),
+        _ => Err(unexpected_character(text, input)),
+    }
+}
+
+/// Matches a Python string in single quotes, converting it
+/// into the text of the equivalent JSON string. Double
+/// quotes are escaped, escaped single quotes are not, and
+/// `\xNN` escapes are written as `\u00NN`, before escapes
+/// are decoded as in a JSON string.
+fn match_single_quoted_string(text: &str, input: &Input) -> Result<Match> {
+    let invalid = || error(ErrorKind::InvalidString, text, input);
+    let mut string = String::new();
+    let mut chars = text.char_indices().skip(1);
+    let end = loop {
+        match chars.next().ok_or_else(invalid)? {
+            (index, '\'') => break index + 1,
+            (_, '"') => string.push_str("\\\""),
+            (_, '\\') => match chars.next().ok_or_else(invalid)? {
+                (_, '\'') => string.push('\''),
+                (_, 'x') => {
+                    let hex: String = chars.by_ref().take(2).map(|(_, char)| char).collect();
+                    string.push_str("\\u00");
+                    string.push_str(&hex);
+                }
+                (_, char) => {
+                    string.push('\\');
+                    string.push(char);
+                }
+            },
+            (_, char) => string.push(char),
+        }
+    };
+
+    match STRING_RE.is_match(&format!("\"{}\"", string)) {
+        true => match unescape_str_with(&string, input.options.lone_surrogates) {
+            Ok(string) => emit(Token::String(string), end),
+            Err(_) => Err(invalid()),
+        },
+        false => Err(invalid()),
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::json::types::Token;
+    use crate::json::{
+        parse, parse_with,
+        types::{ErrorKind, Span, Token},
+        ParseOptions,
+    };
+
+    use super::{tokenize_recovering, tokenize_with, Result, TokenizeOptions};
 
-    use super::tokenize;
+    fn tokenize(text: &str) -> Result<Vec<Token>> {
+        Ok(super::tokenize(text)?
+            .into_iter()
+            .map(|(token, _)| token)
+            .collect())
+    }
 
     #[test]
     fn recognizes_open_brace() {
@@ -224,7 +667,7 @@ mod tests {
         let tokens = tokenize(text).unwrap();
         assert_eq!(
             tokens[0],
-            Token::String(String::from("hello\\\\\\/\\b\\f\\n\\r\\tworld"))
+            Token::String(String::from("hello\\/\u{8}\u{c}\n\r\tworld"))
         );
     }
 
@@ -239,35 +682,35 @@ mod tests {
     fn recognizes_zero() {
         let text = "0";
         let tokens = tokenize(text).unwrap();
-        assert_eq!(tokens[0], Token::Number(0.0));
+        assert_eq!(tokens[0], Token::Number("0".to_owned()));
     }
 
     #[test]
     fn recognizes_positive_number() {
         let text = "123";
         let tokens = tokenize(text).unwrap();
-        assert_eq!(tokens[0], Token::Number(123.0));
+        assert_eq!(tokens[0], Token::Number("123".to_owned()));
     }
 
     #[test]
     fn recognizes_negative_number() {
         let text = "-123";
         let tokens = tokenize(text).unwrap();
-        assert_eq!(tokens[0], Token::Number(-123.0));
+        assert_eq!(tokens[0], Token::Number("-123".to_owned()));
     }
 
     #[test]
     fn recognizes_number_with_fraction() {
         let text = "123.456";
         let tokens = tokenize(text).unwrap();
-        assert_eq!(tokens[0], Token::Number(123.456));
+        assert_eq!(tokens[0], Token::Number("123.456".to_owned()));
     }
 
     #[test]
     fn recognizes_number_with_exponent() {
         let text = "123e+3";
         let tokens = tokenize(text).unwrap();
-        assert_eq!(tokens[0], Token::Number(123e+3));
+        assert_eq!(tokens[0], Token::Number("123e+3".to_owned()));
     }
 
     #[test]
@@ -307,6 +750,277 @@ mod tests {
         assert!(tokens.is_err());
     }
 
+    #[test]
+    fn records_token_spans() {
+        let text = r#"{ "a": -1.5 }"#;
+        let spans: Vec<_> = super::tokenize(text)
+            .unwrap()
+            .into_iter()
+            .map(|(_, span)| (span.start, span.end))
+            .collect();
+        assert_eq!(spans, [(0, 1), (2, 5), (5, 6), (7, 11), (12, 13)]);
+    }
+
+    #[test]
+    fn reports_error_offset() {
+        let text = "[1, tru]";
+        let error = tokenize(text).unwrap_err();
+        assert_eq!(error.offset(), Some(4));
+    }
+
+    #[test]
+    fn keeps_whitespace_when_requested() {
+        let options = TokenizeOptions {
+            keep_trivia: true,
+            ..Default::default()
+        };
+        let tokens = tokenize_with(" [ \n\t1]", &options).unwrap();
+        let expected = vec![
+            (Token::Whitespace(" ".to_owned()), Span { start: 0, end: 1 }),
+            (Token::Punct('['), Span { start: 1, end: 2 }),
+            (
+                Token::Whitespace(" \n\t".to_owned()),
+                Span { start: 2, end: 5 },
+            ),
+            (Token::Number("1".to_owned()), Span { start: 5, end: 6 }),
+            (Token::Punct(']'), Span { start: 6, end: 7 }),
+        ];
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn rejects_comments_by_default() {
+        let text = "// comment\n1";
+        let tokens = tokenize(text);
+        assert!(tokens.is_err());
+    }
+
+    #[test]
+    fn skips_comments_when_allowed() {
+        let options = TokenizeOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        let tokens = tokenize_with("// a\n[1 /* b */]", &options).unwrap();
+        let tokens: Vec<_> = tokens.into_iter().map(|(token, _)| token).collect();
+        assert_eq!(
+            tokens,
+            [
+                Token::Punct('['),
+                Token::Number("1".to_owned()),
+                Token::Punct(']')
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_comments_when_requested() {
+        let options = TokenizeOptions {
+            keep_trivia: true,
+            allow_comments: true,
+            ..Default::default()
+        };
+        let tokens = tokenize_with("1// a\n/* b */", &options).unwrap();
+        let tokens: Vec<_> = tokens.into_iter().map(|(token, _)| token).collect();
+        assert_eq!(
+            tokens,
+            [
+                Token::Number("1".to_owned()),
+                Token::Comment("// a".to_owned()),
+                Token::Whitespace("\n".to_owned()),
+                Token::Comment("/* b */".to_owned()),
+            ]
+        );
+    }
+
+    #[test]
+    fn rejects_unterminated_block_comment() {
+        let options = TokenizeOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        assert!(tokenize_with("1 /* a", &options).is_err());
+        assert!(tokenize_with("1 / a", &options).is_err());
+    }
+
+    #[test]
+    fn recovers_from_errors() {
+        let text = "[tru, 1, @@, 2]";
+        let (tokens, diagnostics) = tokenize_recovering(text, &TokenizeOptions::default());
+        let tokens: Vec<_> = tokens.into_iter().map(|(token, _)| token).collect();
+        assert_eq!(
+            tokens,
+            [
+                Token::Punct('['),
+                Token::Punct(','),
+                Token::Number("1".to_owned()),
+                Token::Punct(','),
+                Token::Punct(','),
+                Token::Number("2".to_owned()),
+                Token::Punct(']'),
+            ]
+        );
+        let spans: Vec<_> = diagnostics
+            .iter()
+            .map(|d| (d.span.start, d.span.end))
+            .collect();
+        assert_eq!(spans, [(1, 4), (9, 11)]);
+    }
+
+    #[test]
+    fn recovers_from_malformed_string_at_end_of_line() {
+        let text = "[\"a\\x, b\"\n, 1]";
+        let (tokens, diagnostics) = tokenize_recovering(text, &TokenizeOptions::default());
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].span, Span { start: 1, end: 9 });
+        assert_eq!(tokens.len(), 4);
+    }
+
+    #[test]
+    fn summarizes_problems_beyond_error_limit() {
+        let options = TokenizeOptions {
+            max_errors: Some(2),
+            ..Default::default()
+        };
+        let (tokens, diagnostics) = tokenize_recovering("[@, 1, @, @, 2, @]", &options);
+        assert_eq!(tokens.len(), 9);
+        let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+        assert_eq!(kinds[2..], [ErrorKind::MoreProblems(2)]);
+        assert_eq!(diagnostics[2].span, Span { start: 10, end: 18 });
+
+        let options = TokenizeOptions {
+            max_errors: Some(0),
+            ..Default::default()
+        };
+        let (_, diagnostics) = tokenize_recovering("[1]", &options);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn reports_no_diagnostics_for_valid_text() {
+        let (tokens, diagnostics) = tokenize_recovering("[1]", &TokenizeOptions::default());
+        assert_eq!(tokens.len(), 3);
+        assert!(diagnostics.is_empty());
+    }
+
+    #[test]
+    fn names_unexpected_character() {
+        let error = tokenize("[1, @]").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::UnexpectedCharacter {
+                char: '@',
+                hint: None
+            }
+        );
+        assert_eq!(error.offset(), Some(4));
+        assert_eq!(error.to_string(), "unexpected character '@' at byte 4");
+    }
+
+    #[test]
+    fn hints_at_single_quotes() {
+        let error = tokenize("{'a': 1}").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "unexpected character '\\'' at byte 1 \
+             (single quotes are not valid JSON; use double quotes)"
+        );
+    }
+
+    #[test]
+    fn hints_at_comments() {
+        let error = tokenize("1 // one").unwrap_err();
+        assert_eq!(error.kind().hint(), Some("comments are not valid JSON"));
+    }
+
+    #[test]
+    fn reports_invalid_literal() {
+        let error = tokenize("[nul]").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidLiteral("null"));
+        assert_eq!(error.offset(), Some(1));
+    }
+
+    #[test]
+    fn reports_invalid_string() {
+        let error = tokenize(r#"["a\x"]"#).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidEscape(r"\x".into()));
+        assert_eq!(error.offset(), Some(3));
+
+        let error = tokenize("[\"a\tb\"]").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::InvalidString);
+        assert_eq!(error.offset(), Some(3));
+
+        let error = tokenize(r#"["a", "b\"]"#).unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnterminatedString);
+        assert_eq!(error.offset(), Some(6));
+    }
+
+    #[test]
+    fn hints_at_unquoted_keys() {
+        let error = tokenize("{name: 1}").unwrap_err();
+        assert_eq!(error.kind().hint(), Some("keys must be quoted"));
+        assert_eq!(error.offset(), Some(1));
+
+        let error = tokenize("{ key : 1}").unwrap_err();
+        assert_eq!(error.kind().hint(), Some("keys must be quoted"));
+    }
+
+    #[test]
+    fn hints_at_equals_sign() {
+        let error = tokenize(r#"{"a" = 1}"#).unwrap_err();
+        assert_eq!(
+            error.kind().hint(),
+            Some("use ':' to separate keys from values")
+        );
+    }
+
+    #[test]
+    fn hints_at_python_literals() {
+        let error = tokenize("[True, None]").unwrap_err();
+        assert_eq!(error.kind().hint(), Some("use `true` instead of `True`"));
+
+        let error = tokenize("[None]").unwrap_err();
+        assert_eq!(error.kind().hint(), Some("use `null` instead of `None`"));
+    }
+
+    #[test]
+    fn explains_malformed_numbers() {
+        for (text, reason) in [
+            ("+1", "leading '+' is not allowed"),
+            (".5", "fraction requires a digit before the decimal point"),
+            ("-.5", "fraction requires a digit before the decimal point"),
+            ("5.", "fraction requires digits after the decimal point"),
+            ("5.e3", "fraction requires digits after the decimal point"),
+            ("01", "leading zeros are not allowed"),
+            ("-00.5", "leading zeros are not allowed"),
+            ("1e", "exponent requires digits"),
+            ("1.5E+", "exponent requires digits"),
+            ("-", "'-' must be followed by a digit"),
+        ] {
+            let error = tokenize(&format!("[0, {}]", text)).unwrap_err();
+            assert_eq!(
+                error.kind(),
+                &ErrorKind::MalformedNumber(reason),
+                "{}",
+                text
+            );
+            assert_eq!(error.offset(), Some(4), "{}", text);
+        }
+    }
+
+    #[test]
+    fn reports_spans_of_malformed_numbers() {
+        let (_, diagnostics) = tokenize_recovering("[+12, 3.]", &TokenizeOptions::default());
+        let spans: Vec<_> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.span)
+            .collect();
+        assert_eq!(
+            spans,
+            [Span { start: 1, end: 4 }, Span { start: 6, end: 8 }]
+        );
+    }
+
     #[test]
     fn recognizes_complex_text() {
         use super::Token::*;
@@ -406,4 +1120,60 @@ mod tests {
         ];
         assert_eq!(tokens, expected);
     }
+
+    #[test]
+    fn accepts_python_literals_when_allowed() {
+        let options = TokenizeOptions {
+            python_literals: true,
+            ..Default::default()
+        };
+        let tokens = tokenize_with(r#"[True, False, None, 'a"b\'c\x41']"#, &options).unwrap();
+        let tokens: Vec<_> = tokens.into_iter().map(|(token, _)| token).collect();
+        assert_eq!(
+            tokens,
+            [
+                Token::Punct('['),
+                Token::True,
+                Token::Punct(','),
+                Token::False,
+                Token::Punct(','),
+                Token::Null,
+                Token::Punct(','),
+                Token::String(r#"a"b'cA"#.to_owned()),
+                Token::Punct(']'),
+            ]
+        );
+
+        for text in ["Nothing", "'abc", r"'\xZZ'", r"'\q'"] {
+            assert!(tokenize_with(text, &options).is_err());
+        }
+        assert!(tokenize("True").is_err());
+        assert!(tokenize("'a'").is_err());
+
+        let options = ParseOptions {
+            python_literals: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            parse_with("{'a': [True, None]}", &options).unwrap(),
+            parse(r#"{"a": [true, null]}"#).unwrap()
+        );
+    }
+
+    #[test]
+    fn tokenizes_long_inputs() {
+        let count = 100_000;
+        let text = format!("[{}0]", "0,".repeat(count));
+        let tokens = tokenize(&text).unwrap();
+        assert_eq!(tokens.len(), 2 * count + 3);
+
+        let text = "x ".repeat(count);
+        let options = TokenizeOptions {
+            max_errors: Some(1),
+            ..Default::default()
+        };
+        let (tokens, diagnostics) = tokenize_recovering(&text, &options);
+        assert!(tokens.is_empty());
+        assert_eq!(diagnostics[1].kind, ErrorKind::MoreProblems(count - 1));
+    }
 }
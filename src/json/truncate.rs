@@ -0,0 +1,287 @@
+use std::borrow::Cow;
+
+use super::types::Value;
+
+impl Value {
+    /// Returns a summarized copy of the value that is safe
+    /// to write to logs. Strings longer than
+    /// `max_string_len` characters are cut short and end
+    /// with a note such as `…(+240 chars)`, without
+    /// splitting a grapheme cluster. Arrays with more
+    /// than `max_array_items` elements keep their first
+    /// elements, followed by a note such as `…97 more`.
+    /// Arrays and objects nested more than `max_depth`
+    /// levels deep are replaced by a note such as
+    /// `[…3 items]` or `{…2 members}`.
+    pub fn truncate_for_log(
+        &self,
+        max_string_len: usize,
+        max_array_items: usize,
+        max_depth: usize,
+    ) -> Value {
+        let limits = Limits {
+            max_string_len,
+            max_array_items,
+            max_depth,
+        };
+        truncate(self, &limits, 0)
+    }
+}
+
+struct Limits {
+    max_string_len: usize,
+    max_array_items: usize,
+    max_depth: usize,
+}
+
+fn truncate(value: &Value, limits: &Limits, depth: usize) -> Value {
+    match value {
+        Value::String(string) => Value::String(truncate_string(string, limits.max_string_len)),
+        Value::Array(array) if !array.is_empty() && depth >= limits.max_depth => {
+            Value::String(format!("[…{} items]", array.len()))
+        }
+        Value::Object(object) if !object.is_empty() && depth >= limits.max_depth => {
+            Value::String(format!("{{…{} members}}", object.len()))
+        }
+        Value::Array(array) => {
+            let mut truncated: Vec<_> = array
+                .iter()
+                .take(limits.max_array_items)
+                .map(|value| truncate(value, limits, depth + 1))
+                .collect();
+            if array.len() > limits.max_array_items {
+                let remaining = array.len() - limits.max_array_items;
+                truncated.push(Value::String(format!("…{} more", remaining)));
+            }
+            Value::Array(truncated)
+        }
+        Value::Object(object) => Value::Object(
+            object
+                .iter()
+                .map(|(key, value)| (key.clone(), truncate(value, limits, depth + 1)))
+                .collect(),
+        ),
+        Value::Ext(tag, value) => {
+            Value::Ext(tag.clone(), Box::new(truncate(value, limits, depth + 1)))
+        }
+        _ => value.clone(),
+    }
+}
+
+fn truncate_string(string: &str, max_len: usize) -> String {
+    let len = string.chars().count();
+    if len <= max_len {
+        return string.to_owned();
+    }
+
+    let kept = cut(string, max_len, true);
+    let omitted = len - kept.chars().count();
+    format!("{}…(+{} chars)", kept, omitted)
+}
+
+/// Options that control how [`truncate_json_string`]
+/// shortens a string. The default options cut on character
+/// boundaries and end a shortened string with `…`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TruncateOptions {
+    /// Whether cuts keep grapheme clusters whole, such as a
+    /// letter with combining accents, an emoji with
+    /// modifiers, or a flag.
+    pub graphemes: bool,
+    /// The text that ends a shortened string. It counts
+    /// toward the maximum length, and is left out if it
+    /// does not fit.
+    pub ellipsis: String,
+}
+
+impl Default for TruncateOptions {
+    fn default() -> Self {
+        Self {
+            graphemes: false,
+            ellipsis: "…".to_owned(),
+        }
+    }
+}
+
+/// Shortens a string to at most `max_len` characters, for
+/// use as the content of a JSON string in logs or previews.
+/// The string is cut on a character boundary, or on a
+/// grapheme boundary if the options ask for it, so that the
+/// result is always valid text. A string that fits is
+/// returned as is.
+pub fn truncate_json_string<'a>(
+    string: &'a str,
+    max_len: usize,
+    options: &TruncateOptions,
+) -> Cow<'a, str> {
+    if string.chars().nth(max_len).is_none() {
+        return Cow::Borrowed(string);
+    }
+
+    let ellipsis_len = options.ellipsis.chars().count();
+    if ellipsis_len > max_len {
+        return Cow::Borrowed(cut(string, max_len, options.graphemes));
+    }
+
+    let kept = cut(string, max_len - ellipsis_len, options.graphemes);
+    Cow::Owned(format!("{}{}", kept, options.ellipsis))
+}
+
+/// Returns the longest prefix of the string with at most
+/// `max_len` characters that ends on a character boundary,
+/// or on a grapheme boundary if asked.
+fn cut(string: &str, max_len: usize, graphemes: bool) -> &str {
+    let chars: Vec<(usize, char)> = string.char_indices().collect();
+    if chars.len() <= max_len {
+        return string;
+    }
+
+    let mut end = max_len;
+    while graphemes && end > 0 && !is_grapheme_boundary(&chars, end) {
+        end -= 1;
+    }
+    &string[..chars[end].0]
+}
+
+/// Checks whether a grapheme cluster may end before the
+/// character at the given index. This covers the common
+/// cases of combining marks, variation selectors, emoji
+/// modifiers, zero-width joiner sequences, and flags made
+/// of regional indicator pairs.
+fn is_grapheme_boundary(chars: &[(usize, char)], index: usize) -> bool {
+    let (previous, next) = (chars[index - 1].1, chars[index].1);
+    if previous == '\u{200d}' || is_extending(next) {
+        return false;
+    }
+    if is_regional_indicator(next) {
+        let run = chars[..index]
+            .iter()
+            .rev()
+            .take_while(|(_, c)| is_regional_indicator(*c))
+            .count();
+        return run % 2 == 0;
+    }
+    true
+}
+
+/// Checks whether a character extends the grapheme cluster
+/// before it rather than starting a new one.
+fn is_extending(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036f}'
+            | '\u{0483}'..='\u{0489}'
+            | '\u{0591}'..='\u{05bd}'
+            | '\u{0610}'..='\u{061a}'
+            | '\u{064b}'..='\u{065f}'
+            | '\u{0900}'..='\u{0903}'
+            | '\u{093a}'..='\u{094f}'
+            | '\u{1ab0}'..='\u{1aff}'
+            | '\u{1dc0}'..='\u{1dff}'
+            | '\u{200c}'..='\u{200d}'
+            | '\u{20d0}'..='\u{20ff}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{fe20}'..='\u{fe2f}'
+            | '\u{1f3fb}'..='\u{1f3ff}'
+            | '\u{e0020}'..='\u{e007f}'
+            | '\u{e0100}'..='\u{e01ef}'
+    )
+}
+
+fn is_regional_indicator(c: char) -> bool {
+    ('\u{1f1e6}'..='\u{1f1ff}').contains(&c)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Value};
+
+    use super::{truncate_json_string, TruncateOptions};
+
+    fn graphemes() -> TruncateOptions {
+        TruncateOptions {
+            graphemes: true,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn shortens_long_strings() {
+        let value = parse(r#""abcdefghij""#).unwrap();
+        let truncated = value.truncate_for_log(4, 10, 10);
+        assert_eq!(truncated, Value::String("abcd…(+6 chars)".to_owned()));
+    }
+
+    #[test]
+    fn keeps_short_strings() {
+        let value = parse(r#""abcd""#).unwrap();
+        assert_eq!(value.truncate_for_log(4, 10, 10), value);
+    }
+
+    #[test]
+    fn counts_characters_rather_than_bytes() {
+        let value = parse(r#""😀😀😀""#).unwrap();
+        let truncated = value.truncate_for_log(1, 10, 10);
+        assert_eq!(truncated, Value::String("😀…(+2 chars)".to_owned()));
+    }
+
+    #[test]
+    fn shortens_long_arrays() {
+        let value = parse("[1, 2, 3, 4, 5]").unwrap();
+        let truncated = value.truncate_for_log(10, 2, 10);
+        let expected = parse(r#"[1, 2, "…3 more"]"#).unwrap();
+        assert_eq!(truncated, expected);
+    }
+
+    #[test]
+    fn summarizes_deeply_nested_containers() {
+        let value = parse(r#"{"a": {"b": [1, 2, 3], "c": {"d": 1}, "e": []}}"#).unwrap();
+        let truncated = value.truncate_for_log(10, 10, 2);
+        let expected =
+            parse(r#"{"a": {"b": "[…3 items]", "c": "{…1 members}", "e": []}}"#).unwrap();
+        assert_eq!(truncated, expected);
+    }
+
+    #[test]
+    fn leaves_scalars_untouched() {
+        let value = parse("[1, true, null]").unwrap();
+        assert_eq!(value.truncate_for_log(0, 10, 10), value);
+    }
+
+    #[test]
+    fn keeps_grapheme_clusters_whole_in_logs() {
+        let value = Value::String("ae\u{301}io".to_owned());
+        let truncated = value.truncate_for_log(2, 10, 10);
+        assert_eq!(truncated, Value::String("a…(+4 chars)".to_owned()));
+    }
+
+    #[test]
+    fn counts_ellipsis_toward_maximum_length() {
+        let options = TruncateOptions::default();
+        assert_eq!(truncate_json_string("abcdef", 4, &options), "abc…");
+        assert_eq!(truncate_json_string("abcd", 4, &options), "abcd");
+
+        let options = TruncateOptions {
+            ellipsis: "...".to_owned(),
+            ..Default::default()
+        };
+        assert_eq!(truncate_json_string("abcdef", 5, &options), "ab...");
+        assert_eq!(truncate_json_string("abcdef", 2, &options), "ab");
+    }
+
+    #[test]
+    fn cuts_on_character_boundaries() {
+        let options = TruncateOptions::default();
+        assert_eq!(truncate_json_string("😀😀😀", 2, &options), "😀…");
+        assert_eq!(truncate_json_string("e\u{301}e", 2, &options), "e…");
+    }
+
+    #[test]
+    fn optionally_cuts_on_grapheme_boundaries() {
+        let options = graphemes();
+        assert_eq!(truncate_json_string("ae\u{301}x", 3, &options), "a…");
+        assert_eq!(truncate_json_string("👍\u{1f3fd}!", 2, &options), "…");
+        assert_eq!(truncate_json_string("👩\u{200d}💻abc", 3, &options), "…");
+        assert_eq!(truncate_json_string("🇫🇷🇩🇪🇮🇹", 4, &options), "🇫🇷…");
+    }
+}
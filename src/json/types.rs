@@ -1,106 +1,545 @@
-use std::{collections::HashMap, fmt::Display};
+use std::fmt::Display;
 
-use colored::Colorize;
+use super::{map::Map, number::Number, options::FormatOptions, serializer::write_fmt};
 
 /// An enumeration of tokens that may appear within JSON
 /// text. The tokens contain information that is relevant
-/// to each variant.
-#[derive(PartialEq, Debug)]
+/// to each variant. Whitespace and comments are only
+/// emitted when the tokenizer is asked to keep trivia.
+#[derive(PartialEq, Debug, Clone)]
 pub enum Token {
     Punct(char),
     String(String),
-    Number(f64),
+    Number(String),
     True,
     False,
     Null,
+    Whitespace(String),
+    Comment(String),
+}
+
+/// A range of byte offsets within an input text. The
+/// range includes `start` and excludes `end`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
-#[derive(Debug, Clone)]
-pub struct Error;
+/// An error that occurred while parsing or processing
+/// JSON. Errors that relate to a position in an input
+/// text carry the byte offset of that position, and errors
+/// returned by the parser also carry its line and column.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Error {
+    kind: ErrorKind,
+    offset: Option<usize>,
+    /// The line and column of the offset, both counted
+    /// from one.
+    location: Option<(usize, usize)>,
+}
+
+/// An enumeration of the kinds of errors that may occur.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    /// The text is not valid JSON.
+    Syntax,
+    /// A character cannot begin any token. The hint
+    /// suggests a fix for common mistakes.
+    UnexpectedCharacter {
+        char: char,
+        hint: Option<&'static str>,
+    },
+    /// A segment beginning like the given literal, such as
+    /// `true`, does not match it.
+    InvalidLiteral(&'static str),
+    /// A segment beginning with '"' is not a valid string,
+    /// such as one that holds a raw control character.
+    InvalidString,
+    /// A string is missing its closing quote.
+    UnterminatedString,
+    /// A backslash in a string does not begin a valid
+    /// escape, such as `\x` or a `\u` escape that holds a
+    /// lone surrogate.
+    InvalidEscape(String),
+    /// A segment beginning with '-' or a digit is not a
+    /// valid number.
+    InvalidNumber,
+    /// A number is malformed in a common way, such as
+    /// `+1` or `.5`, for the given reason.
+    MalformedNumber(&'static str),
+    /// A block comment is missing its closing `*/`.
+    UnterminatedComment,
+    /// An array or object has a comma after its last
+    /// element.
+    TrailingComma,
+    /// A token appears where the grammar expects something
+    /// else, described by the given text, such as `:`.
+    UnexpectedToken { expected: &'static str },
+    /// The text ends where the grammar expects more, such as
+    /// inside an array.
+    UnexpectedEnd,
+    /// More text follows a complete value.
+    TrailingData,
+    /// A number cannot be represented exactly in the
+    /// numeric type chosen by the parse options.
+    LossyNumber(String),
+    /// The text of a number is longer than the parse
+    /// options allow.
+    NumberTooLong { length: usize, max: usize },
+    /// The exponent of a number has a larger magnitude than
+    /// the parse options allow.
+    ExponentTooLarge { exponent: String, max: u32 },
+    /// The text is longer than the parse options allow.
+    InputTooLarge { size: usize, max: usize },
+    /// The text has more tokens than the parse options
+    /// allow.
+    TooManyTokens { max: usize },
+    /// Arrays and objects are nested more deeply than the
+    /// parse options allow.
+    TooDeep { max: usize },
+    /// A string is longer than the parse options allow.
+    StringTooLong { length: usize, max: usize },
+    /// A key appears more than once in an object, and the
+    /// parse options reject duplicate keys.
+    DuplicateKey(String),
+    /// A JSON pointer is not valid.
+    InvalidPointer(String),
+    /// A JSONPath expression is not valid.
+    InvalidJsonPath(String),
+    /// A value does not have the type that an operation
+    /// requires, such as an array.
+    UnexpectedType(&'static str),
+    /// A value or variable referenced by name does not
+    /// exist.
+    NotFound(String),
+    /// A placeholder is missing its closing brace.
+    UnclosedPlaceholder,
+    /// A number has a fractional part where an integer is
+    /// required.
+    NotAnInteger(String),
+    /// A number does not fit in the required numeric type.
+    OutOfRange {
+        number: String,
+        target: &'static str,
+    },
+    /// An object key was rejected by the key handler, for
+    /// the given reason.
+    RejectedKey(String),
+    /// A schema document is not valid, for the given
+    /// reason.
+    InvalidSchema(String),
+    /// Input bytes are not valid UTF-8. The excerpt shows
+    /// the bytes around the error in hex, with the invalid
+    /// bytes in brackets.
+    InvalidUtf8(String),
+    /// A file could not be read or written, with the path
+    /// and the reason.
+    Io(String),
+    /// A configuration file includes itself, directly or
+    /// indirectly.
+    IncludeCycle(String),
+    /// A binary cache could not be decoded, for the given
+    /// reason.
+    InvalidCache(&'static str),
+    /// A number cannot be written because it is not finite
+    /// or the format options reject it.
+    RejectedNumber(String),
+    /// The text written for a value is longer than the
+    /// format options allow.
+    OutputTooLarge { max: usize },
+    /// A map key that is not a string was found where the
+    /// key policy requires strings.
+    NonStringKey(String),
+    /// Input bytes are not valid UTF-16.
+    InvalidUtf16,
+    /// A media type or charset is not supported.
+    UnsupportedContentType(String),
+    /// The given number of further problems were found but
+    /// not reported, because the error limit was reached.
+    MoreProblems(usize),
+    /// A flattened key refers to a value inside a member
+    /// that is not an object.
+    ConflictingKey(String),
+    /// A `$ref` does not refer to a value.
+    UnresolvedRef(String),
+    /// A `$ref` refers to a value that contains itself.
+    CyclicRef(String),
+}
+
+impl Error {
+    /// Creates an error that does not relate to a position
+    /// in an input text.
+    pub fn new(kind: ErrorKind) -> Self {
+        Self {
+            kind,
+            offset: None,
+            location: None,
+        }
+    }
+
+    /// Creates an error that relates to a byte offset in
+    /// an input text.
+    pub fn at(kind: ErrorKind, offset: usize) -> Self {
+        Self {
+            kind,
+            offset: Some(offset),
+            location: None,
+        }
+    }
+
+    /// Adds the line and column of the offset to an error
+    /// that relates to a position in the given text.
+    pub(super) fn locate(mut self, text: &str) -> Self {
+        if let Some(before) = self.offset.and_then(|offset| text.as_bytes().get(..offset)) {
+            let line_start = before
+                .iter()
+                .rposition(|&byte| byte == b'\n')
+                .map_or(0, |i| i + 1);
+            let line = before.iter().filter(|&&byte| byte == b'\n').count() + 1;
+            // Columns count characters, so continuation bytes
+            // of multi-byte characters are skipped.
+            let column = before[line_start..]
+                .iter()
+                .filter(|&&byte| byte & 0xc0 != 0x80)
+                .count()
+                + 1;
+            self.location = Some((line, column));
+        }
+        self
+    }
+
+    /// Returns the kind of the error.
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// Returns the byte offset in the input text that the
+    /// error relates to, if any.
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    /// Returns the line in the input text that the error
+    /// relates to, counted from one, if known.
+    pub fn line(&self) -> Option<usize> {
+        self.location.map(|(line, _)| line)
+    }
+
+    /// Returns the column in the input text that the error
+    /// relates to, counted in characters from one, if known.
+    pub fn column(&self) -> Option<usize> {
+        self.location.map(|(_, column)| column)
+    }
+}
+
+impl ErrorKind {
+    /// Returns a suggestion for how to fix the error, if
+    /// one is known.
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            ErrorKind::UnexpectedCharacter { hint, .. } => *hint,
+            ErrorKind::TrailingComma => Some("remove the comma after the last element"),
+            ErrorKind::TrailingData => Some("a document holds a single value"),
+            _ => None,
+        }
+    }
+}
+
+impl Display for Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.kind)?;
+        match (self.location, self.offset) {
+            (Some((line, column)), _) => write!(f, " at line {}, column {}", line, column)?,
+            (None, Some(offset)) => write!(f, " at byte {}", offset)?,
+            (None, None) => {}
+        }
+        if let Some(hint) = self.kind.hint() {
+            write!(f, " ({})", hint)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for ErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ErrorKind::Syntax => write!(f, "invalid JSON syntax"),
+            ErrorKind::UnexpectedCharacter { char, .. } => {
+                write!(f, "unexpected character {:?}", char)
+            }
+            ErrorKind::InvalidLiteral(literal) => write!(f, "expected `{}`", literal),
+            ErrorKind::InvalidString => write!(f, "invalid string"),
+            ErrorKind::UnterminatedString => write!(f, "unterminated string"),
+            ErrorKind::InvalidEscape(escape) => write!(f, "invalid escape `{}`", escape),
+            ErrorKind::InvalidNumber => write!(f, "invalid number"),
+            ErrorKind::MalformedNumber(reason) => write!(f, "invalid number: {}", reason),
+            ErrorKind::UnterminatedComment => write!(f, "unterminated block comment"),
+            ErrorKind::TrailingComma => write!(f, "trailing comma"),
+            ErrorKind::UnexpectedToken { expected } => {
+                write!(f, "unexpected token, expected {}", expected)
+            }
+            ErrorKind::UnexpectedEnd => write!(f, "unexpected end of text"),
+            ErrorKind::TrailingData => write!(f, "unexpected text after the value"),
+            ErrorKind::LossyNumber(lexeme) => {
+                write!(f, "number {} cannot be represented exactly", lexeme)
+            }
+            ErrorKind::NumberTooLong { length, max } => {
+                write!(f, "number is {} characters long, more than {}", length, max)
+            }
+            ErrorKind::InputTooLarge { size, max } => {
+                write!(f, "text is {} bytes long, more than {}", size, max)
+            }
+            ErrorKind::TooManyTokens { max } => write!(f, "text has more than {} tokens", max),
+            ErrorKind::TooDeep { max } => write!(f, "values are nested more than {} deep", max),
+            ErrorKind::StringTooLong { length, max } => {
+                write!(f, "string is {} bytes long, more than {}", length, max)
+            }
+            ErrorKind::DuplicateKey(key) => write!(f, "duplicate key {}", key),
+            ErrorKind::ExponentTooLarge { exponent, max } => {
+                write!(
+                    f,
+                    "exponent {} is larger in magnitude than {}",
+                    exponent, max
+                )
+            }
+            ErrorKind::InvalidPointer(pointer) => write!(f, "invalid JSON pointer {:?}", pointer),
+            ErrorKind::InvalidJsonPath(path) => write!(f, "invalid JSONPath {:?}", path),
+            ErrorKind::UnexpectedType(expected) => write!(f, "expected {}", expected),
+            ErrorKind::NotFound(name) => write!(f, "{} was not found", name),
+            ErrorKind::UnclosedPlaceholder => write!(f, "placeholder is missing a closing brace"),
+            ErrorKind::NotAnInteger(number) => write!(f, "{} is not an integer", number),
+            ErrorKind::OutOfRange { number, target } => {
+                write!(f, "{} is out of range for {}", number, target)
+            }
+            ErrorKind::RejectedKey(reason) => write!(f, "key rejected: {}", reason),
+            ErrorKind::InvalidSchema(reason) => write!(f, "invalid schema: {}", reason),
+            ErrorKind::InvalidUtf8(excerpt) => write!(f, "invalid UTF-8 in bytes {}", excerpt),
+            ErrorKind::Io(reason) => write!(f, "{}", reason),
+            ErrorKind::IncludeCycle(path) => write!(f, "{} includes itself", path),
+            ErrorKind::InvalidCache(reason) => write!(f, "invalid cache: {}", reason),
+            ErrorKind::RejectedNumber(number) => {
+                write!(f, "{} is rejected by the format options", number)
+            }
+            ErrorKind::OutputTooLarge { max } => {
+                write!(f, "output is longer than the maximum of {} bytes", max)
+            }
+            ErrorKind::NonStringKey(key) => write!(f, "key {} is not a string", key),
+            ErrorKind::InvalidUtf16 => write!(f, "invalid UTF-16"),
+            ErrorKind::UnsupportedContentType(content_type) => {
+                write!(f, "unsupported content type {}", content_type)
+            }
+            ErrorKind::MoreProblems(count) => write!(f, "and {} more problems", count),
+            ErrorKind::ConflictingKey(key) => write!(f, "key {} conflicts with another key", key),
+            ErrorKind::UnresolvedRef(reference) => write!(f, "unresolved reference {}", reference),
+            ErrorKind::CyclicRef(reference) => write!(f, "cyclic reference {}", reference),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// A problem found in an input text, along with the span
+/// of text that it relates to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub kind: ErrorKind,
+    pub span: Span,
+}
+
+impl Display for Diagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at bytes {}..{}",
+            self.kind, self.span.start, self.span.end
+        )?;
+        if let Some(hint) = self.kind.hint() {
+            write!(f, " ({})", hint)?;
+        }
+        Ok(())
+    }
+}
 
 /// An enumeration of values that may appear within JSON
 /// text. The enumeration can be traversed as a tree, with
 /// object and array types containing nested values.
-#[derive(Debug)]
+///
+/// Values display as compact JSON text, or as indented
+/// JSON text with the alternate flag (`{:#}`).
+#[derive(Clone, PartialEq)]
 pub enum Value {
     String(String),
-    Number(f64),
+    Number(Number),
     Boolean(bool),
     Null,
-    Object(HashMap<String, Value>),
+    Object(Map),
     Array(Vec<Value>),
+    /// The unparsed text of an array or object, as produced
+    /// by [`parse_shallow`](super::parse_shallow). It is
+    /// written out verbatim when serialized.
+    Raw(String),
+    /// A tagged value recognized by the
+    /// [`ext_handler`](super::ParseOptions::ext_handler) of
+    /// the parse options, such as `{"$date": "..."}`,
+    /// holding the tag and the tagged value. It is written
+    /// back as an object with a single member.
+    Ext(String, Box<Value>),
 }
 
 impl Value {
-    const INDENT_SIZE: usize = 2;
-
-    fn display(value: &Value, depth: usize) -> String {
-        match value {
-            Value::String(string) => Value::display_string(string),
-            Value::Number(number) => Value::display_number(number),
-            Value::Boolean(bool) => Value::display_bool(bool),
-            Value::Object(object) => Value::display_object(object, depth),
-            Value::Array(array) => Value::display_array(array, depth),
-            Value::Null => Value::display_null(),
+    /// The boolean value `true`.
+    pub const TRUE: Value = Value::Boolean(true);
+    /// The boolean value `false`.
+    pub const FALSE: Value = Value::Boolean(false);
+    /// The value `null`.
+    pub const NULL: Value = Value::Null;
+
+    /// Returns the number of characters in a string,
+    /// elements in an array, or members in an object.
+    /// Returns `None` for other values.
+    pub fn len(&self) -> Option<usize> {
+        match self {
+            Value::String(string) => Some(string.chars().count()),
+            Value::Array(array) => Some(array.len()),
+            Value::Object(object) => Some(object.len()),
+            _ => None,
         }
     }
 
-    fn display_string(string: &str) -> String {
-        format!(r#""{}""#, string).green().to_string()
+    /// Returns whether the value is an empty string, array,
+    /// or object. Other values are never empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == Some(0)
     }
 
-    fn display_number(number: &f64) -> String {
-        number.to_string()
+    /// Returns a wrapper that displays the value as
+    /// indented JSON text with terminal colors.
+    pub fn colored(&self) -> Colored<'_> {
+        Colored(self)
     }
+}
 
-    fn display_bool(bool: &bool) -> String {
-        bool.to_string()
+impl Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let options = FormatOptions {
+            indent: f.alternate().then_some(2),
+            ..Default::default()
+        };
+        write_fmt(self, &options, false, f)
     }
+}
 
-    fn display_null() -> String {
-        "null".dimmed().to_string()
+impl std::fmt::Debug for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::String(string) => write!(f, "String({:?})", string),
+            Value::Number(number) => write!(f, "Number({})", number),
+            Value::Boolean(bool) => write!(f, "Boolean({})", bool),
+            Value::Null => write!(f, "Null"),
+            Value::Raw(raw) => write!(f, "Raw({:?})", raw),
+            Value::Ext(tag, value) => write!(f, "Ext({:?}, {:?})", tag, value),
+            Value::Object(object) => {
+                write!(f, "Object ")?;
+                f.debug_map().entries(object.iter()).finish()
+            }
+            Value::Array(array) => {
+                write!(f, "Array ")?;
+                f.debug_list().entries(array).finish()
+            }
+        }
     }
+}
 
-    fn display_object(object: &HashMap<String, Value>, depth: usize) -> String {
-        format!(
-            "{{{newline}{members}{newline}{indent}}}",
-            newline = if !object.is_empty() { "\n" } else { "" },
-            indent = " ".repeat(Value::INDENT_SIZE).repeat(depth),
-            members = object
-                .iter()
-                .map(|member| format!(
-                    r#"{indent}{key}: {value}"#,
-                    indent = " ".repeat(Value::INDENT_SIZE).repeat(depth + 1),
-                    key = format!(r#""{}""#, member.0).blue(),
-                    value = Value::display(member.1, depth + 1)
-                ))
-                .collect::<Vec<_>>()
-                .join(",\n"),
-        )
-    }
-
-    fn display_array(array: &[Value], depth: usize) -> String {
-        format!(
-            "[{newline}{values}{newline}{indent}]",
-            newline = if !array.is_empty() { "\n" } else { "" },
-            indent = " ".repeat(Value::INDENT_SIZE).repeat(depth),
-            values = array
-                .iter()
-                .map(|value| format!(
-                    "{indent}{value}",
-                    indent = " ".repeat(Value::INDENT_SIZE).repeat(depth + 1),
-                    value = Value::display(value, depth + 1)
-                ))
-                .collect::<Vec<_>>()
-                .join(",\n")
-        )
+/// A value that displays as indented JSON text with
+/// terminal colors, created by [`Value::colored`].
+pub struct Colored<'a>(&'a Value);
+
+impl Display for Colored<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let options = FormatOptions {
+            indent: Some(2),
+            ..Default::default()
+        };
+        write_fmt(self.0, &options, true, f)
     }
 }
 
-impl Display for Value {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", Value::display(self, 0))
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Error, ErrorKind, Value};
+
+    #[test]
+    fn displays_compact_text() {
+        let value = parse(r#"{"a": [1, "x", null]}"#).unwrap();
+        assert_eq!(format!("{}", value), r#"{"a":[1,"x",null]}"#);
+    }
+
+    #[test]
+    fn displays_pretty_text_with_alternate_flag() {
+        let value = parse(r#"{"a": [1, true]}"#).unwrap();
+        assert_eq!(
+            format!("{:#}", value),
+            "{\n  \"a\": [\n    1,\n    true\n  ]\n}"
+        );
+    }
+
+    #[test]
+    fn measures_length_of_containers_and_strings() {
+        let value = parse(r#"["", "héllo", [1, 2], {}, 0, null]"#).unwrap();
+        let lengths: Vec<_> = match &value {
+            Value::Array(array) => array.iter().map(Value::len).collect(),
+            _ => unreachable!(),
+        };
+        assert_eq!(lengths, [Some(0), Some(5), Some(2), Some(0), None, None]);
+        assert!(parse("{}").unwrap().is_empty());
+        assert!(!Value::NULL.is_empty());
+        assert!(!value.is_empty());
+    }
+
+    #[test]
+    fn provides_constants() {
+        assert_eq!(
+            parse("[true, false, null]").unwrap(),
+            Value::Array(vec![Value::TRUE, Value::FALSE, Value::NULL])
+        );
+    }
+
+    #[test]
+    fn debugs_as_typed_tree() {
+        let value = parse(r#"{"a": [1.5, "x", false, null]}"#).unwrap();
+        assert_eq!(
+            format!("{:?}", value),
+            r#"Object {"a": Array [Number(1.5), String("x"), Boolean(false), Null]}"#
+        );
+    }
+
+    #[test]
+    fn reports_line_and_column_of_errors() {
+        let error = parse("{\n  \"é\": 1,\n  \"b\" 2\n}").unwrap_err();
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::UnexpectedToken { expected: "`:`" }
+        );
+        assert_eq!((error.line(), error.column()), (Some(3), Some(7)));
+        assert_eq!(
+            error.to_string(),
+            "unexpected token, expected `:` at line 3, column 7"
+        );
+
+        let error = parse("[1,\n").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::UnexpectedEnd);
+        assert_eq!((error.line(), error.column()), (Some(1), Some(4)));
+
+        let error = parse("[1] x").unwrap_err();
+        assert_eq!((error.line(), error.column()), (Some(1), Some(5)));
+        let error = parse("[1] 2").unwrap_err();
+        assert_eq!(error.kind(), &ErrorKind::TrailingData);
+
+        let error = Error::at(ErrorKind::Syntax, 3);
+        assert_eq!((error.line(), error.column()), (None, None));
+        assert_eq!(error.to_string(), "invalid JSON syntax at byte 3");
     }
 }
@@ -0,0 +1,106 @@
+use super::{map::Map, types::Value};
+
+/// Rules that control how a sparse update is applied by
+/// [`Map::apply_sparse_update`]. The default rules match
+/// the semantics of JSON Merge Patch (RFC 7396).
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UpdateRules {
+    /// Whether an object in the update is applied to an
+    /// existing object member as a nested update, rather
+    /// than replacing it.
+    pub merge_nested: bool,
+    /// Whether a null in the update deletes the member,
+    /// rather than setting it to null.
+    pub null_deletes: bool,
+}
+
+impl Default for UpdateRules {
+    fn default() -> Self {
+        Self {
+            merge_nested: true,
+            null_deletes: true,
+        }
+    }
+}
+
+impl Map {
+    /// Applies a sparse update to the object. Members that
+    /// are missing from the update are left untouched,
+    /// members that are null in the update are deleted, and
+    /// members that are present in the update replace the
+    /// existing ones, subject to the given rules.
+    pub fn apply_sparse_update(&mut self, update: &Map, rules: UpdateRules) {
+        for (key, value) in update {
+            match value {
+                Value::Null if rules.null_deletes => {
+                    self.remove(key);
+                }
+                Value::Object(nested) if rules.merge_nested => {
+                    if !matches!(self.get(key), Some(Value::Object(_))) {
+                        self.insert(key.clone(), Value::Object(Map::new()));
+                    }
+                    if let Some(Value::Object(object)) = self.get_mut(key) {
+                        object.apply_sparse_update(nested, rules);
+                    }
+                }
+                value => {
+                    self.insert(key.clone(), value.clone());
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, Map, Value};
+
+    use super::UpdateRules;
+
+    fn object(text: &str) -> Map {
+        match parse(text).unwrap() {
+            Value::Object(object) => object,
+            _ => unreachable!(),
+        }
+    }
+
+    #[test]
+    fn applies_nulls_missing_and_present_members() {
+        let mut target = object(r#"{"name": "a", "email": "a@x", "age": 3}"#);
+        let update = object(r#"{"email": null, "age": 4, "city": "y"}"#);
+        target.apply_sparse_update(&update, UpdateRules::default());
+        assert_eq!(target, object(r#"{"name": "a", "age": 4, "city": "y"}"#));
+    }
+
+    #[test]
+    fn merges_nested_objects() {
+        let mut target = object(r#"{"a": {"b": 1, "c": 2}, "d": 1}"#);
+        let update = object(r#"{"a": {"b": null, "e": 3}, "d": {"f": null, "g": 4}}"#);
+        target.apply_sparse_update(&update, UpdateRules::default());
+        assert_eq!(target, object(r#"{"a": {"c": 2, "e": 3}, "d": {"g": 4}}"#));
+    }
+
+    #[test]
+    fn replaces_nested_objects_when_not_merging() {
+        let mut target = object(r#"{"a": {"b": 1}}"#);
+        let update = object(r#"{"a": {"c": null}}"#);
+        let rules = UpdateRules {
+            merge_nested: false,
+            ..Default::default()
+        };
+        target.apply_sparse_update(&update, rules);
+        assert_eq!(target, object(r#"{"a": {"c": null}}"#));
+    }
+
+    #[test]
+    fn stores_nulls_when_not_deleting() {
+        let mut target = object(r#"{"a": 1}"#);
+        let update = object(r#"{"a": null}"#);
+        let rules = UpdateRules {
+            null_deletes: false,
+            ..Default::default()
+        };
+        target.apply_sparse_update(&update, rules);
+        assert_eq!(target, object(r#"{"a": null}"#));
+    }
+}
@@ -0,0 +1,87 @@
+use std::io::Read;
+
+use super::{
+    parse,
+    types::{Error, ErrorKind, Result, Value},
+};
+
+/// The number of valid bytes shown on either side of the
+/// invalid bytes in an excerpt.
+const CONTEXT: usize = 4;
+
+/// Parses JSON text from bytes. It will return an error
+/// under the same conditions as `parse`, or if the bytes
+/// are not valid UTF-8, in which case the error carries the
+/// offset of the first invalid byte and a hex dump of the
+/// bytes around it.
+pub fn parse_slice(bytes: &[u8]) -> Result<Value> {
+    parse(decode(bytes)?)
+}
+
+/// Parses JSON text read from a reader until it is
+/// exhausted. It will return an error under the same
+/// conditions as `parse_slice`, or if reading fails.
+pub fn parse_reader(mut reader: impl Read) -> Result<Value> {
+    let mut bytes = vec![];
+    reader
+        .read_to_end(&mut bytes)
+        .map_err(|error| Error::new(ErrorKind::Io(error.to_string())))?;
+    parse_slice(&bytes)
+}
+
+/// Converts bytes to text, reporting invalid UTF-8 with the
+/// offset of the first invalid byte and an excerpt of the
+/// surrounding bytes.
+pub(super) fn decode(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes).map_err(|error| {
+        let start = error.valid_up_to();
+        let end = start + error.error_len().unwrap_or(bytes.len() - start);
+        let before = &bytes[start.saturating_sub(CONTEXT)..start];
+        let after = &bytes[end..(end + CONTEXT).min(bytes.len())];
+
+        let mut excerpt: Vec<_> = before.iter().map(hex).collect();
+        let invalid: Vec<_> = bytes[start..end].iter().map(hex).collect();
+        excerpt.push(format!("[{}]", invalid.join(" ")));
+        excerpt.extend(after.iter().map(hex));
+        Error::at(ErrorKind::InvalidUtf8(excerpt.join(" ")), start)
+    })
+}
+
+fn hex(byte: &u8) -> String {
+    format!("{:02x}", byte)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{parse, ErrorKind};
+
+    use super::{parse_reader, parse_slice};
+
+    #[test]
+    fn parses_valid_bytes() {
+        let text = r#"{"name": "café"}"#;
+        assert_eq!(parse_slice(text.as_bytes()).unwrap(), parse(text).unwrap());
+        assert_eq!(parse_reader(text.as_bytes()).unwrap(), parse(text).unwrap());
+    }
+
+    #[test]
+    fn reports_invalid_bytes_with_excerpt() {
+        let error = parse_slice(b"[\"abcdef\xff\xfeghij\"]").unwrap_err();
+        assert_eq!(error.offset(), Some(8));
+        assert_eq!(
+            error.kind(),
+            &ErrorKind::InvalidUtf8("63 64 65 66 [ff] fe 67 68 69".to_owned())
+        );
+        assert_eq!(
+            error.to_string(),
+            "invalid UTF-8 in bytes 63 64 65 66 [ff] fe 67 68 69 at byte 8"
+        );
+    }
+
+    #[test]
+    fn reports_truncated_sequences() {
+        let error = parse_slice(b"\"\xc3").unwrap_err();
+        assert_eq!(error.offset(), Some(1));
+        assert_eq!(error.kind(), &ErrorKind::InvalidUtf8("22 [c3]".to_owned()));
+    }
+}
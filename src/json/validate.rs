@@ -0,0 +1,362 @@
+use super::{
+    keys::{normalize_keys, reject_duplicate_keys},
+    limits::enforce_limits,
+    number::{reject_lossy_numbers, reject_oversized_numbers},
+    options::ParseOptions,
+    tokenizer::{tokenize_recovering, TokenizeOptions},
+    types::{Diagnostic, Error, ErrorKind, Result, Span, Token},
+};
+
+/// Options that control what [`validate`] accepts. The
+/// default options accept text exactly as specified at
+/// https://www.json.org.
+#[derive(Debug, Clone, Default)]
+pub struct ValidateOptions {
+    /// Whether JavaScript-style line and block comments are
+    /// accepted, as in JSONC.
+    pub allow_comments: bool,
+    /// Whether a comma is accepted after the last element
+    /// of an array or the last member of an object.
+    pub allow_trailing_commas: bool,
+    /// The maximum number of diagnostics returned. Further
+    /// problems are summarized by a final
+    /// [`ErrorKind::MoreProblems`] diagnostic. When `None`,
+    /// every problem is returned.
+    pub max_errors: Option<usize>,
+    /// The limits and checks that parsing would apply, such
+    /// as `max_depth` and `reject_duplicate_keys`, and the
+    /// `python_literals` extension.
+    pub parse_options: ParseOptions,
+}
+
+/// The token expected next while checking the structure.
+#[derive(Clone, Copy, PartialEq)]
+enum Expect {
+    Value,
+    ValueOrClose,
+    Key,
+    KeyOrClose,
+    Colon,
+    CommaOrClose,
+    End,
+}
+
+/// Checks whether a text is valid JSON without building a
+/// value, and reports every problem found rather than only
+/// the first. Malformed tokens are reported and skipped,
+/// and checking resumes after a structural problem, such
+/// as a missing comma, so that later problems are found
+/// too. Problems with the limits in the parse options are
+/// reported once each. Returns the diagnostics in order of
+/// appearance if there are any.
+pub fn validate(text: &str, options: &ValidateOptions) -> std::result::Result<(), Vec<Diagnostic>> {
+    let parse_options = &options.parse_options;
+    if let Some(max) = parse_options.max_size.filter(|&max| text.len() > max) {
+        let size = text.len();
+        return Err(vec![Diagnostic {
+            kind: ErrorKind::InputTooLarge { size, max },
+            span: Span {
+                start: max,
+                end: size,
+            },
+        }]);
+    }
+
+    let tokenize_options = TokenizeOptions {
+        allow_comments: options.allow_comments,
+        python_literals: parse_options.python_literals,
+        lone_surrogates: parse_options.lone_surrogates,
+        ..Default::default()
+    };
+    let (tokens, mut diagnostics) = tokenize_recovering(text, &tokenize_options);
+    check_structure(
+        text,
+        &tokens,
+        &diagnostics.clone(),
+        options,
+        &mut diagnostics,
+    );
+
+    let mut limits: Vec<Result<()>> = vec![enforce_limits(&tokens, parse_options)];
+    if parse_options.max_number_length.is_some() || parse_options.max_exponent.is_some() {
+        limits.push(reject_oversized_numbers(&tokens, parse_options));
+    }
+    if parse_options.reject_lossy_numbers {
+        limits.push(reject_lossy_numbers(&tokens, parse_options));
+    }
+    if parse_options.reject_duplicate_keys {
+        let mut tokens = tokens.clone();
+        limits.push(normalize_keys(&mut tokens, parse_options));
+        limits.push(reject_duplicate_keys(&tokens));
+    }
+    for error in limits.into_iter().filter_map(Result::err) {
+        diagnostics.push(diagnostic(error, &tokens));
+    }
+
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span.start);
+    if let Some(max) = options.max_errors.filter(|&max| diagnostics.len() > max) {
+        let omitted = diagnostics.split_off(max);
+        diagnostics.push(Diagnostic {
+            kind: ErrorKind::MoreProblems(omitted.len()),
+            span: Span {
+                start: omitted[0].span.start,
+                end: text.len(),
+            },
+        });
+    }
+
+    match diagnostics.is_empty() {
+        true => Ok(()),
+        false => Err(diagnostics),
+    }
+}
+
+/// Checks that the tokens form a single value, recording a
+/// diagnostic for each problem. Malformed tokens, which the
+/// tokenizer reported and skipped, are treated as values
+/// where a value is expected and ignored elsewhere, and no
+/// problem is reported right after one, so that they do not
+/// cause further problems.
+fn check_structure(
+    text: &str,
+    tokens: &[(Token, Span)],
+    malformed: &[Diagnostic],
+    options: &ValidateOptions,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let mut tokens: Vec<(Option<&Token>, Span)> = tokens
+        .iter()
+        .map(|(token, span)| (Some(token), *span))
+        .chain(malformed.iter().map(|diagnostic| (None, diagnostic.span)))
+        .collect();
+    tokens.sort_by_key(|(_, span)| span.start);
+
+    let mut open: Vec<char> = vec![];
+    let mut expect = Expect::Value;
+    let mut comma = None;
+    let mut after_malformed = false;
+    let mut report = |kind, span| diagnostics.push(Diagnostic { kind, span });
+
+    for (token, span) in tokens {
+        let previous_comma = comma.take();
+        let after_value = |open: &[char]| match open.is_empty() {
+            true => Expect::End,
+            false => Expect::CommaOrClose,
+        };
+        let is_value = !matches!(token, Some(Token::Punct(',' | ':' | ']' | '}')));
+        let close = match token {
+            Some(Token::Punct(close @ (']' | '}'))) => Some(*close),
+            _ => None,
+        };
+        let closes_open = close.is_some() && close == open.last().map(|&open| closing(open));
+
+        expect = match expect {
+            Expect::End => {
+                report(ErrorKind::Syntax, span);
+                break;
+            }
+            Expect::Value | Expect::ValueOrClose if is_value => match token {
+                Some(Token::Punct('[')) => {
+                    open.push('[');
+                    Expect::ValueOrClose
+                }
+                Some(Token::Punct('{')) => {
+                    open.push('{');
+                    Expect::KeyOrClose
+                }
+                _ => after_value(&open),
+            },
+            Expect::Key | Expect::KeyOrClose if matches!(token, Some(Token::String(_)) | None) => {
+                Expect::Colon
+            }
+            Expect::Colon if token == Some(&Token::Punct(':')) => Expect::Value,
+            Expect::Colon | Expect::CommaOrClose if token.is_none() => expect,
+            Expect::CommaOrClose if token == Some(&Token::Punct(',')) => {
+                comma = Some(span);
+                match open.last() {
+                    Some('[') => Expect::Value,
+                    _ => Expect::Key,
+                }
+            }
+            Expect::ValueOrClose | Expect::KeyOrClose | Expect::CommaOrClose if closes_open => {
+                open.pop();
+                after_value(&open)
+            }
+            Expect::Value | Expect::Key if closes_open && previous_comma.is_some() => {
+                if !options.allow_trailing_commas {
+                    report(ErrorKind::TrailingComma, previous_comma.unwrap());
+                }
+                open.pop();
+                after_value(&open)
+            }
+            _ => {
+                if !after_malformed {
+                    report(ErrorKind::Syntax, span);
+                }
+                recover(expect, token, is_value, &mut open)
+            }
+        };
+        after_malformed = token.is_none();
+    }
+
+    if expect != Expect::End {
+        let end = Span {
+            start: text.len(),
+            end: text.len(),
+        };
+        report(ErrorKind::Syntax, end);
+    }
+}
+
+/// Returns the token expected after an unexpected token,
+/// guessing at what was meant. A missing colon or comma
+/// before a value is assumed, and a closing bracket closes
+/// every container up to the one it matches. Other tokens
+/// are skipped.
+fn recover(expect: Expect, token: Option<&Token>, is_value: bool, open: &mut Vec<char>) -> Expect {
+    if let Some(Token::Punct(close @ (']' | '}'))) = token {
+        if let Some(position) = open.iter().rposition(|&open| closing(open) == *close) {
+            open.truncate(position);
+            return match open.is_empty() {
+                true => Expect::End,
+                false => Expect::CommaOrClose,
+            };
+        }
+    }
+
+    match (expect, open.last()) {
+        (Expect::Colon | Expect::CommaOrClose, Some('[')) | (Expect::Colon | Expect::Key, _)
+            if is_value =>
+        {
+            match token {
+                Some(Token::Punct('[')) => {
+                    open.push('[');
+                    Expect::ValueOrClose
+                }
+                Some(Token::Punct('{')) => {
+                    open.push('{');
+                    Expect::KeyOrClose
+                }
+                _ => Expect::CommaOrClose,
+            }
+        }
+        (Expect::CommaOrClose, Some('{')) if matches!(token, Some(Token::String(_))) => {
+            Expect::Colon
+        }
+        _ => expect,
+    }
+}
+
+fn closing(open: char) -> char {
+    match open {
+        '[' => ']',
+        _ => '}',
+    }
+}
+
+/// Converts an error found by a parse check into a
+/// diagnostic spanning the token at its offset.
+fn diagnostic(error: Error, tokens: &[(Token, Span)]) -> Diagnostic {
+    let offset = error.offset().unwrap_or(0);
+    let span = tokens
+        .iter()
+        .map(|(_, span)| *span)
+        .find(|span| span.start == offset)
+        .unwrap_or(Span {
+            start: offset,
+            end: offset,
+        });
+    Diagnostic {
+        kind: error.kind().clone(),
+        span,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::json::{ErrorKind, ParseOptions, Span};
+
+    use super::{validate, ValidateOptions};
+
+    fn problems(text: &str, options: &ValidateOptions) -> Vec<(ErrorKind, usize)> {
+        match validate(text, options) {
+            Ok(()) => vec![],
+            Err(diagnostics) => diagnostics
+                .into_iter()
+                .map(|diagnostic| (diagnostic.kind, diagnostic.span.start))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn accepts_valid_text() {
+        let options = ValidateOptions::default();
+        for text in ["1", r#"{"a": [1, {"b": null}], "c": "d"}"#, " [] "] {
+            assert_eq!(validate(text, &options), Ok(()), "{}", text);
+        }
+    }
+
+    #[test]
+    fn reports_every_problem() {
+        let options = ValidateOptions::default();
+        assert_eq!(
+            problems(r#"{"a" 1, "b": [1 2], "c": @}"#, &options),
+            [
+                (ErrorKind::Syntax, 5),
+                (ErrorKind::Syntax, 16),
+                (
+                    ErrorKind::UnexpectedCharacter {
+                        char: '@',
+                        hint: None
+                    },
+                    25
+                ),
+            ]
+        );
+        assert_eq!(problems("[1, 2", &options), [(ErrorKind::Syntax, 5)]);
+        assert_eq!(problems("[1] 2", &options), [(ErrorKind::Syntax, 4)]);
+        assert_eq!(problems("", &options), [(ErrorKind::Syntax, 0)]);
+    }
+
+    #[test]
+    fn accepts_extensions_when_enabled() {
+        let text = "[1, /* two */ 2,]";
+        let problems_by_default = problems(text, &ValidateOptions::default());
+        assert_eq!(problems_by_default[0].1, 4);
+        assert_eq!(
+            problems_by_default.last(),
+            Some(&(ErrorKind::TrailingComma, 15))
+        );
+
+        let options = ValidateOptions {
+            allow_comments: true,
+            allow_trailing_commas: true,
+            ..Default::default()
+        };
+        assert_eq!(validate(text, &options), Ok(()));
+    }
+
+    #[test]
+    fn reports_limits_and_caps_diagnostics() {
+        let options = ValidateOptions {
+            max_errors: Some(2),
+            parse_options: ParseOptions {
+                max_depth: Some(1),
+                reject_duplicate_keys: true,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let diagnostics = validate(r#"{"a": [1], "a": 2, "b": 3 4, 5 6}"#, &options).unwrap_err();
+        let kinds: Vec<_> = diagnostics.iter().map(|d| d.kind.clone()).collect();
+        assert_eq!(
+            kinds,
+            [
+                ErrorKind::TooDeep { max: 1 },
+                ErrorKind::DuplicateKey("a".into()),
+                ErrorKind::MoreProblems(3),
+            ]
+        );
+        assert_eq!(diagnostics[1].span, Span { start: 11, end: 14 });
+    }
+}
@@ -0,0 +1,254 @@
+use super::{
+    glob::{glob_matches, glob_segments},
+    pointer::{parse_index, JsonPointer},
+    types::{Result, Value},
+};
+
+/// A callback that is notified of changes to a watched
+/// value, with the path of the changed value and its new
+/// value, or `None` if it was removed.
+type Watcher = Box<dyn FnMut(&JsonPointer, Option<&Value>)>;
+
+/// A value whose mutations report the paths that changed.
+/// Code can subscribe to parts of the value with glob
+/// patterns, as used by [`Value::select_glob`], and is only
+/// notified of changes within those parts. Changes are
+/// reported at the most specific paths that differ, so
+/// replacing an object only reports the members whose
+/// values are different.
+pub struct WatchedValue {
+    value: Value,
+    watchers: Vec<(Vec<String>, Watcher)>,
+}
+
+impl WatchedValue {
+    /// Wraps a value for watching.
+    pub fn new(value: Value) -> Self {
+        Self {
+            value,
+            watchers: vec![],
+        }
+    }
+
+    /// Returns the current value.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Returns the current value, ending the watch.
+    pub fn into_inner(self) -> Value {
+        self.value
+    }
+
+    /// Registers a callback that is called for every
+    /// changed path that is within a part of the value
+    /// matching the pattern. Subscribing to `server` is
+    /// notified of changes to `/server/port`, for example.
+    pub fn subscribe(
+        &mut self,
+        pattern: &str,
+        watcher: impl FnMut(&JsonPointer, Option<&Value>) + 'static,
+    ) {
+        let pattern = glob_segments(pattern)
+            .into_iter()
+            .map(String::from)
+            .collect();
+        self.watchers.push((pattern, Box::new(watcher)));
+    }
+
+    /// Sets the value at a JSON pointer, creating missing
+    /// containers as by [`Value::pointer_or_insert`], and
+    /// returns the paths that changed. It will return an
+    /// error under the same conditions as
+    /// `pointer_or_insert`.
+    pub fn set(&mut self, pointer: &str, value: Value) -> Result<Vec<JsonPointer>> {
+        let old = self.value.clone();
+        *self.value.pointer_or_insert(pointer, Value::Null)? = value;
+        Ok(self.notify(&old))
+    }
+
+    /// Removes the value at a JSON pointer from its
+    /// container, and returns the paths that changed. The
+    /// root cannot be removed, and nothing changes if the
+    /// pointer does not refer to a value.
+    pub fn remove(&mut self, pointer: &str) -> Vec<JsonPointer> {
+        let Ok(pointer) = JsonPointer::parse(pointer) else {
+            return vec![];
+        };
+        let (Some(parent), Some(token)) = (pointer.parent(), pointer.last()) else {
+            return vec![];
+        };
+
+        let old = self.value.clone();
+        match parent.resolve_mut(&mut self.value) {
+            Some(Value::Object(object)) => {
+                object.remove(token);
+            }
+            Some(Value::Array(array)) => {
+                if let Some(index) = parse_index(token).filter(|&index| index < array.len()) {
+                    array.remove(index);
+                }
+            }
+            _ => {}
+        }
+        self.notify(&old)
+    }
+
+    /// Replaces the whole value, such as when a
+    /// configuration file is reloaded, and returns the paths
+    /// that changed.
+    pub fn replace(&mut self, value: Value) -> Vec<JsonPointer> {
+        let old = std::mem::replace(&mut self.value, value);
+        self.notify(&old)
+    }
+
+    /// Finds the paths that differ from an old value and
+    /// calls the watchers of each.
+    fn notify(&mut self, old: &Value) -> Vec<JsonPointer> {
+        let mut changes = vec![];
+        diff(
+            Some(old),
+            Some(&self.value),
+            &mut JsonPointer::root(),
+            &mut changes,
+        );
+
+        for change in &changes {
+            let tokens = change.tokens();
+            let new = change.resolve(&self.value);
+            for (pattern, watcher) in &mut self.watchers {
+                let watched = (0..=tokens.len()).any(|len| glob_matches(pattern, &tokens[..len]));
+                if watched {
+                    watcher(change, new);
+                }
+            }
+        }
+        changes
+    }
+}
+
+/// Records the most specific paths at which two values
+/// differ. A missing value is treated as an empty
+/// container, so that the contents of added and removed
+/// containers are reported individually.
+fn diff(
+    old: Option<&Value>,
+    new: Option<&Value>,
+    pointer: &mut JsonPointer,
+    changes: &mut Vec<JsonPointer>,
+) {
+    if old == new {
+        return;
+    }
+
+    let tokens = match (old, new) {
+        (Some(Value::Object(_)) | None, Some(Value::Object(_)) | None)
+        | (Some(Value::Array(_)) | None, Some(Value::Array(_)) | None) => {
+            let mut tokens = children(old);
+            for token in children(new) {
+                if !tokens.contains(&token) {
+                    tokens.push(token);
+                }
+            }
+            tokens
+        }
+        _ => vec![],
+    };
+    if tokens.is_empty() {
+        return changes.push(pointer.clone());
+    }
+
+    for token in tokens {
+        let old = old.and_then(|old| child(old, &token));
+        let new = new.and_then(|new| child(new, &token));
+        pointer.push(token);
+        diff(old, new, pointer, changes);
+        pointer.pop();
+    }
+}
+
+fn children(value: Option<&Value>) -> Vec<String> {
+    match value {
+        Some(Value::Object(object)) => object.keys().cloned().collect(),
+        Some(Value::Array(array)) => (0..array.len()).map(|index| index.to_string()).collect(),
+        _ => vec![],
+    }
+}
+
+fn child<'a>(value: &'a Value, token: &str) -> Option<&'a Value> {
+    match value {
+        Value::Object(object) => object.get(token),
+        Value::Array(array) => array.get(parse_index(token)?),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use crate::json::{parse, JsonPointer, Value};
+
+    use super::WatchedValue;
+
+    fn paths(changes: Vec<JsonPointer>) -> Vec<String> {
+        changes.iter().map(JsonPointer::to_string).collect()
+    }
+
+    #[test]
+    fn reports_changed_paths() {
+        let mut watched = WatchedValue::new(parse(r#"{"a": {"b": 1, "c": 2}}"#).unwrap());
+        assert_eq!(paths(watched.set("/a/b", Value::Null).unwrap()), ["/a/b"]);
+        assert!(watched
+            .set("/a/c", Value::Number(2.into()))
+            .unwrap()
+            .is_empty());
+        assert_eq!(paths(watched.set("/d/0", Value::TRUE).unwrap()), ["/d/0"]);
+        assert_eq!(paths(watched.remove("/a")), ["/a/b", "/a/c"]);
+        assert!(watched.remove("/missing").is_empty());
+        assert_eq!(watched.value(), &parse(r#"{"d": [true]}"#).unwrap());
+    }
+
+    #[test]
+    fn reports_most_specific_changes_on_replace() {
+        let mut watched = WatchedValue::new(
+            parse(r#"{"server": {"port": 80, "host": "a"}, "x": [1, 2]}"#).unwrap(),
+        );
+        let changes = watched
+            .replace(parse(r#"{"server": {"port": 81, "host": "a"}, "x": [1], "y": {}}"#).unwrap());
+        assert_eq!(paths(changes), ["/server/port", "/x/1", "/y"]);
+    }
+
+    #[test]
+    fn notifies_matching_subscribers() {
+        let seen = Rc::new(RefCell::new(vec![]));
+        let mut watched =
+            WatchedValue::new(parse(r#"{"server": {"port": 80}, "log": 1}"#).unwrap());
+
+        let server = seen.clone();
+        watched.subscribe("server", move |pointer, value| {
+            server
+                .borrow_mut()
+                .push(format!("{} = {:?}", pointer, value.cloned()));
+        });
+        let ports = seen.clone();
+        watched.subscribe("*.port", move |pointer, _| {
+            ports.borrow_mut().push(format!("port {}", pointer));
+        });
+
+        watched.set("/log", Value::Number(2.into())).unwrap();
+        watched
+            .set("/server/port", Value::Number(81.into()))
+            .unwrap();
+        watched.remove("/server/port");
+        assert_eq!(
+            *seen.borrow(),
+            [
+                "/server/port = Some(Number(81))",
+                "port /server/port",
+                "/server/port = None",
+                "port /server/port",
+            ]
+        );
+    }
+}
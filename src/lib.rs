@@ -0,0 +1,13 @@
+//! A JSON parser and toolkit. The most common entry points
+//! are re-exported at the root of the crate, while the full
+//! API lives in the [`json`] module.
+//!
+//! ```
+//! let value = json::parse(r#"{"hello": "world"}"#)?;
+//! assert_eq!(value.pointer("/hello"), Some(&json::Value::from("world")));
+//! # Ok::<(), json::Error>(())
+//! ```
+
+pub mod json;
+
+pub use json::{parse, Error, ErrorKind, Result, Value};
@@ -1,15 +1,620 @@
-use std::io::{stdin, Read};
+use std::{
+    env,
+    fs::{self, File},
+    io::{self, stdin, stdout, Read, Write},
+    process::ExitCode,
+    time::{SystemTime, UNIX_EPOCH},
+};
 
-mod json;
+use ::json::json::{self, Map, Value};
 
-fn main() {
-    let mut text = String::new();
-    if stdin().read_to_string(&mut text).is_err() {
-        println!("Input text does not contain valid UTF-8.");
+#[cfg(feature = "bench")]
+mod bench;
+mod completions;
+
+/// Counts allocations for the `bench` subcommand. It is
+/// only installed with the `bench` feature, since counting
+/// slows down every other subcommand.
+#[cfg(feature = "bench")]
+#[global_allocator]
+static ALLOCATOR: bench::CountingAllocator = bench::CountingAllocator;
+
+/// Prints a line of output unless `--quiet` is set.
+macro_rules! emit {
+    ($options:expr, $($arg:tt)*) => {
+        if !$options.quiet {
+            println!($($arg)*);
+        }
+    };
+}
+
+/// The options that apply to every subcommand, set by
+/// global flags that may appear anywhere in the arguments.
+#[derive(Debug, Clone, Copy)]
+struct Options {
+    /// The form of results and errors, set by `--output`.
+    output: Output,
+    /// Whether nothing is printed, set by `--quiet`. Only
+    /// the exit code reports the outcome.
+    quiet: bool,
+    /// Whether the input is checked for lints, which are
+    /// reported as errors, set by `--strict`.
+    strict: bool,
+}
+
+/// The reason a subcommand failed, which determines its
+/// exit code.
+#[derive(Debug)]
+enum Failure {
+    /// The input was read but is invalid or was rejected,
+    /// such as text that is not valid JSON. Exits with 1.
+    Semantic(String),
+    /// The arguments were malformed, or reading or writing
+    /// a file failed. Exits with 2.
+    Usage(String),
+}
+
+impl From<json::Error> for Failure {
+    fn from(error: json::Error) -> Self {
+        match error.kind() {
+            json::ErrorKind::Io(_) => Failure::Usage(error.to_string()),
+            _ => Failure::Semantic(error.to_string()),
+        }
+    }
+}
+
+/// The form in which results and errors are printed, set
+/// by the global `--output` flag.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Output {
+    /// Text meant for people, such as `key=value` lines.
+    Text,
+    /// JSON that can be consumed by scripts. Errors are
+    /// printed to standard error as `{"error": "..."}`.
+    Json,
+}
+
+/// Runs a subcommand. Every subcommand exits with 0 on
+/// success, 1 if the input is invalid or was rejected, and
+/// 2 if the arguments are malformed or I/O fails.
+fn main() -> ExitCode {
+    let mut args: Vec<String> = env::args().skip(1).collect();
+    let options = match take_global_flags(&mut args) {
+        Ok(options) => options,
+        Err(failure) => {
+            let options = Options {
+                output: Output::Text,
+                quiet: false,
+                strict: false,
+            };
+            return finish(Err(failure), "parse arguments", options);
+        }
+    };
+
+    match args.split_first() {
+        Some((command, paths)) if command == "concat" => concat(paths, options),
+        Some((command, args)) if command == "flatten" => flatten(args, options),
+        Some((command, args)) if command == "unflatten" => unflatten(args, options),
+        Some((command, args)) if command == "to-env" => to_env(args, options),
+        Some((command, args)) if command == "from-env" => from_env(args, options),
+        Some((command, paths)) if command == "infer-schema" => infer_schema(paths, options),
+        Some((command, args)) if command == "rust-types" => rust_types(args, options),
+        Some((command, args)) if command == "generate" => generate(args, options),
+        Some((command, args)) if command == "completions" => completions(args, options),
+        #[cfg(feature = "bench")]
+        Some((command, args)) if command == "bench" => bench(args, options),
+        Some((command, args)) if command == "stats" => stats(args, options),
+        Some((arg, _)) => finish(
+            Err(Failure::Usage(format!("unexpected argument {:?}", arg))),
+            "parse arguments",
+            options,
+        ),
+        None => print(options),
+    }
+}
+
+/// Removes the global `--output text|json`, `--quiet`, and
+/// `--strict` flags from the arguments, wherever they
+/// appear, and returns their values.
+fn take_global_flags(args: &mut Vec<String>) -> Result<Options, Failure> {
+    let mut options = Options {
+        output: Output::Text,
+        quiet: false,
+        strict: false,
+    };
+    let mut index = 0;
+    while index < args.len() {
+        match args[index].as_str() {
+            "--output" => {
+                options.output = match args.get(index + 1).map(String::as_str) {
+                    Some("text") => Output::Text,
+                    Some("json") => Output::Json,
+                    _ => return Err(Failure::Usage("--output must be `text` or `json`".into())),
+                };
+                args.drain(index..index + 2);
+            }
+            "--quiet" => {
+                options.quiet = true;
+                args.remove(index);
+            }
+            "--strict" => {
+                options.strict = true;
+                args.remove(index);
+            }
+            _ => index += 1,
+        }
+    }
+    Ok(options)
+}
+
+/// Parses JSON text from standard input and prints it.
+fn print(options: Options) -> ExitCode {
+    let result = read_input(None).and_then(|text| {
+        let value = parse_input(&text, options)?;
+        match options.output {
+            Output::Text => emit!(options, "{}", value.colored()),
+            Output::Json => emit!(options, "{}", value),
+        }
+        Ok(())
+    });
+    finish(result, "parse", options)
+}
+
+/// Parses JSON input and, with `--strict`, checks it for
+/// lints, which are problems that do not make the text
+/// invalid. A lint is reported as an error. The only lint
+/// is a key that appears twice in one object.
+fn parse_input(text: &str, options: Options) -> Result<Value, Failure> {
+    let value = json::parse(text)?;
+    if options.strict {
+        if let Some((pointer, span)) = json::find_duplicate_keys(text)?.into_iter().next() {
+            return Err(Failure::Semantic(format!(
+                "duplicate key {} at offset {}",
+                pointer, span.start
+            )));
+        }
+    }
+    Ok(value)
+}
+
+/// Concatenates the JSON documents in the given files into
+/// one array, written to standard output.
+fn concat(paths: &[String], options: Options) -> ExitCode {
+    let files: Result<Vec<_>, _> = paths
+        .iter()
+        .map(|path| File::open(path).map_err(|error| io_failure(path, error)))
+        .collect();
+    let result = files.and_then(|files| {
+        let output: Box<dyn Write> = match options.quiet {
+            true => Box::new(io::sink()),
+            false => Box::new(stdout().lock()),
+        };
+        json::concat_to_array(files, output)?;
+        Ok(())
+    });
+
+    if result.is_ok() {
+        emit!(options, "");
+    }
+    finish(result, "concatenate documents", options)
+}
+
+/// Prints the JSON document in a file, or standard input,
+/// as `key=value` lines, with nested keys joined by the
+/// separator and values written as JSON text. With JSON
+/// output, the flattened object is printed instead.
+fn flatten(args: &[String], options: Options) -> ExitCode {
+    let result = separator_and_input(args, ".").and_then(|(separator, text)| {
+        let value = parse_input(&text, options)?;
+        let flat = value.flatten(&separator);
+        match (&flat, options.output) {
+            (Value::Object(flat), Output::Text) => {
+                for (key, value) in flat {
+                    emit!(options, "{}={}", key, value);
+                }
+            }
+            _ => emit!(options, "{}", flat),
+        }
+        Ok(())
+    });
+    finish(result, "flatten", options)
+}
+
+/// Reads `key=value` lines from a file, or standard input,
+/// and prints the nested JSON document they describe.
+/// Values that are not valid JSON are taken as strings.
+fn unflatten(args: &[String], options: Options) -> ExitCode {
+    let result = separator_and_input(args, ".").and_then(|(separator, text)| {
+        let mut flat = Map::new();
+        for line in text.lines().filter(|line| !line.trim().is_empty()) {
+            let (key, value) = line
+                .split_once('=')
+                .ok_or_else(|| Failure::Semantic(format!("line {:?} is missing '='", line)))?;
+            let value = json::parse(value).unwrap_or_else(|_| Value::from(value));
+            flat.insert(key, value);
+        }
+        let value = Value::Object(flat).unflatten(&separator)?;
+        print_value(&value, options);
+        Ok(())
+    });
+    finish(result, "unflatten", options)
+}
+
+/// Prints the JSON document in a file, or standard input,
+/// as `NAME=value` lines for use as environment variables.
+/// Values are quoted for the shell where needed. With JSON
+/// output, an object of names and values is printed.
+fn to_env(args: &[String], options: Options) -> ExitCode {
+    let result = separator_and_input(args, "_").and_then(|(separator, text)| {
+        let value = parse_input(&text, options)?;
+        let vars = value.to_env_vars(&separator);
+        match options.output {
+            Output::Text => {
+                for (name, value) in vars {
+                    emit!(options, "{}={}", name, shell_quote(&value));
+                }
+            }
+            Output::Json => {
+                let vars: Map = vars
+                    .into_iter()
+                    .map(|(name, value)| (name, Value::String(value)))
+                    .collect();
+                emit!(options, "{}", Value::Object(vars));
+            }
+        }
+        Ok(())
+    });
+    finish(result, "convert to environment variables", options)
+}
+
+/// Reads `NAME=value` lines, as written by `to-env` or
+/// found in `.env` files, from a file or standard input,
+/// and prints the nested JSON document they describe.
+/// Blank lines, comments, and `export` prefixes are
+/// ignored, and quoted values are unquoted.
+fn from_env(args: &[String], options: Options) -> ExitCode {
+    let result = separator_and_input(args, "_").and_then(|(separator, text)| {
+        let mut vars = vec![];
+        for line in text.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (name, value) = line
+                .split_once('=')
+                .ok_or_else(|| Failure::Semantic(format!("line {:?} is missing '='", line)))?;
+            vars.push((name.trim().to_owned(), shell_unquote(value)));
+        }
+        let value = Value::from_env_vars(vars, &separator)?;
+        print_value(&value, options);
+        Ok(())
+    });
+    finish(result, "convert from environment variables", options)
+}
+
+/// Quotes a value for the shell with single quotes, unless
+/// it only holds characters that never need quoting.
+fn shell_quote(value: &str) -> String {
+    let is_safe = !value.is_empty()
+        && value
+            .chars()
+            .all(|char| char.is_ascii_alphanumeric() || "_./:@%+,-".contains(char));
+    match is_safe {
+        true => value.to_owned(),
+        false => format!("'{}'", value.replace('\'', r"'\''")),
+    }
+}
+
+/// Removes the quotes from a value written for the shell
+/// or in a `.env` file.
+fn shell_unquote(value: &str) -> String {
+    let value = value.trim();
+    if value.len() >= 2 && value.starts_with('\'') && value.ends_with('\'') {
+        return value[1..value.len() - 1].replace(r"'\''", "'");
+    }
+    if value.len() >= 2 && value.starts_with('"') && value.ends_with('"') {
+        return value[1..value.len() - 1]
+            .replace(r#"\""#, "\"")
+            .replace(r"\\", r"\");
+    }
+    value.to_owned()
+}
+
+/// Prints a JSON Schema that describes the JSON documents
+/// in all of the given files.
+fn infer_schema(paths: &[String], options: Options) -> ExitCode {
+    let mut inferrer = json::SchemaInferrer::new();
+    let result = paths.iter().try_for_each(|path| {
+        let text = read_input(Some(path))?;
+        inferrer.observe(&parse_input(&text, options)?);
+        Ok(())
+    });
+    if result.is_ok() {
+        print_value(&inferrer.to_schema(), options);
+    }
+    finish(result, "infer schema", options)
+}
+
+/// Prints Rust struct definitions that match the JSON
+/// documents in all of the given files. The root type is
+/// named by `--name`, `--serde` adds serde derives, and
+/// `--from-value` adds implementations of `FromValue`.
+fn rust_types(args: &[String], options: Options) -> ExitCode {
+    let result = (|| {
+        let mut types_options = json::RustTypesOptions::default();
+        let mut samples = vec![];
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--name" => {
+                    let name = args
+                        .next()
+                        .ok_or_else(|| Failure::Usage("--name requires a value".into()))?;
+                    types_options.root_name = Some(name.clone());
+                }
+                "--serde" => types_options.serde = true,
+                "--from-value" => types_options.from_value = true,
+                _ => samples.push(parse_input(&read_input(Some(arg))?, options)?),
+            }
+        }
+        if !options.quiet {
+            print!("{}", json::generate_rust_types(&samples, &types_options));
+        }
+        Ok(())
+    })();
+    finish(result, "generate Rust types", options)
+}
+
+/// Prints synthetic JSON documents, one per line. They
+/// conform to the schema given by `--schema`, or are random
+/// values up to `--depth` levels deep with `--random`. The
+/// number of documents is set by `--count`, and `--seed`
+/// makes the output reproducible.
+fn generate(args: &[String], options: Options) -> ExitCode {
+    let result = (|| {
+        let mut schema = None;
+        let mut random = false;
+        let mut count = 1;
+        let mut depth = 3;
+        let mut seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |time| time.as_nanos() as u64);
+
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| Failure::Usage(format!("{} requires a value", arg)))
+            };
+            let number = |value: &String| {
+                value
+                    .parse()
+                    .map_err(|_| Failure::Usage(format!("{} requires a number", arg)))
+            };
+            match arg.as_str() {
+                "--schema" => schema = Some(value()?.clone()),
+                "--random" => random = true,
+                "--count" => count = number(value()?)?,
+                "--depth" => depth = number(value()?)? as usize,
+                "--seed" => seed = number(value()?)?,
+                _ => return Err(Failure::Usage(format!("unexpected argument {:?}", arg))),
+            }
+        }
+
+        let schema = match (schema, random) {
+            (Some(path), false) => Some(json::Schema::compile(&json::read_file(&path)?)?),
+            (None, true) => None,
+            _ => {
+                let error = "either --schema or --random is required";
+                return Err(Failure::Usage(error.to_owned()));
+            }
+        };
+
+        let mut generator = json::Generator::new(seed);
+        for _ in 0..count {
+            let value = match &schema {
+                Some(schema) => generator.from_schema(schema),
+                None => generator.random(depth),
+            };
+            emit!(options, "{}", value);
+        }
+        Ok(())
+    })();
+    finish(result, "generate documents", options)
+}
+
+/// Reports the parse and serialize throughput and the peak
+/// memory of each engine on the JSON document in a file.
+/// Each operation runs for `--seconds`, one by default.
+#[cfg(feature = "bench")]
+fn bench(args: &[String], options: Options) -> ExitCode {
+    let result = (|| {
+        let mut seconds = 1.0;
+        let mut path = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seconds" => {
+                    seconds = args
+                        .next()
+                        .and_then(|value| value.parse::<f64>().ok())
+                        .filter(|seconds| seconds.is_finite() && *seconds >= 0.0)
+                        .ok_or_else(|| Failure::Usage("--seconds requires a number".into()))?;
+                }
+                _ if path.is_none() => path = Some(arg),
+                _ => return Err(Failure::Usage(format!("unexpected argument {:?}", arg))),
+            }
+        }
+
+        let path = path.ok_or_else(|| Failure::Usage("expected a file".into()))?;
+        let text = read_input(Some(path))?;
+        let measurements = bench::run(&text, std::time::Duration::from_secs_f64(seconds))?;
+
+        match options.output {
+            Output::Text => {
+                emit!(
+                    options,
+                    "{:<10} {:>14} {:>14} {:>12}",
+                    "engine",
+                    "parse",
+                    "serialize",
+                    "peak memory"
+                );
+                for measurement in &measurements {
+                    let serialize = measurement.serialize.map_or("-".to_owned(), |rate| {
+                        format!("{}/s", bench::format_bytes(rate))
+                    });
+                    emit!(
+                        options,
+                        "{:<10} {:>14} {:>14} {:>12}",
+                        measurement.engine,
+                        format!("{}/s", bench::format_bytes(measurement.parse)),
+                        serialize,
+                        bench::format_bytes(measurement.peak_memory as f64),
+                    );
+                }
+            }
+            Output::Json => {
+                let measurements = measurements.iter().map(bench::Measurement::to_value);
+                let report = Value::object()
+                    .set("file", path.as_str())
+                    .set("bytes", text.len() as u64)
+                    .set("engines", Value::Array(measurements.collect()))
+                    .build();
+                emit!(options, "{}", report);
+            }
+        }
+        Ok(())
+    })();
+    finish(result, "benchmark", options)
+}
+
+/// Prints the count, sum, minimum, maximum, and mean of
+/// the numbers selected by the JSONPath expression given by
+/// `--path` in the JSON document in a file, or standard
+/// input.
+fn stats(args: &[String], options: Options) -> ExitCode {
+    let result = (|| {
+        let mut path = None;
+        let mut file = None;
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--path" => {
+                    let value = args
+                        .next()
+                        .ok_or_else(|| Failure::Usage("--path requires a value".into()))?;
+                    path = Some(value);
+                }
+                _ if file.is_none() => file = Some(arg),
+                _ => return Err(Failure::Usage(format!("unexpected argument {:?}", arg))),
+            }
+        }
+
+        let path = path.ok_or_else(|| Failure::Usage("--path is required".into()))?;
+        let value = parse_input(&read_input(file)?, options)?;
+        let stats = json::aggregate(&value, path)?;
+        match options.output {
+            Output::Text => {
+                let optional = |stat: Option<f64>| stat.map_or("-".to_owned(), |s| s.to_string());
+                emit!(options, "count {}", stats.count);
+                emit!(options, "sum   {}", stats.sum);
+                emit!(options, "min   {}", optional(stats.min));
+                emit!(options, "max   {}", optional(stats.max));
+                emit!(options, "mean  {}", optional(stats.mean));
+            }
+            Output::Json => emit!(options, "{}", stats.to_value()),
+        }
+        Ok(())
+    })();
+    finish(result, "compute statistics", options)
+}
+
+/// Reads the `--separator` option, which defaults to the
+/// given separator, and the text of the file named by the
+/// remaining argument, or of standard input if there is
+/// none.
+fn separator_and_input(args: &[String], default: &str) -> Result<(String, String), Failure> {
+    let mut separator = default.to_owned();
+    let mut path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--separator" => {
+                separator = args
+                    .next()
+                    .ok_or_else(|| Failure::Usage("--separator requires a value".into()))?
+                    .to_owned();
+            }
+            _ if path.is_none() => path = Some(arg),
+            _ => return Err(Failure::Usage(format!("unexpected argument {:?}", arg))),
+        }
+    }
+    Ok((separator, read_input(path)?))
+}
+
+/// Reads the text of a file, or of standard input if no
+/// file is given.
+fn read_input(path: Option<&String>) -> Result<String, Failure> {
+    match path {
+        Some(path) => fs::read_to_string(path).map_err(|error| io_failure(path, error)),
+        None => {
+            let mut text = String::new();
+            stdin()
+                .read_to_string(&mut text)
+                .map_err(|error| Failure::Usage(error.to_string()))?;
+            Ok(text)
+        }
     }
+}
+
+/// Describes a failure to read or write a file.
+fn io_failure(path: &str, error: io::Error) -> Failure {
+    Failure::Usage(format!("{}: {}", path, error))
+}
+
+/// Prints a completion script for the shell named by the
+/// only argument.
+fn completions(args: &[String], options: Options) -> ExitCode {
+    let result = match args {
+        [shell] => completions::script(shell)
+            .map(|script| {
+                if !options.quiet {
+                    print!("{}", script);
+                }
+            })
+            .ok_or_else(|| Failure::Usage(format!("unsupported shell {:?}", shell))),
+        _ => Err(Failure::Usage("expected one of bash, zsh, or fish".into())),
+    };
+    finish(result, "generate completions", options)
+}
+
+/// Prints a resulting document, indented for people or
+/// compact for scripts.
+fn print_value(value: &Value, options: Options) {
+    match options.output {
+        Output::Text => emit!(options, "{:#}", value),
+        Output::Json => emit!(options, "{}", value),
+    }
+}
 
-    match json::parse(&text) {
-        Err(_) => println!("Input text does not contain valid JSON."),
-        Ok(value) => println!("{}", value),
+/// Reports the error of a failed command, if any, and
+/// returns the exit code of the command.
+fn finish(result: Result<(), Failure>, command: &str, options: Options) -> ExitCode {
+    let (error, code) = match result {
+        Ok(()) => return ExitCode::SUCCESS,
+        Err(Failure::Semantic(error)) => (error, 1),
+        Err(Failure::Usage(error)) => (error, 2),
+    };
+    if !options.quiet {
+        match options.output {
+            Output::Text => eprintln!("Could not {}: {}.", command, error),
+            Output::Json => {
+                let message = format!("could not {}: {}", command, error);
+                let report = Value::object().set("error", message).build();
+                eprintln!("{}", report);
+            }
+        }
     }
+    ExitCode::from(code)
 }